@@ -0,0 +1,102 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#pragma once
+
+#include "binary.h"
+#include "serde.h"
+
+// Maximum length supported for BCS sequences and maps.
+#define SERDE_BCS_MAX_LENGTH ((size_t)((1ull << 31) - 1))
+#define SERDE_BCS_MAX_CONTAINER_DEPTH ((size_t)500)
+
+static inline serde_error_t
+serde_bcs_serialize_u32_as_uleb128(serde_serializer_t *s, uint32_t value) {
+    while (value >= 0x80) {
+        SERDE_TRY(serde_serialize_u8(s, (uint8_t)((value & 0x7F) | 0x80)));
+        value = value >> 7;
+    }
+    return serde_serialize_u8(s, (uint8_t)value);
+}
+
+static inline serde_error_t serde_bcs_serialize_len(serde_serializer_t *s,
+                                                    size_t value) {
+    if (value > SERDE_BCS_MAX_LENGTH) {
+        return SERDE_ERROR_LENGTH_TOO_LARGE;
+    }
+    return serde_bcs_serialize_u32_as_uleb128(s, (uint32_t)value);
+}
+
+static inline serde_error_t
+serde_bcs_serialize_variant_index(serde_serializer_t *s, uint32_t value) {
+    return serde_bcs_serialize_u32_as_uleb128(s, value);
+}
+
+static inline serde_error_t
+serde_bcs_deserialize_uleb128_as_u32(serde_deserializer_t *d,
+                                     uint32_t *value) {
+    uint64_t result = 0;
+    for (int shift = 0; shift < 32; shift += 7) {
+        uint8_t byte;
+        SERDE_TRY(serde_deserialize_u8(d, &byte));
+        uint8_t digit = byte & 0x7F;
+        result |= (uint64_t)digit << shift;
+        if (result > UINT32_MAX) {
+            // Overflow while parsing uleb128-encoded uint32 value.
+            return SERDE_ERROR_INVALID_VALUE;
+        }
+        if (digit == byte) {
+            if (shift > 0 && digit == 0) {
+                // Invalid uleb128 number (unexpected zero digit).
+                return SERDE_ERROR_INVALID_VALUE;
+            }
+            *value = (uint32_t)result;
+            return SERDE_OK;
+        }
+    }
+    // Overflow while parsing uleb128-encoded uint32 value.
+    return SERDE_ERROR_INVALID_VALUE;
+}
+
+static inline serde_error_t serde_bcs_deserialize_len(serde_deserializer_t *d,
+                                                      size_t *value) {
+    uint32_t result;
+    SERDE_TRY(serde_bcs_deserialize_uleb128_as_u32(d, &result));
+    if (result > SERDE_BCS_MAX_LENGTH) {
+        return SERDE_ERROR_LENGTH_TOO_LARGE;
+    }
+    *value = (size_t)result;
+    return SERDE_OK;
+}
+
+static inline serde_error_t
+serde_bcs_deserialize_variant_index(serde_deserializer_t *d, uint32_t *value) {
+    return serde_bcs_deserialize_uleb128_as_u32(d, value);
+}
+
+static const serde_binary_format_t serde_bcs_format = {
+    serde_bcs_serialize_len,
+    serde_bcs_serialize_variant_index,
+    serde_bcs_deserialize_len,
+    serde_bcs_deserialize_variant_index,
+    false,
+    true,
+    SERDE_BCS_MAX_CONTAINER_DEPTH,
+};
+
+// `arena` is used as scratch space to sort map entries and may be NULL if no
+// maps are serialized.
+static inline void serde_bcs_serializer_init(serde_serializer_t *serializer,
+                                             uint8_t *bytes, size_t capacity,
+                                             serde_arena_t *arena) {
+    serde_serializer_init(serializer, &serde_bcs_format, bytes, capacity,
+                          arena);
+}
+
+static inline void
+serde_bcs_deserializer_init(serde_deserializer_t *deserializer,
+                            const uint8_t *bytes, size_t length,
+                            serde_arena_t *arena) {
+    serde_deserializer_init(deserializer, &serde_bcs_format, bytes, length,
+                            arena);
+}
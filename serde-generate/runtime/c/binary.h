@@ -0,0 +1,579 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#pragma once
+
+#include "serde.h"
+
+typedef struct serde_serializer serde_serializer_t;
+typedef struct serde_deserializer serde_deserializer_t;
+
+// Description of a binary encoding (e.g. BCS or Bincode). See `bcs.h` and
+// `bincode.h`.
+typedef struct serde_binary_format {
+    serde_error_t (*serialize_len)(serde_serializer_t *, size_t);
+    serde_error_t (*serialize_variant_index)(serde_serializer_t *, uint32_t);
+    serde_error_t (*deserialize_len)(serde_deserializer_t *, size_t *);
+    serde_error_t (*deserialize_variant_index)(serde_deserializer_t *,
+                                               uint32_t *);
+    bool supports_floats;
+    bool enforce_strict_map_ordering;
+    size_t max_container_depth;
+} serde_binary_format_t;
+
+// Serializer writing into a caller-provided buffer.
+struct serde_serializer {
+    const serde_binary_format_t *format;
+    uint8_t *bytes;
+    size_t capacity;
+    size_t offset;
+    size_t container_depth_budget;
+    // Scratch space used to sort map entries in canonical encodings.
+    // May be NULL otherwise.
+    serde_arena_t *arena;
+};
+
+// Deserializer reading from a caller-provided buffer. Variable-size data is
+// allocated in `arena`.
+struct serde_deserializer {
+    const serde_binary_format_t *format;
+    const uint8_t *bytes;
+    size_t length;
+    size_t offset;
+    size_t container_depth_budget;
+    serde_arena_t *arena;
+};
+
+static inline void serde_serializer_init(serde_serializer_t *serializer,
+                                         const serde_binary_format_t *format,
+                                         uint8_t *bytes, size_t capacity,
+                                         serde_arena_t *arena) {
+    serializer->format = format;
+    serializer->bytes = bytes;
+    serializer->capacity = capacity;
+    serializer->offset = 0;
+    serializer->container_depth_budget = format->max_container_depth;
+    serializer->arena = arena;
+}
+
+static inline void
+serde_deserializer_init(serde_deserializer_t *deserializer,
+                        const serde_binary_format_t *format,
+                        const uint8_t *bytes, size_t length,
+                        serde_arena_t *arena) {
+    deserializer->format = format;
+    deserializer->bytes = bytes;
+    deserializer->length = length;
+    deserializer->offset = 0;
+    deserializer->container_depth_budget = format->max_container_depth;
+    deserializer->arena = arena;
+}
+
+// --- Serialization ---
+
+static inline serde_error_t serde_serializer_write(serde_serializer_t *s,
+                                                   const void *data,
+                                                   size_t len) {
+    if (len > s->capacity - s->offset) {
+        return SERDE_ERROR_BUFFER_TOO_SMALL;
+    }
+    if (len > 0) {
+        memcpy(s->bytes + s->offset, data, len);
+    }
+    s->offset += len;
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_serialize_u8(serde_serializer_t *s,
+                                               uint8_t value) {
+    return serde_serializer_write(s, &value, 1);
+}
+
+static inline serde_error_t serde_serialize_u16(serde_serializer_t *s,
+                                                uint16_t value) {
+    uint8_t buf[2];
+    buf[0] = (uint8_t)value;
+    buf[1] = (uint8_t)(value >> 8);
+    return serde_serializer_write(s, buf, sizeof(buf));
+}
+
+static inline serde_error_t serde_serialize_u32(serde_serializer_t *s,
+                                                uint32_t value) {
+    uint8_t buf[4];
+    for (int i = 0; i < 4; i++) {
+        buf[i] = (uint8_t)(value >> (8 * i));
+    }
+    return serde_serializer_write(s, buf, sizeof(buf));
+}
+
+static inline serde_error_t serde_serialize_u64(serde_serializer_t *s,
+                                                uint64_t value) {
+    uint8_t buf[8];
+    for (int i = 0; i < 8; i++) {
+        buf[i] = (uint8_t)(value >> (8 * i));
+    }
+    return serde_serializer_write(s, buf, sizeof(buf));
+}
+
+static inline serde_error_t serde_serialize_u128(serde_serializer_t *s,
+                                                 serde_uint128_t value) {
+    SERDE_TRY(serde_serialize_u64(s, value.low));
+    return serde_serialize_u64(s, value.high);
+}
+
+static inline serde_error_t serde_serialize_i8(serde_serializer_t *s,
+                                               int8_t value) {
+    return serde_serialize_u8(s, (uint8_t)value);
+}
+
+static inline serde_error_t serde_serialize_i16(serde_serializer_t *s,
+                                                int16_t value) {
+    return serde_serialize_u16(s, (uint16_t)value);
+}
+
+static inline serde_error_t serde_serialize_i32(serde_serializer_t *s,
+                                                int32_t value) {
+    return serde_serialize_u32(s, (uint32_t)value);
+}
+
+static inline serde_error_t serde_serialize_i64(serde_serializer_t *s,
+                                                int64_t value) {
+    return serde_serialize_u64(s, (uint64_t)value);
+}
+
+static inline serde_error_t serde_serialize_i128(serde_serializer_t *s,
+                                                 serde_int128_t value) {
+    SERDE_TRY(serde_serialize_u64(s, value.low));
+    return serde_serialize_i64(s, value.high);
+}
+
+static inline serde_error_t serde_serialize_unit(serde_serializer_t *s,
+                                                 serde_unit_t value) {
+    (void)s;
+    (void)value;
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_serialize_bool(serde_serializer_t *s,
+                                                 bool value) {
+    return serde_serialize_u8(s, value ? 1 : 0);
+}
+
+static inline serde_error_t serde_serialize_char(serde_serializer_t *s,
+                                                 uint32_t value) {
+    (void)s;
+    (void)value;
+    return SERDE_ERROR_NOT_SUPPORTED;
+}
+
+static inline serde_error_t serde_serialize_f32(serde_serializer_t *s,
+                                                float value) {
+    uint32_t bits;
+    if (!s->format->supports_floats) {
+        return SERDE_ERROR_NOT_SUPPORTED;
+    }
+    memcpy(&bits, &value, sizeof(bits));
+    return serde_serialize_u32(s, bits);
+}
+
+static inline serde_error_t serde_serialize_f64(serde_serializer_t *s,
+                                                double value) {
+    uint64_t bits;
+    if (!s->format->supports_floats) {
+        return SERDE_ERROR_NOT_SUPPORTED;
+    }
+    memcpy(&bits, &value, sizeof(bits));
+    return serde_serialize_u64(s, bits);
+}
+
+static inline serde_error_t serde_serialize_len(serde_serializer_t *s,
+                                                size_t value) {
+    return s->format->serialize_len(s, value);
+}
+
+static inline serde_error_t
+serde_serialize_variant_index(serde_serializer_t *s, uint32_t value) {
+    return s->format->serialize_variant_index(s, value);
+}
+
+static inline serde_error_t serde_serialize_option_tag(serde_serializer_t *s,
+                                                       bool value) {
+    return serde_serialize_bool(s, value);
+}
+
+static inline serde_error_t serde_serialize_str(serde_serializer_t *s,
+                                                serde_str_t value) {
+    SERDE_TRY(serde_serialize_len(s, value.len));
+    return serde_serializer_write(s, value.data, value.len);
+}
+
+static inline serde_error_t serde_serialize_bytes(serde_serializer_t *s,
+                                                  serde_bytes_t value) {
+    SERDE_TRY(serde_serialize_len(s, value.len));
+    return serde_serializer_write(s, value.data, value.len);
+}
+
+static inline size_t serde_serializer_get_buffer_offset(serde_serializer_t *s) {
+    return s->offset;
+}
+
+static inline serde_error_t
+serde_serializer_increase_container_depth(serde_serializer_t *s) {
+    if (s->container_depth_budget == 0) {
+        return SERDE_ERROR_CONTAINER_DEPTH;
+    }
+    s->container_depth_budget--;
+    return SERDE_OK;
+}
+
+static inline void
+serde_serializer_decrease_container_depth(serde_serializer_t *s) {
+    s->container_depth_budget++;
+}
+
+// Reserve space to track the offsets of the entries of a map, if the encoding
+// requires map entries to be sorted. Otherwise, `*offsets` is set to NULL.
+static inline serde_error_t
+serde_serializer_alloc_map_offsets(serde_serializer_t *s, size_t len,
+                                   size_t **offsets) {
+    void *result;
+    *offsets = NULL;
+    if (!s->format->enforce_strict_map_ordering || len <= 1) {
+        return SERDE_OK;
+    }
+    SERDE_TRY(
+        serde_arena_alloc_array(s->arena, len + 1, sizeof(size_t), &result));
+    *offsets = (size_t *)result;
+    return SERDE_OK;
+}
+
+static inline int serde_compare_slices(const uint8_t *a, size_t a_len,
+                                       const uint8_t *b, size_t b_len) {
+    size_t len = (a_len < b_len) ? a_len : b_len;
+    int result = memcmp(a, b, len);
+    if (result != 0) {
+        return result;
+    }
+    return (a_len < b_len) ? -1 : (a_len > b_len) ? 1 : 0;
+}
+
+static inline void serde_reverse_bytes(uint8_t *start, uint8_t *end) {
+    while (start + 1 < end) {
+        uint8_t tmp = *start;
+        *start++ = *--end;
+        *end = tmp;
+    }
+}
+
+// Sort the last `len` map entries in place, given their starting offsets, then
+// release the offsets from the arena. Use an insertion sort where entries are
+// moved with rotations so that no additional memory is needed.
+static inline serde_error_t
+serde_serializer_sort_map_entries(serde_serializer_t *s, size_t *offsets,
+                                  size_t len) {
+    if (offsets == NULL) {
+        return SERDE_OK;
+    }
+    offsets[len] = s->offset;
+    for (size_t i = 1; i < len; i++) {
+        uint8_t *entry = s->bytes + offsets[i];
+        size_t entry_len = offsets[i + 1] - offsets[i];
+        size_t j = i;
+        while (j > 0 &&
+               serde_compare_slices(s->bytes + offsets[j - 1],
+                                    offsets[j] - offsets[j - 1], entry,
+                                    entry_len) > 0) {
+            j--;
+        }
+        if (j == i) {
+            continue;
+        }
+        // Rotate the range [offsets[j], offsets[i + 1]) to move entry `i`
+        // to position `j`.
+        uint8_t *start = s->bytes + offsets[j];
+        uint8_t *end = s->bytes + offsets[i + 1];
+        serde_reverse_bytes(start, entry);
+        serde_reverse_bytes(entry, end);
+        serde_reverse_bytes(start, end);
+        for (size_t k = i; k > j; k--) {
+            offsets[k] = offsets[k - 1] + entry_len;
+        }
+    }
+    s->arena->offset = (size_t)((uint8_t *)offsets - s->arena->data);
+    return SERDE_OK;
+}
+
+// --- Deserialization ---
+
+static inline serde_error_t serde_deserializer_read(serde_deserializer_t *d,
+                                                    void *data, size_t len) {
+    if (len > d->length - d->offset) {
+        return SERDE_ERROR_END_OF_INPUT;
+    }
+    if (len > 0) {
+        memcpy(data, d->bytes + d->offset, len);
+    }
+    d->offset += len;
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_deserializer_alloc(serde_deserializer_t *d,
+                                                     size_t size,
+                                                     void **result) {
+    return serde_arena_alloc(d->arena, size, result);
+}
+
+static inline serde_error_t
+serde_deserializer_alloc_array(serde_deserializer_t *d, size_t count,
+                               size_t size, void **result) {
+    return serde_arena_alloc_array(d->arena, count, size, result);
+}
+
+// Allocate `count` elements in the arena of a deserializer and assign the
+// result to the pointer `ptr`. Return from the current function on error.
+#define SERDE_DESERIALIZER_ALLOC(deserializer, ptr, count)                     \
+    do {                                                                       \
+        void *serde_ptr_;                                                      \
+        SERDE_TRY(serde_deserializer_alloc_array(                              \
+            (deserializer), (count), sizeof(*(ptr)), &serde_ptr_));            \
+        (ptr) = serde_ptr_;                                                    \
+    } while (0)
+
+static inline serde_error_t serde_deserialize_u8(serde_deserializer_t *d,
+                                                 uint8_t *value) {
+    return serde_deserializer_read(d, value, 1);
+}
+
+static inline serde_error_t serde_deserialize_u16(serde_deserializer_t *d,
+                                                  uint16_t *value) {
+    uint8_t buf[2];
+    SERDE_TRY(serde_deserializer_read(d, buf, sizeof(buf)));
+    *value = (uint16_t)(buf[0] | ((uint16_t)buf[1] << 8));
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_deserialize_u32(serde_deserializer_t *d,
+                                                  uint32_t *value) {
+    uint8_t buf[4];
+    SERDE_TRY(serde_deserializer_read(d, buf, sizeof(buf)));
+    *value = 0;
+    for (int i = 0; i < 4; i++) {
+        *value |= (uint32_t)buf[i] << (8 * i);
+    }
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_deserialize_u64(serde_deserializer_t *d,
+                                                  uint64_t *value) {
+    uint8_t buf[8];
+    SERDE_TRY(serde_deserializer_read(d, buf, sizeof(buf)));
+    *value = 0;
+    for (int i = 0; i < 8; i++) {
+        *value |= (uint64_t)buf[i] << (8 * i);
+    }
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_deserialize_u128(serde_deserializer_t *d,
+                                                   serde_uint128_t *value) {
+    SERDE_TRY(serde_deserialize_u64(d, &value->low));
+    return serde_deserialize_u64(d, &value->high);
+}
+
+static inline serde_error_t serde_deserialize_i8(serde_deserializer_t *d,
+                                                 int8_t *value) {
+    uint8_t result;
+    SERDE_TRY(serde_deserialize_u8(d, &result));
+    *value = (int8_t)result;
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_deserialize_i16(serde_deserializer_t *d,
+                                                  int16_t *value) {
+    uint16_t result;
+    SERDE_TRY(serde_deserialize_u16(d, &result));
+    *value = (int16_t)result;
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_deserialize_i32(serde_deserializer_t *d,
+                                                  int32_t *value) {
+    uint32_t result;
+    SERDE_TRY(serde_deserialize_u32(d, &result));
+    *value = (int32_t)result;
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_deserialize_i64(serde_deserializer_t *d,
+                                                  int64_t *value) {
+    uint64_t result;
+    SERDE_TRY(serde_deserialize_u64(d, &result));
+    *value = (int64_t)result;
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_deserialize_i128(serde_deserializer_t *d,
+                                                   serde_int128_t *value) {
+    SERDE_TRY(serde_deserialize_u64(d, &value->low));
+    return serde_deserialize_i64(d, &value->high);
+}
+
+static inline serde_error_t serde_deserialize_unit(serde_deserializer_t *d,
+                                                   serde_unit_t *value) {
+    (void)d;
+    *value = 0;
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_deserialize_bool(serde_deserializer_t *d,
+                                                   bool *value) {
+    uint8_t byte;
+    SERDE_TRY(serde_deserialize_u8(d, &byte));
+    switch (byte) {
+    case 0:
+        *value = false;
+        return SERDE_OK;
+    case 1:
+        *value = true;
+        return SERDE_OK;
+    default:
+        return SERDE_ERROR_INVALID_VALUE;
+    }
+}
+
+static inline serde_error_t serde_deserialize_char(serde_deserializer_t *d,
+                                                   uint32_t *value) {
+    (void)d;
+    (void)value;
+    return SERDE_ERROR_NOT_SUPPORTED;
+}
+
+static inline serde_error_t serde_deserialize_f32(serde_deserializer_t *d,
+                                                  float *value) {
+    uint32_t bits;
+    if (!d->format->supports_floats) {
+        return SERDE_ERROR_NOT_SUPPORTED;
+    }
+    SERDE_TRY(serde_deserialize_u32(d, &bits));
+    memcpy(value, &bits, sizeof(bits));
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_deserialize_f64(serde_deserializer_t *d,
+                                                  double *value) {
+    uint64_t bits;
+    if (!d->format->supports_floats) {
+        return SERDE_ERROR_NOT_SUPPORTED;
+    }
+    SERDE_TRY(serde_deserialize_u64(d, &bits));
+    memcpy(value, &bits, sizeof(bits));
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_deserialize_len(serde_deserializer_t *d,
+                                                  size_t *value) {
+    return d->format->deserialize_len(d, value);
+}
+
+static inline serde_error_t
+serde_deserialize_variant_index(serde_deserializer_t *d, uint32_t *value) {
+    return d->format->deserialize_variant_index(d, value);
+}
+
+static inline serde_error_t
+serde_deserialize_option_tag(serde_deserializer_t *d, bool *value) {
+    return serde_deserialize_bool(d, value);
+}
+
+static inline bool serde_is_valid_utf8(const uint8_t *input, size_t len) {
+    uint8_t trailing_digits = 0;
+    for (size_t i = 0; i < len; i++) {
+        uint8_t byte = input[i];
+        if (trailing_digits == 0) {
+            // Start new codepoint.
+            if (byte >> 7 == 0) {
+                // ASCII character
+            } else if (byte >> 5 == 0x6) {
+                // Expecting a 2-byte codepoint
+                trailing_digits = 1;
+            } else if (byte >> 4 == 0xe) {
+                // Expecting a 3-byte codepoint
+                trailing_digits = 2;
+            } else if (byte >> 3 == 0x1e) {
+                // Expecting a 4-byte codepoint
+                trailing_digits = 3;
+            } else {
+                return false;
+            }
+        } else {
+            // Process "trailing digit".
+            if (byte >> 6 != 0x2) {
+                return false;
+            }
+            trailing_digits -= 1;
+        }
+    }
+    return trailing_digits == 0;
+}
+
+static inline serde_error_t serde_deserialize_bytes(serde_deserializer_t *d,
+                                                    serde_bytes_t *value) {
+    size_t len;
+    void *data;
+    SERDE_TRY(serde_deserialize_len(d, &len));
+    if (len > d->length - d->offset) {
+        return SERDE_ERROR_END_OF_INPUT;
+    }
+    SERDE_TRY(serde_deserializer_alloc(d, len, &data));
+    SERDE_TRY(serde_deserializer_read(d, data, len));
+    value->data = (const uint8_t *)data;
+    value->len = len;
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_deserialize_str(serde_deserializer_t *d,
+                                                  serde_str_t *value) {
+    serde_bytes_t bytes;
+    SERDE_TRY(serde_deserialize_bytes(d, &bytes));
+    if (!serde_is_valid_utf8(bytes.data, bytes.len)) {
+        return SERDE_ERROR_INVALID_VALUE;
+    }
+    value->data = (const char *)bytes.data;
+    value->len = bytes.len;
+    return SERDE_OK;
+}
+
+static inline size_t
+serde_deserializer_get_buffer_offset(serde_deserializer_t *d) {
+    return d->offset;
+}
+
+static inline serde_error_t
+serde_deserializer_increase_container_depth(serde_deserializer_t *d) {
+    if (d->container_depth_budget == 0) {
+        return SERDE_ERROR_CONTAINER_DEPTH;
+    }
+    d->container_depth_budget--;
+    return SERDE_OK;
+}
+
+static inline void
+serde_deserializer_decrease_container_depth(serde_deserializer_t *d) {
+    d->container_depth_budget++;
+}
+
+// Check that the serialized key in the range [start2, end2) is strictly
+// greater than the previous key [start1, end1), if the encoding requires map
+// entries to be sorted.
+static inline serde_error_t serde_deserializer_check_that_key_slices_are_increasing(
+    serde_deserializer_t *d, size_t start1, size_t end1, size_t start2,
+    size_t end2) {
+    if (!d->format->enforce_strict_map_ordering) {
+        return SERDE_OK;
+    }
+    if (serde_compare_slices(d->bytes + start1, end1 - start1,
+                             d->bytes + start2, end2 - start2) >= 0) {
+        return SERDE_ERROR_UNSORTED_MAP_KEYS;
+    }
+    return SERDE_OK;
+}
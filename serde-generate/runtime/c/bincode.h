@@ -0,0 +1,65 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#pragma once
+
+#include "binary.h"
+#include "serde.h"
+
+// Maximum length allowed for sequences (vectors, bytes, strings) and maps.
+#define SERDE_BINCODE_MAX_LENGTH ((size_t)((1ull << 31) - 1))
+
+static inline serde_error_t serde_bincode_serialize_len(serde_serializer_t *s,
+                                                        size_t value) {
+    if (value > SERDE_BINCODE_MAX_LENGTH) {
+        return SERDE_ERROR_LENGTH_TOO_LARGE;
+    }
+    return serde_serialize_u64(s, (uint64_t)value);
+}
+
+static inline serde_error_t
+serde_bincode_serialize_variant_index(serde_serializer_t *s, uint32_t value) {
+    return serde_serialize_u32(s, value);
+}
+
+static inline serde_error_t
+serde_bincode_deserialize_len(serde_deserializer_t *d, size_t *value) {
+    uint64_t result;
+    SERDE_TRY(serde_deserialize_u64(d, &result));
+    if (result > SERDE_BINCODE_MAX_LENGTH) {
+        return SERDE_ERROR_LENGTH_TOO_LARGE;
+    }
+    *value = (size_t)result;
+    return SERDE_OK;
+}
+
+static inline serde_error_t
+serde_bincode_deserialize_variant_index(serde_deserializer_t *d,
+                                        uint32_t *value) {
+    return serde_deserialize_u32(d, value);
+}
+
+static const serde_binary_format_t serde_bincode_format = {
+    serde_bincode_serialize_len,
+    serde_bincode_serialize_variant_index,
+    serde_bincode_deserialize_len,
+    serde_bincode_deserialize_variant_index,
+    true,
+    false,
+    SIZE_MAX,
+};
+
+static inline void
+serde_bincode_serializer_init(serde_serializer_t *serializer, uint8_t *bytes,
+                              size_t capacity, serde_arena_t *arena) {
+    serde_serializer_init(serializer, &serde_bincode_format, bytes, capacity,
+                          arena);
+}
+
+static inline void
+serde_bincode_deserializer_init(serde_deserializer_t *deserializer,
+                                const uint8_t *bytes, size_t length,
+                                serde_arena_t *arena) {
+    serde_deserializer_init(deserializer, &serde_bincode_format, bytes, length,
+                            arena);
+}
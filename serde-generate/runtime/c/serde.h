@@ -0,0 +1,177 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#pragma once
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <string.h>
+
+// Error codes returned by (de)serialization functions.
+typedef enum serde_error {
+    SERDE_OK = 0,
+    // The output buffer is too small.
+    SERDE_ERROR_BUFFER_TOO_SMALL,
+    // The input is not large enough.
+    SERDE_ERROR_END_OF_INPUT,
+    // The arena is missing or exhausted.
+    SERDE_ERROR_ARENA_EXHAUSTED,
+    // Invalid encoding of a value (e.g. boolean, UTF-8 string, ULEB128 integer).
+    SERDE_ERROR_INVALID_VALUE,
+    // Unknown variant index for an enum.
+    SERDE_ERROR_UNKNOWN_VARIANT,
+    // Length of a sequence, a string or a map is too large.
+    SERDE_ERROR_LENGTH_TOO_LARGE,
+    // Too many nested containers.
+    SERDE_ERROR_CONTAINER_DEPTH,
+    // Keys of a map are not serialized in the expected order.
+    SERDE_ERROR_UNSORTED_MAP_KEYS,
+    // Some input bytes were not read.
+    SERDE_ERROR_TRAILING_BYTES,
+    // The operation is not supported by the encoding.
+    SERDE_ERROR_NOT_SUPPORTED,
+} serde_error_t;
+
+// Propagate errors to the caller.
+#define SERDE_TRY(expr)                                                        \
+    do {                                                                       \
+        serde_error_t serde_err_ = (expr);                                     \
+        if (serde_err_ != SERDE_OK) {                                          \
+            return serde_err_;                                                 \
+        }                                                                      \
+    } while (0)
+
+// Human-readable description of an error code.
+static inline const char *serde_error_message(serde_error_t error) {
+    switch (error) {
+    case SERDE_OK:
+        return "No error";
+    case SERDE_ERROR_BUFFER_TOO_SMALL:
+        return "Output buffer is too small";
+    case SERDE_ERROR_END_OF_INPUT:
+        return "Input is not large enough";
+    case SERDE_ERROR_ARENA_EXHAUSTED:
+        return "Arena is missing or exhausted";
+    case SERDE_ERROR_INVALID_VALUE:
+        return "Invalid value";
+    case SERDE_ERROR_UNKNOWN_VARIANT:
+        return "Unknown variant index for enum";
+    case SERDE_ERROR_LENGTH_TOO_LARGE:
+        return "Length is too large";
+    case SERDE_ERROR_CONTAINER_DEPTH:
+        return "Too many nested containers";
+    case SERDE_ERROR_UNSORTED_MAP_KEYS:
+        return "Error while decoding map: keys are not serialized in the "
+               "expected order";
+    case SERDE_ERROR_TRAILING_BYTES:
+        return "Some input bytes were not read";
+    case SERDE_ERROR_NOT_SUPPORTED:
+        return "Not supported";
+    }
+    return "Unknown error";
+}
+
+// Unit values.
+typedef uint8_t serde_unit_t;
+
+// Basic implementation for 128-bit unsigned integers.
+typedef struct serde_uint128 {
+    uint64_t high;
+    uint64_t low;
+} serde_uint128_t;
+
+// 128-bit signed integers.
+typedef struct serde_int128 {
+    int64_t high;
+    uint64_t low;
+} serde_int128_t;
+
+// UTF-8 strings (not null-terminated).
+typedef struct serde_str {
+    const char *data;
+    size_t len;
+} serde_str_t;
+
+// Byte arrays.
+typedef struct serde_bytes {
+    const uint8_t *data;
+    size_t len;
+} serde_bytes_t;
+
+// Alignment used for all allocations in an arena.
+typedef union serde_max_align {
+    long double f;
+    uint64_t u;
+    void *p;
+    void (*fp)(void);
+} serde_max_align_t;
+
+#define SERDE_ALIGNMENT (sizeof(serde_max_align_t))
+
+// A caller-provided memory region used to allocate variable-size data
+// (sequences, strings, maps, options and boxed values) without malloc.
+typedef struct serde_arena {
+    uint8_t *data;
+    size_t capacity;
+    size_t offset;
+} serde_arena_t;
+
+static inline void serde_arena_init(serde_arena_t *arena, void *data,
+                                    size_t capacity) {
+    arena->data = (uint8_t *)data;
+    arena->capacity = capacity;
+    arena->offset = 0;
+}
+
+// Release all allocations at once.
+static inline void serde_arena_reset(serde_arena_t *arena) {
+    arena->offset = 0;
+}
+
+static inline serde_error_t serde_arena_alloc(serde_arena_t *arena,
+                                              size_t size, void **result) {
+    if (size == 0) {
+        *result = NULL;
+        return SERDE_OK;
+    }
+    if (arena == NULL) {
+        return SERDE_ERROR_ARENA_EXHAUSTED;
+    }
+    size_t start = (size_t)(arena->data + arena->offset) % SERDE_ALIGNMENT;
+    start = (start == 0) ? arena->offset
+                         : arena->offset + (SERDE_ALIGNMENT - start);
+    if (start > arena->capacity || size > arena->capacity - start) {
+        return SERDE_ERROR_ARENA_EXHAUSTED;
+    }
+    *result = arena->data + start;
+    arena->offset = start + size;
+    return SERDE_OK;
+}
+
+static inline serde_error_t serde_arena_alloc_array(serde_arena_t *arena,
+                                                    size_t count, size_t size,
+                                                    void **result) {
+    if (size != 0 && count > SIZE_MAX / size) {
+        return SERDE_ERROR_ARENA_EXHAUSTED;
+    }
+    return serde_arena_alloc(arena, count * size, result);
+}
+
+// Convenience constructor for strings.
+static inline serde_str_t serde_str_from_cstr(const char *value) {
+    serde_str_t result;
+    result.data = value;
+    result.len = strlen(value);
+    return result;
+}
+
+static inline bool serde_str_equal(serde_str_t lhs, serde_str_t rhs) {
+    return lhs.len == rhs.len &&
+           (lhs.len == 0 || memcmp(lhs.data, rhs.data, lhs.len) == 0);
+}
+
+static inline bool serde_bytes_equal(serde_bytes_t lhs, serde_bytes_t rhs) {
+    return lhs.len == rhs.len &&
+           (lhs.len == 0 || memcmp(lhs.data, rhs.data, lhs.len) == 0);
+}
@@ -57,7 +57,8 @@ inline void BcsSerializer::serialize_u32_as_uleb128(uint32_t value) {
 
 inline void BcsSerializer::serialize_len(size_t value) {
     if (value > BCS_MAX_LENGTH) {
-        throw serde::serialization_error("Length is too large");
+        fail("Length is too large");
+        return;
     }
     serialize_u32_as_uleb128((uint32_t)value);
 }
@@ -98,25 +99,26 @@ inline uint32_t BcsDeserializer::deserialize_uleb128_as_u32() {
         auto digit = byte & 0x7F;
         value |= (uint64_t)digit << shift;
         if (value > std::numeric_limits<uint32_t>::max()) {
-            throw serde::deserialization_error(
-                "Overflow while parsing uleb128-encoded uint32 value");
+            fail("Overflow while parsing uleb128-encoded uint32 value");
+            return 0;
         }
         if (digit == byte) {
             if (shift > 0 && digit == 0) {
-                throw serde::deserialization_error(
-                    "Invalid uleb128 number (unexpected zero digit)");
+                fail("Invalid uleb128 number (unexpected zero digit)");
+                return 0;
             }
             return (uint32_t)value;
         }
     }
-    throw serde::deserialization_error(
-        "Overflow while parsing uleb128-encoded uint32 value");
+    fail("Overflow while parsing uleb128-encoded uint32 value");
+    return 0;
 }
 
 inline size_t BcsDeserializer::deserialize_len() {
     auto value = deserialize_uleb128_as_u32();
     if (value > BCS_MAX_LENGTH) {
-        throw serde::deserialization_error("Length is too large");
+        fail("Length is too large");
+        return 0;
     }
     return (size_t)value;
 }
@@ -131,9 +133,8 @@ inline void BcsDeserializer::check_that_key_slices_are_increasing(
                                       bytes_.cbegin() + std::get<1>(key1),
                                       bytes_.cbegin() + std::get<0>(key2),
                                       bytes_.cbegin() + std::get<1>(key2))) {
-        throw serde::deserialization_error(
-            "Error while decoding map: keys are not serialized in the "
-            "expected order");
+        fail("Error while decoding map: keys are not serialized in the "
+             "expected order");
     }
 }
 
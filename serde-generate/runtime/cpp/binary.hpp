@@ -5,6 +5,7 @@
 
 #include <algorithm>
 #include <cassert>
+#include <optional>
 #include <variant>
 
 #include "serde.hpp"
@@ -16,12 +17,22 @@ class BinarySerializer {
   protected:
     std::vector<uint8_t> bytes_;
     size_t container_depth_budget_;
+#ifdef SERDE_NO_EXCEPTIONS
+    std::optional<serde::error> error_;
+#endif
 
   public:
     BinarySerializer(size_t max_container_depth)
         : container_depth_budget_(max_container_depth) {}
 
-    void serialize_str(const std::string &value);
+    // Throw a `serialization_error`, or record the first error if exceptions are disabled.
+    void fail(std::string message);
+    bool has_error() const;
+#ifdef SERDE_NO_EXCEPTIONS
+    const serde::error &error() const { return *error_; }
+#endif
+
+    void serialize_str(std::string_view value);
 
     void serialize_bool(bool value);
     void serialize_unit();
@@ -53,6 +64,9 @@ template <class D>
 class BinaryDeserializer {
     size_t pos_;
     size_t container_depth_budget_;
+#ifdef SERDE_NO_EXCEPTIONS
+    std::optional<serde::error> error_;
+#endif
 
   protected:
     std::vector<uint8_t> bytes_;
@@ -63,6 +77,14 @@ class BinaryDeserializer {
         : pos_(0), container_depth_budget_(max_container_depth),
           bytes_(std::move(bytes)) {}
 
+    // Throw a `deserialization_error`, or record the first error if exceptions are
+    // disabled. In the latter case, reads return zero bytes from then on.
+    void fail(std::string message);
+    bool has_error() const;
+#ifdef SERDE_NO_EXCEPTIONS
+    const serde::error &error() const { return *error_; }
+#endif
+
     std::string deserialize_str();
 
     bool deserialize_bool();
@@ -91,7 +113,27 @@ class BinaryDeserializer {
 };
 
 template <class S>
-void BinarySerializer<S>::serialize_str(const std::string &value) {
+void BinarySerializer<S>::fail(std::string message) {
+#ifdef SERDE_NO_EXCEPTIONS
+    if (!error_) {
+        error_ = serde::error(std::move(message));
+    }
+#else
+    throw serde::serialization_error(message);
+#endif
+}
+
+template <class S>
+bool BinarySerializer<S>::has_error() const {
+#ifdef SERDE_NO_EXCEPTIONS
+    return error_.has_value();
+#else
+    return false;
+#endif
+}
+
+template <class S>
+void BinarySerializer<S>::serialize_str(std::string_view value) {
     static_cast<S *>(this)->serialize_len(value.size());
     for (auto c : value) {
         bytes_.push_back(c);
@@ -103,17 +145,17 @@ void BinarySerializer<S>::serialize_unit() {}
 
 template <class S>
 void BinarySerializer<S>::serialize_f32(float) {
-    throw serde::serialization_error("not implemented");
+    fail("not implemented");
 }
 
 template <class S>
 void BinarySerializer<S>::serialize_f64(double) {
-    throw serde::serialization_error("not implemented");
+    fail("not implemented");
 }
 
 template <class S>
 void BinarySerializer<S>::serialize_char(char32_t) {
-    throw serde::serialization_error("not implemented");
+    fail("not implemented");
 }
 
 template <class S>
@@ -197,7 +239,8 @@ size_t BinarySerializer<S>::get_buffer_offset() {
 template <class S>
 void BinarySerializer<S>::increase_container_depth() {
     if (container_depth_budget_ == 0) {
-        throw serialization_error("Too many nested containers");
+        fail("Too many nested containers");
+        return;
     }
     container_depth_budget_--;
 }
@@ -207,10 +250,34 @@ void BinarySerializer<S>::decrease_container_depth() {
     container_depth_budget_++;
 }
 
+template <class D>
+void BinaryDeserializer<D>::fail(std::string message) {
+#ifdef SERDE_NO_EXCEPTIONS
+    if (!error_) {
+        error_ = serde::error(std::move(message));
+    }
+#else
+    throw serde::deserialization_error(message);
+#endif
+}
+
+template <class D>
+bool BinaryDeserializer<D>::has_error() const {
+#ifdef SERDE_NO_EXCEPTIONS
+    return error_.has_value();
+#else
+    return false;
+#endif
+}
+
 template <class D>
 uint8_t BinaryDeserializer<D>::read_byte() {
+    if (has_error()) {
+        return 0;
+    }
     if (pos_ >= bytes_.size()) {
-        throw serde::deserialization_error("Input is not large enough");
+        fail("Input is not large enough");
+        return 0;
     }
     return bytes_.at(pos_++);
 }
@@ -249,12 +316,12 @@ template <class D>
 std::string BinaryDeserializer<D>::deserialize_str() {
     auto len = static_cast<D *>(this)->deserialize_len();
     std::string result;
-    result.reserve(len);
-    for (size_t i = 0; i < len; i++) {
+    result.reserve(std::min(len, bytes_.size() - pos_));
+    for (size_t i = 0; i < len && !has_error(); i++) {
         result.push_back(read_byte());
     }
     if (!is_valid_utf8(result)) {
-        throw serde::deserialization_error("Invalid UTF8 string: " + result);
+        fail("Invalid UTF8 string: " + result);
     }
     return result;
 }
@@ -266,17 +333,20 @@ std::monostate BinaryDeserializer<D>::deserialize_unit() {
 
 template <class D>
 float BinaryDeserializer<D>::deserialize_f32() {
-    throw serde::deserialization_error("not implemented");
+    fail("not implemented");
+    return 0;
 }
 
 template <class D>
 double BinaryDeserializer<D>::deserialize_f64() {
-    throw serde::deserialization_error("not implemented");
+    fail("not implemented");
+    return 0;
 }
 
 template <class D>
 char32_t BinaryDeserializer<D>::deserialize_char() {
-    throw serde::deserialization_error("not implemented");
+    fail("not implemented");
+    return 0;
 }
 
 template <class D>
@@ -287,7 +357,8 @@ bool BinaryDeserializer<D>::deserialize_bool() {
     case 1:
         return true;
     default:
-        throw serde::deserialization_error("Invalid boolean value");
+        fail("Invalid boolean value");
+        return false;
     }
 }
 
@@ -377,7 +448,8 @@ size_t BinaryDeserializer<D>::get_buffer_offset() {
 template <class S>
 void BinaryDeserializer<S>::increase_container_depth() {
     if (container_depth_budget_ == 0) {
-        throw deserialization_error("Too many nested containers");
+        fail("Too many nested containers");
+        return;
     }
     container_depth_budget_--;
 }
@@ -58,7 +58,8 @@ inline void BincodeSerializer::serialize_f64(double value) {
 
 inline void BincodeSerializer::serialize_len(size_t value) {
     if (value > BINCODE_MAX_LENGTH) {
-        throw serde::serialization_error("Length is too large");
+        fail("Length is too large");
+        return;
     }
     Parent::serialize_u64((uint64_t)value);
 }
@@ -80,7 +81,8 @@ inline double BincodeDeserializer::deserialize_f64() {
 inline size_t BincodeDeserializer::deserialize_len() {
     auto value = (size_t)Parent::deserialize_u64();
     if (value > BINCODE_MAX_LENGTH) {
-        throw serde::deserialization_error("Length is too large");
+        fail("Length is too large");
+        return 0;
     }
     return (size_t)value;
 }
@@ -4,17 +4,27 @@
 #pragma once
 
 #include <array>
+#include <cassert>
 #include <cstdint>
 #include <functional>
 #include <map>
 #include <memory>
 #include <optional>
 #include <string>
+#include <string_view>
 #include <tuple>
 #include <type_traits>
 #include <variant>
 #include <vector>
 
+// Errors are reported with exceptions unless `SERDE_NO_EXCEPTIONS` is defined. This is the
+// default when compiling with `-fno-exceptions`. In this mode, serializers and deserializers
+// record the first error instead (see `has_error()`) and stop reading their input.
+#if !defined(SERDE_NO_EXCEPTIONS) && !defined(__cpp_exceptions) &&                    \
+    !defined(__EXCEPTIONS) && !defined(_CPPUNWIND)
+#define SERDE_NO_EXCEPTIONS
+#endif
+
 namespace serde {
 
 class serialization_error : public std::invalid_argument {
@@ -33,6 +43,57 @@ class deserialization_error : public std::invalid_argument {
         : std::invalid_argument(what_arg) {}
 };
 
+// Error recorded by serializers and deserializers when exceptions are disabled.
+class error {
+  public:
+    explicit error(std::string message) : message_(std::move(message)) {}
+
+    const char *what() const { return message_.c_str(); }
+
+  private:
+    std::string message_;
+};
+
+// Either a value or an error. This is a minimal version of C++23 `std::expected`.
+template <typename T>
+class expected {
+  public:
+    expected(T value) : storage_(std::in_place_index<0>, std::move(value)) {}
+
+    expected(serde::error error)
+        : storage_(std::in_place_index<1>, std::move(error)) {}
+
+    bool has_value() const { return storage_.index() == 0; }
+
+    explicit operator bool() const { return has_value(); }
+
+    T &value() {
+        assert(has_value());
+        return *std::get_if<0>(&storage_);
+    }
+
+    const T &value() const {
+        assert(has_value());
+        return *std::get_if<0>(&storage_);
+    }
+
+    T &operator*() { return value(); }
+
+    const T &operator*() const { return value(); }
+
+    T *operator->() { return &value(); }
+
+    const T *operator->() const { return &value(); }
+
+    const serde::error &error() const {
+        assert(!has_value());
+        return *std::get_if<1>(&storage_);
+    }
+
+  private:
+    std::variant<T, serde::error> storage_;
+};
+
 // Basic implementation for 128-bit unsigned integers.
 struct uint128_t {
     uint64_t high;
@@ -63,6 +124,8 @@ inline bool operator==(const int128_t &lhs, const int128_t &rhs) {
 template <typename T>
 class value_ptr {
   public:
+    using element_type = T;
+
     value_ptr() : ptr_(nullptr) {}
 
     value_ptr(const T &value) : ptr_(new T{value}) {}
@@ -109,7 +172,7 @@ bool operator==(const value_ptr<T> &lhs, const value_ptr<T> &rhs) {
 
 // Trait to enable serialization of values of type T.
 // This is similar to the `serde::Serialize` trait in Rust.
-template <typename T>
+template <typename T, typename Enable = void>
 struct Serializable {
     template <typename Serializer>
     static void serialize(const T &value, Serializer &serializer);
@@ -117,12 +180,50 @@ struct Serializable {
 
 // Trait to enable deserialization of values of type T.
 // This is similar to the `serde::Deserialize` trait in Rust.
-template <typename T>
+template <typename T, typename Enable = void>
 struct Deserializable {
     template <typename Deserializer>
     static T deserialize(Deserializer &deserializer);
 };
 
+// --- Hooks for other container types ---
+// Generated code may use other types than the standard ones (see `cpp::TypeMapping` in
+// serde-generate). Such types are (de)serialized by specializing one of the traits below, e.g.
+// `template <typename T> struct is_sequence<absl::InlinedVector<T, 4>> : std::true_type {};`
+
+// Sequences with `value_type`, `size()`, iteration and `push_back()`.
+template <typename T>
+struct is_sequence : std::false_type {};
+
+// Maps with `key_type`, `mapped_type`, `size()`, iteration over pairs and `emplace()`.
+// Entries are sorted during serialization if the format requires it.
+template <typename T>
+struct is_map : std::false_type {};
+
+// Strings with `data()`, `size()` and a constructor taking `(const char *, size_t)`.
+template <typename T>
+struct is_string : std::false_type {};
+
+// Optional values with `value_type`, `has_value()`, `operator*` and assignment from a
+// value. Default-constructed values must be empty.
+template <typename T>
+struct is_optional : std::false_type {};
+
+// Non-nullable pointers with `element_type`, `operator*` and a constructor taking an
+// `element_type`.
+template <typename T>
+struct is_box : std::false_type {};
+
+// Types defining their own (de)serialization with the static member templates
+// `T::serde_serialize(value, serializer)` and `T::serde_deserialize(deserializer)`, as signaled
+// by a member type `T::serde_members`. Generated code uses this for generic containers (i.e.
+// class templates) and their variants.
+template <typename T, typename Enable = void>
+struct has_serde_members : std::false_type {};
+
+template <typename T>
+struct has_serde_members<T, std::void_t<typename T::serde_members>> : std::true_type {};
+
 // --- Implementation of Serializable for base types ---
 
 // string
@@ -270,6 +371,26 @@ struct Serializable<int128_t> {
     }
 };
 
+// Strings with another allocator
+template <typename Allocator>
+struct Serializable<std::basic_string<char, std::char_traits<char>, Allocator>> {
+    template <typename Serializer>
+    static void
+    serialize(const std::basic_string<char, std::char_traits<char>, Allocator> &value,
+              Serializer &serializer) {
+        serializer.serialize_str(std::string_view(value.data(), value.size()));
+    }
+};
+
+// Strings (hook)
+template <typename T>
+struct Serializable<T, std::enable_if_t<is_string<T>::value>> {
+    template <typename Serializer>
+    static void serialize(const T &value, Serializer &serializer) {
+        serializer.serialize_str(std::string_view(value.data(), value.size()));
+    }
+};
+
 // --- Derivation of Serializable for composite types ---
 
 // Value pointers (non-nullable)
@@ -296,6 +417,29 @@ struct Serializable<std::optional<T>> {
     }
 };
 
+// Non-nullable pointers (hook)
+template <typename T>
+struct Serializable<T, std::enable_if_t<is_box<T>::value>> {
+    template <typename Serializer>
+    static void serialize(const T &value, Serializer &serializer) {
+        Serializable<typename T::element_type>::serialize(*value, serializer);
+    }
+};
+
+// Options (hook)
+template <typename T>
+struct Serializable<T, std::enable_if_t<is_optional<T>::value>> {
+    template <typename Serializer>
+    static void serialize(const T &option, Serializer &serializer) {
+        if (option.has_value()) {
+            serializer.serialize_option_tag(true);
+            Serializable<typename T::value_type>::serialize(*option, serializer);
+        } else {
+            serializer.serialize_option_tag(false);
+        }
+    }
+};
+
 // Vectors (sequences)
 template <typename T, typename Allocator>
 struct Serializable<std::vector<T, Allocator>> {
@@ -309,6 +453,27 @@ struct Serializable<std::vector<T, Allocator>> {
     }
 };
 
+// Sequences (hook)
+template <typename T>
+struct Serializable<T, std::enable_if_t<is_sequence<T>::value>> {
+    template <typename Serializer>
+    static void serialize(const T &value, Serializer &serializer) {
+        serializer.serialize_len(value.size());
+        for (const auto &item : value) {
+            Serializable<typename T::value_type>::serialize(item, serializer);
+        }
+    }
+};
+
+// Types with (de)serialization members (hook)
+template <typename T>
+struct Serializable<T, std::enable_if_t<has_serde_members<T>::value>> {
+    template <typename Serializer>
+    static void serialize(const T &value, Serializer &serializer) {
+        T::serde_serialize(value, serializer);
+    }
+};
+
 // Fixed-size arrays
 template <typename T, std::size_t N>
 struct Serializable<std::array<T, N>> {
@@ -321,24 +486,40 @@ struct Serializable<std::array<T, N>> {
     }
 };
 
+// Maps (common implementation)
+template <typename K, typename V, typename Map, typename Serializer>
+void serialize_map(const Map &value, Serializer &serializer) {
+    serializer.serialize_len(value.size());
+    std::vector<size_t> offsets;
+    for (const auto &item : value) {
+        if constexpr (Serializer::enforce_strict_map_ordering) {
+            offsets.push_back(serializer.get_buffer_offset());
+        }
+        Serializable<K>::serialize(item.first, serializer);
+        Serializable<V>::serialize(item.second, serializer);
+    }
+    if constexpr (Serializer::enforce_strict_map_ordering) {
+        serializer.sort_last_entries(std::move(offsets));
+    }
+}
+
 // Maps
-template <typename K, typename V, typename Allocator>
-struct Serializable<std::map<K, V, Allocator>> {
+template <typename K, typename V, typename Compare, typename Allocator>
+struct Serializable<std::map<K, V, Compare, Allocator>> {
     template <typename Serializer>
-    static void serialize(const std::map<K, V, Allocator> &value,
+    static void serialize(const std::map<K, V, Compare, Allocator> &value,
                           Serializer &serializer) {
-        serializer.serialize_len(value.size());
-        std::vector<size_t> offsets;
-        for (const auto &item : value) {
-            if constexpr (Serializer::enforce_strict_map_ordering) {
-                offsets.push_back(serializer.get_buffer_offset());
-            }
-            Serializable<K>::serialize(item.first, serializer);
-            Serializable<V>::serialize(item.second, serializer);
-        }
-        if constexpr (Serializer::enforce_strict_map_ordering) {
-            serializer.sort_last_entries(std::move(offsets));
-        }
+        serialize_map<K, V>(value, serializer);
+    }
+};
+
+// Maps (hook)
+template <typename T>
+struct Serializable<T, std::enable_if_t<is_map<T>::value>> {
+    template <typename Serializer>
+    static void serialize(const T &value, Serializer &serializer) {
+        serialize_map<typename T::key_type, typename T::mapped_type>(value,
+                                                                     serializer);
     }
 };
 
@@ -521,6 +702,27 @@ struct Deserializable<int128_t> {
     }
 };
 
+// Strings with another allocator
+template <typename Allocator>
+struct Deserializable<std::basic_string<char, std::char_traits<char>, Allocator>> {
+    template <typename Deserializer>
+    static std::basic_string<char, std::char_traits<char>, Allocator>
+    deserialize(Deserializer &deserializer) {
+        auto value = deserializer.deserialize_str();
+        return {value.data(), value.size()};
+    }
+};
+
+// Strings (hook)
+template <typename T>
+struct Deserializable<T, std::enable_if_t<is_string<T>::value>> {
+    template <typename Deserializer>
+    static T deserialize(Deserializer &deserializer) {
+        auto value = deserializer.deserialize_str();
+        return T(value.data(), value.size());
+    }
+};
+
 // --- Derivation of Deserializable for composite types ---
 
 // Value pointers
@@ -546,47 +748,112 @@ struct Deserializable<std::optional<T>> {
     }
 };
 
+// Non-nullable pointers (hook)
+template <typename T>
+struct Deserializable<T, std::enable_if_t<is_box<T>::value>> {
+    template <typename Deserializer>
+    static T deserialize(Deserializer &deserializer) {
+        return T(Deserializable<typename T::element_type>::deserialize(deserializer));
+    }
+};
+
+// Options (hook)
+template <typename T>
+struct Deserializable<T, std::enable_if_t<is_optional<T>::value>> {
+    template <typename Deserializer>
+    static T deserialize(Deserializer &deserializer) {
+        T result;
+        auto tag = deserializer.deserialize_option_tag();
+        if (tag) {
+            result = Deserializable<typename T::value_type>::deserialize(deserializer);
+        }
+        return result;
+    }
+};
+
+// Sequences (common implementation)
+template <typename T, typename Seq, typename Deserializer>
+Seq deserialize_seq(Deserializer &deserializer) {
+    Seq result;
+    size_t len = deserializer.deserialize_len();
+    for (size_t i = 0; i < len && !deserializer.has_error(); i++) {
+        result.push_back(Deserializable<T>::deserialize(deserializer));
+    }
+    return result;
+}
+
 // Vectors
 template <typename T, typename Allocator>
 struct Deserializable<std::vector<T, Allocator>> {
     template <typename Deserializer>
-    static std::vector<T> deserialize(Deserializer &deserializer) {
-        std::vector<T> result;
-        size_t len = deserializer.deserialize_len();
-        for (size_t i = 0; i < len; i++) {
-            result.push_back(Deserializable<T>::deserialize(deserializer));
-        }
-        return result;
+    static std::vector<T, Allocator> deserialize(Deserializer &deserializer) {
+        return deserialize_seq<T, std::vector<T, Allocator>>(deserializer);
     }
 };
 
-// Maps
-template <typename K, typename V>
-struct Deserializable<std::map<K, V>> {
+// Sequences (hook)
+template <typename T>
+struct Deserializable<T, std::enable_if_t<is_sequence<T>::value>> {
+    template <typename Deserializer>
+    static T deserialize(Deserializer &deserializer) {
+        return deserialize_seq<typename T::value_type, T>(deserializer);
+    }
+};
+
+// Types with (de)serialization members (hook)
+template <typename T>
+struct Deserializable<T, std::enable_if_t<has_serde_members<T>::value>> {
     template <typename Deserializer>
-    static std::map<K, V> deserialize(Deserializer &deserializer) {
-        std::map<K, V> result;
-        size_t len = deserializer.deserialize_len();
-        std::optional<std::tuple<size_t, size_t>> previous_key_slice;
-        for (size_t i = 0; i < len; i++) {
-            if constexpr (Deserializer::enforce_strict_map_ordering) {
-                auto start = deserializer.get_buffer_offset();
-                auto key = Deserializable<K>::deserialize(deserializer);
-                auto end = deserializer.get_buffer_offset();
-                if (previous_key_slice.has_value()) {
-                    deserializer.check_that_key_slices_are_increasing(
-                        previous_key_slice.value(), {start, end});
-                }
-                previous_key_slice = {start, end};
-                auto value = Deserializable<V>::deserialize(deserializer);
-                result.insert({key, value});
-            } else {
-                auto key = Deserializable<K>::deserialize(deserializer);
-                auto value = Deserializable<V>::deserialize(deserializer);
-                result.insert({key, value});
+    static T deserialize(Deserializer &deserializer) {
+        return T::template serde_deserialize<Deserializer>(deserializer);
+    }
+};
+
+// Maps (common implementation)
+template <typename K, typename V, typename Map, typename Deserializer>
+Map deserialize_map(Deserializer &deserializer) {
+    Map result;
+    size_t len = deserializer.deserialize_len();
+    std::optional<std::tuple<size_t, size_t>> previous_key_slice;
+    for (size_t i = 0; i < len && !deserializer.has_error(); i++) {
+        if constexpr (Deserializer::enforce_strict_map_ordering) {
+            auto start = deserializer.get_buffer_offset();
+            auto key = Deserializable<K>::deserialize(deserializer);
+            auto end = deserializer.get_buffer_offset();
+            if (previous_key_slice.has_value()) {
+                deserializer.check_that_key_slices_are_increasing(
+                    previous_key_slice.value(), {start, end});
             }
+            previous_key_slice = {start, end};
+            auto value = Deserializable<V>::deserialize(deserializer);
+            result.emplace(std::move(key), std::move(value));
+        } else {
+            auto key = Deserializable<K>::deserialize(deserializer);
+            auto value = Deserializable<V>::deserialize(deserializer);
+            result.emplace(std::move(key), std::move(value));
         }
-        return result;
+    }
+    return result;
+}
+
+// Maps
+template <typename K, typename V, typename Compare, typename Allocator>
+struct Deserializable<std::map<K, V, Compare, Allocator>> {
+    template <typename Deserializer>
+    static std::map<K, V, Compare, Allocator>
+    deserialize(Deserializer &deserializer) {
+        return deserialize_map<K, V, std::map<K, V, Compare, Allocator>>(
+            deserializer);
+    }
+};
+
+// Maps (hook)
+template <typename T>
+struct Deserializable<T, std::enable_if_t<is_map<T>::value>> {
+    template <typename Deserializer>
+    static T deserialize(Deserializer &deserializer) {
+        return deserialize_map<typename T::key_type, typename T::mapped_type, T>(
+            deserializer);
     }
 };
 
@@ -608,9 +875,10 @@ template <class... Types>
 struct Deserializable<std::tuple<Types...>> {
     template <typename Deserializer>
     static std::tuple<Types...> deserialize(Deserializer &deserializer) {
-        // Visit each of the type components.
-        return std::make_tuple(
-            Deserializable<Types>::deserialize(deserializer)...);
+        // Visit each of the type components. Unlike function arguments, the
+        // elements of a braced initializer list are evaluated in order.
+        return std::tuple<Types...>{
+            Deserializable<Types>::deserialize(deserializer)...};
     }
 };
 
@@ -640,9 +908,15 @@ struct Deserializable<std::variant<Types...>> {
 
         // Read the variant index and execute the corresponding case.
         auto index = deserializer.deserialize_variant_index();
-        if (index > cases.size()) {
-            throw deserialization_error("Unknown variant index for enum");
+        if (index >= cases.size()) {
+            deserializer.fail("Unknown variant index for enum");
+        }
+#ifdef SERDE_NO_EXCEPTIONS
+        // Stop here so that recursive enums cannot loop on invalid inputs.
+        if (deserializer.has_error()) {
+            return {};
         }
+#endif
         return cases.at(index)(deserializer);
     }
 };
@@ -0,0 +1,69 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+using Serde;
+
+namespace Bcs
+{
+    /// <summary>
+    /// BCS rules for <see cref="SpanSerializer{F}"/> and <see cref="SpanDeserializer{F}"/>.
+    /// </summary>
+    public struct BcsFormat : IBinaryFormat
+    {
+        public long MaxContainerDepth => BcsSerializer.MAX_CONTAINER_DEPTH;
+
+        public bool HasCanonicalMaps => true;
+
+        static void serialize_u32_as_uleb128<F>(ref SpanSerializer<F> serializer, uint value) where F : struct, IBinaryFormat
+        {
+            while ((value >> 7) != 0)
+            {
+                serializer.serialize_u8((byte)((value & 0x7f) | 0x80));
+                value >>= 7;
+            }
+            serializer.serialize_u8((byte)value);
+        }
+
+        public void serialize_len<F>(ref SpanSerializer<F> serializer, long value) where F : struct, IBinaryFormat
+        {
+            if ((value < 0) || (value > BcsSerializer.MAX_LENGTH))
+            {
+                throw new SerializationException("length value doesn't fit in uint32");
+            }
+            serialize_u32_as_uleb128(ref serializer, (uint)value);
+        }
+
+        public void serialize_variant_index<F>(ref SpanSerializer<F> serializer, int value) where F : struct, IBinaryFormat =>
+            serialize_u32_as_uleb128(ref serializer, (uint)value);
+
+        static int deserialize_uleb128_as_u32<F>(ref SpanDeserializer<F> deserializer) where F : struct, IBinaryFormat
+        {
+            long value = 0;
+            for (int shift = 0; shift < 32; shift += 7)
+            {
+                byte x = deserializer.deserialize_u8();
+                byte digit = (byte)(x & 0x7F);
+                value |= ((long)digit << shift);
+                if ((value < 0) || (value > int.MaxValue))
+                {
+                    throw new DeserializationException("Overflow while parsing uleb128-encoded uint32 value");
+                }
+                if (digit == x)
+                {
+                    if (shift > 0 && digit == 0)
+                    {
+                        throw new DeserializationException("Invalid uleb128 number (unexpected zero digit)");
+                    }
+                    return (int)value;
+                }
+            }
+            throw new DeserializationException("Overflow while parsing uleb128-encoded uint32 value");
+        }
+
+        public long deserialize_len<F>(ref SpanDeserializer<F> deserializer) where F : struct, IBinaryFormat =>
+            deserialize_uleb128_as_u32(ref deserializer);
+
+        public int deserialize_variant_index<F>(ref SpanDeserializer<F> deserializer) where F : struct, IBinaryFormat =>
+            deserialize_uleb128_as_u32(ref deserializer);
+    }
+}
@@ -0,0 +1,36 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+using Serde;
+
+namespace Bincode
+{
+    /// <summary>
+    /// Bincode rules for <see cref="SpanSerializer{F}"/> and <see cref="SpanDeserializer{F}"/>.
+    /// </summary>
+    public struct BincodeFormat : IBinaryFormat
+    {
+        public long MaxContainerDepth => long.MaxValue;
+
+        public bool HasCanonicalMaps => false;
+
+        public void serialize_len<F>(ref SpanSerializer<F> serializer, long value) where F : struct, IBinaryFormat =>
+            serializer.serialize_i64(value);
+
+        public void serialize_variant_index<F>(ref SpanSerializer<F> serializer, int value) where F : struct, IBinaryFormat =>
+            serializer.serialize_i32(value);
+
+        public long deserialize_len<F>(ref SpanDeserializer<F> deserializer) where F : struct, IBinaryFormat
+        {
+            long value = deserializer.deserialize_i64();
+            if (value < 0 || value > int.MaxValue)
+            {
+                throw new DeserializationException("Incorrect length value");
+            }
+            return value;
+        }
+
+        public int deserialize_variant_index<F>(ref SpanDeserializer<F> deserializer) where F : struct, IBinaryFormat =>
+            deserializer.deserialize_i32();
+    }
+}
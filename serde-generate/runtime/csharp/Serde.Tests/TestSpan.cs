@@ -0,0 +1,99 @@
+using System;
+using System.Numerics;
+using NUnit.Framework;
+using Bcs;
+using Bincode;
+
+namespace Serde.Tests
+{
+    [TestFixture]
+    public class TestSpan
+    {
+        [Test]
+        public void TestSliceOrdering()
+        {
+            byte[] buffer = new byte[13];
+            SpanSerializer<BcsFormat> serializer = new SpanSerializer<BcsFormat>(buffer);
+            serializer.serialize_u8(255);
+            serializer.serialize_u32(1);
+            serializer.serialize_u32(1);
+            serializer.serialize_u32(2);
+            Assert.AreEqual(serializer.get_buffer_offset(), 13);
+            CollectionAssert.AreEqual(buffer, new byte[] { 255, /**/ 1, /**/ 0, 0, /**/ 0, 1, 0, /**/ 0, /**/ 0, /**/ 2, 0, 0, 0 });
+
+            int[] offsets = { 1, 2, 4, 7, 8, 9 };
+            serializer.sort_map_entries(offsets);
+            CollectionAssert.AreEqual(buffer, new byte[] { 255, /**/ 0, /**/ 0, /**/ 0, 0, /**/ 0, 1, 0,  /**/ 1, /**/ 2, 0, 0, 0 });
+        }
+
+        [Test]
+        public void TestULEB128Encoding()
+        {
+            byte[] buffer = new byte[7];
+            SpanSerializer<BcsFormat> serializer = new SpanSerializer<BcsFormat>(buffer);
+            serializer.serialize_len(0);
+            serializer.serialize_len(1);
+            serializer.serialize_len(127);
+            serializer.serialize_len(128);
+            serializer.serialize_len(3000);
+            CollectionAssert.AreEqual(buffer, new byte[] { 0, 1, 127, 128, 1, 184, 23 });
+
+            SpanDeserializer<BcsFormat> deserializer = new SpanDeserializer<BcsFormat>(buffer);
+            Assert.AreEqual(deserializer.deserialize_len(), 0);
+            Assert.AreEqual(deserializer.deserialize_len(), 1);
+            Assert.AreEqual(deserializer.deserialize_len(), 127);
+            Assert.AreEqual(deserializer.deserialize_len(), 128);
+            Assert.AreEqual(deserializer.deserialize_len(), 3000);
+        }
+
+        [Test]
+        public void TestSameOutputAsBinarySerializers()
+        {
+            BcsSerializer bcs = new BcsSerializer();
+            BincodeSerializer bincode = new BincodeSerializer();
+            byte[] bcsBuffer = new byte[64];
+            byte[] bincodeBuffer = new byte[64];
+            SpanSerializer<BcsFormat> bcsSpan = new SpanSerializer<BcsFormat>(bcsBuffer);
+            SpanSerializer<BincodeFormat> bincodeSpan = new SpanSerializer<BincodeFormat>(bincodeBuffer);
+
+            bcs.serialize_str("abé"); bcsSpan.serialize_str("abé");
+            bcs.serialize_f32(1.5f); bcsSpan.serialize_f32(1.5f);
+            bcs.serialize_f64(-0.25); bcsSpan.serialize_f64(-0.25);
+            bcs.serialize_i128(BigInteger.MinusOne); bcsSpan.serialize_i128(BigInteger.MinusOne);
+            bcs.serialize_variant_index(300); bcsSpan.serialize_variant_index(300);
+            bincode.serialize_str("abé"); bincodeSpan.serialize_str("abé");
+            bincode.serialize_i16(-2); bincodeSpan.serialize_i16(-2);
+            bincode.serialize_variant_index(300); bincodeSpan.serialize_variant_index(300);
+
+            CollectionAssert.AreEqual(bcs.get_bytes(), new ArraySegment<byte>(bcsBuffer, 0, bcsSpan.get_buffer_offset()));
+            CollectionAssert.AreEqual(bincode.get_bytes(), new ArraySegment<byte>(bincodeBuffer, 0, bincodeSpan.get_buffer_offset()));
+
+            SpanDeserializer<BcsFormat> deserializer = new SpanDeserializer<BcsFormat>(bcs.get_bytes());
+            Assert.AreEqual(deserializer.deserialize_str(), "abé");
+            Assert.AreEqual(deserializer.deserialize_f32(), 1.5f);
+            Assert.AreEqual(deserializer.deserialize_f64(), -0.25);
+            Assert.AreEqual(deserializer.deserialize_i128(), BigInteger.MinusOne);
+            Assert.AreEqual(deserializer.deserialize_variant_index(), 300);
+        }
+
+        [Test]
+        public void TestBufferTooSmall()
+        {
+            SpanSerializer<BcsFormat> serializer = new SpanSerializer<BcsFormat>(new byte[3]);
+            try
+            {
+                serializer.serialize_u32(1);
+                Assert.Fail("Expected SerializationException");
+            }
+            catch (SerializationException) { }
+
+            SpanDeserializer<BcsFormat> deserializer = new SpanDeserializer<BcsFormat>(new byte[3]);
+            try
+            {
+                deserializer.deserialize_u32();
+                Assert.Fail("Expected DeserializationException");
+            }
+            catch (DeserializationException) { }
+        }
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+using System;
+using System.Buffers;
+using System.Numerics;
+using System.Text;
+
+namespace Serde
+{
+    /// <summary>
+    /// Implementation of <see cref="ISerializer"/> that writes into an <see cref="IBufferWriter{T}"/>.
+    /// Values are encoded by <see cref="SpanSerializer{F}"/>, which remains the faster option when the
+    /// interface is not needed.
+    /// </summary>
+    public class BufferWriterSerializer<F> : ISerializer where F : struct, IBinaryFormat
+    {
+        // Maximal size of an encoded length or variant index (i.e. a ULEB128-encoded u64).
+        const int MaxLenSize = 10;
+
+        static readonly Encoding utf8 = Encoding.GetEncoding("utf-8", new EncoderExceptionFallback(), new DecoderExceptionFallback());
+        readonly IBufferWriter<byte> bufferWriter;
+        Memory<byte> buffer;
+        int position;
+        long containerDepthBudget;
+
+        /// <summary>
+        /// Serializes into <paramref name="output"/>. The serialized bytes are only committed by <see cref="Flush"/>.
+        /// </summary>
+        public BufferWriterSerializer(IBufferWriter<byte> output)
+        {
+            bufferWriter = output;
+            buffer = output.GetMemory();
+            position = 0;
+            containerDepthBudget = default(F).MaxContainerDepth;
+        }
+
+        /// <summary>
+        /// Commits the serialized bytes to the underlying <see cref="IBufferWriter{T}"/> and returns their number.
+        /// </summary>
+        public int Flush()
+        {
+            int length = position;
+            bufferWriter.Advance(length);
+            buffer = Memory<byte>.Empty;
+            position = 0;
+            return length;
+        }
+
+        /// <summary>
+        /// Returns a serializer writing at the current position, with room for at least <paramref name="size"/> bytes.
+        /// </summary>
+        SpanSerializer<F> Reserve(int size)
+        {
+            if (buffer.Length - position < size)
+            {
+                // Nothing is committed before Flush, so we copy the bytes written so far.
+                Memory<byte> larger = bufferWriter.GetMemory(Math.Max(position + size, 2 * buffer.Length));
+                buffer.Slice(0, position).CopyTo(larger);
+                buffer = larger;
+            }
+            return new SpanSerializer<F>(buffer.Span, position);
+        }
+
+        public void increase_container_depth()
+        {
+            if (containerDepthBudget == 0)
+            {
+                throw new SerializationException("Exceeded maximum container depth");
+            }
+            containerDepthBudget -= 1;
+        }
+
+        public void decrease_container_depth()
+        {
+            containerDepthBudget += 1;
+        }
+
+        public void serialize_len(long value)
+        {
+            SpanSerializer<F> serializer = Reserve(MaxLenSize);
+            serializer.serialize_len(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_variant_index(int value)
+        {
+            SpanSerializer<F> serializer = Reserve(MaxLenSize);
+            serializer.serialize_variant_index(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_char(char value) => throw new SerializationException("Not implemented: char serialization");
+
+        public void serialize_f32(float value)
+        {
+            SpanSerializer<F> serializer = Reserve(4);
+            serializer.serialize_f32(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_f64(double value)
+        {
+            SpanSerializer<F> serializer = Reserve(8);
+            serializer.serialize_f64(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_str(string value)
+        {
+            SpanSerializer<F> serializer = Reserve(MaxLenSize + utf8.GetByteCount(value));
+            serializer.serialize_str(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_bytes(ValueArray<byte> value)
+        {
+            SpanSerializer<F> serializer = Reserve(MaxLenSize + value.Count);
+            serializer.serialize_bytes(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_bool(bool value) => serialize_u8(value ? (byte)1 : (byte)0);
+
+        public void serialize_unit(Unit value) { }
+
+        public void serialize_u8(byte value)
+        {
+            SpanSerializer<F> serializer = Reserve(1);
+            serializer.serialize_u8(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_u16(ushort value)
+        {
+            SpanSerializer<F> serializer = Reserve(2);
+            serializer.serialize_u16(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_u32(uint value)
+        {
+            SpanSerializer<F> serializer = Reserve(4);
+            serializer.serialize_u32(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_u64(ulong value)
+        {
+            SpanSerializer<F> serializer = Reserve(8);
+            serializer.serialize_u64(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_u128(BigInteger value)
+        {
+            SpanSerializer<F> serializer = Reserve(16);
+            serializer.serialize_u128(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_i8(sbyte value)
+        {
+            SpanSerializer<F> serializer = Reserve(1);
+            serializer.serialize_i8(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_i16(short value)
+        {
+            SpanSerializer<F> serializer = Reserve(2);
+            serializer.serialize_i16(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_i32(int value)
+        {
+            SpanSerializer<F> serializer = Reserve(4);
+            serializer.serialize_i32(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_i64(long value)
+        {
+            SpanSerializer<F> serializer = Reserve(8);
+            serializer.serialize_i64(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_i128(BigInteger value)
+        {
+            SpanSerializer<F> serializer = Reserve(16);
+            serializer.serialize_i128(value);
+            position = serializer.get_buffer_offset();
+        }
+
+        public void serialize_option_tag(bool value) => serialize_bool(value);
+
+        public int get_buffer_offset() => position;
+
+        public void sort_map_entries(int[] offsets) => Reserve(0).sort_map_entries(offsets);
+
+        /// <summary>
+        /// Returns a copy of the bytes serialized since the last call to <see cref="Flush"/>.
+        /// </summary>
+        public byte[] get_bytes() => buffer.Slice(0, position).ToArray();
+    }
+}
@@ -0,0 +1,25 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+namespace Serde
+{
+    /// <summary>
+    /// Serialization of the values of a type parameter, as expected by generic classes.
+    /// </summary>
+    public delegate void Serialize<T>(T value, ISerializer serializer);
+
+    /// <summary>
+    /// Deserialization of the values of a type parameter, as expected by generic classes.
+    /// </summary>
+    public delegate T Deserialize<T>(IDeserializer deserializer);
+
+    /// <summary>
+    /// Same as <see cref="Serialize{T}"/> for <see cref="SpanSerializer{F}"/>.
+    /// </summary>
+    public delegate void SpanSerialize<F, T>(T value, ref SpanSerializer<F> serializer) where F : struct, IBinaryFormat;
+
+    /// <summary>
+    /// Same as <see cref="Deserialize{T}"/> for <see cref="SpanDeserializer{F}"/>.
+    /// </summary>
+    public delegate T SpanDeserialize<F, T>(ref SpanDeserializer<F> deserializer) where F : struct, IBinaryFormat;
+}
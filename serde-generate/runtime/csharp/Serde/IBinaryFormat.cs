@@ -0,0 +1,27 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+namespace Serde
+{
+    /// <summary>
+    /// Encoding-specific rules of <see cref="SpanSerializer{F}"/> and <see cref="SpanDeserializer{F}"/>.
+    /// Implementations are empty structs (e.g. <c>Bcs.BcsFormat</c>) so that calls are resolved statically.
+    /// </summary>
+    public interface IBinaryFormat
+    {
+        long MaxContainerDepth { get; }
+
+        /// <summary>
+        /// Whether map entries are sorted by the lexicographic order of their serialized keys.
+        /// </summary>
+        bool HasCanonicalMaps { get; }
+
+        void serialize_len<F>(ref SpanSerializer<F> serializer, long value) where F : struct, IBinaryFormat;
+
+        void serialize_variant_index<F>(ref SpanSerializer<F> serializer, int value) where F : struct, IBinaryFormat;
+
+        long deserialize_len<F>(ref SpanDeserializer<F> deserializer) where F : struct, IBinaryFormat;
+
+        int deserialize_variant_index<F>(ref SpanDeserializer<F> deserializer) where F : struct, IBinaryFormat;
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+using System;
+using System.Numerics;
+
+namespace Serde
+{
+    /// <summary>
+    /// Implementation of <see cref="IDeserializer"/> that reads from a <see cref="ReadOnlyMemory{T}"/>.
+    /// Values are decoded by <see cref="SpanDeserializer{F}"/>, which remains the faster option when the
+    /// interface is not needed.
+    /// </summary>
+    public class MemoryDeserializer<F> : IDeserializer where F : struct, IBinaryFormat
+    {
+        readonly ReadOnlyMemory<byte> input;
+        int position;
+        long containerDepthBudget;
+
+        public MemoryDeserializer(ReadOnlyMemory<byte> _input)
+        {
+            input = _input;
+            position = 0;
+            containerDepthBudget = default(F).MaxContainerDepth;
+        }
+
+        /// <summary>
+        /// Returns a deserializer reading at the current position.
+        /// </summary>
+        SpanDeserializer<F> Reader() => new SpanDeserializer<F>(input.Span, position);
+
+        public int get_buffer_offset() => position;
+
+        public long deserialize_len()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            long value = deserializer.deserialize_len();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public int deserialize_variant_index()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            int value = deserializer.deserialize_variant_index();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public void check_that_key_slices_are_increasing(Range key1, Range key2) =>
+            Reader().check_that_key_slices_are_increasing(key1, key2);
+
+        public char deserialize_char() => throw new DeserializationException("Not implemented: char deserialization");
+
+        public float deserialize_f32()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            float value = deserializer.deserialize_f32();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public double deserialize_f64()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            double value = deserializer.deserialize_f64();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public void increase_container_depth()
+        {
+            if (containerDepthBudget == 0)
+            {
+                throw new DeserializationException("Exceeded maximum container depth");
+            }
+            containerDepthBudget -= 1;
+        }
+
+        public void decrease_container_depth()
+        {
+            containerDepthBudget += 1;
+        }
+
+        public string deserialize_str()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            string value = deserializer.deserialize_str();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public ValueArray<byte> deserialize_bytes()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            ValueArray<byte> value = deserializer.deserialize_bytes();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public bool deserialize_bool()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            bool value = deserializer.deserialize_bool();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public Unit deserialize_unit() => new Unit();
+
+        public byte deserialize_u8()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            byte value = deserializer.deserialize_u8();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public ushort deserialize_u16()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            ushort value = deserializer.deserialize_u16();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public uint deserialize_u32()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            uint value = deserializer.deserialize_u32();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public ulong deserialize_u64()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            ulong value = deserializer.deserialize_u64();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public BigInteger deserialize_u128()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            BigInteger value = deserializer.deserialize_u128();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public sbyte deserialize_i8()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            sbyte value = deserializer.deserialize_i8();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public short deserialize_i16()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            short value = deserializer.deserialize_i16();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public int deserialize_i32()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            int value = deserializer.deserialize_i32();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public long deserialize_i64()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            long value = deserializer.deserialize_i64();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public BigInteger deserialize_i128()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            BigInteger value = deserializer.deserialize_i128();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+
+        public bool deserialize_option_tag()
+        {
+            SpanDeserializer<F> deserializer = Reader();
+            bool value = deserializer.deserialize_option_tag();
+            position = deserializer.get_buffer_offset();
+            return value;
+        }
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+using System;
+using System.Buffers;
+using System.Buffers.Binary;
+using System.Numerics;
+using System.Runtime.InteropServices;
+using System.Text;
+
+namespace Serde
+{
+    /// <summary>
+    /// Allocation-free counterpart of <see cref="BinaryDeserializer"/> that reads from a <see cref="ReadOnlySpan{T}"/>.
+    /// Being a ref struct, it cannot implement <see cref="IDeserializer"/> but it provides the same methods.
+    /// See <see cref="MemoryDeserializer{F}"/> for an implementation of the interface.
+    /// Only the deserialized values (e.g. strings and arrays) are allocated.
+    /// </summary>
+    public ref struct SpanDeserializer<F> where F : struct, IBinaryFormat
+    {
+        static readonly Encoding utf8 = Encoding.GetEncoding("utf-8", new EncoderExceptionFallback(), new DecoderExceptionFallback());
+        readonly ReadOnlySpan<byte> input;
+        int position;
+        long containerDepthBudget;
+
+        public SpanDeserializer(ReadOnlySpan<byte> _input)
+        {
+            input = _input;
+            position = 0;
+            containerDepthBudget = default(F).MaxContainerDepth;
+        }
+
+        /// <summary>
+        /// Resumes deserialization of <paramref name="_input"/> after the first <paramref name="offset"/> bytes.
+        /// </summary>
+        internal SpanDeserializer(ReadOnlySpan<byte> _input, int offset)
+        {
+            input = _input;
+            position = offset;
+            containerDepthBudget = default(F).MaxContainerDepth;
+        }
+
+        ReadOnlySpan<byte> Read(int size)
+        {
+            if (input.Length - position < size)
+            {
+                throw new DeserializationException($"Need {size - (input.Length - position)} more bytes");
+            }
+            ReadOnlySpan<byte> result = input.Slice(position, size);
+            position += size;
+            return result;
+        }
+
+        public int get_buffer_offset() => position;
+
+        public long deserialize_len() => default(F).deserialize_len(ref this);
+
+        public int deserialize_variant_index() => default(F).deserialize_variant_index(ref this);
+
+        public void check_that_key_slices_are_increasing(Range key1, Range key2)
+        {
+            if (default(F).HasCanonicalMaps
+                && Verification.CompareLexicographic(input.Slice(key1.Start, key1.Length), input.Slice(key2.Start, key2.Length)) >= 0)
+            {
+                throw new DeserializationException("Error while decoding map: keys are not serialized in the expected order");
+            }
+        }
+
+        public char deserialize_char() => throw new DeserializationException("Not implemented: char deserialization");
+
+        public float deserialize_f32()
+        {
+            Span<int> content = stackalloc int[1];
+            content[0] = BinaryPrimitives.ReadInt32LittleEndian(Read(4));
+            return MemoryMarshal.Cast<int, float>(content)[0];
+        }
+
+        public double deserialize_f64() => BitConverter.Int64BitsToDouble(BinaryPrimitives.ReadInt64LittleEndian(Read(8)));
+
+        public void increase_container_depth()
+        {
+            if (containerDepthBudget == 0)
+            {
+                throw new DeserializationException("Exceeded maximum container depth");
+            }
+            containerDepthBudget -= 1;
+        }
+
+        public void decrease_container_depth()
+        {
+            containerDepthBudget += 1;
+        }
+
+        public string deserialize_str()
+        {
+            long len = deserialize_len();
+            if (len < 0 || len > int.MaxValue)
+            {
+                throw new DeserializationException("Incorrect length value for C# string");
+            }
+            ReadOnlySpan<byte> content = Read((int)len);
+            byte[] bytes = ArrayPool<byte>.Shared.Rent(content.Length);
+            try
+            {
+                content.CopyTo(bytes);
+                return utf8.GetString(bytes, 0, content.Length);
+            }
+            finally
+            {
+                ArrayPool<byte>.Shared.Return(bytes);
+            }
+        }
+
+        public ValueArray<byte> deserialize_bytes()
+        {
+            long len = deserialize_len();
+            if (len < 0 || len > int.MaxValue)
+            {
+                throw new DeserializationException("Incorrect length value for C# array");
+            }
+            return new ValueArray<byte>(Read((int)len).ToArray());
+        }
+
+        public bool deserialize_bool()
+        {
+            byte value = deserialize_u8();
+            switch (value)
+            {
+                case 0: return false;
+                case 1: return true;
+                default: throw new DeserializationException("Incorrect value for bool: " + value);
+            }
+        }
+
+        public Unit deserialize_unit() => new Unit();
+
+        public byte deserialize_u8() => Read(1)[0];
+
+        public ushort deserialize_u16() => BinaryPrimitives.ReadUInt16LittleEndian(Read(2));
+
+        public uint deserialize_u32() => BinaryPrimitives.ReadUInt32LittleEndian(Read(4));
+
+        public ulong deserialize_u64() => BinaryPrimitives.ReadUInt64LittleEndian(Read(8));
+
+        public BigInteger deserialize_u128()
+        {
+            BigInteger signed = deserialize_i128();
+            if (signed >= 0)
+            {
+                return signed;
+            }
+            else
+            {
+                return signed + (BigInteger.One << 128);
+            }
+        }
+
+        public sbyte deserialize_i8() => (sbyte)Read(1)[0];
+
+        public short deserialize_i16() => BinaryPrimitives.ReadInt16LittleEndian(Read(2));
+
+        public int deserialize_i32() => BinaryPrimitives.ReadInt32LittleEndian(Read(4));
+
+        public long deserialize_i64() => BinaryPrimitives.ReadInt64LittleEndian(Read(8));
+
+        public BigInteger deserialize_i128() => new BigInteger(Read(16).ToArray());
+
+        public bool deserialize_option_tag()
+        {
+            byte value = deserialize_u8();
+            switch (value)
+            {
+                case 0: return false;
+                case 1: return true;
+                default: throw new DeserializationException("Incorrect value for Option tag: " + value);
+            }
+        }
+    }
+}
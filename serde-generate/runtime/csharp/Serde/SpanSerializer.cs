@@ -0,0 +1,278 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+using System;
+using System.Buffers;
+using System.Buffers.Binary;
+using System.Diagnostics;
+using System.Numerics;
+using System.Runtime.InteropServices;
+using System.Text;
+
+namespace Serde
+{
+    /// <summary>
+    /// Allocation-free counterpart of <see cref="BinarySerializer"/> that writes into a <see cref="Span{T}"/>
+    /// or an <see cref="IBufferWriter{T}"/>. Being a ref struct, it cannot implement <see cref="ISerializer"/>
+    /// but it provides the same methods. See <see cref="BufferWriterSerializer{F}"/> for an implementation of the interface.
+    /// </summary>
+    public ref struct SpanSerializer<F> where F : struct, IBinaryFormat
+    {
+        static readonly Encoding utf8 = Encoding.GetEncoding("utf-8", new EncoderExceptionFallback(), new DecoderExceptionFallback());
+        readonly IBufferWriter<byte> bufferWriter;
+        Span<byte> buffer;
+        int position;
+        long containerDepthBudget;
+
+        /// <summary>
+        /// Serializes into a fixed buffer. A <see cref="SerializationException"/> is thrown if the buffer is too small.
+        /// </summary>
+        public SpanSerializer(Span<byte> output)
+        {
+            bufferWriter = null;
+            buffer = output;
+            position = 0;
+            containerDepthBudget = default(F).MaxContainerDepth;
+        }
+
+        /// <summary>
+        /// Resumes serialization into a fixed buffer of which the first <paramref name="offset"/> bytes were written.
+        /// </summary>
+        internal SpanSerializer(Span<byte> output, int offset)
+        {
+            bufferWriter = null;
+            buffer = output;
+            position = offset;
+            containerDepthBudget = default(F).MaxContainerDepth;
+        }
+
+        /// <summary>
+        /// Serializes into <paramref name="output"/>. The serialized bytes are only committed by <see cref="Flush"/>.
+        /// </summary>
+        public SpanSerializer(IBufferWriter<byte> output)
+        {
+            bufferWriter = output;
+            buffer = output.GetSpan();
+            position = 0;
+            containerDepthBudget = default(F).MaxContainerDepth;
+        }
+
+        /// <summary>
+        /// Commits the serialized bytes to the underlying <see cref="IBufferWriter{T}"/> (if any) and returns their number.
+        /// </summary>
+        public int Flush()
+        {
+            int length = position;
+            if (bufferWriter != null)
+            {
+                bufferWriter.Advance(length);
+                buffer = Span<byte>.Empty;
+                position = 0;
+            }
+            return length;
+        }
+
+        Span<byte> Reserve(int size)
+        {
+            if (buffer.Length - position < size)
+            {
+                Grow(size);
+            }
+            Span<byte> result = buffer.Slice(position, size);
+            position += size;
+            return result;
+        }
+
+        void Grow(int size)
+        {
+            if (bufferWriter == null)
+            {
+                throw new SerializationException("Output buffer is too small");
+            }
+            // Nothing is committed before Flush, so we copy the bytes written so far.
+            Span<byte> larger = bufferWriter.GetSpan(Math.Max(position + size, 2 * buffer.Length));
+            buffer.Slice(0, position).CopyTo(larger);
+            buffer = larger;
+        }
+
+        public void increase_container_depth()
+        {
+            if (containerDepthBudget == 0)
+            {
+                throw new SerializationException("Exceeded maximum container depth");
+            }
+            containerDepthBudget -= 1;
+        }
+
+        public void decrease_container_depth()
+        {
+            containerDepthBudget += 1;
+        }
+
+        public void serialize_len(long value) => default(F).serialize_len(ref this, value);
+
+        public void serialize_variant_index(int value) => default(F).serialize_variant_index(ref this, value);
+
+        public void serialize_char(char value) => throw new SerializationException("Not implemented: char serialization");
+
+        public void serialize_f32(float value)
+        {
+            Span<float> content = stackalloc float[1];
+            content[0] = value;
+            BinaryPrimitives.WriteInt32LittleEndian(Reserve(4), MemoryMarshal.Cast<float, int>(content)[0]);
+        }
+
+        public void serialize_f64(double value) => BinaryPrimitives.WriteInt64LittleEndian(Reserve(8), BitConverter.DoubleToInt64Bits(value));
+
+        public void serialize_str(string value)
+        {
+            int length = utf8.GetByteCount(value);
+            serialize_len(length);
+            byte[] content = ArrayPool<byte>.Shared.Rent(length);
+            utf8.GetBytes(value, 0, value.Length, content, 0);
+            new ReadOnlySpan<byte>(content, 0, length).CopyTo(Reserve(length));
+            ArrayPool<byte>.Shared.Return(content);
+        }
+
+        public void serialize_bytes(ValueArray<byte> value)
+        {
+            serialize_len(value.Count);
+            value.AsReadOnlySpan().CopyTo(Reserve(value.Count));
+        }
+
+        public void serialize_bool(bool value) => serialize_u8(value ? (byte)1 : (byte)0);
+
+        public void serialize_unit(Unit value) { }
+
+        public void serialize_u8(byte value) => Reserve(1)[0] = value;
+
+        public void serialize_u16(ushort value) => BinaryPrimitives.WriteUInt16LittleEndian(Reserve(2), value);
+
+        public void serialize_u32(uint value) => BinaryPrimitives.WriteUInt32LittleEndian(Reserve(4), value);
+
+        public void serialize_u64(ulong value) => BinaryPrimitives.WriteUInt64LittleEndian(Reserve(8), value);
+
+        public void serialize_u128(BigInteger value)
+        {
+            if (value.Sign < 0 || value >> 128 != 0)
+            {
+                throw new SerializationException("Invalid value for an unsigned int128");
+            }
+            byte[] content = value.ToByteArray();
+            // BigInteger.ToByteArray() may add a most-significant zero
+            // byte for signing purpose: ignore it.
+            Debug.Assert(content.Length <= 16 || content[16] == 0);
+
+            Span<byte> output = Reserve(16);
+            for (int i = 0; i < 16; i++)
+            {
+                output[i] = i < content.Length ? content[i] : (byte)0;
+            }
+        }
+
+        public void serialize_i8(sbyte value) => Reserve(1)[0] = (byte)value;
+
+        public void serialize_i16(short value) => BinaryPrimitives.WriteInt16LittleEndian(Reserve(2), value);
+
+        public void serialize_i32(int value) => BinaryPrimitives.WriteInt32LittleEndian(Reserve(4), value);
+
+        public void serialize_i64(long value) => BinaryPrimitives.WriteInt64LittleEndian(Reserve(8), value);
+
+        public void serialize_i128(BigInteger value)
+        {
+            if (value >= 0)
+            {
+                if (value >> 127 != 0)
+                {
+                    throw new SerializationException("Invalid value for a signed int128");
+                }
+                serialize_u128(value);
+            }
+            else
+            {
+                if ((-(value + 1)) >> 127 != 0)
+                {
+                    throw new SerializationException("Invalid value for a signed int128");
+                }
+                serialize_u128(value + (BigInteger.One << 128));
+            }
+        }
+
+        public void serialize_option_tag(bool value) => serialize_bool(value);
+
+        public int get_buffer_offset() => position;
+
+        /// <summary>
+        /// Sorts the map entries starting at the given offsets, if required by the format.
+        /// </summary>
+        public void sort_map_entries(ReadOnlySpan<int> offsets)
+        {
+            if (!default(F).HasCanonicalMaps || offsets.Length <= 1)
+            {
+                return;
+            }
+            int offset0 = offsets[0];
+            int length = position - offset0;
+            Range[] ranges = ArrayPool<Range>.Shared.Rent(offsets.Length);
+            for (int i = 0; i < offsets.Length; i++)
+            {
+                int end = i + 1 < offsets.Length ? offsets[i + 1] : position;
+                ranges[i] = new Range(offsets[i] - offset0, end - offset0);
+            }
+            byte[] data = ArrayPool<byte>.Shared.Rent(length);
+            buffer.Slice(offset0, length).CopyTo(data);
+
+            SortRanges(new Span<Range>(ranges, 0, offsets.Length), new ReadOnlySpan<byte>(data, 0, length));
+
+            int offset = offset0;
+            for (int i = 0; i < offsets.Length; i++)
+            {
+                new ReadOnlySpan<byte>(data, ranges[i].Start, ranges[i].Length).CopyTo(buffer.Slice(offset));
+                offset += ranges[i].Length;
+            }
+            ArrayPool<byte>.Shared.Return(data);
+            ArrayPool<Range>.Shared.Return(ranges);
+        }
+
+        /// <summary>
+        /// Heap sort of the given ranges of <paramref name="data"/> in lexicographic order (without allocating).
+        /// </summary>
+        static void SortRanges(Span<Range> ranges, ReadOnlySpan<byte> data)
+        {
+            for (int i = ranges.Length / 2 - 1; i >= 0; i--)
+            {
+                SiftDown(ranges, data, i, ranges.Length);
+            }
+            for (int end = ranges.Length - 1; end > 0; end--)
+            {
+                Range tmp = ranges[0];
+                ranges[0] = ranges[end];
+                ranges[end] = tmp;
+                SiftDown(ranges, data, 0, end);
+            }
+        }
+
+        static void SiftDown(Span<Range> ranges, ReadOnlySpan<byte> data, int root, int end)
+        {
+            while (2 * root + 1 < end)
+            {
+                int child = 2 * root + 1;
+                if (child + 1 < end && Compare(data, ranges[child], ranges[child + 1]) < 0)
+                {
+                    child += 1;
+                }
+                if (Compare(data, ranges[root], ranges[child]) >= 0)
+                {
+                    return;
+                }
+                Range tmp = ranges[root];
+                ranges[root] = ranges[child];
+                ranges[child] = tmp;
+                root = child;
+            }
+        }
+
+        static int Compare(ReadOnlySpan<byte> data, Range key1, Range key2) =>
+            Verification.CompareLexicographic(data.Slice(key1.Start, key1.Length), data.Slice(key2.Start, key2.Length));
+    }
+}
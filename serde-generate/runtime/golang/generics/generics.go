@@ -0,0 +1,132 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+package generics
+
+import (
+	"github.com/novifinancial/serde-reflection/serde-generate/runtime/golang/serde"
+)
+
+// Generic helpers used by code generated with `golang::CodeGenerator::with_generic_helpers`.
+// The encoding of values is the same as with the helpers generated for each type.
+
+// SerializeOption serializes an optional value, using `serializeValue` for the content.
+func SerializeOption[T any](serializer serde.Serializer, value *T, serializeValue func(serde.Serializer, T) error) error {
+	if value == nil {
+		return serializer.SerializeOptionTag(false)
+	}
+	if err := serializer.SerializeOptionTag(true); err != nil {
+		return err
+	}
+	return serializeValue(serializer, *value)
+}
+
+// DeserializeOption deserializes an optional value, using `deserializeValue` for the content.
+func DeserializeOption[T any](deserializer serde.Deserializer, deserializeValue func(serde.Deserializer) (T, error)) (*T, error) {
+	tag, err := deserializer.DeserializeOptionTag()
+	if err != nil || !tag {
+		return nil, err
+	}
+	value, err := deserializeValue(deserializer)
+	if err != nil {
+		return nil, err
+	}
+	return &value, nil
+}
+
+// SerializeSeq serializes the length of a sequence followed by its items.
+func SerializeSeq[T any](serializer serde.Serializer, value []T, serializeItem func(serde.Serializer, T) error) error {
+	if err := serializer.SerializeLen(uint64(len(value))); err != nil {
+		return err
+	}
+	return SerializeArray(serializer, value, serializeItem)
+}
+
+// DeserializeSeq deserializes the length of a sequence followed by its items.
+func DeserializeSeq[T any](deserializer serde.Deserializer, deserializeItem func(serde.Deserializer) (T, error)) ([]T, error) {
+	length, err := deserializer.DeserializeLen()
+	if err != nil {
+		return nil, err
+	}
+	obj := make([]T, length)
+	if err := DeserializeArray(deserializer, obj, deserializeItem); err != nil {
+		return nil, err
+	}
+	return obj, nil
+}
+
+// SerializeArray serializes the items of a fixed-size array (given as a slice), without length.
+func SerializeArray[T any](serializer serde.Serializer, value []T, serializeItem func(serde.Serializer, T) error) error {
+	for _, item := range value {
+		if err := serializeItem(serializer, item); err != nil {
+			return err
+		}
+	}
+	return nil
+}
+
+// DeserializeArray deserializes `len(obj)` items into `obj`.
+func DeserializeArray[T any](deserializer serde.Deserializer, obj []T, deserializeItem func(serde.Deserializer) (T, error)) error {
+	for i := range obj {
+		item, err := deserializeItem(deserializer)
+		if err != nil {
+			return err
+		}
+		obj[i] = item
+	}
+	return nil
+}
+
+// SerializeMap serializes the length of a map followed by its entries. Entries are sorted if the
+// encoding requires it.
+func SerializeMap[K comparable, V any](serializer serde.Serializer, value map[K]V, serializeKey func(serde.Serializer, K) error, serializeValue func(serde.Serializer, V) error) error {
+	if err := serializer.SerializeLen(uint64(len(value))); err != nil {
+		return err
+	}
+	offsets := make([]uint64, len(value))
+	count := 0
+	for k, v := range value {
+		offsets[count] = serializer.GetBufferOffset()
+		count += 1
+		if err := serializeKey(serializer, k); err != nil {
+			return err
+		}
+		if err := serializeValue(serializer, v); err != nil {
+			return err
+		}
+	}
+	serializer.SortMapEntries(offsets)
+	return nil
+}
+
+// DeserializeMap deserializes the length of a map followed by its entries. Keys are checked to be
+// in increasing order if the encoding requires it.
+func DeserializeMap[K comparable, V any](deserializer serde.Deserializer, deserializeKey func(serde.Deserializer) (K, error), deserializeValue func(serde.Deserializer) (V, error)) (map[K]V, error) {
+	length, err := deserializer.DeserializeLen()
+	if err != nil {
+		return nil, err
+	}
+	obj := make(map[K]V)
+	previousSlice := serde.Slice{0, 0}
+	for i := 0; i < int(length); i++ {
+		var slice serde.Slice
+		slice.Start = deserializer.GetBufferOffset()
+		key, err := deserializeKey(deserializer)
+		if err != nil {
+			return nil, err
+		}
+		slice.End = deserializer.GetBufferOffset()
+		if i > 0 {
+			if err := deserializer.CheckThatKeySlicesAreIncreasing(previousSlice, slice); err != nil {
+				return nil, err
+			}
+		}
+		previousSlice = slice
+		value, err := deserializeValue(deserializer)
+		if err != nil {
+			return nil, err
+		}
+		obj[key] = value
+	}
+	return obj, nil
+}
@@ -0,0 +1,7 @@
+module github.com/novifinancial/serde-reflection/serde-generate/runtime/golang/generics
+
+go 1.18
+
+require github.com/novifinancial/serde-reflection/serde-generate/runtime/golang v0.0.0
+
+replace github.com/novifinancial/serde-reflection/serde-generate/runtime/golang => ../
@@ -3,6 +3,11 @@
 
 package serde
 
+import (
+	"fmt"
+	"math/big"
+)
+
 type Uint128 struct {
 	High uint64
 	Low  uint64
@@ -12,3 +17,53 @@ type Int128 struct {
 	High int64
 	Low  uint64
 }
+
+var (
+	maxUint64  = new(big.Int).SetUint64(^uint64(0))
+	maxUint128 = new(big.Int).Sub(new(big.Int).Lsh(big.NewInt(1), 128), big.NewInt(1))
+	maxInt128  = new(big.Int).Sub(new(big.Int).Lsh(big.NewInt(1), 127), big.NewInt(1))
+	minInt128  = new(big.Int).Neg(new(big.Int).Lsh(big.NewInt(1), 127))
+)
+
+func (obj Uint128) bigInt() *big.Int {
+	value := new(big.Int).Lsh(new(big.Int).SetUint64(obj.High), 64)
+	return value.Add(value, new(big.Int).SetUint64(obj.Low))
+}
+
+func (obj Int128) bigInt() *big.Int {
+	value := new(big.Int).Lsh(big.NewInt(obj.High), 64)
+	return value.Add(value, new(big.Int).SetUint64(obj.Low))
+}
+
+// MarshalJSON encodes the integer as a JSON number (same as serde_json).
+func (obj Uint128) MarshalJSON() ([]byte, error) {
+	return []byte(obj.bigInt().String()), nil
+}
+
+// UnmarshalJSON decodes the integer from a JSON number.
+func (obj *Uint128) UnmarshalJSON(data []byte) error {
+	value, ok := new(big.Int).SetString(string(data), 10)
+	if !ok || value.Sign() < 0 || value.Cmp(maxUint128) > 0 {
+		return fmt.Errorf("Invalid value for uint128: %s", data)
+	}
+	obj.Low = new(big.Int).And(value, maxUint64).Uint64()
+	obj.High = new(big.Int).Rsh(value, 64).Uint64()
+	return nil
+}
+
+// MarshalJSON encodes the integer as a JSON number (same as serde_json).
+func (obj Int128) MarshalJSON() ([]byte, error) {
+	return []byte(obj.bigInt().String()), nil
+}
+
+// UnmarshalJSON decodes the integer from a JSON number.
+func (obj *Int128) UnmarshalJSON(data []byte) error {
+	value, ok := new(big.Int).SetString(string(data), 10)
+	if !ok || value.Cmp(minInt128) < 0 || value.Cmp(maxInt128) > 0 {
+		return fmt.Errorf("Invalid value for int128: %s", data)
+	}
+	// Bitwise operations on big integers follow two's complement semantics.
+	obj.Low = new(big.Int).And(value, maxUint64).Uint64()
+	obj.High = new(big.Int).Rsh(value, 64).Int64()
+	return nil
+}
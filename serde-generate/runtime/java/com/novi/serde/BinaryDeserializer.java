@@ -44,7 +44,7 @@ public abstract class BinaryDeserializer implements Deserializer {
         } catch (CharacterCodingException ex) {
             throw new DeserializationError("Incorrect UTF8 string");
         }
-        return new String(content);
+        return new String(content, StandardCharsets.UTF_8);
     }
 
     public Bytes deserialize_bytes() throws DeserializationError {
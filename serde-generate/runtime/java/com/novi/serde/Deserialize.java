@@ -0,0 +1,12 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+package com.novi.serde;
+
+/**
+ * Deserialization of the values of a type parameter, as expected by generic classes.
+ */
+@FunctionalInterface
+public interface Deserialize<T> {
+    T deserialize(Deserializer deserializer) throws DeserializationError;
+}
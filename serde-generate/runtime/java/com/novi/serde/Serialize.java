@@ -0,0 +1,12 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+package com.novi.serde;
+
+/**
+ * Serialization of the values of a type parameter, as expected by generic classes.
+ */
+@FunctionalInterface
+public interface Serialize<T> {
+    void serialize(T value, Serializer serializer) throws SerializationError;
+}
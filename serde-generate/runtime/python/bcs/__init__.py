@@ -19,8 +19,10 @@ MAX_CONTAINER_DEPTH = 500
 class BcsSerializer(sb.BinarySerializer):
     def __init__(self):
         super().__init__(
-            output=io.BytesIO(), container_depth_budget=MAX_CONTAINER_DEPTH
+            output=sb.make_output(), container_depth_budget=MAX_CONTAINER_DEPTH
         )
+        if sb.uses_speedups(self.output):
+            self.serialize_u32_as_uleb128 = self.output.write_uleb128_u32
 
     def serialize_u32_as_uleb128(self, value: int):
         while value >= 0x80:
@@ -60,8 +62,10 @@ class BcsSerializer(sb.BinarySerializer):
 class BcsDeserializer(sb.BinaryDeserializer):
     def __init__(self, content):
         super().__init__(
-            input=io.BytesIO(content), container_depth_budget=MAX_CONTAINER_DEPTH
+            input=sb.make_input(content), container_depth_budget=MAX_CONTAINER_DEPTH
         )
+        if sb.uses_speedups(self.input):
+            self.deserialize_uleb128_as_u32 = self.input.read_uleb128_u32
 
     def deserialize_uleb128_as_u32(self) -> int:
         value = 0
@@ -18,7 +18,7 @@ MAX_LENGTH = (1 << 31) - 1
 
 class BincodeSerializer(sb.BinarySerializer):
     def __init__(self):
-        super().__init__(output=io.BytesIO(), container_depth_budget=None)
+        super().__init__(output=sb.make_output(), container_depth_budget=None)
 
     def serialize_f32(self, value: st.float32):
         self.output.write(struct.pack("<f", value))
@@ -40,7 +40,7 @@ class BincodeSerializer(sb.BinarySerializer):
 
 class BincodeDeserializer(sb.BinaryDeserializer):
     def __init__(self, content):
-        super().__init__(input=io.BytesIO(content), container_depth_budget=None)
+        super().__init__(input=sb.make_input(content), container_depth_budget=None)
 
     def deserialize_f32(self) -> st.float32:
         (value,) = struct.unpack("<f", self.read(4))
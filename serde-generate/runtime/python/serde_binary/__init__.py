@@ -9,12 +9,68 @@ Note: This internal module is currently only meant to share code between the BCS
 
 import dataclasses
 import collections
+import functools
 import io
+import os
 import typing
 from typing import get_type_hints
 
 import serde_types as st
 
+# Optional native accelerator (see `build_speedups.py`).
+try:
+    if os.environ.get("SERDE_BINARY_NO_SPEEDUPS"):
+        raise ImportError
+    from . import _speedups  # pyre-ignore
+except ImportError:
+    _speedups = None
+
+
+def make_output() -> typing.Any:
+    """Create the output buffer of a serializer, using the native runtime if available."""
+    if _speedups is not None:
+        return _speedups.Writer()
+    return io.BytesIO()
+
+
+def make_input(content: bytes) -> typing.Any:
+    """Create the input buffer of a deserializer, using the native runtime if available."""
+    if _speedups is not None:
+        return _speedups.Reader(content, st.DeserializationError)
+    return io.BytesIO(content)
+
+
+def uses_speedups(buffer: typing.Any) -> bool:
+    """Whether the given input or output buffer was created by the native runtime."""
+    return _speedups is not None and isinstance(
+        buffer, (_speedups.Reader, _speedups.Writer)
+    )
+
+
+def is_struct_type(obj_type) -> bool:
+    """Whether the given type is a struct or an enum variant (dataclass or pydantic model)."""
+    if dataclasses.is_dataclass(obj_type):
+        return True
+    # Pydantic models: the base class of an enum defines `VARIANTS` but no `INDEX`.
+    return hasattr(obj_type, "model_fields") and (
+        hasattr(obj_type, "INDEX") or not hasattr(obj_type, "VARIANTS")
+    )
+
+
+def get_field_names(obj_type) -> typing.List[str]:
+    """Names of the fields of a struct or an enum variant, in order."""
+    if dataclasses.is_dataclass(obj_type):
+        return [field.name for field in dataclasses.fields(obj_type)]
+    return list(obj_type.model_fields)
+
+
+def get_field_types(obj_type) -> typing.Dict[str, typing.Any]:
+    """Types of the fields of a struct or an enum variant, indexed by names."""
+    if dataclasses.is_dataclass(obj_type):
+        return get_type_hints(obj_type)
+    # Pydantic models keep the serde type of integers in `Annotated` metadata (Python >= 3.9).
+    return get_type_hints(obj_type, include_extras=True)
+
 
 @dataclasses.dataclass
 class BinarySerializer:
@@ -29,6 +85,19 @@ class BinarySerializer:
     primitive_type_serializer: typing.Mapping = dataclasses.field(init=False)
 
     def __post_init__(self):
+        if uses_speedups(self.output):
+            # Shadow the methods of pure Python primitives.
+            self.serialize_bool = self.output.write_bool
+            self.serialize_u8 = self.output.write_u8
+            self.serialize_u16 = self.output.write_u16
+            self.serialize_u32 = self.output.write_u32
+            self.serialize_u64 = self.output.write_u64
+            self.serialize_u128 = self.output.write_u128
+            self.serialize_i8 = self.output.write_i8
+            self.serialize_i16 = self.output.write_i16
+            self.serialize_i32 = self.output.write_i32
+            self.serialize_i64 = self.output.write_i64
+            self.serialize_i128 = self.output.write_i128
         self.primitive_type_serializer = {
             bool: self.serialize_bool,
             st.uint8: self.serialize_u8,
@@ -132,6 +201,10 @@ class BinarySerializer:
             self.primitive_type_serializer[obj_type](obj)
 
         elif hasattr(obj_type, "__origin__"):  # Generic type
+            if hasattr(obj_type, "__metadata__"):  # Annotated type (pydantic models)
+                self.serialize_any(obj, self.get_annotated_serde_type(obj_type))
+                return
+
             types = getattr(obj_type, "__args__")
 
             if getattr(obj_type, "__origin__") == collections.abc.Sequence:  # Sequence
@@ -167,7 +240,7 @@ class BinarySerializer:
                 raise st.SerializationError("Unexpected type", obj_type)
 
         else:
-            if not dataclasses.is_dataclass(obj_type):  # Enum
+            if not is_struct_type(obj_type):  # Enum
                 if not hasattr(obj_type, "VARIANTS"):
                     raise st.SerializationError("Unexpected type", obj_type)
                 if not hasattr(obj, "INDEX"):
@@ -177,7 +250,7 @@ class BinarySerializer:
                 self.serialize_variant_index(obj.__class__.INDEX)
                 # Proceed to variant
                 obj_type = obj_type.VARIANTS[obj.__class__.INDEX]
-                if not dataclasses.is_dataclass(obj_type):
+                if not is_struct_type(obj_type):
                     raise st.SerializationError("Unexpected type", obj_type)
 
             # pyre-ignore
@@ -185,15 +258,21 @@ class BinarySerializer:
                 raise st.SerializationError("Wrong Value for the type", obj, obj_type)
 
             # Content of struct or variant
-            fields = dataclasses.fields(obj_type)
-            types = get_type_hints(obj_type)
+            types = get_field_types(obj_type)
             self.increase_container_depth()
-            for field in fields:
-                field_value = obj.__dict__[field.name]
-                field_type = types[field.name]
+            for name in get_field_names(obj_type):
+                field_value = obj.__dict__[name]
+                field_type = types[name]
                 self.serialize_any(field_value, field_type)
             self.decrease_container_depth()
 
+    def get_annotated_serde_type(self, obj_type):
+        # A primitive Serde type comes first in the annotations, if any.
+        serde_type = obj_type.__metadata__[0]
+        if serde_type in self.primitive_type_serializer:
+            return serde_type
+        return obj_type.__origin__
+
 
 @dataclasses.dataclass
 class BinaryDeserializer:
@@ -208,6 +287,19 @@ class BinaryDeserializer:
     primitive_type_deserializer: typing.Mapping = dataclasses.field(init=False)
 
     def __post_init__(self):
+        if uses_speedups(self.input):
+            # Shadow the methods of pure Python primitives.
+            self.deserialize_bool = self.input.read_bool
+            self.deserialize_u8 = functools.partial(self.input.read_u8, st.uint8)
+            self.deserialize_u16 = functools.partial(self.input.read_u16, st.uint16)
+            self.deserialize_u32 = functools.partial(self.input.read_u32, st.uint32)
+            self.deserialize_u64 = functools.partial(self.input.read_u64, st.uint64)
+            self.deserialize_u128 = functools.partial(self.input.read_u128, st.uint128)
+            self.deserialize_i8 = functools.partial(self.input.read_i8, st.int8)
+            self.deserialize_i16 = functools.partial(self.input.read_i16, st.int16)
+            self.deserialize_i32 = functools.partial(self.input.read_i32, st.int32)
+            self.deserialize_i64 = functools.partial(self.input.read_i64, st.int64)
+            self.deserialize_i128 = functools.partial(self.input.read_i128, st.int128)
         self.primitive_type_deserializer = {
             bool: self.deserialize_bool,
             st.uint8: self.deserialize_u8,
@@ -333,6 +425,9 @@ class BinaryDeserializer:
             return self.primitive_type_deserializer[obj_type]()
 
         elif hasattr(obj_type, "__origin__"):  # Generic type
+            if hasattr(obj_type, "__metadata__"):  # Annotated type (pydantic models)
+                return self.deserialize_annotated(obj_type)
+
             types = getattr(obj_type, "__args__")
             if getattr(obj_type, "__origin__") == collections.abc.Sequence:  # Sequence
                 assert len(types) == 1
@@ -389,17 +484,19 @@ class BinaryDeserializer:
 
         else:
             # handle structs
-            if dataclasses.is_dataclass(obj_type):
+            if is_struct_type(obj_type):
                 values = []
-                fields = dataclasses.fields(obj_type)
-                typing_hints = get_type_hints(obj_type)
+                names = get_field_names(obj_type)
+                typing_hints = get_field_types(obj_type)
                 self.increase_container_depth()
-                for field in fields:
-                    field_type = typing_hints[field.name]
+                for name in names:
+                    field_type = typing_hints[name]
                     field_value = self.deserialize_any(field_type)
                     values.append(field_value)
                 self.decrease_container_depth()
-                return obj_type(*values)
+                if dataclasses.is_dataclass(obj_type):
+                    return obj_type(*values)
+                return obj_type(**dict(zip(names, values)))
 
             # handle variant
             elif hasattr(obj_type, "VARIANTS"):
@@ -413,3 +510,14 @@ class BinaryDeserializer:
 
             else:
                 raise st.DeserializationError("Unexpected type", obj_type)
+
+    def deserialize_annotated(self, obj_type) -> typing.Any:
+        python_type = obj_type.__origin__
+        # A primitive Serde type comes first in the annotations, if any.
+        serde_type = obj_type.__metadata__[0]
+        if serde_type not in self.primitive_type_deserializer:
+            return self.deserialize_any(python_type)
+        value = self.primitive_type_deserializer[serde_type]()
+        if type(value) is not python_type:
+            value = python_type(value)
+        return value
@@ -0,0 +1,543 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Optional native accelerator for the module `serde_binary`.
+//
+// This module provides `Reader` and `Writer` buffers which replace `io.BytesIO` in binary
+// serializers and deserializers. In addition to the subset of the `io.BytesIO` API used by the
+// pure Python runtime, they provide native methods to encode and decode primitive values.
+//
+// See `build_speedups.py` for build instructions.
+
+#define PY_SSIZE_T_CLEAN
+#include <Python.h>
+#include <stdint.h>
+#include <string.h>
+
+// --- Reader ---
+
+typedef struct {
+    PyObject_HEAD
+    // Immutable copy of the input.
+    PyObject *content;
+    Py_ssize_t offset;
+    // Exception type raised on invalid inputs (i.e. `serde_types.DeserializationError`).
+    PyObject *error_type;
+} Reader;
+
+static void Reader_dealloc(Reader *self) {
+    Py_XDECREF(self->content);
+    Py_XDECREF(self->error_type);
+    Py_TYPE(self)->tp_free((PyObject *)self);
+}
+
+static int Reader_init(Reader *self, PyObject *args, PyObject *kwds) {
+    static char *kwlist[] = {"content", "error_type", NULL};
+    PyObject *content, *error_type;
+    if (!PyArg_ParseTupleAndKeywords(args, kwds, "OO", kwlist, &content, &error_type)) {
+        return -1;
+    }
+    PyObject *bytes = PyBytes_FromObject(content);
+    if (bytes == NULL) {
+        return -1;
+    }
+    Py_XDECREF(self->content);
+    self->content = bytes;
+    Py_INCREF(error_type);
+    Py_XDECREF(self->error_type);
+    self->error_type = error_type;
+    self->offset = 0;
+    return 0;
+}
+
+static Py_ssize_t Reader_remaining(Reader *self) {
+    return PyBytes_GET_SIZE(self->content) - self->offset;
+}
+
+// Consume `len` bytes of input or raise an error.
+static const unsigned char *Reader_take(Reader *self, Py_ssize_t len) {
+    if (Reader_remaining(self) < len) {
+        PyErr_SetString(self->error_type, "Input is too short");
+        return NULL;
+    }
+    const unsigned char *data =
+        (const unsigned char *)PyBytes_AS_STRING(self->content) + self->offset;
+    self->offset += len;
+    return data;
+}
+
+static PyObject *Reader_read(Reader *self, PyObject *args) {
+    Py_ssize_t len = -1;
+    if (!PyArg_ParseTuple(args, "|n", &len)) {
+        return NULL;
+    }
+    Py_ssize_t remaining = Reader_remaining(self);
+    if (len < 0 || len > remaining) {
+        len = remaining;
+    }
+    PyObject *result =
+        PyBytes_FromStringAndSize(PyBytes_AS_STRING(self->content) + self->offset, len);
+    if (result != NULL) {
+        self->offset += len;
+    }
+    return result;
+}
+
+static PyObject *Reader_tell(Reader *self, PyObject *Py_UNUSED(ignored)) {
+    return PyLong_FromSsize_t(self->offset);
+}
+
+static PyObject *Reader_getbuffer(Reader *self, PyObject *Py_UNUSED(ignored)) {
+    return PyMemoryView_FromObject(self->content);
+}
+
+static uint64_t load_u64(const unsigned char *data, Py_ssize_t size) {
+    uint64_t value = 0;
+    for (Py_ssize_t i = 0; i < size; i++) {
+        value |= (uint64_t)data[i] << (8 * i);
+    }
+    return value;
+}
+
+// Decode an integer of `size` bytes and apply the constructor `ctor` (unless it is None).
+static PyObject *Reader_read_int(Reader *self, Py_ssize_t size, int is_signed, PyObject *ctor) {
+    const unsigned char *data = Reader_take(self, size);
+    if (data == NULL) {
+        return NULL;
+    }
+    PyObject *value;
+    if (size <= 8) {
+        uint64_t bits = load_u64(data, size);
+        if (is_signed) {
+            int shift = 64 - 8 * (int)size;
+            value = PyLong_FromLongLong((long long)(int64_t)(bits << shift) >> shift);
+        } else {
+            value = PyLong_FromUnsignedLongLong(bits);
+        }
+    } else {
+        uint64_t low = load_u64(data, 8);
+        uint64_t high = load_u64(data + 8, 8);
+        PyObject *high_obj = is_signed ? PyLong_FromLongLong((long long)(int64_t)high)
+                                       : PyLong_FromUnsignedLongLong(high);
+        PyObject *low_obj = PyLong_FromUnsignedLongLong(low);
+        PyObject *shift = PyLong_FromLong(64);
+        PyObject *shifted = NULL;
+        value = NULL;
+        if (high_obj != NULL && low_obj != NULL && shift != NULL) {
+            shifted = PyNumber_Lshift(high_obj, shift);
+        }
+        if (shifted != NULL) {
+            value = PyNumber_Add(shifted, low_obj);
+        }
+        Py_XDECREF(high_obj);
+        Py_XDECREF(low_obj);
+        Py_XDECREF(shift);
+        Py_XDECREF(shifted);
+    }
+    if (value == NULL || ctor == Py_None) {
+        return value;
+    }
+    PyObject *result = PyObject_CallFunctionObjArgs(ctor, value, NULL);
+    Py_DECREF(value);
+    return result;
+}
+
+#define READER_INT_METHOD(name, size, is_signed)                                                   \
+    static PyObject *Reader_##name(Reader *self, PyObject *ctor) {                                 \
+        return Reader_read_int(self, size, is_signed, ctor);                                       \
+    }
+
+READER_INT_METHOD(read_u8, 1, 0)
+READER_INT_METHOD(read_u16, 2, 0)
+READER_INT_METHOD(read_u32, 4, 0)
+READER_INT_METHOD(read_u64, 8, 0)
+READER_INT_METHOD(read_u128, 16, 0)
+READER_INT_METHOD(read_i8, 1, 1)
+READER_INT_METHOD(read_i16, 2, 1)
+READER_INT_METHOD(read_i32, 4, 1)
+READER_INT_METHOD(read_i64, 8, 1)
+READER_INT_METHOD(read_i128, 16, 1)
+
+static PyObject *Reader_read_bool(Reader *self, PyObject *Py_UNUSED(ignored)) {
+    const unsigned char *data = Reader_take(self, 1);
+    if (data == NULL) {
+        return NULL;
+    }
+    switch (data[0]) {
+    case 0:
+        Py_RETURN_FALSE;
+    case 1:
+        Py_RETURN_TRUE;
+    default:
+        PyErr_Format(self->error_type, "Unexpected boolean value: %d", data[0]);
+        return NULL;
+    }
+}
+
+static PyObject *Reader_read_uleb128_u32(Reader *self, PyObject *Py_UNUSED(ignored)) {
+    uint64_t value = 0;
+    for (int shift = 0; shift < 32; shift += 7) {
+        const unsigned char *data = Reader_take(self, 1);
+        if (data == NULL) {
+            return NULL;
+        }
+        uint8_t digit = data[0] & 0x7F;
+        value |= (uint64_t)digit << shift;
+        if (value > UINT32_MAX) {
+            break;
+        }
+        if (digit == data[0]) {
+            if (shift > 0 && digit == 0) {
+                PyErr_SetString(self->error_type,
+                                "Invalid uleb128 number (unexpected zero digit)");
+                return NULL;
+            }
+            return PyLong_FromUnsignedLongLong(value);
+        }
+    }
+    PyErr_SetString(self->error_type, "Overflow while parsing uleb128-encoded uint32 value");
+    return NULL;
+}
+
+static PyMethodDef Reader_methods[] = {
+    {"read", (PyCFunction)Reader_read, METH_VARARGS, "Read at most `n` bytes."},
+    {"tell", (PyCFunction)Reader_tell, METH_NOARGS, "Current offset in the input."},
+    {"getbuffer", (PyCFunction)Reader_getbuffer, METH_NOARGS, "View of the entire input."},
+    {"read_u8", (PyCFunction)Reader_read_u8, METH_O, NULL},
+    {"read_u16", (PyCFunction)Reader_read_u16, METH_O, NULL},
+    {"read_u32", (PyCFunction)Reader_read_u32, METH_O, NULL},
+    {"read_u64", (PyCFunction)Reader_read_u64, METH_O, NULL},
+    {"read_u128", (PyCFunction)Reader_read_u128, METH_O, NULL},
+    {"read_i8", (PyCFunction)Reader_read_i8, METH_O, NULL},
+    {"read_i16", (PyCFunction)Reader_read_i16, METH_O, NULL},
+    {"read_i32", (PyCFunction)Reader_read_i32, METH_O, NULL},
+    {"read_i64", (PyCFunction)Reader_read_i64, METH_O, NULL},
+    {"read_i128", (PyCFunction)Reader_read_i128, METH_O, NULL},
+    {"read_bool", (PyCFunction)Reader_read_bool, METH_NOARGS, NULL},
+    {"read_uleb128_u32", (PyCFunction)Reader_read_uleb128_u32, METH_NOARGS, NULL},
+    {NULL},
+};
+
+static PyTypeObject ReaderType = {
+    PyVarObject_HEAD_INIT(NULL, 0).tp_name = "_speedups.Reader",
+    .tp_doc = "Input buffer for binary deserializers",
+    .tp_basicsize = sizeof(Reader),
+    .tp_itemsize = 0,
+    .tp_flags = Py_TPFLAGS_DEFAULT,
+    .tp_new = PyType_GenericNew,
+    .tp_init = (initproc)Reader_init,
+    .tp_dealloc = (destructor)Reader_dealloc,
+    .tp_methods = Reader_methods,
+};
+
+// --- Writer ---
+
+typedef struct {
+    PyObject_HEAD
+    unsigned char *data;
+    Py_ssize_t length;
+    Py_ssize_t capacity;
+    Py_ssize_t position;
+    // Number of active views created by `getbuffer`.
+    Py_ssize_t exports;
+} Writer;
+
+static void Writer_dealloc(Writer *self) {
+    PyMem_Free(self->data);
+    Py_TYPE(self)->tp_free((PyObject *)self);
+}
+
+static int Writer_init(Writer *self, PyObject *args, PyObject *kwds) {
+    static char *kwlist[] = {NULL};
+    if (!PyArg_ParseTupleAndKeywords(args, kwds, "", kwlist)) {
+        return -1;
+    }
+    if (self->data == NULL) {
+        self->data = PyMem_Malloc(64);
+        if (self->data == NULL) {
+            PyErr_NoMemory();
+            return -1;
+        }
+        self->capacity = 64;
+    }
+    self->length = 0;
+    self->position = 0;
+    return 0;
+}
+
+// Write `len` bytes at the current position, overwriting or extending the content.
+static int Writer_put(Writer *self, const void *data, Py_ssize_t len) {
+    Py_ssize_t end = self->position + len;
+    if (end > self->capacity) {
+        if (self->exports > 0) {
+            PyErr_SetString(PyExc_BufferError,
+                            "Existing exports of data: object cannot be re-sized");
+            return -1;
+        }
+        Py_ssize_t capacity = self->capacity;
+        while (capacity < end) {
+            capacity *= 2;
+        }
+        unsigned char *buffer = PyMem_Realloc(self->data, capacity);
+        if (buffer == NULL) {
+            PyErr_NoMemory();
+            return -1;
+        }
+        self->data = buffer;
+        self->capacity = capacity;
+    }
+    memcpy(self->data + self->position, data, len);
+    self->position = end;
+    if (end > self->length) {
+        self->length = end;
+    }
+    return 0;
+}
+
+static PyObject *Writer_write(Writer *self, PyObject *arg) {
+    Py_buffer view;
+    if (PyObject_GetBuffer(arg, &view, PyBUF_SIMPLE) < 0) {
+        return NULL;
+    }
+    int status = Writer_put(self, view.buf, view.len);
+    Py_ssize_t len = view.len;
+    PyBuffer_Release(&view);
+    if (status < 0) {
+        return NULL;
+    }
+    return PyLong_FromSsize_t(len);
+}
+
+static PyObject *Writer_seek(Writer *self, PyObject *arg) {
+    Py_ssize_t position = PyLong_AsSsize_t(arg);
+    if (position == -1 && PyErr_Occurred()) {
+        return NULL;
+    }
+    if (position < 0 || position > self->length) {
+        PyErr_Format(PyExc_ValueError, "invalid position: %zd", position);
+        return NULL;
+    }
+    self->position = position;
+    return PyLong_FromSsize_t(position);
+}
+
+static PyObject *Writer_tell(Writer *self, PyObject *Py_UNUSED(ignored)) {
+    return PyLong_FromSsize_t(self->position);
+}
+
+static PyObject *Writer_getvalue(Writer *self, PyObject *Py_UNUSED(ignored)) {
+    return PyBytes_FromStringAndSize((const char *)self->data, self->length);
+}
+
+static PyObject *Writer_getbuffer(Writer *self, PyObject *Py_UNUSED(ignored)) {
+    return PyMemoryView_FromObject((PyObject *)self);
+}
+
+static int Writer_bf_getbuffer(Writer *self, Py_buffer *view, int flags) {
+    if (PyBuffer_FillInfo(view, (PyObject *)self, self->data, self->length, 0, flags) < 0) {
+        return -1;
+    }
+    self->exports++;
+    return 0;
+}
+
+static void Writer_bf_releasebuffer(Writer *self, Py_buffer *Py_UNUSED(view)) {
+    self->exports--;
+}
+
+static PyBufferProcs Writer_as_buffer = {
+    (getbufferproc)Writer_bf_getbuffer,
+    (releasebufferproc)Writer_bf_releasebuffer,
+};
+
+static int Writer_put_u64(Writer *self, uint64_t value, Py_ssize_t size) {
+    unsigned char buffer[8];
+    for (Py_ssize_t i = 0; i < size; i++) {
+        buffer[i] = (unsigned char)(value >> (8 * i));
+    }
+    return Writer_put(self, buffer, size);
+}
+
+// Encode the integer `value` on `size` bytes. Like `int.to_bytes`, raise `OverflowError` if
+// the value is out of range.
+static PyObject *Writer_write_int(Writer *self, PyObject *value, Py_ssize_t size, int is_signed) {
+    PyObject *number = PyNumber_Long(value);
+    if (number == NULL) {
+        return NULL;
+    }
+    int status = -1;
+    if (size <= 8) {
+        int bits = 8 * (int)size;
+        if (is_signed) {
+            long long x = PyLong_AsLongLong(number);
+            if (!(x == -1 && PyErr_Occurred())) {
+                if (bits < 64 && (x < -(1LL << (bits - 1)) || x >= (1LL << (bits - 1)))) {
+                    PyErr_SetString(PyExc_OverflowError, "int too big to convert");
+                } else {
+                    status = Writer_put_u64(self, (uint64_t)x, size);
+                }
+            }
+        } else {
+            unsigned long long x = PyLong_AsUnsignedLongLong(number);
+            if (!(x == (unsigned long long)-1 && PyErr_Occurred())) {
+                if (bits < 64 && x >= (1ULL << bits)) {
+                    PyErr_SetString(PyExc_OverflowError, "int too big to convert");
+                } else {
+                    status = Writer_put_u64(self, (uint64_t)x, size);
+                }
+            }
+        }
+    } else {
+        // The low 64 bits are taken modulo 2^64. The high bits must fit in 64 bits.
+        unsigned long long low = PyLong_AsUnsignedLongLongMask(number);
+        PyObject *shift = PyLong_FromLong(64);
+        PyObject *high_obj = shift != NULL ? PyNumber_Rshift(number, shift) : NULL;
+        if (!(low == (unsigned long long)-1 && PyErr_Occurred()) && high_obj != NULL) {
+            uint64_t high = 0;
+            int ok;
+            if (is_signed) {
+                long long x = PyLong_AsLongLong(high_obj);
+                ok = !(x == -1 && PyErr_Occurred());
+                high = (uint64_t)x;
+            } else {
+                unsigned long long x = PyLong_AsUnsignedLongLong(high_obj);
+                ok = !(x == (unsigned long long)-1 && PyErr_Occurred());
+                high = (uint64_t)x;
+            }
+            if (ok && Writer_put_u64(self, low, 8) == 0) {
+                status = Writer_put_u64(self, high, 8);
+            }
+        }
+        if (PyErr_Occurred() && PyErr_ExceptionMatches(PyExc_OverflowError)) {
+            PyErr_SetString(PyExc_OverflowError, "int too big to convert");
+        }
+        Py_XDECREF(shift);
+        Py_XDECREF(high_obj);
+    }
+    Py_DECREF(number);
+    if (status < 0) {
+        return NULL;
+    }
+    Py_RETURN_NONE;
+}
+
+#define WRITER_INT_METHOD(name, size, is_signed)                                                   \
+    static PyObject *Writer_##name(Writer *self, PyObject *value) {                                \
+        return Writer_write_int(self, value, size, is_signed);                                     \
+    }
+
+WRITER_INT_METHOD(write_u8, 1, 0)
+WRITER_INT_METHOD(write_u16, 2, 0)
+WRITER_INT_METHOD(write_u32, 4, 0)
+WRITER_INT_METHOD(write_u64, 8, 0)
+WRITER_INT_METHOD(write_u128, 16, 0)
+WRITER_INT_METHOD(write_i8, 1, 1)
+WRITER_INT_METHOD(write_i16, 2, 1)
+WRITER_INT_METHOD(write_i32, 4, 1)
+WRITER_INT_METHOD(write_i64, 8, 1)
+WRITER_INT_METHOD(write_i128, 16, 1)
+
+static PyObject *Writer_write_bool(Writer *self, PyObject *value) {
+    int truth = PyObject_IsTrue(value);
+    if (truth < 0) {
+        return NULL;
+    }
+    unsigned char byte = (unsigned char)truth;
+    if (Writer_put(self, &byte, 1) < 0) {
+        return NULL;
+    }
+    Py_RETURN_NONE;
+}
+
+static PyObject *Writer_write_uleb128_u32(Writer *self, PyObject *value) {
+    PyObject *number = PyNumber_Long(value);
+    if (number == NULL) {
+        return NULL;
+    }
+    unsigned long long x = PyLong_AsUnsignedLongLong(number);
+    Py_DECREF(number);
+    if (x == (unsigned long long)-1 && PyErr_Occurred()) {
+        return NULL;
+    }
+    if (x > UINT32_MAX) {
+        PyErr_SetString(PyExc_OverflowError, "int too big to convert");
+        return NULL;
+    }
+    unsigned char buffer[5];
+    Py_ssize_t len = 0;
+    while (x >= 0x80) {
+        buffer[len++] = (unsigned char)((x & 0x7F) | 0x80);
+        x >>= 7;
+    }
+    buffer[len++] = (unsigned char)x;
+    if (Writer_put(self, buffer, len) < 0) {
+        return NULL;
+    }
+    Py_RETURN_NONE;
+}
+
+static PyMethodDef Writer_methods[] = {
+    {"write", (PyCFunction)Writer_write, METH_O, "Write bytes at the current position."},
+    {"seek", (PyCFunction)Writer_seek, METH_O, "Change the current position."},
+    {"tell", (PyCFunction)Writer_tell, METH_NOARGS, "Current position."},
+    {"getvalue", (PyCFunction)Writer_getvalue, METH_NOARGS, "Copy of the content."},
+    {"getbuffer", (PyCFunction)Writer_getbuffer, METH_NOARGS, "View of the content."},
+    {"write_u8", (PyCFunction)Writer_write_u8, METH_O, NULL},
+    {"write_u16", (PyCFunction)Writer_write_u16, METH_O, NULL},
+    {"write_u32", (PyCFunction)Writer_write_u32, METH_O, NULL},
+    {"write_u64", (PyCFunction)Writer_write_u64, METH_O, NULL},
+    {"write_u128", (PyCFunction)Writer_write_u128, METH_O, NULL},
+    {"write_i8", (PyCFunction)Writer_write_i8, METH_O, NULL},
+    {"write_i16", (PyCFunction)Writer_write_i16, METH_O, NULL},
+    {"write_i32", (PyCFunction)Writer_write_i32, METH_O, NULL},
+    {"write_i64", (PyCFunction)Writer_write_i64, METH_O, NULL},
+    {"write_i128", (PyCFunction)Writer_write_i128, METH_O, NULL},
+    {"write_bool", (PyCFunction)Writer_write_bool, METH_O, NULL},
+    {"write_uleb128_u32", (PyCFunction)Writer_write_uleb128_u32, METH_O, NULL},
+    {NULL},
+};
+
+static PyTypeObject WriterType = {
+    PyVarObject_HEAD_INIT(NULL, 0).tp_name = "_speedups.Writer",
+    .tp_doc = "Output buffer for binary serializers",
+    .tp_basicsize = sizeof(Writer),
+    .tp_itemsize = 0,
+    .tp_flags = Py_TPFLAGS_DEFAULT,
+    .tp_new = PyType_GenericNew,
+    .tp_init = (initproc)Writer_init,
+    .tp_dealloc = (destructor)Writer_dealloc,
+    .tp_methods = Writer_methods,
+    .tp_as_buffer = &Writer_as_buffer,
+};
+
+// --- Module ---
+
+static PyModuleDef speedups_module = {
+    PyModuleDef_HEAD_INIT,
+    .m_name = "_speedups",
+    .m_doc = "Native accelerator for the module `serde_binary`.",
+    .m_size = -1,
+};
+
+PyMODINIT_FUNC PyInit__speedups(void) {
+    if (PyType_Ready(&ReaderType) < 0 || PyType_Ready(&WriterType) < 0) {
+        return NULL;
+    }
+    PyObject *module = PyModule_Create(&speedups_module);
+    if (module == NULL) {
+        return NULL;
+    }
+    Py_INCREF(&ReaderType);
+    if (PyModule_AddObject(module, "Reader", (PyObject *)&ReaderType) < 0) {
+        Py_DECREF(&ReaderType);
+        Py_DECREF(module);
+        return NULL;
+    }
+    Py_INCREF(&WriterType);
+    if (PyModule_AddObject(module, "Writer", (PyObject *)&WriterType) < 0) {
+        Py_DECREF(&WriterType);
+        Py_DECREF(module);
+        return NULL;
+    }
+    return module;
+}
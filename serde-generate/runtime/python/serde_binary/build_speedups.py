@@ -0,0 +1,53 @@
+# Copyright (c) Facebook, Inc. and its affiliates
+# SPDX-License-Identifier: MIT OR Apache-2.0
+
+"""
+Build the optional native module `serde_binary._speedups` next to `serde_binary/__init__.py`.
+
+Usage: python3 path/to/serde_binary/build_speedups.py [--build-lib DIR] [--build-temp DIR]
+
+When the native module is available, binary serializers and deserializers use it transparently.
+Set the environment variable `SERDE_BINARY_NO_SPEEDUPS=1` to force the pure Python runtime.
+"""
+
+import argparse
+import os
+import tempfile
+
+from setuptools import Extension
+from setuptools.dist import Distribution
+
+
+def build(build_lib=None, build_temp=None):
+    package_dir = os.path.dirname(os.path.abspath(__file__))
+    if build_lib is None:
+        build_lib = os.path.dirname(package_dir)
+    with tempfile.TemporaryDirectory() as temp_dir:
+        distribution = Distribution(
+            {
+                "name": "serde_binary",
+                "ext_modules": [
+                    Extension(
+                        "serde_binary._speedups",
+                        [os.path.join(package_dir, "_speedups.c")],
+                    )
+                ],
+            }
+        )
+        distribution.script_args = [
+            "build_ext",
+            "--build-lib",
+            build_lib,
+            "--build-temp",
+            build_temp or temp_dir,
+        ]
+        distribution.parse_command_line()
+        distribution.run_commands()
+
+
+if __name__ == "__main__":
+    parser = argparse.ArgumentParser(description=__doc__)
+    parser.add_argument("--build-lib", help="Directory containing the package `serde_binary`")
+    parser.add_argument("--build-temp", help="Directory for temporary build files")
+    args = parser.parse_args()
+    build(args.build_lib, args.build_temp)
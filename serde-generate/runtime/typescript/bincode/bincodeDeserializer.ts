@@ -1,8 +1,8 @@
 import {BinaryDeserializer} from "../serde/binaryDeserializer";
 
 export class BincodeDeserializer extends BinaryDeserializer {
-    deserializeLen(): BigInt {
-        return this.deserializeU64();
+    deserializeLen(): number {
+        return this.deserializeU64AsNumber();
     }
 
     public deserializeVariantIndex(): number {
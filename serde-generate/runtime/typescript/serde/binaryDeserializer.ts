@@ -66,7 +66,7 @@ export abstract class BinaryDeserializer implements Deserializer {
     return new DataView(this.read(4)).getUint32(0, true);
   }
 
-  public deserializeU64(): BigInt {
+  public deserializeU64(): bigint {
     const low = this.deserializeU32();
     const high = this.deserializeU32();
 
@@ -74,12 +74,12 @@ export abstract class BinaryDeserializer implements Deserializer {
     return (BigInt(high) << BinaryDeserializer.BIG_32) | BigInt(low);
   }
 
-  public deserializeU128(): BigInt {
+  public deserializeU128(): bigint {
     const low = this.deserializeU64();
     const high = this.deserializeU64();
 
     // combine the two 64-bit values and return (little endian)
-    return (BigInt(high) << BinaryDeserializer.BIG_64) | BigInt(low);
+    return (high << BinaryDeserializer.BIG_64) | low;
   }
 
   public deserializeI8(): number {
@@ -94,20 +94,37 @@ export abstract class BinaryDeserializer implements Deserializer {
     return new DataView(this.read(4)).getInt32(0, true);
   }
 
-  public deserializeI64(): BigInt {
-    const low = this.deserializeI32();
-    const high = this.deserializeI32();
+  public deserializeI64(): bigint {
+    return BigInt.asIntN(64, this.deserializeU64());
+  }
 
-    // combine the two 32-bit values and return (little endian)
-    return (BigInt(high) << BinaryDeserializer.BIG_32) | BigInt(low);
+  public deserializeI128(): bigint {
+    return BigInt.asIntN(128, this.deserializeU128());
   }
 
-  public deserializeI128(): BigInt {
-    const low = this.deserializeI64();
-    const high = this.deserializeI64();
+  // Convert a bigint to a number, checking that no precision is lost.
+  private static toSafeNumber(value: bigint): number {
+    const result = Number(value);
+    if (!Number.isSafeInteger(result)) {
+      throw new Error('Integer value is not a safe number: ' + value);
+    }
+    return result;
+  }
 
-    // combine the two 64-bit values and return (little endian)
-    return (BigInt(high) << BinaryDeserializer.BIG_64) | BigInt(low);
+  public deserializeU64AsNumber(): number {
+    return BinaryDeserializer.toSafeNumber(this.deserializeU64());
+  }
+
+  public deserializeU128AsNumber(): number {
+    return BinaryDeserializer.toSafeNumber(this.deserializeU128());
+  }
+
+  public deserializeI64AsNumber(): number {
+    return BinaryDeserializer.toSafeNumber(this.deserializeI64());
+  }
+
+  public deserializeI128AsNumber(): number {
+    return BinaryDeserializer.toSafeNumber(this.deserializeI128());
   }
 
   public deserializeOptionTag(): boolean {
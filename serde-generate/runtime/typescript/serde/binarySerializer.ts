@@ -10,6 +10,12 @@ export abstract class BinarySerializer implements Serializer {
     // parsing it directly from the string representation of the number will overcome it and allow es6 to be configured as well
     private static readonly BIG_32Fs = BigInt('4294967295');
     private static readonly BIG_64Fs = BigInt('18446744073709551615');
+    private static readonly BIG_128Fs = BigInt('340282366920938463463374607431768211455');
+    private static readonly BIG_ZERO = BigInt(0);
+    private static readonly MIN_I64 = BigInt('-9223372036854775808');
+    private static readonly MAX_I64 = BigInt('9223372036854775807');
+    private static readonly MIN_I128 = BigInt('-170141183460469231731687303715884105728');
+    private static readonly MAX_I128 = BigInt('170141183460469231731687303715884105727');
 
     private static readonly textEncoder =
         typeof window === 'undefined' ? new util.TextEncoder() : new TextEncoder();
@@ -84,22 +90,45 @@ export abstract class BinarySerializer implements Serializer {
         this.serializeWithFunction(DataView.prototype.setUint32, 4, value);
     }
 
-    public serializeU64(value: BigInt | number): void {
-        const low = BigInt(value) & BinarySerializer.BIG_32Fs;
-        const high = BigInt(value) >> BinarySerializer.BIG_32;
+    // Convert `value` to a bigint, checking that it is an integer within the given range.
+    // Values of type `number` must also be safe integers.
+    private static toCheckedBigInt(value: bigint | number, min: bigint, max: bigint): bigint {
+        if (typeof value === 'number' && !Number.isSafeInteger(value)) {
+            throw new Error('Not a safe integer: ' + value);
+        }
+        const result = BigInt(value);
+        if (result < min || result > max) {
+            throw new Error('Integer value out of range: ' + value);
+        }
+        return result;
+    }
+
+    // Write the 64 lowest bits of a non-negative bigint.
+    private serializeU64Bits(value: bigint): void {
+        const low = value & BinarySerializer.BIG_32Fs;
+        const high = (value >> BinarySerializer.BIG_32) & BinarySerializer.BIG_32Fs;
 
         // write little endian number
         this.serializeU32(Number(low));
         this.serializeU32(Number(high));
     }
 
-    public serializeU128(value: BigInt | number): void {
-        const low = BigInt(value) & BinarySerializer.BIG_64Fs;
-        const high = BigInt(value) >> BinarySerializer.BIG_64;
+    public serializeU64(value: bigint | number): void {
+        this.serializeU64Bits(
+            BinarySerializer.toCheckedBigInt(value, BinarySerializer.BIG_ZERO, BinarySerializer.BIG_64Fs)
+        );
+    }
+
+    public serializeU128(value: bigint | number): void {
+        const bits = BinarySerializer.toCheckedBigInt(
+            value,
+            BinarySerializer.BIG_ZERO,
+            BinarySerializer.BIG_128Fs
+        );
 
         // write little endian number
-        this.serializeU64(low);
-        this.serializeU64(high);
+        this.serializeU64Bits(bits);
+        this.serializeU64Bits(bits >> BinarySerializer.BIG_64);
     }
 
     public serializeI8(value: number): void {
@@ -124,21 +153,22 @@ export abstract class BinarySerializer implements Serializer {
     }
 
     public serializeI64(value: bigint | number): void {
-        const low = BigInt(value) & BinarySerializer.BIG_32Fs;
-        const high = BigInt(value) >> BinarySerializer.BIG_32;
-
-        // write little endian number
-        this.serializeI32(Number(low));
-        this.serializeI32(Number(high));
+        const bits = BigInt.asUintN(
+            64,
+            BinarySerializer.toCheckedBigInt(value, BinarySerializer.MIN_I64, BinarySerializer.MAX_I64)
+        );
+        this.serializeU64Bits(bits);
     }
 
     public serializeI128(value: bigint | number): void {
-        const low = BigInt(value) & BinarySerializer.BIG_64Fs;
-        const high = BigInt(value) >> BinarySerializer.BIG_64;
+        const bits = BigInt.asUintN(
+            128,
+            BinarySerializer.toCheckedBigInt(value, BinarySerializer.MIN_I128, BinarySerializer.MAX_I128)
+        );
 
         // write little endian number
-        this.serializeI64(low);
-        this.serializeI64(high);
+        this.serializeU64Bits(bits);
+        this.serializeU64Bits(bits >> BinarySerializer.BIG_64);
     }
 
     public serializeOptionTag(value: boolean): void {
@@ -11,7 +11,7 @@ fn get_dependencies<'a>(
 ) -> Result<BTreeSet<&'a str>> {
     let mut result = BTreeSet::new();
     format.visit(&mut |format| {
-        if let Format::TypeName(x) = format {
+        if let Format::TypeName(x) | Format::TypeApp { name: x, .. } = format {
             if !external.contains(x) {
                 result.insert(x.as_str());
             }
@@ -23,7 +23,8 @@ fn get_dependencies<'a>(
 
 /// Build a map of dependencies between the entries of a `Registry`.
 /// * By definition, an entry named `x` depends on `y` iff the container format of `x` in the registry
-/// syntactically contains a reference to `y` (i.e. an expression `Format::TypeName(y)`).
+/// syntactically contains a reference to `y` (i.e. an expression `Format::TypeName(y)` or
+/// `Format::TypeApp { name: y, .. }`).
 /// * Dependencies can play a role in code generation in some languages (e.g. Rust or C++) where inductive
 /// definitions may require explicit "boxing" (i.e. adding pointer indirections) to ensure finite object sizes.
 pub fn get_dependency_map(registry: &Registry) -> Result<BTreeMap<&str, BTreeSet<&str>>> {
@@ -0,0 +1,1149 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{
+    analyzer, common,
+    indent::{IndentConfig, IndentedWriter},
+    lint::Language,
+    CodeGeneratorConfig, Encoding, Error,
+};
+use serde_reflection::{ContainerFormat, Format, FormatHolder, Named, Registry, VariantFormat};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::{Result, Write};
+use std::path::PathBuf;
+
+/// Main configuration object for code-generation in C.
+pub struct CodeGenerator<'a> {
+    /// Language-independent configuration.
+    config: &'a CodeGeneratorConfig,
+    /// Prefix used for all generated names (e.g. "my_module").
+    /// Derived from `config.module_name`.
+    prefix: String,
+    /// Mapping from external type names to suitably qualified names (e.g. "MyClass" -> "name_MyClass").
+    /// Derived from `config.external_definitions`.
+    external_qualified_names: HashMap<String, String>,
+}
+
+/// Shared state for the code generation of a C header file.
+struct CEmitter<'a, T> {
+    /// Writer.
+    out: IndentedWriter<T>,
+    /// Generator.
+    generator: &'a CodeGenerator<'a>,
+    /// Enums translated into native C enums (if `config.c_style_enums` is set).
+    c_style_enums: BTreeSet<&'a str>,
+    /// Position of each struct definition in the output. (Used to add pointer indirections.)
+    positions: HashMap<String, usize>,
+    /// Current namespace (e.g. vec!["name", "MyClass"])
+    current_namespace: Vec<String>,
+    /// Fingerprints of the containers, if requested.
+    fingerprints: BTreeMap<String, u64>,
+}
+
+/// A struct definition in the generated code: either a container of the registry or an
+/// anonymous composite type such as a sequence, a map, a tuple, or a fixed-size array.
+#[derive(Clone, Copy)]
+enum Definition<'a> {
+    Container(&'a ContainerFormat),
+    Composite(&'a Format),
+}
+
+impl<'a> CodeGenerator<'a> {
+    /// Create a C code generator for the given config.
+    pub fn new(config: &'a CodeGeneratorConfig) -> Self {
+        let mut external_qualified_names = HashMap::new();
+        for (namespace, names) in &config.external_definitions {
+            for name in names {
+                external_qualified_names.insert(
+                    name.to_string(),
+                    format!("{}_{}", Self::sanitize(namespace), name),
+                );
+            }
+        }
+        Self {
+            config,
+            prefix: Self::sanitize(&config.module_name),
+            external_qualified_names,
+        }
+    }
+
+    /// Turn a module name (e.g. "foo::bar") into a valid C identifier prefix (e.g. "foo_bar").
+    fn sanitize(name: &str) -> String {
+        let mut result = String::new();
+        for c in name.chars() {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                result.push(c);
+            } else if !result.ends_with('_') {
+                result.push('_');
+            }
+        }
+        result
+    }
+
+    pub fn output(&self, out: &mut dyn Write, registry: &Registry) -> crate::Result<()> {
+        let registry = &common::check_and_monomorphize_registry(registry)?;
+        // Empty enumerations are not valid C.
+        for (name, format) in registry {
+            if let ContainerFormat::Enum(variants) = format {
+                if variants.is_empty() {
+                    return Err(Error::EmptyEnum {
+                        language: "C",
+                        container: name.clone(),
+                    });
+                }
+            }
+        }
+        let c_style_enums = if self.config.c_style_enums {
+            registry
+                .iter()
+                .filter_map(|(name, format)| match format {
+                    ContainerFormat::Enum(variants)
+                        if variants.values().all(|v| v.value == VariantFormat::Unit) =>
+                    {
+                        Some(name.as_str())
+                    }
+                    _ => None,
+                })
+                .collect()
+        } else {
+            BTreeSet::new()
+        };
+        let mut emitter = CEmitter {
+            out: IndentedWriter::new(out, IndentConfig::Space(4)),
+            generator: self,
+            c_style_enums,
+            positions: HashMap::new(),
+            current_namespace: vec![self.config.module_name.clone()],
+            fingerprints: common::get_fingerprints(self.config, registry)?,
+        };
+
+        // Collect all struct definitions together with the definitions that they contain by value.
+        let mut definitions = BTreeMap::new();
+        for (name, format) in registry {
+            if emitter.c_style_enums.contains(name.as_str()) {
+                continue;
+            }
+            definitions.insert(name.clone(), Definition::Container(format));
+            format.visit(&mut |f| {
+                if Self::is_composite(f) {
+                    definitions.insert(common::mangle_type(f), Definition::Composite(f));
+                }
+                Ok(())
+            })?;
+        }
+        let mut dependencies = BTreeMap::new();
+        for (name, definition) in &definitions {
+            dependencies.insert(name.clone(), emitter.get_value_dependencies(*definition));
+        }
+        // Definitions appearing later in the output are referred to with pointers.
+        let entries = analyzer::best_effort_topological_sort(&dependencies);
+        for (position, name) in entries.iter().enumerate() {
+            emitter.positions.insert(name.clone(), position);
+        }
+
+        emitter.output_preamble()?;
+        for name in &emitter.c_style_enums.clone() {
+            if let ContainerFormat::Enum(variants) = &registry[*name] {
+                emitter.output_c_style_enum(name, variants)?;
+            }
+        }
+        writeln!(emitter.out)?;
+        for name in &entries {
+            emitter.output_forward_definitions(name, definitions[name])?;
+        }
+        for name in &entries {
+            match definitions[name] {
+                Definition::Container(format) => emitter.output_container(name, format)?,
+                Definition::Composite(format) => emitter.output_composite(name, format)?,
+            }
+        }
+
+        if self.config.serialization {
+            let names: Vec<_> = emitter
+                .c_style_enums
+                .iter()
+                .map(|name| name.to_string())
+                .chain(entries.iter().cloned())
+                .collect();
+            writeln!(emitter.out)?;
+            for name in &names {
+                emitter.output_serialization_prototypes(name)?;
+            }
+            for name in &names {
+                match definitions.get(name) {
+                    Some(Definition::Container(format)) => {
+                        emitter.output_container_serialization(name, format)?
+                    }
+                    Some(Definition::Composite(format)) => {
+                        emitter.output_composite_serialization(name, format)?
+                    }
+                    None => {
+                        if let ContainerFormat::Enum(variants) = &registry[name] {
+                            emitter.output_c_style_enum_serialization(name, variants)?;
+                        }
+                    }
+                }
+            }
+            for encoding in &self.config.encodings {
+                for name in registry.keys() {
+                    emitter.output_container_serialization_for_encoding(name, *encoding)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn is_composite(format: &Format) -> bool {
+        use Format::*;
+        matches!(format, Seq(_) | Map { .. } | Tuple(_) | TupleArray { .. })
+    }
+}
+
+impl<'a, T> CEmitter<'a, T>
+where
+    T: std::io::Write,
+{
+    fn output_preamble(&mut self) -> Result<()> {
+        writeln!(
+            self.out,
+            r#"#pragma once
+
+#include "serde.h""#
+        )?;
+        if self.generator.config.serialization {
+            writeln!(self.out, "#include \"binary.h\"")?;
+            for encoding in &self.generator.config.encodings {
+                writeln!(self.out, "#include \"{}.h\"", encoding.name())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn enter_class(&mut self, name: &str) {
+        self.out.indent();
+        self.current_namespace.push(name.to_string());
+    }
+
+    fn leave_class(&mut self) {
+        self.out.unindent();
+        self.current_namespace.pop();
+    }
+
+    fn output_comment(&mut self, name: &str) -> std::io::Result<()> {
+        let mut path = self.current_namespace.clone();
+        path.push(name.to_string());
+        if let Some(doc) = self.generator.config.comments.get(&path) {
+            let text = textwrap::indent(doc, "// ").replace("\n\n", "\n//\n");
+            write!(self.out, "{}", text)?;
+        }
+        Ok(())
+    }
+
+    fn output_custom_code(&mut self, name: &str) -> std::io::Result<()> {
+        let mut path = self.current_namespace.clone();
+        path.push(name.to_string());
+        if let Some(code) = self.generator.config.custom_code.get(&path) {
+            write!(self.out, "\n{}", code)?;
+        }
+        Ok(())
+    }
+
+    /// Compute a fully qualified reference to the container type `name`.
+    fn quote_qualified_name(&self, name: &str) -> String {
+        self.generator
+            .external_qualified_names
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| format!("{}_{}", self.generator.prefix, name))
+    }
+
+    /// Name of the struct definition used to represent `format` by value, if any.
+    fn get_definition_name(&self, format: &Format) -> Option<String> {
+        match format {
+            Format::TypeName(x) => {
+                if self.generator.external_qualified_names.contains_key(x)
+                    || self.c_style_enums.contains(x.as_str())
+                {
+                    None
+                } else {
+                    Some(x.to_string())
+                }
+            }
+            _ if CodeGenerator::is_composite(format) => Some(common::mangle_type(format)),
+            _ => None,
+        }
+    }
+
+    fn get_value_dependencies(&self, definition: Definition) -> BTreeSet<String> {
+        let mut result = BTreeSet::new();
+        match definition {
+            Definition::Container(format) => {
+                for fields in Self::get_container_fields(format) {
+                    for field in fields {
+                        result.extend(self.get_definition_name(&field.value));
+                    }
+                }
+            }
+            Definition::Composite(Format::Tuple(formats)) => {
+                for format in formats {
+                    result.extend(self.get_definition_name(format));
+                }
+            }
+            Definition::Composite(Format::TupleArray { content, .. }) => {
+                result.extend(self.get_definition_name(content));
+            }
+            // Sequences and maps store their content behind pointers.
+            Definition::Composite(_) => (),
+        }
+        result
+    }
+
+    /// Whether a value of the given format must be stored behind a pointer in the definition
+    /// `owner` (i.e. when the definition of the value comes later in the output).
+    fn is_boxed(&self, format: &Format, owner: Option<&str>) -> bool {
+        match (self.get_definition_name(format), owner) {
+            (Some(name), Some(owner)) => self.positions[&name] >= self.positions[owner],
+            _ => false,
+        }
+    }
+
+    fn quote_type(&self, format: &Format, owner: Option<&str>) -> String {
+        use Format::*;
+        let result = match format {
+            TypeName(x) => self.quote_qualified_name(x),
+            Unit => "serde_unit_t".into(),
+            Bool => "bool".into(),
+            I8 => "int8_t".into(),
+            I16 => "int16_t".into(),
+            I32 => "int32_t".into(),
+            I64 => "int64_t".into(),
+            I128 => "serde_int128_t".into(),
+            U8 => "uint8_t".into(),
+            U16 => "uint16_t".into(),
+            U32 => "uint32_t".into(),
+            U64 => "uint64_t".into(),
+            U128 => "serde_uint128_t".into(),
+            F32 => "float".into(),
+            F64 => "double".into(),
+            Char => "uint32_t".into(),
+            Str => "serde_str_t".into(),
+            Bytes => "serde_bytes_t".into(),
+
+            // Absent values are represented by NULL pointers.
+            Option(format) => return Self::quote_pointer(&self.quote_type(format, None)),
+            Seq(_) | Map { .. } | Tuple(_) | TupleArray { .. } => {
+                format!("{}_{}", self.generator.prefix, common::mangle_type(format))
+            }
+
+            Variable(_) | Parameter(_) | TypeApp { .. } => panic!("unexpected value"),
+        };
+        if self.is_boxed(format, owner) {
+            Self::quote_pointer(&result)
+        } else {
+            result
+        }
+    }
+
+    fn quote_pointer(ty: &str) -> String {
+        if ty.ends_with('*') {
+            format!("{}*", ty)
+        } else {
+            format!("{} *", ty)
+        }
+    }
+
+    /// Escape field and variant names that are reserved words in C (e.g. `int` -> `int_`).
+    fn quote_identifier(name: &str) -> String {
+        if Language::C.keywords().contains(&name) {
+            format!("{}_", name)
+        } else {
+            name.to_string()
+        }
+    }
+
+    fn quote_declaration(ty: &str, name: &str) -> String {
+        if ty.ends_with('*') {
+            format!("{}{}", ty, name)
+        } else {
+            format!("{} {}", ty, name)
+        }
+    }
+
+    /// Tags keep the exact names of the enum and the variant so that they cannot collide, e.g.
+    /// `prefix_FooBar__Baz` and `prefix_Foo__BarBaz`.
+    fn quote_enum_tag(&self, name: &str, variant: &str) -> String {
+        format!("{}__{}", self.quote_qualified_name(name), variant)
+    }
+
+    fn get_container_fields(format: &ContainerFormat) -> Vec<Vec<Named<Format>>> {
+        use ContainerFormat::*;
+        match format {
+            UnitStruct => vec![Vec::new()],
+            NewTypeStruct(format) => vec![vec![Named {
+                name: "value".to_string(),
+                value: format.as_ref().clone(),
+            }]],
+            TupleStruct(formats) => vec![Self::get_tuple_fields(formats)],
+            Struct(fields) => vec![fields.clone()],
+            Enum(variants) => variants
+                .values()
+                .map(|variant| Self::get_variant_fields(&variant.value))
+                .collect(),
+            // Generic containers are monomorphized beforehand, but their fields are well defined.
+            Generic { format, .. } => Self::get_container_fields(format),
+        }
+    }
+
+    fn get_variant_fields(format: &VariantFormat) -> Vec<Named<Format>> {
+        use VariantFormat::*;
+        match format {
+            Unit => Vec::new(),
+            NewType(format) => vec![Named {
+                name: "value".to_string(),
+                value: format.as_ref().clone(),
+            }],
+            Tuple(formats) => Self::get_tuple_fields(formats),
+            Struct(fields) => fields.clone(),
+            Variable(_) => panic!("incorrect value"),
+        }
+    }
+
+    fn get_tuple_fields(formats: &[Format]) -> Vec<Named<Format>> {
+        formats
+            .iter()
+            .enumerate()
+            .map(|(i, format)| Named {
+                name: format!("field{}", i),
+                value: format.clone(),
+            })
+            .collect()
+    }
+
+    fn output_forward_definitions(&mut self, name: &str, definition: Definition) -> Result<()> {
+        let qname = self.quote_qualified_name(name);
+        writeln!(self.out, "typedef struct {0} {0};", qname)?;
+        if let Definition::Container(ContainerFormat::Enum(variants)) = definition {
+            for variant in variants.values() {
+                if variant.value != VariantFormat::Unit {
+                    writeln!(
+                        self.out,
+                        "typedef struct {0}_{1} {0}_{1};",
+                        qname, variant.name
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn output_struct_body(&mut self, owner: &str, fields: &[Named<Format>]) -> Result<()> {
+        self.out.indent();
+        for field in fields {
+            self.output_comment(&field.name)?;
+            writeln!(
+                self.out,
+                "{};",
+                Self::quote_declaration(
+                    &self.quote_type(&field.value, Some(owner)),
+                    &Self::quote_identifier(&field.name)
+                )
+            )?;
+        }
+        if fields.is_empty() {
+            // Empty structs are not allowed in C.
+            writeln!(self.out, "serde_unit_t unused;")?;
+        }
+        self.out.unindent();
+        Ok(())
+    }
+
+    fn output_container(&mut self, name: &str, format: &ContainerFormat) -> Result<()> {
+        let qname = self.quote_qualified_name(name);
+        writeln!(self.out)?;
+        if let ContainerFormat::Enum(variants) = format {
+            self.output_enum_container(name, variants)?;
+        } else {
+            self.output_comment(name)?;
+            writeln!(self.out, "struct {} {{", qname)?;
+            self.current_namespace.push(name.to_string());
+            let fields = Self::get_container_fields(format).remove(0);
+            self.output_struct_body(name, &fields)?;
+            self.current_namespace.pop();
+            writeln!(self.out, "}};")?;
+        }
+        self.output_fingerprint(name)?;
+        self.output_custom_code(name)
+    }
+
+    fn output_fingerprint(&mut self, name: &str) -> Result<()> {
+        if let Some(fingerprint) = self.fingerprints.get(name) {
+            writeln!(
+                self.out,
+                "#define {}_FINGERPRINT {:#018x}ull",
+                self.quote_qualified_name(name),
+                fingerprint
+            )?;
+        }
+        Ok(())
+    }
+
+    fn output_enum_container(
+        &mut self,
+        name: &str,
+        variants: &BTreeMap<u32, Named<VariantFormat>>,
+    ) -> Result<()> {
+        let qname = self.quote_qualified_name(name);
+        writeln!(self.out, "typedef enum {}_tag {{", qname)?;
+        self.out.indent();
+        for (index, variant) in variants {
+            writeln!(
+                self.out,
+                "{} = {},",
+                self.quote_enum_tag(name, &variant.name),
+                index
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}} {}_tag;", qname)?;
+
+        self.current_namespace.push(name.to_string());
+        for variant in variants.values() {
+            if variant.value == VariantFormat::Unit {
+                continue;
+            }
+            writeln!(self.out)?;
+            self.output_comment(&variant.name)?;
+            writeln!(self.out, "struct {}_{} {{", qname, variant.name)?;
+            self.current_namespace.push(variant.name.clone());
+            self.output_struct_body(name, &Self::get_variant_fields(&variant.value))?;
+            self.current_namespace.pop();
+            writeln!(self.out, "}};")?;
+        }
+        self.current_namespace.pop();
+
+        writeln!(self.out)?;
+        self.output_comment(name)?;
+        writeln!(self.out, "struct {} {{", qname)?;
+        self.enter_class(name);
+        writeln!(self.out, "{}_tag tag;", qname)?;
+        if variants.values().any(|v| v.value != VariantFormat::Unit) {
+            writeln!(self.out, "union {{")?;
+            self.out.indent();
+            for variant in variants.values() {
+                if variant.value != VariantFormat::Unit {
+                    writeln!(
+                        self.out,
+                        "{}_{} {};",
+                        qname,
+                        variant.name,
+                        Self::quote_identifier(&variant.name)
+                    )?;
+                }
+            }
+            self.out.unindent();
+            writeln!(self.out, "}} value;")?;
+        }
+        self.leave_class();
+        writeln!(self.out, "}};")
+    }
+
+    fn output_c_style_enum(
+        &mut self,
+        name: &str,
+        variants: &BTreeMap<u32, Named<VariantFormat>>,
+    ) -> Result<()> {
+        let qname = self.quote_qualified_name(name);
+        writeln!(self.out)?;
+        self.output_comment(name)?;
+        writeln!(self.out, "typedef enum {} {{", qname)?;
+        self.enter_class(name);
+        for (index, variant) in variants {
+            self.output_comment(&variant.name)?;
+            writeln!(
+                self.out,
+                "{} = {},",
+                self.quote_enum_tag(name, &variant.name),
+                index
+            )?;
+        }
+        self.leave_class();
+        writeln!(self.out, "}} {};", qname)?;
+        self.output_fingerprint(name)?;
+        self.output_custom_code(name)
+    }
+
+    fn output_composite(&mut self, name: &str, format: &Format) -> Result<()> {
+        use Format::*;
+        writeln!(self.out)?;
+        writeln!(self.out, "struct {} {{", self.quote_qualified_name(name))?;
+        self.out.indent();
+        match format {
+            Seq(content) => {
+                let ty = Self::quote_pointer(&self.quote_type(content, None));
+                writeln!(self.out, "{};", Self::quote_declaration(&ty, "data"))?;
+                writeln!(self.out, "size_t len;")?;
+            }
+            Map { key, value } => {
+                let ty = Self::quote_pointer(&self.quote_type(key, None));
+                writeln!(self.out, "{};", Self::quote_declaration(&ty, "keys"))?;
+                let ty = Self::quote_pointer(&self.quote_type(value, None));
+                writeln!(self.out, "{};", Self::quote_declaration(&ty, "values"))?;
+                writeln!(self.out, "size_t len;")?;
+            }
+            Tuple(formats) => {
+                for field in Self::get_tuple_fields(formats) {
+                    let ty = self.quote_type(&field.value, Some(name));
+                    writeln!(self.out, "{};", Self::quote_declaration(&ty, &field.name))?;
+                }
+            }
+            TupleArray { content, size } => {
+                let ty = self.quote_type(content, Some(name));
+                writeln!(
+                    self.out,
+                    "{};",
+                    Self::quote_declaration(&ty, &format!("values[{}]", size))
+                )?;
+            }
+            _ => panic!("unexpected value"),
+        }
+        self.out.unindent();
+        writeln!(self.out, "}};")
+    }
+
+    fn output_serialization_prototypes(&mut self, name: &str) -> Result<()> {
+        let qname = self.quote_qualified_name(name);
+        writeln!(
+            self.out,
+            "static inline serde_error_t {0}_serialize(const {0} *obj, serde_serializer_t *serializer);",
+            qname
+        )?;
+        writeln!(
+            self.out,
+            "static inline serde_error_t {0}_deserialize(serde_deserializer_t *deserializer, {0} *obj);",
+            qname
+        )
+    }
+
+    /// Serialize the value `value` of type `format` found in the definition `owner`.
+    /// The variable `depth` is used to create fresh local variables.
+    fn output_serialize_value(
+        &mut self,
+        value: &str,
+        format: &Format,
+        owner: Option<&str>,
+        depth: usize,
+    ) -> Result<()> {
+        use Format::*;
+        match format {
+            TypeName(x) => {
+                let reference = if self.is_boxed(format, owner) {
+                    value.to_string()
+                } else {
+                    format!("&{}", value)
+                };
+                writeln!(
+                    self.out,
+                    "SERDE_TRY({}_serialize({}, serializer));",
+                    self.quote_qualified_name(x),
+                    reference
+                )
+            }
+            Option(content) => {
+                writeln!(
+                    self.out,
+                    "SERDE_TRY(serde_serialize_option_tag(serializer, {} != NULL));",
+                    value
+                )?;
+                writeln!(self.out, "if ({} != NULL) {{", value)?;
+                self.out.indent();
+                self.output_serialize_value(&format!("(*{})", value), content, None, depth + 1)?;
+                self.out.unindent();
+                writeln!(self.out, "}}")
+            }
+            Seq(_) | Map { .. } | Tuple(_) | TupleArray { .. } => {
+                let reference = if self.is_boxed(format, owner) {
+                    value.to_string()
+                } else {
+                    format!("&{}", value)
+                };
+                writeln!(
+                    self.out,
+                    "SERDE_TRY({}_{}_serialize({}, serializer));",
+                    self.generator.prefix,
+                    common::mangle_type(format),
+                    reference
+                )
+            }
+            Variable(_) => panic!("unexpected value"),
+            _ => writeln!(
+                self.out,
+                "SERDE_TRY(serde_serialize_{}(serializer, {}));",
+                common::mangle_type(format),
+                value
+            ),
+        }
+    }
+
+    /// Deserialize a value of type `format` into the location `value` found in the definition `owner`.
+    /// The variable `depth` is used to create fresh local variables.
+    fn output_deserialize_value(
+        &mut self,
+        value: &str,
+        format: &Format,
+        owner: Option<&str>,
+        depth: usize,
+    ) -> Result<()> {
+        use Format::*;
+        let qname = match format {
+            TypeName(x) => self.quote_qualified_name(x),
+            Seq(_) | Map { .. } | Tuple(_) | TupleArray { .. } => {
+                format!("{}_{}", self.generator.prefix, common::mangle_type(format))
+            }
+            Option(content) => {
+                writeln!(self.out, "{{")?;
+                self.out.indent();
+                writeln!(self.out, "bool present{};", depth)?;
+                writeln!(
+                    self.out,
+                    "SERDE_TRY(serde_deserialize_option_tag(deserializer, &present{}));",
+                    depth
+                )?;
+                writeln!(self.out, "if (present{}) {{", depth)?;
+                self.out.indent();
+                writeln!(
+                    self.out,
+                    "SERDE_DESERIALIZER_ALLOC(deserializer, {}, 1);",
+                    value
+                )?;
+                self.output_deserialize_value(&format!("(*{})", value), content, None, depth + 1)?;
+                self.out.unindent();
+                writeln!(self.out, "}} else {{")?;
+                writeln!(self.out, "    {} = NULL;", value)?;
+                writeln!(self.out, "}}")?;
+                self.out.unindent();
+                return writeln!(self.out, "}}");
+            }
+            Variable(_) => panic!("unexpected value"),
+            _ => {
+                return writeln!(
+                    self.out,
+                    "SERDE_TRY(serde_deserialize_{}(deserializer, &{}));",
+                    common::mangle_type(format),
+                    value
+                );
+            }
+        };
+        if self.is_boxed(format, owner) {
+            writeln!(
+                self.out,
+                "SERDE_DESERIALIZER_ALLOC(deserializer, {}, 1);",
+                value
+            )?;
+            writeln!(
+                self.out,
+                "SERDE_TRY({}_deserialize(deserializer, {}));",
+                qname, value
+            )
+        } else {
+            writeln!(
+                self.out,
+                "SERDE_TRY({}_deserialize(deserializer, &{}));",
+                qname, value
+            )
+        }
+    }
+
+    fn output_serialize_fields(
+        &mut self,
+        prefix: &str,
+        owner: &str,
+        fields: &[Named<Format>],
+    ) -> Result<()> {
+        for field in fields {
+            self.output_serialize_value(
+                &format!("{}{}", prefix, Self::quote_identifier(&field.name)),
+                &field.value,
+                Some(owner),
+                0,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn output_deserialize_fields(
+        &mut self,
+        prefix: &str,
+        owner: &str,
+        fields: &[Named<Format>],
+    ) -> Result<()> {
+        for field in fields {
+            self.output_deserialize_value(
+                &format!("{}{}", prefix, Self::quote_identifier(&field.name)),
+                &field.value,
+                Some(owner),
+                0,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn output_container_serialization(
+        &mut self,
+        name: &str,
+        format: &ContainerFormat,
+    ) -> Result<()> {
+        let qname = self.quote_qualified_name(name);
+        writeln!(
+            self.out,
+            "\nstatic inline serde_error_t {0}_serialize(const {0} *obj, serde_serializer_t *serializer) {{",
+            qname
+        )?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            "SERDE_TRY(serde_serializer_increase_container_depth(serializer));"
+        )?;
+        if let ContainerFormat::Enum(variants) = format {
+            writeln!(self.out, "switch (obj->tag) {{")?;
+            for (index, variant) in variants {
+                writeln!(
+                    self.out,
+                    "case {}:",
+                    self.quote_enum_tag(name, &variant.name)
+                )?;
+                self.out.indent();
+                writeln!(
+                    self.out,
+                    "SERDE_TRY(serde_serialize_variant_index(serializer, {}));",
+                    index
+                )?;
+                self.output_serialize_fields(
+                    &format!("obj->value.{}.", Self::quote_identifier(&variant.name)),
+                    name,
+                    &Self::get_variant_fields(&variant.value),
+                )?;
+                writeln!(self.out, "break;")?;
+                self.out.unindent();
+            }
+            writeln!(self.out, "default:")?;
+            writeln!(self.out, "    return SERDE_ERROR_UNKNOWN_VARIANT;")?;
+            writeln!(self.out, "}}")?;
+        } else {
+            let fields = Self::get_container_fields(format).remove(0);
+            self.output_serialize_fields("obj->", name, &fields)?;
+        }
+        writeln!(
+            self.out,
+            "serde_serializer_decrease_container_depth(serializer);\nreturn SERDE_OK;"
+        )?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+
+        writeln!(
+            self.out,
+            "\nstatic inline serde_error_t {0}_deserialize(serde_deserializer_t *deserializer, {0} *obj) {{",
+            qname
+        )?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            "SERDE_TRY(serde_deserializer_increase_container_depth(deserializer));"
+        )?;
+        if let ContainerFormat::Enum(variants) = format {
+            writeln!(self.out, "uint32_t index;")?;
+            writeln!(
+                self.out,
+                "SERDE_TRY(serde_deserialize_variant_index(deserializer, &index));"
+            )?;
+            writeln!(self.out, "switch (index) {{")?;
+            for (index, variant) in variants {
+                writeln!(self.out, "case {}:", index)?;
+                self.out.indent();
+                writeln!(
+                    self.out,
+                    "obj->tag = {};",
+                    self.quote_enum_tag(name, &variant.name)
+                )?;
+                self.output_deserialize_fields(
+                    &format!("obj->value.{}.", Self::quote_identifier(&variant.name)),
+                    name,
+                    &Self::get_variant_fields(&variant.value),
+                )?;
+                writeln!(self.out, "break;")?;
+                self.out.unindent();
+            }
+            writeln!(self.out, "default:")?;
+            writeln!(self.out, "    return SERDE_ERROR_UNKNOWN_VARIANT;")?;
+            writeln!(self.out, "}}")?;
+        } else {
+            let fields = Self::get_container_fields(format).remove(0);
+            self.output_deserialize_fields("obj->", name, &fields)?;
+        }
+        writeln!(
+            self.out,
+            "serde_deserializer_decrease_container_depth(deserializer);\nreturn SERDE_OK;"
+        )?;
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_c_style_enum_serialization(
+        &mut self,
+        name: &str,
+        variants: &BTreeMap<u32, Named<VariantFormat>>,
+    ) -> Result<()> {
+        writeln!(
+            self.out,
+            r#"
+static inline serde_error_t {0}_serialize(const {0} *obj, serde_serializer_t *serializer) {{
+    SERDE_TRY(serde_serializer_increase_container_depth(serializer));
+    SERDE_TRY(serde_serialize_variant_index(serializer, (uint32_t)*obj));
+    serde_serializer_decrease_container_depth(serializer);
+    return SERDE_OK;
+}}
+
+static inline serde_error_t {0}_deserialize(serde_deserializer_t *deserializer, {0} *obj) {{
+    uint32_t index;
+    SERDE_TRY(serde_deserializer_increase_container_depth(deserializer));
+    SERDE_TRY(serde_deserialize_variant_index(deserializer, &index));
+    switch (index) {{
+{1}        *obj = ({0})index;
+        break;
+    default:
+        return SERDE_ERROR_UNKNOWN_VARIANT;
+    }}
+    serde_deserializer_decrease_container_depth(deserializer);
+    return SERDE_OK;
+}}"#,
+            self.quote_qualified_name(name),
+            variants
+                .keys()
+                .map(|index| format!("    case {}:\n", index))
+                .collect::<String>()
+        )
+    }
+
+    fn output_composite_serialization(&mut self, name: &str, format: &Format) -> Result<()> {
+        use Format::*;
+        let qname = self.quote_qualified_name(name);
+        writeln!(
+            self.out,
+            "\nstatic inline serde_error_t {0}_serialize(const {0} *obj, serde_serializer_t *serializer) {{",
+            qname
+        )?;
+        self.out.indent();
+        match format {
+            Seq(content) => {
+                writeln!(
+                    self.out,
+                    "SERDE_TRY(serde_serialize_len(serializer, obj->len));"
+                )?;
+                writeln!(self.out, "for (size_t i = 0; i < obj->len; i++) {{")?;
+                self.out.indent();
+                self.output_serialize_value("obj->data[i]", content, None, 0)?;
+                self.out.unindent();
+                writeln!(self.out, "}}\nreturn SERDE_OK;")?;
+            }
+            Map { key, value } => {
+                writeln!(self.out, "size_t *offsets;")?;
+                writeln!(
+                    self.out,
+                    "SERDE_TRY(serde_serialize_len(serializer, obj->len));"
+                )?;
+                writeln!(
+                    self.out,
+                    "SERDE_TRY(serde_serializer_alloc_map_offsets(serializer, obj->len, &offsets));"
+                )?;
+                writeln!(self.out, "for (size_t i = 0; i < obj->len; i++) {{")?;
+                self.out.indent();
+                writeln!(self.out, "if (offsets != NULL) {{")?;
+                writeln!(
+                    self.out,
+                    "    offsets[i] = serde_serializer_get_buffer_offset(serializer);"
+                )?;
+                writeln!(self.out, "}}")?;
+                self.output_serialize_value("obj->keys[i]", key, None, 0)?;
+                self.output_serialize_value("obj->values[i]", value, None, 0)?;
+                self.out.unindent();
+                writeln!(self.out, "}}")?;
+                writeln!(
+                    self.out,
+                    "return serde_serializer_sort_map_entries(serializer, offsets, obj->len);"
+                )?;
+            }
+            Tuple(formats) => {
+                self.output_serialize_fields("obj->", name, &Self::get_tuple_fields(formats))?;
+                writeln!(self.out, "return SERDE_OK;")?;
+            }
+            TupleArray { content, size } => {
+                writeln!(self.out, "for (size_t i = 0; i < {}; i++) {{", size)?;
+                self.out.indent();
+                self.output_serialize_value("obj->values[i]", content, Some(name), 0)?;
+                self.out.unindent();
+                writeln!(self.out, "}}\nreturn SERDE_OK;")?;
+            }
+            _ => panic!("unexpected value"),
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+
+        writeln!(
+            self.out,
+            "\nstatic inline serde_error_t {0}_deserialize(serde_deserializer_t *deserializer, {0} *obj) {{",
+            qname
+        )?;
+        self.out.indent();
+        match format {
+            Seq(content) => {
+                writeln!(self.out, "size_t len;")?;
+                writeln!(
+                    self.out,
+                    "SERDE_TRY(serde_deserialize_len(deserializer, &len));"
+                )?;
+                writeln!(
+                    self.out,
+                    "SERDE_DESERIALIZER_ALLOC(deserializer, obj->data, len);"
+                )?;
+                writeln!(self.out, "obj->len = len;")?;
+                writeln!(self.out, "for (size_t i = 0; i < len; i++) {{")?;
+                self.out.indent();
+                self.output_deserialize_value("obj->data[i]", content, None, 0)?;
+                self.out.unindent();
+                writeln!(self.out, "}}\nreturn SERDE_OK;")?;
+            }
+            Map { key, value } => {
+                writeln!(self.out, "size_t len;")?;
+                writeln!(self.out, "size_t previous_key_start = 0;")?;
+                writeln!(self.out, "size_t previous_key_end = 0;")?;
+                writeln!(
+                    self.out,
+                    "SERDE_TRY(serde_deserialize_len(deserializer, &len));"
+                )?;
+                writeln!(
+                    self.out,
+                    "SERDE_DESERIALIZER_ALLOC(deserializer, obj->keys, len);"
+                )?;
+                writeln!(
+                    self.out,
+                    "SERDE_DESERIALIZER_ALLOC(deserializer, obj->values, len);"
+                )?;
+                writeln!(self.out, "obj->len = len;")?;
+                writeln!(self.out, "for (size_t i = 0; i < len; i++) {{")?;
+                self.out.indent();
+                writeln!(
+                    self.out,
+                    "size_t key_start = serde_deserializer_get_buffer_offset(deserializer);"
+                )?;
+                self.output_deserialize_value("obj->keys[i]", key, None, 0)?;
+                writeln!(
+                    self.out,
+                    r#"size_t key_end = serde_deserializer_get_buffer_offset(deserializer);
+if (i > 0) {{
+    SERDE_TRY(serde_deserializer_check_that_key_slices_are_increasing(
+        deserializer, previous_key_start, previous_key_end, key_start, key_end));
+}}
+previous_key_start = key_start;
+previous_key_end = key_end;"#
+                )?;
+                self.output_deserialize_value("obj->values[i]", value, None, 0)?;
+                self.out.unindent();
+                writeln!(self.out, "}}\nreturn SERDE_OK;")?;
+            }
+            Tuple(formats) => {
+                self.output_deserialize_fields("obj->", name, &Self::get_tuple_fields(formats))?;
+                writeln!(self.out, "return SERDE_OK;")?;
+            }
+            TupleArray { content, size } => {
+                writeln!(self.out, "for (size_t i = 0; i < {}; i++) {{", size)?;
+                self.out.indent();
+                self.output_deserialize_value("obj->values[i]", content, Some(name), 0)?;
+                self.out.unindent();
+                writeln!(self.out, "}}\nreturn SERDE_OK;")?;
+            }
+            _ => panic!("unexpected value"),
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_container_serialization_for_encoding(
+        &mut self,
+        name: &str,
+        encoding: Encoding,
+    ) -> Result<()> {
+        writeln!(
+            self.out,
+            r#"
+static inline serde_error_t {0}_{1}_serialize(const {0} *obj, uint8_t *buffer, size_t capacity, serde_arena_t *arena, size_t *length) {{
+    serde_serializer_t serializer;
+    serde_{1}_serializer_init(&serializer, buffer, capacity, arena);
+    SERDE_TRY({0}_serialize(obj, &serializer));
+    *length = serde_serializer_get_buffer_offset(&serializer);
+    return SERDE_OK;
+}}
+
+static inline serde_error_t {0}_{1}_deserialize(const uint8_t *input, size_t length, serde_arena_t *arena, {0} *obj) {{
+    serde_deserializer_t deserializer;
+    serde_{1}_deserializer_init(&deserializer, input, length, arena);
+    SERDE_TRY({0}_deserialize(&deserializer, obj));
+    if (serde_deserializer_get_buffer_offset(&deserializer) < length) {{
+        return SERDE_ERROR_TRAILING_BYTES;
+    }}
+    return SERDE_OK;
+}}"#,
+            self.quote_qualified_name(name),
+            encoding.name(),
+        )
+    }
+}
+
+/// Installer for generated source files in C.
+pub struct Installer {
+    install_dir: PathBuf,
+}
+
+impl Installer {
+    pub fn new(install_dir: PathBuf) -> Self {
+        Installer { install_dir }
+    }
+
+    fn create_header_file(&self, name: &str) -> Result<std::fs::File> {
+        let dir_path = &self.install_dir;
+        std::fs::create_dir_all(dir_path)?;
+        std::fs::File::create(dir_path.join(name.to_string() + ".h"))
+    }
+}
+
+impl crate::SourceInstaller for Installer {
+    type Error = crate::Error;
+
+    fn install_module(
+        &self,
+        config: &crate::CodeGeneratorConfig,
+        registry: &Registry,
+    ) -> std::result::Result<(), Self::Error> {
+        let mut file = self.create_header_file(&config.module_name)?;
+        let generator = CodeGenerator::new(&config);
+        generator.output(&mut file, &registry)
+    }
+
+    fn install_serde_runtime(&self) -> std::result::Result<(), Self::Error> {
+        let mut file = self.create_header_file("serde")?;
+        write!(file, "{}", include_str!("../runtime/c/serde.h"))?;
+        let mut file = self.create_header_file("binary")?;
+        write!(file, "{}", include_str!("../runtime/c/binary.h"))?;
+        Ok(())
+    }
+
+    fn install_bincode_runtime(&self) -> std::result::Result<(), Self::Error> {
+        let mut file = self.create_header_file("bincode")?;
+        write!(file, "{}", include_str!("../runtime/c/bincode.h"))?;
+        Ok(())
+    }
+
+    fn install_bcs_runtime(&self) -> std::result::Result<(), Self::Error> {
+        let mut file = self.create_header_file("bcs")?;
+        write!(file, "{}", include_str!("../runtime/c/bcs.h"))?;
+        Ok(())
+    }
+}
@@ -1,12 +1,23 @@
 // Copyright (c) Facebook, Inc. and its affiliates
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use serde_reflection::Format;
+use crate::{CodeGeneratorConfig, Error, Result};
+use serde_reflection::{ContainerFormat, Format, FormatHolder, Registry, VariantFormat};
+use std::collections::BTreeMap;
 
 pub(crate) fn mangle_type(format: &Format) -> String {
     use Format::*;
     match format {
-        TypeName(x) => x.to_string(),
+        TypeName(x) | Parameter(x) => x.to_string(),
+        TypeApp { name, arguments } => format!(
+            "{}_{}",
+            name,
+            arguments
+                .iter()
+                .map(mangle_type)
+                .collect::<Vec<_>>()
+                .join("_")
+        ),
         Unit => "unit".into(),
         Bool => "bool".into(),
         I8 => "i8".into(),
@@ -41,3 +52,114 @@ pub(crate) fn mangle_type(format: &Format) -> String {
         Variable(_) => panic!("unexpected value"),
     }
 }
+
+/// The type parameters of generic containers that occur in `format`, in order of first
+/// occurrence.
+pub(crate) fn type_parameters(format: &Format) -> Vec<String> {
+    let mut parameters = Vec::new();
+    format
+        .visit(&mut |format| {
+            if let Format::Parameter(name) = format {
+                if !parameters.contains(name) {
+                    parameters.push(name.clone());
+                }
+            }
+            Ok(())
+        })
+        .unwrap();
+    parameters
+}
+
+/// Make sure that the formats of `registry` are complete, i.e. do not contain variables left
+/// over by an incomplete tracing. Report the first container and field found otherwise.
+pub(crate) fn check_registry(registry: &Registry) -> Result<()> {
+    fn check<T: FormatHolder>(container: &str, field: Option<String>, format: &T) -> Result<()> {
+        format
+            .visit(&mut |_| Ok(()))
+            .map_err(|_| Error::UnknownFormat {
+                container: container.to_string(),
+                field,
+            })
+    }
+
+    fn check_container(name: &str, format: &ContainerFormat) -> Result<()> {
+        match format {
+            ContainerFormat::UnitStruct => (),
+            ContainerFormat::NewTypeStruct(format) => check(name, None, format.as_ref())?,
+            ContainerFormat::TupleStruct(formats) => {
+                for (index, format) in formats.iter().enumerate() {
+                    check(name, Some(index.to_string()), format)?;
+                }
+            }
+            ContainerFormat::Struct(fields) => {
+                for field in fields {
+                    check(name, Some(field.name.clone()), &field.value)?;
+                }
+            }
+            ContainerFormat::Enum(variants) => {
+                for variant in variants.values() {
+                    match &variant.value {
+                        VariantFormat::Unit => (),
+                        VariantFormat::Variable(_) => {
+                            return Err(Error::UnknownFormat {
+                                container: name.to_string(),
+                                field: Some(variant.name.clone()),
+                            })
+                        }
+                        VariantFormat::NewType(format) => {
+                            check(name, Some(variant.name.clone()), format.as_ref())?
+                        }
+                        VariantFormat::Tuple(formats) => {
+                            for (index, format) in formats.iter().enumerate() {
+                                let field = format!("{}.{}", variant.name, index);
+                                check(name, Some(field), format)?;
+                            }
+                        }
+                        VariantFormat::Struct(fields) => {
+                            for field in fields {
+                                let path = format!("{}.{}", variant.name, field.name);
+                                check(name, Some(path), &field.value)?;
+                            }
+                        }
+                    }
+                }
+            }
+            ContainerFormat::Generic { format, .. } => {
+                if let ContainerFormat::Generic { .. } = format.as_ref() {
+                    return Err(Error::UnexpectedGeneric(name.to_string()));
+                }
+                check_container(name, format)?
+            }
+        }
+        Ok(())
+    }
+
+    for (name, format) in registry {
+        check_container(name, format)?;
+    }
+    Ok(())
+}
+
+/// Same as `check_registry` but also replace generic containers by their instances (see
+/// `serde_reflection::monomorphize`). This is used by languages without generic definitions.
+pub(crate) fn check_and_monomorphize_registry(registry: &Registry) -> Result<Registry> {
+    check_registry(registry)?;
+    Ok(serde_reflection::monomorphize(registry)?)
+}
+
+/// Fingerprints of the non-generic containers of the registry (see `serde_reflection::fingerprint`),
+/// or nothing if fingerprints were not requested.
+pub(crate) fn get_fingerprints(
+    config: &CodeGeneratorConfig,
+    registry: &Registry,
+) -> Result<BTreeMap<String, u64>> {
+    let mut result = BTreeMap::new();
+    if config.fingerprints {
+        for (name, format) in registry {
+            if !matches!(format, ContainerFormat::Generic { .. }) {
+                result.insert(name.clone(), serde_reflection::fingerprint(registry, name)?);
+            }
+        }
+    }
+    Ok(result)
+}
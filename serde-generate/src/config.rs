@@ -13,6 +13,7 @@ pub struct CodeGeneratorConfig {
     pub(crate) comments: DocComments,
     pub(crate) custom_code: CustomCode,
     pub(crate) c_style_enums: bool,
+    pub(crate) fingerprints: bool,
 }
 
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq)]
@@ -67,6 +68,7 @@ impl CodeGeneratorConfig {
             comments: BTreeMap::new(),
             custom_code: BTreeMap::new(),
             c_style_enums: false,
+            fingerprints: false,
         }
     }
 
@@ -117,6 +119,15 @@ impl CodeGeneratorConfig {
         self.c_style_enums = c_style_enums;
         self
     }
+
+    /// Whether to add a constant `FINGERPRINT` to the definition of each container (see
+    /// `serde_reflection::fingerprint`). Peers may compare fingerprints to detect that they
+    /// disagree on the formats of their messages. Generic containers have no fingerprint.
+    /// In Java, fields and variants named `FINGERPRINT` are then rejected.
+    pub fn with_fingerprints(mut self, fingerprints: bool) -> Self {
+        self.fingerprints = fingerprints;
+        self
+    }
 }
 
 impl Encoding {
@@ -0,0 +1,956 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{
+    c, cpp, csharp, golang, java, python3, rust, typescript, CodeGeneratorConfig, Encoding, Error,
+    Result, SourceInstaller,
+};
+use heck::CamelCase;
+use include_dir::include_dir as include_directory;
+use serde_reflection::Registry;
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Name of the module (or package, or crate) used for the generated code.
+const MODULE_NAME: &str = "conformance";
+
+/// Path of the Go runtime, as imported by the generated code.
+const GO_RUNTIME_MODULE: &str =
+    "github.com/novifinancial/serde-reflection/serde-generate/runtime/golang";
+
+/// Target languages supported by the conformance harness.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Language {
+    Python3,
+    Cpp,
+    Java,
+    Rust,
+    Go,
+    TypeScript,
+    CSharp,
+    C,
+}
+
+impl Language {
+    /// All the languages supported by the conformance harness.
+    pub fn all() -> Vec<Language> {
+        vec![
+            Language::Python3,
+            Language::Cpp,
+            Language::Java,
+            Language::Rust,
+            Language::Go,
+            Language::TypeScript,
+            Language::CSharp,
+            Language::C,
+        ]
+    }
+
+    /// Same name as the `--language` option of `serdegen`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::Python3 => "python3",
+            Language::Cpp => "cpp",
+            Language::Java => "java",
+            Language::Rust => "rust",
+            Language::Go => "go",
+            Language::TypeScript => "typescript",
+            Language::CSharp => "csharp",
+            Language::C => "c",
+        }
+    }
+
+    /// The commands that must succeed before compiling and running code in this language
+    /// (e.g. printing the version of a tool, importing a library), together with a
+    /// description of what is missing otherwise.
+    fn requirements(self) -> &'static [(&'static str, &'static [&'static str], &'static str)] {
+        match self {
+            Language::Python3 => &[
+                ("python3", &["--version"], "`python3` is not installed"),
+                (
+                    "python3",
+                    &["-c", "import numpy"],
+                    "the Python module `numpy` is not installed",
+                ),
+            ],
+            Language::Cpp => &[("clang++", &["--version"], "`clang++` is not installed")],
+            Language::Java => &[
+                ("javac", &["-version"], "`javac` is not installed"),
+                ("java", &["-version"], "`java` is not installed"),
+            ],
+            Language::Rust => &[("cargo", &["--version"], "`cargo` is not installed")],
+            Language::Go => &[("go", &["version"], "`go` is not installed")],
+            Language::TypeScript => &[
+                ("node", &["--version"], "`node` is not installed"),
+                ("tsc", &["--version"], "`tsc` is not installed"),
+            ],
+            Language::CSharp => &[("dotnet", &["--version"], "`dotnet` is not installed")],
+            Language::C => &[("clang", &["--version"], "`clang` is not installed")],
+        }
+    }
+}
+
+/// The result of the conformance tests for one language.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// All samples were decoded then re-encoded into the same bytes.
+    Passed,
+    /// The toolchain of the language, or a library needed by the generated code, is not
+    /// installed.
+    Skipped(String),
+    /// Code generation, compilation, or some of the samples failed. The message contains
+    /// the output of the failing step, including the indices of the failing samples.
+    Failed(String),
+}
+
+/// The results of the conformance tests, indexed by language.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Report {
+    pub outcomes: BTreeMap<Language, Outcome>,
+}
+
+impl Report {
+    /// Whether no language has failed. (Skipped languages are not failures.)
+    pub fn is_success(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    /// Iterate over the failing languages and the corresponding messages.
+    pub fn failures(&self) -> impl Iterator<Item = (Language, &str)> {
+        self.outcomes
+            .iter()
+            .filter_map(|(language, outcome)| match outcome {
+                Outcome::Failed(message) => Some((*language, message.as_str())),
+                _ => None,
+            })
+    }
+}
+
+impl std::fmt::Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (language, outcome) in &self.outcomes {
+            match outcome {
+                Outcome::Passed => writeln!(f, "{}: passed", language.name())?,
+                Outcome::Skipped(reason) => {
+                    writeln!(f, "{}: skipped ({})", language.name(), reason)?
+                }
+                Outcome::Failed(message) => {
+                    writeln!(f, "{}: FAILED", language.name())?;
+                    for line in message.lines() {
+                        writeln!(f, "    {}", line)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check that the code generated for a registry decodes then re-encodes sample values
+/// into the same bytes, in every target language whose toolchain is installed.
+pub struct Harness<'a> {
+    registry: &'a Registry,
+    root: String,
+    encoding: Encoding,
+    languages: Vec<Language>,
+    work_dir: Option<PathBuf>,
+    cargo_target_dir: Option<PathBuf>,
+}
+
+impl<'a> Harness<'a> {
+    /// Create a harness for the values of the container `root` encoded with `encoding`.
+    pub fn new(registry: &'a Registry, root: String, encoding: Encoding) -> Self {
+        Self {
+            registry,
+            root,
+            encoding,
+            languages: Language::all(),
+            work_dir: None,
+            cargo_target_dir: None,
+        }
+    }
+
+    /// Which languages to test (default: all of them).
+    pub fn with_languages(mut self, languages: Vec<Language>) -> Self {
+        self.languages = languages;
+        self
+    }
+
+    /// Where to write the generated code (default: a temporary directory, removed after the
+    /// tests). Files are kept after the tests, in a sub-directory for each language.
+    pub fn with_work_dir(mut self, work_dir: PathBuf) -> Self {
+        self.work_dir = Some(work_dir);
+        self
+    }
+
+    /// A stable `target` directory for cargo, to avoid recompiling Rust dependencies
+    /// every time (default: inside the work directory).
+    pub fn with_cargo_target_dir(mut self, cargo_target_dir: PathBuf) -> Self {
+        self.cargo_target_dir = Some(cargo_target_dir);
+        self
+    }
+
+    /// Run the tests on the given serialized samples. Errors are only returned when the
+    /// harness itself fails (e.g. unknown root container, I/O errors). Failures in a
+    /// target language are reported in the `Report`.
+    pub fn run(&self, samples: &[Vec<u8>]) -> Result<Report> {
+        if !self.registry.contains_key(&self.root) {
+            return Err(Error::UnknownContainer(self.root.clone()));
+        }
+        let temp_dir;
+        let work_dir = match &self.work_dir {
+            Some(dir) => dir.as_path(),
+            None => {
+                temp_dir = TempDir::new()?;
+                temp_dir.0.as_path()
+            }
+        };
+        let mut report = Report::default();
+        for &language in &self.languages {
+            let outcome = match missing_requirement(language) {
+                Some(reason) => Outcome::Skipped(reason.to_string()),
+                None => {
+                    let dir = work_dir.join(language.name());
+                    std::fs::create_dir_all(&dir)?;
+                    let samples_path = dir.join("samples.txt");
+                    write_samples(&samples_path, samples)?;
+                    match self.test_language(language, &dir, &samples_path) {
+                        Ok(()) => Outcome::Passed,
+                        Err(message) => Outcome::Failed(message),
+                    }
+                }
+            };
+            report.outcomes.insert(language, outcome);
+        }
+        Ok(report)
+    }
+
+    fn test_language(
+        &self,
+        language: Language,
+        dir: &Path,
+        samples_path: &Path,
+    ) -> std::result::Result<(), String> {
+        let config =
+            CodeGeneratorConfig::new(MODULE_NAME.to_string()).with_encodings(vec![self.encoding]);
+        let generation = match language {
+            Language::Python3 => {
+                install(
+                    &python3::Installer::new(dir.to_path_buf(), None),
+                    &config,
+                    self,
+                )?;
+                self.write_python_check(dir)
+            }
+            Language::Cpp => {
+                install(&cpp::Installer::new(dir.to_path_buf()), &config, self)?;
+                self.write_cpp_check(dir)
+            }
+            Language::Java => {
+                install(&java::Installer::new(dir.to_path_buf()), &config, self)?;
+                self.write_java_check(dir)
+            }
+            Language::Rust => {
+                rust::Installer::new(dir.to_path_buf())
+                    .install_module(&config, self.registry)
+                    .map_err(|e| format!("Code generation failed: {}", e))?;
+                self.write_rust_check(dir)
+            }
+            Language::Go => {
+                install(
+                    &golang::Installer::new(dir.to_path_buf(), None),
+                    &config,
+                    self,
+                )?;
+                self.write_go_check(dir)
+            }
+            Language::TypeScript => {
+                install(
+                    &typescript::Installer::new(dir.to_path_buf()),
+                    &config,
+                    self,
+                )?;
+                self.write_typescript_check(dir)
+            }
+            Language::CSharp => {
+                install(&csharp::Installer::new(dir.to_path_buf()), &config, self)?;
+                self.write_csharp_check(dir)
+            }
+            Language::C => {
+                install(&c::Installer::new(dir.to_path_buf()), &config, self)?;
+                self.write_c_check(dir)
+            }
+        };
+        generation.map_err(|e| format!("Failed to write the test program: {}", e))?;
+
+        match language {
+            Language::Python3 => run(Command::new("python3")
+                .arg(dir.join("check.py"))
+                .arg(samples_path)
+                .env("PYTHONPATH", dir)),
+            Language::Cpp => {
+                run(Command::new("clang++")
+                    .arg("--std=c++17")
+                    .arg("-I")
+                    .arg(dir)
+                    .arg("-o")
+                    .arg(dir.join("check"))
+                    .arg(dir.join("check.cpp")))?;
+                run(Command::new(dir.join("check")).arg(samples_path))
+            }
+            Language::Java => {
+                let mut paths = Vec::new();
+                find_java_sources(dir, &mut paths).map_err(|e| e.to_string())?;
+                run(Command::new("javac")
+                    .arg("-encoding")
+                    .arg("UTF-8")
+                    .arg("-d")
+                    .arg(dir.join("classes"))
+                    .args(paths))?;
+                run(Command::new("java")
+                    .arg("-enableassertions")
+                    .arg("-Dfile.encoding=UTF-8")
+                    .arg("-cp")
+                    .arg(dir.join("classes"))
+                    .arg("ConformanceCheck")
+                    .arg(samples_path))
+            }
+            Language::Rust => {
+                let target_dir = match &self.cargo_target_dir {
+                    Some(target_dir) => target_dir.clone(),
+                    None => dir.join("target"),
+                };
+                run(Command::new("cargo")
+                    .current_dir(dir.join("check"))
+                    .arg("run")
+                    .arg("--quiet")
+                    .arg("--target-dir")
+                    .arg(target_dir)
+                    .arg("--")
+                    .arg(samples_path))
+            }
+            Language::Go => run(Command::new("go")
+                .current_dir(dir)
+                .arg("run")
+                .arg(".")
+                .arg(samples_path)),
+            Language::TypeScript => {
+                run(Command::new("tsc").arg("--project").arg(dir))?;
+                run(Command::new("node")
+                    .arg(dir.join("dist/check.js"))
+                    .arg(samples_path))
+            }
+            Language::CSharp => run(Command::new("dotnet")
+                .arg("run")
+                .arg("--project")
+                .arg(dir.join("Check"))
+                .arg("--")
+                .arg(samples_path)),
+            Language::C => {
+                run(Command::new("clang")
+                    .arg("--std=c99")
+                    .arg("-I")
+                    .arg(dir)
+                    .arg("-o")
+                    .arg(dir.join("check"))
+                    .arg(dir.join("check.c")))?;
+                run(Command::new(dir.join("check")).arg(samples_path))
+            }
+        }
+    }
+
+    fn write_python_check(&self, dir: &Path) -> std::io::Result<()> {
+        let mut file = std::fs::File::create(dir.join("check.py"))?;
+        writeln!(
+            file,
+            r#"import sys
+from {module} import {root}
+
+failures = 0
+with open(sys.argv[1]) as f:
+    for i, line in enumerate(f.read().splitlines()):
+        input = bytes.fromhex(line)
+        try:
+            output = {root}.{encoding}_deserialize(input).{encoding}_serialize()
+            if output != input:
+                print("sample {{}}: re-encoding differs".format(i))
+                failures += 1
+        except Exception as e:
+            print("sample {{}}: {{}}".format(i, e))
+            failures += 1
+if failures > 0:
+    print("{{}} failures".format(failures))
+    sys.exit(1)"#,
+            module = MODULE_NAME,
+            root = self.root,
+            encoding = self.encoding.name(),
+        )
+    }
+
+    fn write_cpp_check(&self, dir: &Path) -> std::io::Result<()> {
+        let mut file = std::fs::File::create(dir.join("check.cpp"))?;
+        writeln!(
+            file,
+            r#"#include <fstream>
+#include <iostream>
+#include <string>
+#include "{module}.hpp"
+
+int main(int argc, char** argv) {{
+    std::ifstream file(argv[1]);
+    std::string line;
+    size_t failures = 0;
+    for (size_t i = 0; std::getline(file, line); i++) {{
+        std::vector<uint8_t> input;
+        for (size_t j = 0; j + 1 < line.size(); j += 2) {{
+            input.push_back((uint8_t)std::stoul(line.substr(j, 2), nullptr, 16));
+        }}
+        try {{
+            auto output = {module}::{root}::{encoding}Deserialize(input).{encoding}Serialize();
+            if (output != input) {{
+                std::cout << "sample " << i << ": re-encoding differs" << std::endl;
+                failures++;
+            }}
+        }} catch (std::exception &e) {{
+            std::cout << "sample " << i << ": " << e.what() << std::endl;
+            failures++;
+        }}
+    }}
+    if (failures > 0) {{
+        std::cout << failures << " failures" << std::endl;
+        return 1;
+    }}
+    return 0;
+}}"#,
+            module = MODULE_NAME,
+            root = self.root,
+            encoding = self.encoding.name(),
+        )
+    }
+
+    fn write_java_check(&self, dir: &Path) -> std::io::Result<()> {
+        let mut file = std::fs::File::create(dir.join("ConformanceCheck.java"))?;
+        writeln!(
+            file,
+            r#"import java.nio.file.Files;
+import java.nio.file.Paths;
+import java.util.Arrays;
+import java.util.List;
+
+public class ConformanceCheck {{
+    public static void main(String[] args) throws Exception {{
+        List<String> lines = Files.readAllLines(Paths.get(args[0]));
+        int failures = 0;
+        for (int i = 0; i < lines.size(); i++) {{
+            String line = lines.get(i);
+            byte[] input = new byte[line.length() / 2];
+            for (int j = 0; j < input.length; j++) {{
+                input[j] = (byte) Integer.parseInt(line.substring(2 * j, 2 * j + 2), 16);
+            }}
+            try {{
+                byte[] output = {module}.{root}.{encoding}Deserialize(input).{encoding}Serialize();
+                if (!Arrays.equals(input, output)) {{
+                    System.out.println("sample " + i + ": re-encoding differs");
+                    failures++;
+                }}
+            }} catch (Exception e) {{
+                System.out.println("sample " + i + ": " + e);
+                failures++;
+            }}
+        }}
+        if (failures > 0) {{
+            System.out.println(failures + " failures");
+            System.exit(1);
+        }}
+    }}
+}}"#,
+            module = MODULE_NAME,
+            root = self.root,
+            encoding = self.encoding.name(),
+        )
+    }
+
+    fn write_rust_check(&self, dir: &Path) -> std::io::Result<()> {
+        let (dependency, deserialize, serialize) = match self.encoding {
+            Encoding::Bcs => ("bcs = \"0.1\"", "bcs::from_bytes", "bcs::to_bytes"),
+            Encoding::Bincode => (
+                "bincode = \"1.3\"",
+                "bincode::deserialize",
+                "bincode::serialize",
+            ),
+        };
+        let check_dir = dir.join("check");
+        std::fs::create_dir_all(check_dir.join("src"))?;
+        let mut cargo = std::fs::File::create(check_dir.join("Cargo.toml"))?;
+        writeln!(
+            cargo,
+            r#"[package]
+name = "{module}-check"
+version = "0.1.0"
+edition = "2018"
+
+[dependencies]
+{module} = {{ path = "../{module}" }}
+{dependency}
+
+[workspace]"#,
+            module = MODULE_NAME,
+            dependency = dependency,
+        )?;
+        let mut file = std::fs::File::create(check_dir.join("src/main.rs"))?;
+        writeln!(
+            file,
+            r#"use std::io::BufRead;
+
+fn main() {{
+    let path = std::env::args().nth(1).unwrap();
+    let file = std::io::BufReader::new(std::fs::File::open(path).unwrap());
+    let mut failures = 0;
+    for (i, line) in file.lines().enumerate() {{
+        let line = line.unwrap();
+        let input = (0..line.len() / 2)
+            .map(|j| u8::from_str_radix(&line[2 * j..2 * j + 2], 16).unwrap())
+            .collect::<Vec<_>>();
+        let result = {deserialize}::<{module}::{root}>(&input)
+            .map_err(|e| e.to_string())
+            .and_then(|value| {serialize}(&value).map_err(|e| e.to_string()));
+        match result {{
+            Ok(output) if output == input => (),
+            Ok(_) => {{
+                println!("sample {{}}: re-encoding differs", i);
+                failures += 1;
+            }}
+            Err(e) => {{
+                println!("sample {{}}: {{}}", i, e);
+                failures += 1;
+            }}
+        }}
+    }}
+    if failures > 0 {{
+        println!("{{}} failures", failures);
+        std::process::exit(1);
+    }}
+}}"#,
+            module = MODULE_NAME,
+            root = self.root,
+            deserialize = deserialize,
+            serialize = serialize,
+        )
+    }
+    fn write_go_check(&self, dir: &Path) -> std::io::Result<()> {
+        // The runtime is not installed by `golang::Installer`: use the embedded sources.
+        let runtime_dir = dir.join("runtime");
+        std::fs::create_dir_all(&runtime_dir)?;
+        extract(&include_directory!("runtime/golang"), &runtime_dir)?;
+        std::fs::copy(runtime_dir.join("go.sum"), dir.join("go.sum"))?;
+        let mut go_mod = std::fs::File::create(dir.join("go.mod"))?;
+        writeln!(
+            go_mod,
+            r#"module check
+
+go 1.14
+
+require {runtime} v0.0.0
+
+replace {runtime} => ./runtime"#,
+            runtime = GO_RUNTIME_MODULE,
+        )?;
+        let mut file = std::fs::File::create(dir.join("main.go"))?;
+        writeln!(
+            file,
+            r#"package main
+
+import (
+	"bufio"
+	"bytes"
+	"encoding/hex"
+	"fmt"
+	"os"
+
+	"check/{module}"
+)
+
+func roundtrip(input []byte) ([]byte, error) {{
+	value, err := {module}.{encoding}Deserialize{root}(input)
+	if err != nil {{
+		return nil, err
+	}}
+	return value.{encoding}Serialize()
+}}
+
+func main() {{
+	file, err := os.Open(os.Args[1])
+	if err != nil {{
+		panic(err)
+	}}
+	defer file.Close()
+	scanner := bufio.NewScanner(file)
+	scanner.Buffer(make([]byte, 1<<20), 1<<30)
+	failures := 0
+	for i := 0; scanner.Scan(); i++ {{
+		input, err := hex.DecodeString(scanner.Text())
+		if err != nil {{
+			panic(err)
+		}}
+		output, err := roundtrip(input)
+		if err != nil {{
+			fmt.Printf("sample %d: %v\n", i, err)
+			failures++
+		}} else if !bytes.Equal(input, output) {{
+			fmt.Printf("sample %d: re-encoding differs\n", i)
+			failures++
+		}}
+	}}
+	if err := scanner.Err(); err != nil {{
+		panic(err)
+	}}
+	if failures > 0 {{
+		fmt.Printf("%d failures\n", failures)
+		os.Exit(1)
+	}}
+}}"#,
+            module = MODULE_NAME,
+            root = self.root,
+            encoding = self.encoding.name().to_camel_case(),
+        )
+    }
+
+    fn write_typescript_check(&self, dir: &Path) -> std::io::Result<()> {
+        let mut tsconfig_json = std::fs::File::create(dir.join("tsconfig.json"))?;
+        writeln!(
+            tsconfig_json,
+            r#"{{
+  "compilerOptions": {{
+    "target": "es6",
+    "module": "commonjs",
+    "outDir": "./dist",
+    "strict": true,
+    "esModuleInterop": true,
+    "skipLibCheck": true,
+    "lib": ["es6", "esnext.BigInt", "dom"]
+  }},
+  "include": ["check.ts"]
+}}"#
+        )?;
+        // The constructors of the bincode runtime are protected.
+        let (imports, new_serializer, new_deserializer) = match self.encoding {
+            Encoding::Bcs => (
+                r#"import { BcsSerializer } from './bcs/bcsSerializer';
+import { BcsDeserializer } from './bcs/bcsDeserializer';"#,
+                "new BcsSerializer()",
+                "new BcsDeserializer(input)",
+            ),
+            Encoding::Bincode => (
+                r#"import { BincodeSerializer, BincodeDeserializer } from './bincode';
+class CheckSerializer extends BincodeSerializer {
+  constructor() { super(); }
+}
+class CheckDeserializer extends BincodeDeserializer {
+  constructor(data: Uint8Array) { super(data); }
+}"#,
+                "new CheckSerializer()",
+                "new CheckDeserializer(input)",
+            ),
+        };
+        let mut file = std::fs::File::create(dir.join("check.ts"))?;
+        writeln!(
+            file,
+            r#"import * as {module} from './{module}';
+{imports}
+
+// Avoid depending on the type declarations of Node.
+declare const require: any;
+declare const process: any;
+const fs = require('fs');
+
+function roundtrip(input: Uint8Array): Uint8Array {{
+  const deserializer = {new_deserializer};
+  const value = {module}.{root}.deserialize(deserializer);
+  if (deserializer.getBufferOffset() < input.length) {{
+    throw new Error("Some input bytes were not read");
+  }}
+  const serializer = {new_serializer};
+  value.serialize(serializer);
+  return serializer.getBytes();
+}}
+
+const lines = fs.readFileSync(process.argv[2], 'utf8').split('\n');
+// Each sample ends with a newline.
+lines.pop();
+let failures = 0;
+lines.forEach((line: string, i: number) => {{
+  const input = new Uint8Array(line.length / 2);
+  for (let j = 0; j < input.length; j++) {{
+    input[j] = parseInt(line.substr(2 * j, 2), 16);
+  }}
+  try {{
+    const output = roundtrip(input);
+    if (output.length !== input.length || output.some((byte, j) => byte !== input[j])) {{
+      console.log("sample " + i + ": re-encoding differs");
+      failures++;
+    }}
+  }} catch (e) {{
+    console.log("sample " + i + ": " + e);
+    failures++;
+  }}
+}});
+if (failures > 0) {{
+  console.log(failures + " failures");
+  process.exit(1);
+}}"#,
+            module = MODULE_NAME,
+            root = self.root,
+            imports = imports,
+            new_serializer = new_serializer,
+            new_deserializer = new_deserializer,
+        )
+    }
+
+    fn write_csharp_check(&self, dir: &Path) -> std::io::Result<()> {
+        let check_dir = dir.join("Check");
+        std::fs::create_dir_all(&check_dir)?;
+        let mut proj = std::fs::File::create(check_dir.join("Check.csproj"))?;
+        writeln!(
+            proj,
+            r#"<Project Sdk="Microsoft.NET.Sdk">
+    <PropertyGroup>
+       <OutputType>Exe</OutputType>
+       <TargetFramework>net6.0</TargetFramework>
+       <RollForward>Major</RollForward>
+    </PropertyGroup>
+    <ItemGroup>
+      <ProjectReference Include="..\{module}\{module}.csproj" />
+    </ItemGroup>
+</Project>"#,
+            module = MODULE_NAME,
+        )?;
+        let mut file = std::fs::File::create(check_dir.join("Program.cs"))?;
+        writeln!(
+            file,
+            r#"using System;
+using System.IO;
+using System.Linq;
+
+public static class Program {{
+    public static int Main(string[] args) {{
+        string[] lines = File.ReadAllLines(args[0]);
+        int failures = 0;
+        for (int i = 0; i < lines.Length; i++) {{
+            string line = lines[i];
+            byte[] input = new byte[line.Length / 2];
+            for (int j = 0; j < input.Length; j++) {{
+                input[j] = Convert.ToByte(line.Substring(2 * j, 2), 16);
+            }}
+            try {{
+                byte[] output = {module}.{root}.{encoding}Deserialize(input).{encoding}Serialize();
+                if (!input.SequenceEqual(output)) {{
+                    Console.WriteLine("sample " + i + ": re-encoding differs");
+                    failures++;
+                }}
+            }} catch (Exception e) {{
+                Console.WriteLine("sample " + i + ": " + e.Message);
+                failures++;
+            }}
+        }}
+        if (failures > 0) {{
+            Console.WriteLine(failures + " failures");
+            return 1;
+        }}
+        return 0;
+    }}
+}}"#,
+            module = MODULE_NAME,
+            root = self.root,
+            encoding = self.encoding.name().to_camel_case(),
+        )
+    }
+
+    fn write_c_check(&self, dir: &Path) -> std::io::Result<()> {
+        let mut file = std::fs::File::create(dir.join("check.c"))?;
+        writeln!(
+            file,
+            r#"#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include "{module}.h"
+
+static uint8_t arena_buffer[1 << 24];
+
+static int hex_value(char c) {{
+    return c <= '9' ? c - '0' : (c | 0x20) - 'a' + 10;
+}}
+
+static serde_error_t roundtrip(const uint8_t *input, size_t len, uint8_t *output, size_t capacity, size_t *length) {{
+    serde_arena_t arena;
+    serde_arena_init(&arena, arena_buffer, sizeof(arena_buffer));
+    {module}_{root} value;
+    SERDE_TRY({module}_{root}_{encoding}_deserialize(input, len, &arena, &value));
+    return {module}_{root}_{encoding}_serialize(&value, output, capacity, &arena, length);
+}}
+
+int main(int argc, char **argv) {{
+    FILE *file = fopen(argv[1], "rb");
+    if (file == NULL) {{
+        perror(argv[1]);
+        return 1;
+    }}
+    fseek(file, 0, SEEK_END);
+    size_t size = (size_t)ftell(file);
+    fseek(file, 0, SEEK_SET);
+    char *data = malloc(size + 1);
+    if (fread(data, 1, size, file) != size) {{
+        perror(argv[1]);
+        return 1;
+    }}
+    fclose(file);
+
+    size_t failures = 0;
+    const char *line = data;
+    for (size_t i = 0; line < data + size; i++) {{
+        const char *end = memchr(line, '\n', data + size - line);
+        if (end == NULL) {{
+            end = data + size;
+        }}
+        size_t len = (size_t)(end - line) / 2;
+        // Re-encoding must be exact: one more byte of capacity is enough to detect longer outputs.
+        uint8_t *input = malloc(len + 1);
+        uint8_t *output = malloc(len + 1);
+        for (size_t j = 0; j < len; j++) {{
+            input[j] = (uint8_t)(hex_value(line[2 * j]) << 4 | hex_value(line[2 * j + 1]));
+        }}
+        size_t length = 0;
+        serde_error_t error = roundtrip(input, len, output, len + 1, &length);
+        if (error != SERDE_OK) {{
+            printf("sample %zu: %s\n", i, serde_error_message(error));
+            failures++;
+        }} else if (length != len || memcmp(input, output, len) != 0) {{
+            printf("sample %zu: re-encoding differs\n", i);
+            failures++;
+        }}
+        free(input);
+        free(output);
+        line = end + 1;
+    }}
+    free(data);
+    if (failures > 0) {{
+        printf("%zu failures\n", failures);
+        return 1;
+    }}
+    return 0;
+}}"#,
+            module = MODULE_NAME,
+            root = self.root,
+            encoding = self.encoding.name(),
+        )
+    }
+}
+
+/// Install the generated module together with the runtimes.
+fn install<I>(
+    installer: &I,
+    config: &CodeGeneratorConfig,
+    harness: &Harness,
+) -> std::result::Result<(), String>
+where
+    I: SourceInstaller<Error = Error>,
+{
+    let result = installer
+        .install_module(config, harness.registry)
+        .and_then(|()| installer.install_serde_runtime())
+        .and_then(|()| match harness.encoding {
+            Encoding::Bcs => installer.install_bcs_runtime(),
+            Encoding::Bincode => installer.install_bincode_runtime(),
+        });
+    result.map_err(|e| format!("Code generation failed: {}", e))
+}
+
+/// Describe the first requirement of the language that is not met, if any.
+fn missing_requirement(language: Language) -> Option<&'static str> {
+    language
+        .requirements()
+        .iter()
+        .find_map(
+            |(program, args, missing)| match Command::new(program).args(*args).output() {
+                Ok(output) if output.status.success() => None,
+                _ => Some(*missing),
+            },
+        )
+}
+
+/// A fresh directory under the temporary directory of the system, removed when dropped.
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> std::io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "serde-generate-conformance-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        std::fs::create_dir_all(&path)?;
+        Ok(TempDir(path))
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Write the files of an embedded directory, including sub-directories, into `path`.
+fn extract(dir: &include_dir::Dir, path: &Path) -> std::io::Result<()> {
+    for file in dir.files() {
+        std::fs::write(path.join(file.path()), file.contents())?;
+    }
+    for sub_dir in dir.dirs() {
+        std::fs::create_dir_all(path.join(sub_dir.path()))?;
+        extract(sub_dir, path)?;
+    }
+    Ok(())
+}
+
+/// Write the samples in hexadecimal, one per line.
+fn write_samples(path: &Path, samples: &[Vec<u8>]) -> std::io::Result<()> {
+    let mut file = std::io::BufWriter::new(std::fs::File::create(path)?);
+    for sample in samples {
+        for byte in sample {
+            write!(file, "{:02x}", byte)?;
+        }
+        writeln!(file)?;
+    }
+    file.flush()
+}
+
+fn find_java_sources(dir: &Path, paths: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            find_java_sources(&path, paths)?;
+        } else if path.extension().map_or(false, |ext| ext == "java") {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Run a command and return its output in case of failure.
+fn run(command: &mut Command) -> std::result::Result<(), String> {
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to execute {:?}: {}", command, e))?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(format!(
+        "{}{}",
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&output.stderr)
+    ))
+}
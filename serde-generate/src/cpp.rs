@@ -2,9 +2,9 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::{
-    analyzer,
+    analyzer, common,
     indent::{IndentConfig, IndentedWriter},
-    CodeGeneratorConfig, Encoding,
+    CodeGeneratorConfig, Encoding, Error,
 };
 use heck::CamelCase;
 use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
@@ -19,6 +19,97 @@ pub struct CodeGenerator<'a> {
     /// Mapping from external type names to suitably qualified names (e.g. "MyClass" -> "name::MyClass").
     /// Derived from `config.external_definitions`.
     external_qualified_names: HashMap<String, String>,
+    /// C++ types used for strings, byte arrays, sequences, maps, options and boxed values.
+    type_mapping: TypeMapping,
+    /// Whether to return `serde::expected` values from (de)serialization methods.
+    expected_results: bool,
+}
+
+/// C++ types used by the generated code for strings, byte arrays, sequences, maps, options and
+/// boxed values. Types other than the default ones must implement `serde::Serializable` and
+/// `serde::Deserializable`, e.g. by specializing the hooks of `serde.hpp` such as
+/// `serde::is_sequence`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeMapping {
+    seq: String,
+    map: String,
+    str: String,
+    bytes: String,
+    option: String,
+    boxed: String,
+    includes: Vec<String>,
+}
+
+impl Default for TypeMapping {
+    fn default() -> Self {
+        Self {
+            seq: "std::vector".into(),
+            map: "std::map".into(),
+            str: "std::string".into(),
+            bytes: "std::vector<uint8_t>".into(),
+            option: "std::optional".into(),
+            boxed: "serde::value_ptr".into(),
+            includes: Vec::new(),
+        }
+    }
+}
+
+impl TypeMapping {
+    /// Standard containers and strings using polymorphic allocators (`std::pmr`).
+    pub fn std_pmr() -> Self {
+        Self {
+            seq: "std::pmr::vector".into(),
+            map: "std::pmr::map".into(),
+            str: "std::pmr::string".into(),
+            bytes: "std::pmr::vector<uint8_t>".into(),
+            includes: vec!["<memory_resource>".into()],
+            ..Self::default()
+        }
+    }
+
+    /// Template used for sequences (e.g. "std::vector").
+    pub fn with_seq(mut self, seq: String) -> Self {
+        self.seq = seq;
+        self
+    }
+
+    /// Template used for maps (e.g. "std::map").
+    pub fn with_map(mut self, map: String) -> Self {
+        self.map = map;
+        self
+    }
+
+    /// Type used for strings (e.g. "std::string").
+    pub fn with_str(mut self, str: String) -> Self {
+        self.str = str;
+        self
+    }
+
+    /// Type used for byte arrays (e.g. "std::vector<uint8_t>").
+    pub fn with_bytes(mut self, bytes: String) -> Self {
+        self.bytes = bytes;
+        self
+    }
+
+    /// Template used for options (e.g. "std::optional").
+    pub fn with_option(mut self, option: String) -> Self {
+        self.option = option;
+        self
+    }
+
+    /// Template used to box recursive values (e.g. "serde::value_ptr"). The boxed type must be
+    /// copyable and compare the pointed values.
+    pub fn with_boxed(mut self, boxed: String) -> Self {
+        self.boxed = boxed;
+        self
+    }
+
+    /// Additional headers providing the types above, including their delimiters
+    /// (e.g. "<memory_resource>" or "\"absl/container/flat_hash_map.h\"").
+    pub fn with_includes(mut self, includes: Vec<String>) -> Self {
+        self.includes = includes;
+        self
+    }
 }
 
 /// Shared state for the code generation of a C++ source file.
@@ -33,14 +124,18 @@ struct CppEmitter<'a, T> {
     known_sizes: HashSet<&'a str>,
     /// Current namespace (e.g. vec!["name", "MyClass"])
     current_namespace: Vec<String>,
+    /// Whether we are emitting the definitions of a separate source file, as opposed to a header.
+    out_of_line: bool,
+    /// Type parameters of the generic container being emitted, if any. Generic containers are
+    /// class templates whose methods and traits are defined inline.
+    template_parameters: Vec<String>,
+    /// Fingerprints of the containers, if requested.
+    fingerprints: BTreeMap<String, u64>,
 }
 
 impl<'a> CodeGenerator<'a> {
     /// Create a C++ code generator for the given config.
     pub fn new(config: &'a CodeGeneratorConfig) -> Self {
-        if config.c_style_enums {
-            panic!("C++ does not support generating c-style enums");
-        }
         let mut external_qualified_names = HashMap::new();
         for (namespace, names) in &config.external_definitions {
             for name in names {
@@ -51,27 +146,29 @@ impl<'a> CodeGenerator<'a> {
         Self {
             config,
             external_qualified_names,
+            type_mapping: TypeMapping::default(),
+            expected_results: false,
         }
     }
 
-    pub fn output(
-        &self,
-        out: &mut dyn Write,
-        registry: &Registry,
-    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
-        let current_namespace = self
-            .config
-            .module_name
-            .split("::")
-            .map(String::from)
-            .collect();
-        let mut emitter = CppEmitter {
-            out: IndentedWriter::new(out, IndentConfig::Space(4)),
-            generator: self,
-            known_names: HashSet::new(),
-            known_sizes: HashSet::new(),
-            current_namespace,
-        };
+    /// C++ types to use for strings, byte arrays, sequences, maps, options and boxed values.
+    pub fn with_type_mapping(mut self, type_mapping: TypeMapping) -> Self {
+        self.type_mapping = type_mapping;
+        self
+    }
+
+    /// Whether the methods `<encoding>Serialize` and `<encoding>Deserialize` should return
+    /// `serde::expected` values instead of throwing exceptions. This requires the runtime to
+    /// be compiled without exceptions (e.g. with `-fno-exceptions` or `-DSERDE_NO_EXCEPTIONS`).
+    pub fn with_expected_results(mut self, expected_results: bool) -> Self {
+        self.expected_results = expected_results;
+        self
+    }
+
+    pub fn output(&self, out: &mut dyn Write, registry: &Registry) -> crate::Result<()> {
+        self.check_config(registry)?;
+        let mut emitter = self.new_emitter(out);
+        emitter.fingerprints = common::get_fingerprints(self.config, registry)?;
 
         emitter.output_preamble()?;
         emitter.output_open_namespace()?;
@@ -82,7 +179,10 @@ impl<'a> CodeGenerator<'a> {
         for name in entries {
             for dependency in &dependencies[name] {
                 if !emitter.known_names.contains(dependency) {
-                    emitter.output_container_forward_definition(*dependency)?;
+                    emitter.output_container_forward_definition(
+                        *dependency,
+                        registry.get(*dependency),
+                    )?;
                     emitter.known_names.insert(*dependency);
                 }
             }
@@ -99,6 +199,116 @@ impl<'a> CodeGenerator<'a> {
         }
         Ok(())
     }
+
+    /// Output the definitions for `registry` in separate files under `install_dir`:
+    /// * a forward-declaration header `<module_name>/fwd.hpp`,
+    /// * one header `<module_name>/<Name>.hpp` per container, declaring (but not defining) the
+    /// `Serializable` and `Deserializable` specializations,
+    /// * an umbrella header `<module_name>.hpp` including all the headers above,
+    /// * a source file `<module_name>.cpp` with the out-of-line definitions, including the
+    /// specializations explicitly instantiated for the configured encodings.
+    ///
+    /// Without encodings, the specializations remain templates and are defined in the headers.
+    pub fn write_source_files(
+        &self,
+        install_dir: PathBuf,
+        registry: &Registry,
+    ) -> crate::Result<()> {
+        self.check_config(registry)?;
+        let module_name = &self.config.module_name;
+        let dir_path = install_dir.join(module_name);
+        std::fs::create_dir_all(&dir_path)?;
+
+        let dependencies = analyzer::get_dependency_map(registry)?;
+        let entries = analyzer::best_effort_topological_sort(&dependencies);
+
+        let mut file = std::fs::File::create(dir_path.join("fwd.hpp"))?;
+        let mut emitter = self.new_emitter(&mut file);
+        writeln!(emitter.out, "#pragma once\n\n#include \"serde.hpp\"")?;
+        emitter.output_open_namespace()?;
+        for name in &entries {
+            emitter.output_container_forward_definition(name, registry.get(*name))?;
+        }
+        emitter.output_close_namespace()?;
+
+        // Containers defined earlier in `entries` have a known size (see `output`).
+        let mut known_sizes = HashSet::new();
+        let fingerprints = common::get_fingerprints(self.config, registry)?;
+        for name in &entries {
+            let mut file = std::fs::File::create(dir_path.join(format!("{}.hpp", name)))?;
+            let mut emitter = self.new_emitter(&mut file);
+            emitter.known_sizes = known_sizes.clone();
+            emitter.fingerprints = fingerprints.clone();
+            emitter.output_preamble()?;
+            writeln!(emitter.out, "#include \"{}/fwd.hpp\"", module_name)?;
+            for dependency in &dependencies[name] {
+                if known_sizes.contains(dependency) {
+                    writeln!(
+                        emitter.out,
+                        "#include \"{}/{}.hpp\"",
+                        module_name, dependency
+                    )?;
+                }
+            }
+            emitter.output_open_namespace()?;
+            emitter.output_container(name, &registry[*name])?;
+            emitter.output_close_namespace()?;
+            if self.config.serialization {
+                emitter.output_container_trait_declarations(name, &registry[*name])?;
+            }
+            known_sizes.insert(*name);
+        }
+
+        let mut file = std::fs::File::create(install_dir.join(format!("{}.hpp", module_name)))?;
+        writeln!(file, "#pragma once\n")?;
+        for name in &entries {
+            writeln!(file, "#include \"{}/{}.hpp\"", module_name, name)?;
+        }
+
+        let mut file = std::fs::File::create(install_dir.join(format!("{}.cpp", module_name)))?;
+        let mut emitter = self.new_emitter(&mut file);
+        emitter.out_of_line = true;
+        writeln!(emitter.out, "#include \"{}.hpp\"", module_name)?;
+        for name in &entries {
+            emitter.output_container_traits(name, &registry[*name])?;
+        }
+        if self.config.serialization && !self.config.encodings.is_empty() {
+            writeln!(emitter.out)?;
+            for name in &entries {
+                emitter.output_container_trait_instantiations(name, &registry[*name])?;
+            }
+        }
+        Ok(())
+    }
+
+    fn check_config(&self, registry: &Registry) -> crate::Result<()> {
+        if self.config.c_style_enums {
+            return Err(Error::UnsupportedOption {
+                language: "C++",
+                option: "c-style enums",
+            });
+        }
+        common::check_registry(registry)
+    }
+
+    fn new_emitter<T: Write>(&'a self, out: T) -> CppEmitter<'a, T> {
+        let current_namespace = self
+            .config
+            .module_name
+            .split("::")
+            .map(String::from)
+            .collect();
+        CppEmitter {
+            out: IndentedWriter::new(out, IndentConfig::Space(4)),
+            generator: self,
+            known_names: HashSet::new(),
+            known_sizes: HashSet::new(),
+            current_namespace,
+            out_of_line: false,
+            template_parameters: Vec::new(),
+            fingerprints: BTreeMap::new(),
+        }
+    }
 }
 
 impl<'a, T> CppEmitter<'a, T>
@@ -112,6 +322,19 @@ where
 
 #include "serde.hpp""#
         )?;
+        if self.generator.config.serialization && self.generator.expected_results {
+            writeln!(
+                self.out,
+                r#"
+#ifndef SERDE_NO_EXCEPTIONS
+#error "Generated code requires the serde runtime to be compiled without exceptions"
+#endif
+"#
+            )?;
+        }
+        for include in &self.generator.type_mapping.includes {
+            writeln!(self.out, "#include {}", include)?;
+        }
         if self.generator.config.serialization {
             for encoding in &self.generator.config.encodings {
                 writeln!(self.out, "#include \"{}.hpp\"", encoding.name())?;
@@ -172,6 +395,17 @@ where
         Ok(())
     }
 
+    fn output_fingerprint(&mut self, name: &str) -> std::io::Result<()> {
+        if let Some(fingerprint) = self.fingerprints.get(name) {
+            writeln!(
+                self.out,
+                "\nstatic constexpr uint64_t FINGERPRINT = {:#018x}ull;",
+                fingerprint
+            )?;
+        }
+        Ok(())
+    }
+
     /// Compute a fully qualified reference to the container type `name`.
     fn quote_qualified_name(&self, name: &str) -> String {
         self.generator
@@ -183,13 +417,27 @@ where
 
     fn quote_type(&self, format: &Format, require_known_size: bool) -> String {
         use Format::*;
+        let types = &self.generator.type_mapping;
         match format {
             TypeName(x) => {
                 let qname = self.quote_qualified_name(x);
                 if require_known_size && !self.known_sizes.contains(x.as_str()) {
                     // Cannot use unique_ptr because we need a copy constructor (e.g. for vectors)
                     // and in-depth equality.
-                    format!("serde::value_ptr<{}>", qname)
+                    format!("{}<{}>", types.boxed, qname)
+                } else {
+                    qname
+                }
+            }
+            Parameter(x) => x.to_string(),
+            TypeApp { name, arguments } => {
+                let qname = format!(
+                    "{}<{}>",
+                    self.quote_qualified_name(name),
+                    self.quote_types(arguments, require_known_size)
+                );
+                if require_known_size && !self.known_sizes.contains(name.as_str()) {
+                    format!("{}<{}>", types.boxed, qname)
                 } else {
                     qname
                 }
@@ -209,16 +457,18 @@ where
             F32 => "float".into(),
             F64 => "double".into(),
             Char => "char32_t".into(),
-            Str => "std::string".into(),
-            Bytes => "std::vector<uint8_t>".into(),
+            Str => types.str.clone(),
+            Bytes => types.bytes.clone(),
 
             Option(format) => format!(
-                "std::optional<{}>",
+                "{}<{}>",
+                types.option,
                 self.quote_type(format, require_known_size)
             ),
-            Seq(format) => format!("std::vector<{}>", self.quote_type(format, false)),
+            Seq(format) => format!("{}<{}>", types.seq, self.quote_type(format, false)),
             Map { key, value } => format!(
-                "std::map<{}, {}>",
+                "{}<{}, {}>",
+                types.map,
                 self.quote_type(key, false),
                 self.quote_type(value, false)
             ),
@@ -248,10 +498,11 @@ where
         &mut self,
         name: &str,
         fields: &[Named<Format>],
+        is_container: bool,
     ) -> Result<()> {
         writeln!(self.out)?;
         self.output_comment(name)?;
-        writeln!(self.out, "struct {} {{", name)?;
+        self.output_struct_header(name, is_container)?;
         self.enter_class(name);
         for field in fields {
             self.output_comment(&field.name)?;
@@ -265,7 +516,14 @@ where
         if !fields.is_empty() {
             writeln!(self.out)?;
         }
-        self.output_class_method_declarations(name)?;
+        let field_names = fields
+            .iter()
+            .map(|field| field.name.as_str())
+            .collect::<Vec<_>>();
+        self.output_class_methods(name, &field_names, is_container)?;
+        if is_container {
+            self.output_fingerprint(name)?;
+        }
         self.output_custom_code()?;
         self.leave_class();
         writeln!(self.out, "}};")
@@ -286,11 +544,44 @@ where
             Struct(fields) => fields.clone(),
             Variable(_) => panic!("incorrect value"),
         };
-        self.output_struct_or_variant_container(name, &fields)
+        self.output_struct_or_variant_container(name, &fields, false)
     }
 
-    fn output_container_forward_definition(&mut self, name: &str) -> Result<()> {
-        writeln!(self.out, "\nstruct {};", name)
+    fn output_container_forward_definition(
+        &mut self,
+        name: &str,
+        format: Option<&ContainerFormat>,
+    ) -> Result<()> {
+        match format {
+            Some(ContainerFormat::Generic { parameters, .. }) => writeln!(
+                self.out,
+                "\n{}struct {};",
+                Self::quote_template_header(parameters),
+                name
+            ),
+            _ => writeln!(self.out, "\nstruct {};", name),
+        }
+    }
+
+    fn quote_template_header(parameters: &[String]) -> String {
+        format!(
+            "template <{}>\n",
+            parameters
+                .iter()
+                .map(|parameter| format!("typename {}", parameter))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+
+    /// Start the definition of a struct. Generic containers (but not their variants) are
+    /// preceded by a template header.
+    fn output_struct_header(&mut self, name: &str, is_container: bool) -> Result<()> {
+        if is_container && !self.template_parameters.is_empty() {
+            let header = Self::quote_template_header(&self.template_parameters);
+            write!(self.out, "{}", header)?;
+        }
+        writeln!(self.out, "struct {} {{", name)
     }
 
     fn output_enum_container(
@@ -300,7 +591,7 @@ where
     ) -> Result<()> {
         writeln!(self.out)?;
         self.output_comment(name)?;
-        writeln!(self.out, "struct {} {{", name)?;
+        self.output_struct_header(name, true)?;
         self.enter_class(name);
         for (expected_index, (index, variant)) in variants.iter().enumerate() {
             assert_eq!(*index, expected_index as u32);
@@ -316,12 +607,60 @@ where
                 .join(", "),
         )?;
         writeln!(self.out)?;
-        self.output_class_method_declarations(name)?;
+        self.output_class_methods(name, &["value"], true)?;
+        self.output_fingerprint(name)?;
         self.output_custom_code()?;
         self.leave_class();
         writeln!(self.out, "}};")
     }
 
+    /// Declare the methods of a class, or define them inline for generic containers.
+    fn output_class_methods(
+        &mut self,
+        name: &str,
+        fields: &[&str],
+        is_container: bool,
+    ) -> Result<()> {
+        if self.template_parameters.is_empty() {
+            return self.output_class_method_declarations(name);
+        }
+        writeln!(
+            self.out,
+            "friend bool operator==(const {0} &lhs, const {0} &rhs) {{",
+            name
+        )?;
+        self.out.indent();
+        self.output_equality_statements(fields)?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        if self.generator.config.serialization {
+            writeln!(self.out, "\nusing serde_members = void;")?;
+            writeln!(
+                self.out,
+                "\ntemplate <typename Serializer>\nstatic void serde_serialize(const {} &obj, Serializer &serializer) {{",
+                name
+            )?;
+            self.out.indent();
+            self.output_serialize_statements(fields, is_container)?;
+            self.out.unindent();
+            writeln!(self.out, "}}")?;
+            writeln!(
+                self.out,
+                "\ntemplate <typename Deserializer>\nstatic {} serde_deserialize(Deserializer &deserializer) {{",
+                name
+            )?;
+            self.out.indent();
+            self.output_deserialize_statements(name, fields, is_container)?;
+            self.out.unindent();
+            writeln!(self.out, "}}")?;
+            for encoding in &self.generator.config.encodings {
+                self.output_struct_serialize_for_encoding(name, *encoding)?;
+                self.output_struct_deserialize_for_encoding(name, *encoding)?;
+            }
+        }
+        Ok(())
+    }
+
     fn output_class_method_declarations(&mut self, name: &str) -> Result<()> {
         writeln!(
             self.out,
@@ -332,13 +671,14 @@ where
             for encoding in &self.generator.config.encodings {
                 writeln!(
                     self.out,
-                    "std::vector<uint8_t> {}Serialize() const;",
+                    "{} {}Serialize() const;",
+                    self.quote_result_type("std::vector<uint8_t>"),
                     encoding.name()
                 )?;
                 writeln!(
                     self.out,
                     "static {} {}Deserialize(std::vector<uint8_t>);",
-                    name,
+                    self.quote_result_type(name),
                     encoding.name()
                 )?;
             }
@@ -346,6 +686,14 @@ where
         Ok(())
     }
 
+    fn quote_result_type(&self, type_name: &str) -> String {
+        if self.generator.expected_results {
+            format!("serde::expected<{}>", type_name)
+        } else {
+            type_name.to_string()
+        }
+    }
+
     fn output_container(&mut self, name: &str, format: &ContainerFormat) -> Result<()> {
         use ContainerFormat::*;
         let fields = match format {
@@ -363,17 +711,38 @@ where
                 self.output_enum_container(name, variants)?;
                 return Ok(());
             }
+            Generic { parameters, format } => {
+                self.template_parameters = parameters.clone();
+                let result = self.output_container(name, format);
+                self.template_parameters.clear();
+                return result;
+            }
         };
-        self.output_struct_or_variant_container(name, &fields)
+        self.output_struct_or_variant_container(name, &fields, true)
+    }
+
+    fn inline_specifier(&self) -> &'static str {
+        if self.out_of_line {
+            ""
+        } else {
+            "inline "
+        }
     }
 
     fn output_struct_equality_test(&mut self, name: &str, fields: &[&str]) -> Result<()> {
         writeln!(
             self.out,
-            "\ninline bool operator==(const {0} &lhs, const {0} &rhs) {{",
+            "\n{}bool operator==(const {1} &lhs, const {1} &rhs) {{",
+            self.inline_specifier(),
             name,
         )?;
         self.out.indent();
+        self.output_equality_statements(fields)?;
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_equality_statements(&mut self, fields: &[&str]) -> Result<()> {
         for field in fields {
             writeln!(
                 self.out,
@@ -381,9 +750,7 @@ where
                 field,
             )?;
         }
-        writeln!(self.out, "return true;")?;
-        self.out.unindent();
-        writeln!(self.out, "}}")
+        writeln!(self.out, "return true;")
     }
 
     fn output_struct_serialize_for_encoding(
@@ -391,42 +758,80 @@ where
         name: &str,
         encoding: Encoding,
     ) -> Result<()> {
+        let (specifier, qualifier) = self.quote_method_prefix(name, false);
         writeln!(
             self.out,
             r#"
-inline std::vector<uint8_t> {}::{}Serialize() const {{
+{}{} {}{}Serialize() const {{
     auto serializer = serde::{}Serializer();
-    serde::Serializable<{}>::serialize(*this, serializer);
+    serde::Serializable<{}>::serialize(*this, serializer);{}
     return std::move(serializer).bytes();
 }}"#,
-            name,
+            specifier,
+            self.quote_result_type("std::vector<uint8_t>"),
+            qualifier,
             encoding.name(),
             encoding.name().to_camel_case(),
-            name
+            name,
+            self.quote_error_check("serializer"),
         )
     }
 
+    /// The specifier and the qualifier of the definition of a method: out-of-class definitions
+    /// for regular containers, inline definitions for generic containers.
+    fn quote_method_prefix(&self, name: &str, is_static: bool) -> (&'static str, String) {
+        if self.template_parameters.is_empty() {
+            (self.inline_specifier(), format!("{}::", name))
+        } else if is_static {
+            ("static ", String::new())
+        } else {
+            ("", String::new())
+        }
+    }
+
+    fn quote_error_check(&self, context: &str) -> String {
+        if self.generator.expected_results {
+            format!(
+                r#"
+    if ({0}.has_error()) {{
+        return {0}.error();
+    }}"#,
+                context
+            )
+        } else {
+            String::new()
+        }
+    }
+
     fn output_struct_deserialize_for_encoding(
         &mut self,
         name: &str,
         encoding: Encoding,
     ) -> Result<()> {
+        let (specifier, qualifier) = self.quote_method_prefix(name, true);
         writeln!(
             self.out,
             r#"
-inline {} {}::{}Deserialize(std::vector<uint8_t> input) {{
+{}{} {}{}Deserialize(std::vector<uint8_t> input) {{
     auto deserializer = serde::{}Deserializer(input);
     auto value = serde::Deserializable<{}>::deserialize(deserializer);
     if (deserializer.get_buffer_offset() < input.size()) {{
-        throw serde::deserialization_error("Some input bytes were not read");
-    }}
+        {}
+    }}{}
     return value;
 }}"#,
-            name,
-            name,
+            specifier,
+            self.quote_result_type(name),
+            qualifier,
             encoding.name(),
             encoding.name().to_camel_case(),
             name,
+            if self.generator.expected_results {
+                r#"deserializer.fail("Some input bytes were not read");"#
+            } else {
+                r#"throw serde::deserialization_error("Some input bytes were not read");"#
+            },
+            self.quote_error_check("deserializer"),
         )
     }
 
@@ -445,6 +850,12 @@ void serde::Serializable<{0}>::serialize(const {0} &obj, Serializer &serializer)
             name,
         )?;
         self.out.indent();
+        self.output_serialize_statements(fields, is_container)?;
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_serialize_statements(&mut self, fields: &[&str], is_container: bool) -> Result<()> {
         if is_container {
             writeln!(self.out, "serializer.increase_container_depth();")?;
         }
@@ -458,8 +869,7 @@ void serde::Serializable<{0}>::serialize(const {0} &obj, Serializer &serializer)
         if is_container {
             writeln!(self.out, "serializer.decrease_container_depth();")?;
         }
-        self.out.unindent();
-        writeln!(self.out, "}}")
+        Ok(())
     }
 
     fn output_struct_deserializable(
@@ -477,6 +887,17 @@ template <typename Deserializer>
             name,
         )?;
         self.out.indent();
+        self.output_deserialize_statements(name, fields, is_container)?;
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_deserialize_statements(
+        &mut self,
+        name: &str,
+        fields: &[&str],
+        is_container: bool,
+    ) -> Result<()> {
         if is_container {
             writeln!(self.out, "deserializer.increase_container_depth();")?;
         }
@@ -491,9 +912,7 @@ template <typename Deserializer>
         if is_container {
             writeln!(self.out, "deserializer.decrease_container_depth();")?;
         }
-        writeln!(self.out, "return obj;")?;
-        self.out.unindent();
-        writeln!(self.out, "}}")
+        writeln!(self.out, "return obj;")
     }
 
     fn output_struct_traits(
@@ -512,7 +931,10 @@ template <typename Deserializer>
         }
         self.output_close_namespace()?;
         let namespaced_name = self.quote_qualified_name(name);
-        if self.generator.config.serialization {
+        // Out-of-line definitions are only useful if they can be instantiated for some encoding.
+        if self.generator.config.serialization
+            && (!self.out_of_line || !self.generator.config.encodings.is_empty())
+        {
             self.output_struct_serializable(&namespaced_name, fields, is_container)?;
             self.output_struct_deserializable(&namespaced_name, fields, is_container)?;
         }
@@ -533,43 +955,136 @@ template <typename Deserializer>
         }
     }
 
-    fn output_container_traits(&mut self, name: &str, format: &ContainerFormat) -> Result<()> {
+    /// The names of the structs to which traits apply for the given container (including
+    /// variants), together with their fields and whether they are containers (as opposed to variants).
+    fn get_trait_entries<'b>(
+        name: &str,
+        format: &'b ContainerFormat,
+    ) -> Vec<(String, Vec<&'b str>, bool)> {
         use ContainerFormat::*;
         match format {
-            UnitStruct => self.output_struct_traits(name, &[], true),
-            NewTypeStruct(_format) => self.output_struct_traits(name, &["value"], true),
-            TupleStruct(_formats) => self.output_struct_traits(name, &["value"], true),
-            Struct(fields) => self.output_struct_traits(
-                name,
-                &fields
+            UnitStruct => vec![(name.to_string(), Vec::new(), true)],
+            NewTypeStruct(_format) => vec![(name.to_string(), vec!["value"], true)],
+            TupleStruct(_formats) => vec![(name.to_string(), vec!["value"], true)],
+            Struct(fields) => vec![(
+                name.to_string(),
+                fields
                     .iter()
                     .map(|field| field.name.as_str())
                     .collect::<Vec<_>>(),
                 true,
-            ),
+            )],
             Enum(variants) => {
-                self.output_struct_traits(name, &["value"], true)?;
+                let mut entries = vec![(name.to_string(), vec!["value"], true)];
                 for variant in variants.values() {
-                    self.output_struct_traits(
-                        &format!("{}::{}", name, variant.name),
-                        &Self::get_variant_fields(&variant.value),
+                    entries.push((
+                        format!("{}::{}", name, variant.name),
+                        Self::get_variant_fields(&variant.value),
                         false,
-                    )?;
+                    ));
                 }
-                Ok(())
+                entries
             }
+            // Generic containers are class templates with inline definitions.
+            Generic { .. } => Vec::new(),
+        }
+    }
+
+    fn output_container_traits(&mut self, name: &str, format: &ContainerFormat) -> Result<()> {
+        for (name, fields, is_container) in Self::get_trait_entries(name, format) {
+            self.output_struct_traits(&name, &fields, is_container)?;
         }
+        Ok(())
+    }
+
+    /// Declare the traits of a container whose definitions are emitted separately by
+    /// `output_container_traits`. Without encodings, the trait templates are defined right away.
+    fn output_container_trait_declarations(
+        &mut self,
+        name: &str,
+        format: &ContainerFormat,
+    ) -> Result<()> {
+        for (name, fields, is_container) in Self::get_trait_entries(name, format) {
+            let namespaced_name = self.quote_qualified_name(&name);
+            if self.generator.config.encodings.is_empty() {
+                self.output_struct_serializable(&namespaced_name, &fields, is_container)?;
+                self.output_struct_deserializable(&namespaced_name, &fields, is_container)?;
+            } else {
+                writeln!(
+                    self.out,
+                    r#"
+template <>
+template <typename Serializer>
+void serde::Serializable<{0}>::serialize(const {0} &obj, Serializer &serializer);
+
+template <>
+template <typename Deserializer>
+{0} serde::Deserializable<{0}>::deserialize(Deserializer &deserializer);"#,
+                    namespaced_name,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Explicitly instantiate the traits of a container for the configured encodings.
+    fn output_container_trait_instantiations(
+        &mut self,
+        name: &str,
+        format: &ContainerFormat,
+    ) -> Result<()> {
+        for (name, _, _) in Self::get_trait_entries(name, format) {
+            let namespaced_name = self.quote_qualified_name(&name);
+            for encoding in &self.generator.config.encodings {
+                writeln!(
+                    self.out,
+                    r#"template void serde::Serializable<{0}>::serialize<serde::{1}Serializer>(const {0} &, serde::{1}Serializer &);
+template {0} serde::Deserializable<{0}>::deserialize<serde::{1}Deserializer>(serde::{1}Deserializer &);"#,
+                    namespaced_name,
+                    encoding.name().to_camel_case(),
+                )?;
+            }
+        }
+        Ok(())
     }
 }
 
 /// Installer for generated source files in C++.
 pub struct Installer {
     install_dir: PathBuf,
+    split_sources: bool,
+    type_mapping: TypeMapping,
+    expected_results: bool,
 }
 
 impl Installer {
     pub fn new(install_dir: PathBuf) -> Self {
-        Installer { install_dir }
+        Installer {
+            install_dir,
+            split_sources: false,
+            type_mapping: TypeMapping::default(),
+            expected_results: false,
+        }
+    }
+
+    /// Whether to install modules as a forward-declaration header, one header per container
+    /// and a source file with out-of-line definitions (see `CodeGenerator::write_source_files`).
+    pub fn with_split_sources(mut self, split_sources: bool) -> Self {
+        self.split_sources = split_sources;
+        self
+    }
+
+    /// C++ types to use in installed modules (see `CodeGenerator::with_type_mapping`).
+    pub fn with_type_mapping(mut self, type_mapping: TypeMapping) -> Self {
+        self.type_mapping = type_mapping;
+        self
+    }
+
+    /// Whether installed modules report errors with `serde::expected` values (see
+    /// `CodeGenerator::with_expected_results`).
+    pub fn with_expected_results(mut self, expected_results: bool) -> Self {
+        self.expected_results = expected_results;
+        self
     }
 
     fn create_header_file(&self, name: &str) -> Result<std::fs::File> {
@@ -580,15 +1095,20 @@ impl Installer {
 }
 
 impl crate::SourceInstaller for Installer {
-    type Error = Box<dyn std::error::Error>;
+    type Error = crate::Error;
 
     fn install_module(
         &self,
         config: &crate::CodeGeneratorConfig,
         registry: &Registry,
     ) -> std::result::Result<(), Self::Error> {
+        let generator = CodeGenerator::new(&config)
+            .with_type_mapping(self.type_mapping.clone())
+            .with_expected_results(self.expected_results);
+        if self.split_sources {
+            return generator.write_source_files(self.install_dir.clone(), registry);
+        }
         let mut file = self.create_header_file(&config.module_name)?;
-        let generator = CodeGenerator::new(&config);
         generator.output(&mut file, &registry)
     }
 
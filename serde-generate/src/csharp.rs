@@ -8,9 +8,9 @@ use crate::{
 };
 use heck::CamelCase;
 use include_dir::include_dir as include_directory;
-use serde_reflection::{ContainerFormat, Format, FormatHolder, Named, Registry, VariantFormat};
+use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     io::{Result, Write},
     path::PathBuf,
 };
@@ -22,6 +22,27 @@ pub struct CodeGenerator<'a> {
     /// Mapping from external type names to fully-qualified class names (e.g. "MyClass" -> "MyNamespace.MyClass").
     /// Derived from `config.external_definitions`.
     external_qualified_names: HashMap<String, String>,
+    /// How to declare the C# types of structs and enums.
+    type_declarations: TypeDeclarations,
+    /// Whether to emit `#nullable enable` and represent optional fields as `T?`.
+    nullable_annotations: bool,
+    /// Whether to emit `System.Text.Json` converters following the data layout of serde_json.
+    json_converters: bool,
+    /// Whether to also emit (de)serialization methods for `Serde.SpanSerializer` and
+    /// `Serde.SpanDeserializer`.
+    span_serializers: bool,
+}
+
+/// How structs and enums are declared in C#.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TypeDeclarations {
+    /// Sealed classes with hand-written `Equals`, `GetHashCode` and `Clone` (default).
+    Classes,
+    /// Records (C# 9), relying on the equality members synthesized by the compiler.
+    Records,
+    /// Same as `Records` except that structs are declared as `readonly record struct`
+    /// (C# 10) unless they contain themselves by value.
+    RecordStructs,
 }
 
 /// Shared state for the code generation of a C# source file.
@@ -40,6 +61,15 @@ struct CSharpEmitter<'a, T> {
     /// When we find an enum with all Unit variants, we ser/de as a regular C# enum.
     /// We keep track of this so we can use the enum's extension class for ser/de since enums can't have methods.
     cstyle_enum_names: Vec<String>,
+    /// Structs declared as `record struct`, i.e. value types that are never null.
+    record_struct_names: HashSet<String>,
+    /// Whether the serialization code being emitted targets `Serde.SpanSerializer` and
+    /// `Serde.SpanDeserializer` instead of `Serde.ISerializer` and `Serde.IDeserializer`.
+    span: bool,
+    /// Fingerprint of the container defined in the current file, if requested.
+    fingerprint: Option<u64>,
+    /// Type parameters of the generic container defined in the current file (if any).
+    type_parameters: Vec<String>,
 }
 
 impl<'a> CodeGenerator<'a> {
@@ -55,9 +85,46 @@ impl<'a> CodeGenerator<'a> {
         Self {
             config,
             external_qualified_names,
+            type_declarations: TypeDeclarations::Classes,
+            nullable_annotations: false,
+            json_converters: false,
+            span_serializers: false,
         }
     }
 
+    /// How to declare the C# types of structs and enums. Records require C# 9 and record
+    /// structs C# 10.
+    pub fn with_type_declarations(mut self, type_declarations: TypeDeclarations) -> Self {
+        self.type_declarations = type_declarations;
+        self
+    }
+
+    /// Whether to emit `#nullable enable` in every source file and to represent optional
+    /// fields (and tuple elements) as `T?` instead of `Serde.Option<T>`. Options nested in
+    /// sequences, maps or other options still use `Serde.Option<T>`.
+    pub fn with_nullable_annotations(mut self, nullable_annotations: bool) -> Self {
+        self.nullable_annotations = nullable_annotations;
+        self
+    }
+
+    /// Whether to emit a `System.Text.Json` converter for each container, following the
+    /// data layout of serde_json (externally tagged enums, tuples as arrays, etc). The
+    /// generated code requires .NET 6. Generic containers are then emitted once per instance
+    /// (see `serde_reflection::monomorphize`) rather than as generic classes.
+    pub fn with_json_converters(mut self, json_converters: bool) -> Self {
+        self.json_converters = json_converters;
+        self
+    }
+
+    /// Whether to also emit generic `Serialize<F>(ref Serde.SpanSerializer<F>)` and
+    /// `Deserialize<F>(ref Serde.SpanDeserializer<F>)` methods, as well as entry points such as
+    /// `BcsSerialize(Span<byte>)`, `BcsSerialize(IBufferWriter<byte>)` and
+    /// `BcsDeserialize(ReadOnlySpan<byte>)`. These do not allocate besides the resulting objects.
+    pub fn with_span_serializers(mut self, span_serializers: bool) -> Self {
+        self.span_serializers = span_serializers;
+        self
+    }
+
     /// Output class definitions for `registry` in separate source files.
     /// Source files will be created in a subdirectory of `install_dir` corresponding to the given
     /// package name (if any, otherwise `install_dir` itself).
@@ -65,7 +132,16 @@ impl<'a> CodeGenerator<'a> {
         &self,
         install_dir: std::path::PathBuf,
         registry: &Registry,
-    ) -> Result<std::path::PathBuf> {
+    ) -> crate::Result<std::path::PathBuf> {
+        // JSON converters are only emitted for non-generic classes.
+        let monomorphized_registry;
+        let registry = if self.json_converters {
+            monomorphized_registry = common::check_and_monomorphize_registry(registry)?;
+            &monomorphized_registry
+        } else {
+            common::check_registry(registry)?;
+            registry
+        };
         let current_namespace = self
             .config
             .module_name
@@ -92,28 +168,118 @@ impl<'a> CodeGenerator<'a> {
             }
         }
 
+        let record_struct_names = if self.type_declarations == TypeDeclarations::RecordStructs {
+            Self::record_struct_names(registry)
+        } else {
+            HashSet::new()
+        };
+
+        let fingerprints = common::get_fingerprints(self.config, registry)?;
         for (name, format) in registry {
             self.write_container_class(
                 &dir_path,
                 current_namespace.clone(),
                 cstyle_enum_names.clone(),
+                record_struct_names.clone(),
                 name,
                 format,
+                fingerprints.get(name).copied(),
             )?;
         }
         if self.config.serialization {
-            self.write_helper_class(&dir_path, current_namespace, cstyle_enum_names, registry)?;
+            self.write_helper_class(
+                &dir_path,
+                current_namespace.clone(),
+                cstyle_enum_names.clone(),
+                record_struct_names.clone(),
+                registry,
+            )?;
+        }
+        if self.json_converters {
+            self.write_json_helper_class(
+                &dir_path,
+                current_namespace,
+                cstyle_enum_names,
+                record_struct_names,
+                registry,
+            )?;
         }
         Ok(dir_path)
     }
 
+    /// Structs that can be declared as `record struct`: a value type cannot contain itself,
+    /// so we exclude the structs that reach themselves through options and tuples. Generic
+    /// containers are always declared as classes.
+    fn record_struct_names(registry: &Registry) -> HashSet<String> {
+        fn add_value_dependencies<'b>(format: &'b Format, dependencies: &mut Vec<&'b str>) {
+            match format {
+                Format::TypeName(name) => dependencies.push(name),
+                Format::Option(format) => add_value_dependencies(format, dependencies),
+                Format::Tuple(formats) => {
+                    for format in formats {
+                        add_value_dependencies(format, dependencies);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        let mut dependencies = BTreeMap::new();
+        for (name, format) in registry {
+            let mut names = Vec::new();
+            match format {
+                ContainerFormat::UnitStruct => (),
+                ContainerFormat::NewTypeStruct(format) => {
+                    add_value_dependencies(format, &mut names)
+                }
+                ContainerFormat::TupleStruct(formats) => {
+                    for format in formats {
+                        add_value_dependencies(format, &mut names);
+                    }
+                }
+                ContainerFormat::Struct(fields) => {
+                    for field in fields {
+                        add_value_dependencies(&field.value, &mut names);
+                    }
+                }
+                ContainerFormat::Enum(_) | ContainerFormat::Generic { .. } => continue,
+            }
+            dependencies.insert(name.as_str(), names);
+        }
+
+        let mut result = HashSet::new();
+        for name in dependencies.keys() {
+            let mut visited = HashSet::new();
+            let mut stack = dependencies[name].clone();
+            let mut is_recursive = false;
+            while let Some(next) = stack.pop() {
+                if next == *name {
+                    is_recursive = true;
+                    break;
+                }
+                if visited.insert(next) {
+                    if let Some(names) = dependencies.get(next) {
+                        stack.extend(names.iter().cloned());
+                    }
+                }
+            }
+            if !is_recursive {
+                result.insert(name.to_string());
+            }
+        }
+        result
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn write_container_class(
         &self,
         dir_path: &std::path::Path,
         current_namespace: Vec<String>,
         cstyle_enum_names: Vec<String>,
+        record_struct_names: HashSet<String>,
         name: &str,
         format: &ContainerFormat,
+        fingerprint: Option<u64>,
     ) -> Result<()> {
         let mut file = std::fs::File::create(dir_path.join(name.to_string() + ".cs"))?;
         let mut emitter = CSharpEmitter {
@@ -122,6 +288,10 @@ impl<'a> CodeGenerator<'a> {
             current_namespace,
             current_reserved_names: HashMap::new(),
             cstyle_enum_names,
+            record_struct_names,
+            span: false,
+            fingerprint,
+            type_parameters: Vec::new(),
         };
 
         emitter.output_preamble()?;
@@ -137,6 +307,7 @@ impl<'a> CodeGenerator<'a> {
         dir_path: &std::path::Path,
         current_namespace: Vec<String>,
         cstyle_enum_names: Vec<String>,
+        record_struct_names: HashSet<String>,
         registry: &Registry,
     ) -> Result<()> {
         let mut file = std::fs::File::create(dir_path.join("TraitHelpers.cs"))?;
@@ -146,6 +317,10 @@ impl<'a> CodeGenerator<'a> {
             current_namespace,
             current_reserved_names: HashMap::new(),
             cstyle_enum_names,
+            record_struct_names,
+            span: false,
+            fingerprint: None,
+            type_parameters: Vec::new(),
         };
 
         emitter.output_preamble()?;
@@ -155,6 +330,35 @@ impl<'a> CodeGenerator<'a> {
 
         Ok(())
     }
+
+    fn write_json_helper_class(
+        &self,
+        dir_path: &std::path::Path,
+        current_namespace: Vec<String>,
+        cstyle_enum_names: Vec<String>,
+        record_struct_names: HashSet<String>,
+        registry: &Registry,
+    ) -> Result<()> {
+        let mut file = std::fs::File::create(dir_path.join("JsonTraitHelpers.cs"))?;
+        let mut emitter = CSharpEmitter {
+            out: IndentedWriter::new(&mut file, IndentConfig::Space(4)),
+            generator: self,
+            current_namespace,
+            current_reserved_names: HashMap::new(),
+            cstyle_enum_names,
+            record_struct_names,
+            span: false,
+            fingerprint: None,
+            type_parameters: Vec::new(),
+        };
+
+        emitter.output_preamble()?;
+        emitter.output_open_namespace()?;
+        emitter.output_json_trait_helpers(registry)?;
+        emitter.output_close_namespace()?;
+
+        Ok(())
+    }
 }
 
 impl<'a, T> CSharpEmitter<'a, T>
@@ -162,6 +366,9 @@ where
     T: Write,
 {
     fn output_preamble(&mut self) -> Result<()> {
+        if self.generator.nullable_annotations {
+            writeln!(self.out, "#nullable enable\n")?;
+        }
         writeln!(
             self.out,
             r"using System;
@@ -171,6 +378,17 @@ using System.Linq;
 using System.Text;
 using System.Numerics;"
         )?;
+        if self.generator.span_serializers {
+            writeln!(self.out, "using System.Buffers;")?;
+        }
+        if self.generator.json_converters {
+            writeln!(
+                self.out,
+                r"using System.Globalization;
+using System.Text.Json;
+using System.Text.Json.Serialization;"
+            )?;
+        }
         Ok(())
     }
 
@@ -246,19 +464,30 @@ using System.Numerics;"
         Ok(())
     }
 
+    /// Annotation for reference types that may be null, e.g. `object?`.
+    fn nullable_mark(&self) -> &'static str {
+        if self.generator.nullable_annotations {
+            "?"
+        } else {
+            ""
+        }
+    }
+
     fn is_nullable(&self, format: &Format) -> bool {
         use Format::*;
         match format {
-            TypeName(name) => !self.cstyle_enum_names.contains(name),
-            Str | Seq(_) | Map { .. } | TupleArray { .. } => true,
+            TypeName(name) => {
+                !self.cstyle_enum_names.contains(name) && !self.record_struct_names.contains(name)
+            }
+            Str | Seq(_) | Map { .. } | TupleArray { .. } | Parameter(_) | TypeApp { .. } => true,
             Variable(_) => panic!("unexpected value"),
             _ => false,
         }
     }
 
-    fn quote_type(&self, format: &Format) -> String {
+    fn quote_type(&self, format0: &Format) -> String {
         use Format::*;
-        match format {
+        match format0 {
             TypeName(x) => self.quote_qualified_name(x),
             Unit => "Serde.Unit".into(),
             Bool => "bool".into(),
@@ -278,22 +507,68 @@ using System.Numerics;"
             Str => "string".into(),
             Bytes => "Serde.ValueArray<byte>".into(),
 
-            Option(format) => format!("Serde.Option<{}>", self.quote_type(format)),
-            Seq(format) => format!("Serde.ValueArray<{}>", self.quote_type(format)),
+            Option(format) => {
+                if self.is_nullable_option(format0) {
+                    format!("{}?", self.quote_type_argument(format))
+                } else {
+                    format!("Serde.Option<{}>", self.quote_type_argument(format))
+                }
+            }
+            Seq(format) => format!("Serde.ValueArray<{}>", self.quote_type_argument(format)),
             Map { key, value } => format!(
                 "Serde.ValueDictionary<{}, {}>",
-                self.quote_type(key),
-                self.quote_type(value)
+                self.quote_type_argument(key),
+                self.quote_type_argument(value)
             ),
             Tuple(formats) => format!("({})", self.quote_types(formats)),
             TupleArray {
                 content,
                 size: _size,
-            } => format!("Serde.ValueArray<{}>", self.quote_type(content),),
+            } => format!("Serde.ValueArray<{}>", self.quote_type_argument(content),),
+            Parameter(name) => name.to_string(),
+            TypeApp { name, arguments } => format!(
+                "{}<{}>",
+                self.quote_qualified_name(name),
+                arguments
+                    .iter()
+                    .map(|f| self.quote_type_argument(f))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
             Variable(_) => panic!("unexpected value"),
         }
     }
 
+    /// Whether `format` is an option represented as `T?`. This only applies to fields and
+    /// tuple elements: the type arguments of `Serde.Option`, `Serde.ValueArray` and
+    /// `Serde.ValueDictionary` must implement `IEquatable<T>`, which `Nullable<T>` does not.
+    /// Options of type parameters are excluded since `T?` means `T` for value types.
+    fn is_nullable_option(&self, format: &Format) -> bool {
+        self.generator.nullable_annotations
+            && matches!(format, Format::Option(content) if common::type_parameters(content).is_empty())
+    }
+
+    /// Same as `quote_type` for a type argument of `Serde.Option`, `Serde.ValueArray` or
+    /// `Serde.ValueDictionary`.
+    fn quote_type_argument(&self, format: &Format) -> String {
+        match format {
+            Format::Option(content) => {
+                format!("Serde.Option<{}>", self.quote_type_argument(content))
+            }
+            _ => self.quote_type(format),
+        }
+    }
+
+    /// Name of the helper functions for the composite type `format` used as a field.
+    fn helper_name(&self, format: &Format) -> String {
+        match format {
+            Format::Option(content) if self.is_nullable_option(format) => {
+                format!("nullable_{}", common::mangle_type(content))
+            }
+            _ => common::mangle_type(format),
+        }
+    }
+
     fn enter_class(&mut self, name: &str, reserved_subclass_names: &[&str]) {
         self.out.indent();
         self.current_namespace.push(name.to_string());
@@ -327,40 +602,314 @@ using System.Numerics;"
     }
 
     fn output_trait_helpers(&mut self, registry: &Registry) -> Result<()> {
-        let mut subtypes = BTreeMap::new();
-        for format in registry.values() {
-            format
-                .visit(&mut |f| {
-                    if Self::needs_helper(f) {
-                        subtypes.insert(common::mangle_type(f), f.clone());
-                    }
-                    Ok(())
-                })
-                .unwrap();
-        }
+        let subtypes = self.helper_subtypes(registry);
         writeln!(self.out, "static class TraitHelpers {{")?;
         let reserved_names = &[];
         self.enter_class("TraitHelpers", reserved_names);
-        for (mangled_name, subtype) in &subtypes {
-            self.output_serialization_helper(mangled_name, subtype)?;
-            self.output_deserialization_helper(mangled_name, subtype)?;
+        for (mangled_name, (subtype, is_field)) in &subtypes {
+            self.output_serialization_helper(mangled_name, subtype, *is_field)?;
+            self.output_deserialization_helper(mangled_name, subtype, *is_field)?;
+        }
+        if self.generator.span_serializers {
+            self.span = true;
+            for (mangled_name, (subtype, is_field)) in &subtypes {
+                self.output_serialization_helper(mangled_name, subtype, *is_field)?;
+                self.output_deserialization_helper(mangled_name, subtype, *is_field)?;
+            }
+            self.span = false;
         }
         self.leave_class(reserved_names);
         writeln!(self.out, "}}\n")
     }
 
-    fn needs_helper(format: &Format) -> bool {
+    /// Composite types that require helper functions, indexed by helper name. The boolean
+    /// tells whether the type is used as a field (see `is_nullable_option`).
+    fn helper_subtypes(&self, registry: &Registry) -> BTreeMap<String, (Format, bool)> {
+        let mut subtypes = BTreeMap::new();
+        for format in registry.values() {
+            self.add_container_helper_subtypes(format, &mut subtypes);
+        }
+        subtypes
+    }
+
+    fn add_container_helper_subtypes(
+        &self,
+        format: &ContainerFormat,
+        subtypes: &mut BTreeMap<String, (Format, bool)>,
+    ) {
+        match format {
+            ContainerFormat::UnitStruct => (),
+            ContainerFormat::NewTypeStruct(format) => {
+                self.add_helper_subtypes(format, true, subtypes)
+            }
+            ContainerFormat::TupleStruct(formats) => {
+                for format in formats {
+                    self.add_helper_subtypes(format, true, subtypes);
+                }
+            }
+            ContainerFormat::Struct(fields) => {
+                for field in fields {
+                    self.add_helper_subtypes(&field.value, true, subtypes);
+                }
+            }
+            ContainerFormat::Enum(variants) => {
+                for variant in variants.values() {
+                    for format in Self::variant_formats(&variant.value) {
+                        self.add_helper_subtypes(format, true, subtypes);
+                    }
+                }
+            }
+            ContainerFormat::Generic { format, .. } => {
+                self.add_container_helper_subtypes(format, subtypes)
+            }
+        }
+    }
+
+    fn add_helper_subtypes(
+        &self,
+        format: &Format,
+        is_field: bool,
+        subtypes: &mut BTreeMap<String, (Format, bool)>,
+    ) {
         use Format::*;
-        matches!(
-            format,
-            Option(_) | Seq(_) | Map { .. } | Tuple(_) | TupleArray { .. }
+        let name = if is_field {
+            self.helper_name(format)
+        } else {
+            common::mangle_type(format)
+        };
+        match format {
+            Option(content) | Seq(content) | TupleArray { content, .. } => {
+                subtypes.insert(name, (format.clone(), is_field));
+                self.add_helper_subtypes(content, false, subtypes);
+            }
+            Map { key, value } => {
+                subtypes.insert(name, (format.clone(), is_field));
+                self.add_helper_subtypes(key, false, subtypes);
+                self.add_helper_subtypes(value, false, subtypes);
+            }
+            Tuple(formats) => {
+                subtypes.insert(name, (format.clone(), is_field));
+                for format in formats {
+                    self.add_helper_subtypes(format, true, subtypes);
+                }
+            }
+            // Type arguments of generic classes are passed as helper functions.
+            TypeApp { arguments, .. } => {
+                for argument in arguments {
+                    if !matches!(argument, Parameter(_)) {
+                        subtypes.insert(common::mangle_type(argument), (argument.clone(), false));
+                        self.add_helper_subtypes(argument, false, subtypes);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn variant_formats(variant: &VariantFormat) -> Vec<&Format> {
+        match variant {
+            VariantFormat::Unit => Vec::new(),
+            VariantFormat::NewType(format) => vec![format.as_ref()],
+            VariantFormat::Tuple(formats) => formats.iter().collect(),
+            VariantFormat::Struct(fields) => fields.iter().map(|f| &f.value).collect(),
+            VariantFormat::Variable(_) => panic!("incorrect value"),
+        }
+    }
+
+    /// Parameter of the serialization methods being emitted.
+    fn serializer_parameter(&self) -> &'static str {
+        if self.span {
+            "ref Serde.SpanSerializer<F> serializer"
+        } else {
+            "Serde.ISerializer serializer"
+        }
+    }
+
+    /// Parameter of the deserialization methods being emitted.
+    fn deserializer_parameter(&self) -> &'static str {
+        if self.span {
+            "ref Serde.SpanDeserializer<F> deserializer"
+        } else {
+            "Serde.IDeserializer deserializer"
+        }
+    }
+
+    fn serializer_argument(&self) -> &'static str {
+        if self.span {
+            "ref serializer"
+        } else {
+            "serializer"
+        }
+    }
+
+    fn deserializer_argument(&self) -> &'static str {
+        if self.span {
+            "ref deserializer"
+        } else {
+            "deserializer"
+        }
+    }
+
+    /// Type parameters of the (de)serialization methods being emitted.
+    fn generic_parameters(&self) -> &'static str {
+        if self.span {
+            "<F>"
+        } else {
+            ""
+        }
+    }
+
+    /// Type constraints of the (de)serialization methods being emitted (except for overrides).
+    fn generic_constraints(&self) -> &'static str {
+        if self.span {
+            " where F : struct, Serde.IBinaryFormat"
+        } else {
+            ""
+        }
+    }
+
+    /// Type parameters of the (de)serialization helpers being emitted for a format with the
+    /// given type parameters.
+    fn helper_generic_parameters(&self, parameters: &[String]) -> String {
+        let mut all = Vec::new();
+        if self.span {
+            all.push("F");
+        }
+        all.extend(parameters.iter().map(String::as_str));
+        if all.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", all.join(", "))
+        }
+    }
+
+    /// Type constraints of the (de)serialization helpers being emitted for a format with the
+    /// given type parameters.
+    fn helper_generic_constraints(&self, parameters: &[String]) -> String {
+        format!(
+            "{}{}",
+            self.generic_constraints(),
+            Self::quote_type_constraints(parameters)
         )
     }
 
+    /// Type parameters of the generic class being emitted (if any), e.g. `<T0, T1>`.
+    fn quote_type_parameters(&self) -> String {
+        if self.type_parameters.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", self.type_parameters.join(", "))
+        }
+    }
+
+    /// Constraints on type parameters: the type arguments of `Serde.Option`, `Serde.ValueArray`
+    /// and `Serde.ValueDictionary` must implement `IEquatable<T>`.
+    fn quote_type_constraints(parameters: &[String]) -> String {
+        parameters
+            .iter()
+            .map(|p| format!(" where {0} : IEquatable<{0}>", p))
+            .collect()
+    }
+
+    /// Extra parameters of generic serialization methods: one `Serde.Serialize` (or
+    /// `Serde.SpanSerialize` for the format `span_format`) function per type parameter.
+    fn quote_serialize_parameters(&self, parameters: &[String], span_format: &str) -> String {
+        parameters
+            .iter()
+            .map(|p| {
+                if self.span {
+                    format!(
+                        ", Serde.SpanSerialize<{1}, {0}> serialize{0}",
+                        p, span_format
+                    )
+                } else {
+                    format!(", Serde.Serialize<{0}> serialize{0}", p)
+                }
+            })
+            .collect()
+    }
+
+    /// Extra parameters of generic deserialization methods: one `Serde.Deserialize` (or
+    /// `Serde.SpanDeserialize` for the format `span_format`) function per type parameter.
+    fn quote_deserialize_parameters(&self, parameters: &[String], span_format: &str) -> String {
+        parameters
+            .iter()
+            .map(|p| {
+                if self.span {
+                    format!(
+                        ", Serde.SpanDeserialize<{1}, {0}> deserialize{0}",
+                        p, span_format
+                    )
+                } else {
+                    format!(", Serde.Deserialize<{0}> deserialize{0}", p)
+                }
+            })
+            .collect()
+    }
+
+    /// Functions passed along to generic methods for the given type parameters, e.g.
+    /// `, serializeT0, serializeT1`.
+    fn quote_function_arguments(prefix: &str, parameters: &[String]) -> String {
+        parameters
+            .iter()
+            .map(|p| format!(", {}{}", prefix, p))
+            .collect()
+    }
+
+    /// Serialization function for a type argument.
+    fn quote_serialize_function(&self, format: &Format) -> String {
+        if let Format::Parameter(name) = format {
+            return format!("serialize{}", name);
+        }
+        let parameters = common::type_parameters(format);
+        let helper = format!(
+            "{}.serialize_{}",
+            self.quote_qualified_name("TraitHelpers"),
+            common::mangle_type(format)
+        );
+        let arguments = Self::quote_function_arguments("serialize", &parameters);
+        if parameters.is_empty() {
+            format!("{}{}", helper, self.generic_parameters())
+        } else if self.span {
+            format!(
+                "({} v, ref Serde.SpanSerializer<F> s) => {}(v, ref s{})",
+                self.quote_type_argument(format),
+                helper,
+                arguments
+            )
+        } else {
+            format!("(v, s) => {}(v, s{})", helper, arguments)
+        }
+    }
+
+    /// Deserialization function for a type argument.
+    fn quote_deserialize_function(&self, format: &Format) -> String {
+        if let Format::Parameter(name) = format {
+            return format!("deserialize{}", name);
+        }
+        let parameters = common::type_parameters(format);
+        let helper = format!(
+            "{}.deserialize_{}",
+            self.quote_qualified_name("TraitHelpers"),
+            common::mangle_type(format)
+        );
+        let arguments = Self::quote_function_arguments("deserialize", &parameters);
+        if parameters.is_empty() {
+            format!("{}{}", helper, self.generic_parameters())
+        } else if self.span {
+            format!(
+                "(ref Serde.SpanDeserializer<F> d) => {}(ref d{})",
+                helper, arguments
+            )
+        } else {
+            format!("d => {}(d{})", helper, arguments)
+        }
+    }
+
     fn quote_serialize_value(&self, value: &str, format: &Format) -> String {
         use Format::*;
         match format {
-            TypeName(_) => format!("{}.Serialize(serializer);", value),
+            TypeName(_) => format!("{}.Serialize({});", value, self.serializer_argument()),
             Unit => format!("serializer.serialize_unit({});", value),
             Bool => format!("serializer.serialize_bool({});", value),
             I8 => format!("serializer.serialize_i8({});", value),
@@ -378,12 +927,44 @@ using System.Numerics;"
             Char => format!("serializer.serialize_char({});", value),
             Str => format!("serializer.serialize_str({});", value),
             Bytes => format!("serializer.serialize_bytes({});", value),
+            Parameter(name) => format!(
+                "serialize{}({}, {});",
+                name,
+                value,
+                self.serializer_argument()
+            ),
+            TypeApp { arguments, .. } => format!(
+                "{}.Serialize({}{});",
+                value,
+                self.serializer_argument(),
+                arguments
+                    .iter()
+                    .map(|f| format!(", {}", self.quote_serialize_function(f)))
+                    .collect::<String>()
+            ),
             _ => format!(
-                "{}.serialize_{}({}, serializer);",
+                "{}.serialize_{}({}, {}{});",
+                self.quote_qualified_name("TraitHelpers"),
+                self.helper_name(format),
+                value,
+                self.serializer_argument(),
+                Self::quote_function_arguments("serialize", &common::type_parameters(format))
+            ),
+        }
+    }
+
+    /// Same as `quote_serialize_value` for a type argument (see `quote_type_argument`).
+    fn quote_serialize_argument(&self, value: &str, format: &Format) -> String {
+        match format {
+            Format::Option(_) => format!(
+                "{}.serialize_{}({}, {}{});",
                 self.quote_qualified_name("TraitHelpers"),
                 common::mangle_type(format),
-                value
+                value,
+                self.serializer_argument(),
+                Self::quote_function_arguments("serialize", &common::type_parameters(format))
             ),
+            _ => self.quote_serialize_value(value, format),
         }
     }
 
@@ -394,13 +975,15 @@ using System.Numerics;"
                 if self.cstyle_enum_names.contains(name) {
                     let extensions_name = format!("{}Extensions", name.to_camel_case());
                     format!(
-                        "{}.Deserialize(deserializer)",
-                        self.quote_qualified_name(&extensions_name)
+                        "{}.Deserialize({})",
+                        self.quote_qualified_name(&extensions_name),
+                        self.deserializer_argument()
                     )
                 } else {
                     format!(
-                        "{}.Deserialize(deserializer)",
-                        self.quote_qualified_name(name)
+                        "{}.Deserialize({})",
+                        self.quote_qualified_name(name),
+                        self.deserializer_argument()
                     )
                 }
             }
@@ -421,22 +1004,63 @@ using System.Numerics;"
             Char => "deserializer.deserialize_char()".to_string(),
             Str => "deserializer.deserialize_str()".to_string(),
             Bytes => "deserializer.deserialize_bytes()".to_string(),
+            Parameter(name) => format!("deserialize{}({})", name, self.deserializer_argument()),
+            TypeApp { arguments, .. } => format!(
+                "{}.Deserialize({}{})",
+                self.quote_type(format),
+                self.deserializer_argument(),
+                arguments
+                    .iter()
+                    .map(|f| format!(", {}", self.quote_deserialize_function(f)))
+                    .collect::<String>()
+            ),
             _ => format!(
-                "{}.deserialize_{}(deserializer)",
+                "{}.deserialize_{}({}{})",
+                self.quote_qualified_name("TraitHelpers"),
+                self.helper_name(format),
+                self.deserializer_argument(),
+                Self::quote_function_arguments("deserialize", &common::type_parameters(format))
+            ),
+        }
+    }
+
+    /// Same as `quote_deserialize` for a type argument (see `quote_type_argument`).
+    fn quote_deserialize_argument(&self, format: &Format) -> String {
+        match format {
+            Format::Option(_) => format!(
+                "{}.deserialize_{}({}{})",
                 self.quote_qualified_name("TraitHelpers"),
                 common::mangle_type(format),
+                self.deserializer_argument(),
+                Self::quote_function_arguments("deserialize", &common::type_parameters(format))
             ),
+            _ => self.quote_deserialize(format),
         }
     }
 
-    fn output_serialization_helper(&mut self, name: &str, format0: &Format) -> Result<()> {
+    fn output_serialization_helper(
+        &mut self,
+        name: &str,
+        format0: &Format,
+        is_field: bool,
+    ) -> Result<()> {
         use Format::*;
 
+        let nullable = is_field && self.is_nullable_option(format0);
+        let parameters = common::type_parameters(format0);
         write!(
             self.out,
-            "public static void serialize_{}({} value, Serde.ISerializer serializer) {{",
+            "public static void serialize_{}{}({} value, {}{}){} {{",
             name,
-            self.quote_type(format0)
+            self.helper_generic_parameters(&parameters),
+            if nullable {
+                self.quote_type(format0)
+            } else {
+                self.quote_type_argument(format0)
+            },
+            self.serializer_parameter(),
+            self.quote_serialize_parameters(&parameters, "F"),
+            self.helper_generic_constraints(&parameters)
         )?;
         self.out.indent();
         match format0 {
@@ -444,14 +1068,33 @@ using System.Numerics;"
                 write!(
                     self.out,
                     r#"
-if (value.IsSome(out var val)) {{
+if ({}) {{
     serializer.serialize_option_tag(true);
     {}
 }} else {{
     serializer.serialize_option_tag(false);
 }}
 "#,
-                    self.quote_serialize_value("val", format)
+                    if nullable {
+                        "value is {} val"
+                    } else {
+                        "value.IsSome(out var val)"
+                    },
+                    self.quote_serialize_argument("val", format)
+                )?;
+            }
+
+            Seq(format) if self.span => {
+                // Indexing avoids allocating an enumerator.
+                write!(
+                    self.out,
+                    r#"
+serializer.serialize_len(value.Count);
+for (int i = 0; i < value.Count; i++) {{
+    {}
+}}
+"#,
+                    self.quote_serialize_argument("value[i]", format)
                 )?;
             }
 
@@ -464,39 +1107,61 @@ foreach (var item in value) {{
     {}
 }}
 "#,
-                    self.quote_serialize_value("item", format)
+                    self.quote_serialize_argument("item", format)
                 )?;
             }
 
-            Map { key, value } => {
+            Map { key, value } if self.span => {
                 write!(
                     self.out,
                     r#"
 serializer.serialize_len(value.Count);
-int[] offsets = new int[value.Count];
+int[] offsets = ArrayPool<int>.Shared.Rent(value.Count);
 int count = 0;
 foreach (KeyValuePair<{}, {}> entry in value) {{
     offsets[count++] = serializer.get_buffer_offset();
     {}
     {}
 }}
-serializer.sort_map_entries(offsets);
+serializer.sort_map_entries(new ReadOnlySpan<int>(offsets, 0, count));
+ArrayPool<int>.Shared.Return(offsets);
 "#,
-                    self.quote_type(key),
-                    self.quote_type(value),
-                    self.quote_serialize_value("entry.Key", key),
-                    self.quote_serialize_value("entry.Value", value)
+                    self.quote_type_argument(key),
+                    self.quote_type_argument(value),
+                    self.quote_serialize_argument("entry.Key", key),
+                    self.quote_serialize_argument("entry.Value", value)
                 )?;
             }
 
-            Tuple(formats) => {
-                writeln!(self.out)?;
-                for (index, format) in formats.iter().enumerate() {
-                    let expr = format!("value.Item{}", index + 1);
-                    writeln!(self.out, "{}", self.quote_serialize_value(&expr, format))?;
-                }
-            }
-
+            Map { key, value } => {
+                write!(
+                    self.out,
+                    r#"
+serializer.serialize_len(value.Count);
+int[] offsets = new int[value.Count];
+int count = 0;
+foreach (KeyValuePair<{}, {}> entry in value) {{
+    offsets[count++] = serializer.get_buffer_offset();
+    {}
+    {}
+}}
+serializer.sort_map_entries(offsets);
+"#,
+                    self.quote_type_argument(key),
+                    self.quote_type_argument(value),
+                    self.quote_serialize_argument("entry.Key", key),
+                    self.quote_serialize_argument("entry.Value", value)
+                )?;
+            }
+
+            Tuple(formats) => {
+                writeln!(self.out)?;
+                for (index, format) in formats.iter().enumerate() {
+                    let expr = format!("value.Item{}", index + 1);
+                    writeln!(self.out, "{}", self.quote_serialize_value(&expr, format))?;
+                }
+            }
+
             TupleArray { content, size } => {
                 write!(
                     self.out,
@@ -504,32 +1169,76 @@ serializer.sort_map_entries(offsets);
 if (value.Count != {0}) {{
     throw new Serde.SerializationException("Invalid length for fixed-size array: " + value.Count + " instead of " + {0});
 }}
-foreach (var item in value) {{
-    {1}
+{1} {{
+    {2}
 }}
 "#,
                     size,
-                    self.quote_serialize_value("item", content),
+                    if self.span {
+                        "for (int i = 0; i < value.Count; i++)"
+                    } else {
+                        "foreach (var item in value)"
+                    },
+                    self.quote_serialize_argument(
+                        if self.span { "value[i]" } else { "item" },
+                        content
+                    ),
                 )?;
             }
 
-            _ => panic!("unexpected case"),
+            _ => {
+                write!(
+                    self.out,
+                    "\n{}\n",
+                    self.quote_serialize_value("value", format0)
+                )?;
+            }
         }
         self.out.unindent();
         writeln!(self.out, "}}\n")
     }
 
-    fn output_deserialization_helper(&mut self, name: &str, format0: &Format) -> Result<()> {
+    fn output_deserialization_helper(
+        &mut self,
+        name: &str,
+        format0: &Format,
+        is_field: bool,
+    ) -> Result<()> {
         use Format::*;
 
+        let nullable = is_field && self.is_nullable_option(format0);
+        let parameters = common::type_parameters(format0);
         write!(
             self.out,
-            "public static {} deserialize_{}(Serde.IDeserializer deserializer) {{",
-            self.quote_type(format0),
+            "public static {} deserialize_{}{}({}{}){} {{",
+            if nullable {
+                self.quote_type(format0)
+            } else {
+                self.quote_type_argument(format0)
+            },
             name,
+            self.helper_generic_parameters(&parameters),
+            self.deserializer_parameter(),
+            self.quote_deserialize_parameters(&parameters, "F"),
+            self.helper_generic_constraints(&parameters)
         )?;
         self.out.indent();
         match format0 {
+            Option(format) if nullable => {
+                write!(
+                    self.out,
+                    r#"
+bool tag = deserializer.deserialize_option_tag();
+if (!tag) {{
+    return null;
+}} else {{
+    return {};
+}}
+"#,
+                    self.quote_deserialize_argument(format),
+                )?;
+            }
+
             Option(format) => {
                 write!(
                     self.out,
@@ -541,8 +1250,8 @@ if (!tag) {{
     return Serde.Option<{0}>.Some({1});
 }}
 "#,
-                    self.quote_type(format),
-                    self.quote_deserialize(format),
+                    self.quote_type_argument(format),
+                    self.quote_deserialize_argument(format),
                 )?;
             }
 
@@ -557,8 +1266,8 @@ for (int i = 0; i < length; i++) {{
 }}
 return new Serde.ValueArray<{0}>(obj);
 "#,
-                    self.quote_type(format),
-                    self.quote_deserialize(format)
+                    self.quote_type_argument(format),
+                    self.quote_deserialize_argument(format)
                 )?;
             }
 
@@ -586,10 +1295,10 @@ for (long i = 0; i < length; i++) {{
 }}
 return new Serde.ValueDictionary<{0}, {1}>(obj);
 "#,
-                    self.quote_type(key),
-                    self.quote_type(value),
-                    self.quote_deserialize(key),
-                    self.quote_deserialize(value),
+                    self.quote_type_argument(key),
+                    self.quote_type_argument(value),
+                    self.quote_deserialize_argument(key),
+                    self.quote_deserialize_argument(value),
                 )?;
             }
 
@@ -618,13 +1327,15 @@ for (int i = 0; i < {1}; i++) {{
 }}
 return new Serde.ValueArray<{0}>(obj);
 "#,
-                    self.quote_type(content),
+                    self.quote_type_argument(content),
                     size,
-                    self.quote_deserialize(content)
+                    self.quote_deserialize_argument(content)
                 )?;
             }
 
-            _ => panic!("unexpected case"),
+            _ => {
+                write!(self.out, "\nreturn {};\n", self.quote_deserialize(format0))?;
+            }
         }
         self.out.unindent();
         writeln!(self.out, "}}\n")
@@ -637,8 +1348,14 @@ return new Serde.ValueArray<{0}>(obj);
         name: &str,
         variant: &VariantFormat,
     ) -> Result<()> {
+        let fields = Self::variant_fields(variant);
+        self.output_struct_or_variant_container(Some(base), Some(index), name, &fields)
+    }
+
+    /// Fields of a variant, or of a struct seen as a variant.
+    fn variant_fields(variant: &VariantFormat) -> Vec<Named<Format>> {
         use VariantFormat::*;
-        let fields = match variant {
+        match variant {
             Unit => Vec::new(),
             NewType(format) => vec![Named {
                 name: "value".to_string(),
@@ -654,8 +1371,7 @@ return new Serde.ValueArray<{0}>(obj);
                 .collect(),
             Struct(fields) => fields.clone(),
             Variable(_) => panic!("incorrect value"),
-        };
-        self.output_struct_or_variant_container(Some(base), Some(index), name, &fields)
+        }
     }
 
     fn output_variants(
@@ -676,37 +1392,67 @@ return new Serde.ValueArray<{0}>(obj);
         name: &str,
         fields: &[Named<Format>],
     ) -> Result<()> {
+        // Variants are nested in the (possibly generic) class of the enum.
+        let class_name = match variant_base {
+            Some(_) => name.to_string(),
+            None => format!("{}{}", name, self.quote_type_parameters()),
+        };
         // Beginning of class
         writeln!(self.out)?;
+        let is_record = self.generator.type_declarations != TypeDeclarations::Classes;
         let fn_mods = if let Some(base) = variant_base {
             self.output_comment(name)?;
-            writeln!(
-                self.out,
-                "public sealed class {0}: {1}, IEquatable<{0}>, ICloneable {{",
-                name, base
-            )?;
+            if is_record {
+                writeln!(self.out, "public sealed record {}: {} {{", name, base)?;
+            } else {
+                writeln!(
+                    self.out,
+                    "public sealed class {0}: {1}, IEquatable<{0}>, ICloneable {{",
+                    name, base
+                )?;
+            }
             "override "
         } else {
+            let constraints = Self::quote_type_constraints(&self.type_parameters);
             self.output_comment(name)?;
-            writeln!(
-                self.out,
-                "public sealed class {0}: IEquatable<{0}>, ICloneable {{",
-                name
-            )?;
+            self.output_json_converter_attribute(name)?;
+            if self.record_struct_names.contains(name) {
+                writeln!(self.out, "public readonly record struct {} {{", name)?;
+            } else if is_record {
+                writeln!(
+                    self.out,
+                    "public sealed record {}{} {{",
+                    class_name, constraints
+                )?;
+            } else {
+                writeln!(
+                    self.out,
+                    "public sealed class {0}: IEquatable<{0}>, ICloneable{1} {{",
+                    class_name, constraints
+                )?;
+            }
             ""
         };
         let reserved_names = &[];
         self.enter_class(name, reserved_names);
-
         // Fields
         for field in fields {
             self.output_comment(&field.name)?;
-            writeln!(
-                self.out,
-                "public {} {};",
-                self.quote_type(&field.value),
-                field.name
-            )?;
+            if is_record {
+                writeln!(
+                    self.out,
+                    "public {} {} {{ get; init; }}",
+                    self.quote_type(&field.value),
+                    field.name
+                )?;
+            } else {
+                writeln!(
+                    self.out,
+                    "public {} {};",
+                    self.quote_type(&field.value),
+                    field.name
+                )?;
+            }
         }
         if !fields.is_empty() {
             writeln!(self.out)?;
@@ -739,27 +1485,7 @@ return new Serde.ValueArray<{0}>(obj);
 
         // Serialize
         if self.generator.config.serialization {
-            writeln!(
-                self.out,
-                "\npublic {}void Serialize(Serde.ISerializer serializer) {{",
-                fn_mods
-            )?;
-            self.out.indent();
-            writeln!(self.out, "serializer.increase_container_depth();")?;
-            if let Some(index) = variant_index {
-                writeln!(self.out, "serializer.serialize_variant_index({});", index)?;
-            }
-            for field in fields {
-                writeln!(
-                    self.out,
-                    "{}",
-                    self.quote_serialize_value(&field.name, &field.value)
-                )?;
-            }
-            writeln!(self.out, "serializer.decrease_container_depth();")?;
-            self.out.unindent();
-            writeln!(self.out, "}}")?;
-
+            self.output_serialize_method(fn_mods, variant_index, fields)?;
             if variant_index.is_none() {
                 for encoding in &self.generator.config.encodings {
                     self.output_class_serialize_for_encoding(*encoding)?;
@@ -769,69 +1495,74 @@ return new Serde.ValueArray<{0}>(obj);
 
         // Deserialize (struct) or Load (variant)
         if self.generator.config.serialization {
+            self.output_deserialize_method(variant_index, &class_name, fields)?;
             if variant_index.is_none() {
-                writeln!(
-                    self.out,
-                    "\npublic static {}{} Deserialize(Serde.IDeserializer deserializer) {{",
-                    fn_mods, name,
-                )?;
-            } else {
-                writeln!(
-                    self.out,
-                    "\ninternal static {} Load(Serde.IDeserializer deserializer) {{",
-                    name,
-                )?;
+                for encoding in &self.generator.config.encodings {
+                    self.output_class_deserialize_for_encoding(&class_name, *encoding)?;
+                }
             }
-            self.out.indent();
-            writeln!(self.out, "deserializer.increase_container_depth();")?;
-            writeln!(
-                self.out,
-                "{0} obj = new {0}(\n\t{1});",
-                name,
-                fields
-                    .iter()
-                    .map(|f| self.quote_deserialize(&f.value))
-                    .collect::<Vec<_>>()
-                    .join(",\n\t")
-            )?;
-            writeln!(self.out, "deserializer.decrease_container_depth();")?;
-            writeln!(self.out, "return obj;")?;
-            self.out.unindent();
-            writeln!(self.out, "}}")?;
+        }
 
+        // Same with spans
+        if self.generator.config.serialization && self.generator.span_serializers {
+            self.span = true;
+            self.output_serialize_method(fn_mods, variant_index, fields)?;
+            self.output_deserialize_method(variant_index, &class_name, fields)?;
             if variant_index.is_none() {
                 for encoding in &self.generator.config.encodings {
-                    self.output_class_deserialize_for_encoding(name, *encoding)?;
+                    self.output_span_serialize_for_encoding(None, *encoding)?;
+                    self.output_span_deserialize_for_encoding(&class_name, *encoding)?;
                 }
             }
+            self.span = false;
+        }
+        if variant_index.is_none() && self.fingerprint.is_some() {
+            writeln!(self.out)?;
+            self.output_fingerprint()?;
         }
+        if is_record {
+            // Records synthesize equality, hashing and non-destructive mutation (`with`).
+            self.output_custom_code()?;
+            self.leave_class(reserved_names);
+            return writeln!(self.out, "}}");
+        }
+
         // Equality
+        let q = self.nullable_mark();
         writeln!(
             self.out,
-            "public override bool Equals(object obj) => obj is {} other && Equals(other);\n",
-            name
+            "public override bool Equals(object{} obj) => obj is {} other && Equals(other);\n",
+            q, class_name
         )?;
         writeln!(
             self.out,
-            "public static bool operator ==({0} left, {0} right) => Equals(left, right);\n",
-            name
+            "public static bool operator ==({0}{1} left, {0}{1} right) => Equals(left, right);\n",
+            class_name, q
         )?;
         writeln!(
             self.out,
-            "public static bool operator !=({0} left, {0} right) => !Equals(left, right);\n",
-            name
+            "public static bool operator !=({0}{1} left, {0}{1} right) => !Equals(left, right);\n",
+            class_name, q
         )?;
 
-        writeln!(self.out, "public bool Equals({} other) {{", name)?;
+        writeln!(self.out, "public bool Equals({}{} other) {{", class_name, q)?;
         self.out.indent();
         writeln!(self.out, "if (other == null) return false;")?;
         writeln!(self.out, "if (ReferenceEquals(this, other)) return true;")?;
         for field in fields {
-            writeln!(
-                self.out,
-                "if (!{0}.Equals(other.{0})) return false;",
-                &field.name,
-            )?;
+            if self.is_nullable_option(&field.value) {
+                writeln!(
+                    self.out,
+                    "if (!Equals({0}, other.{0})) return false;",
+                    &field.name,
+                )?;
+            } else {
+                writeln!(
+                    self.out,
+                    "if (!{0}.Equals(other.{0})) return false;",
+                    &field.name,
+                )?;
+            }
         }
         writeln!(self.out, "return true;")?;
         self.out.unindent();
@@ -844,11 +1575,19 @@ return new Serde.ValueArray<{0}>(obj);
         self.out.indent();
         writeln!(self.out, "int value = 7;")?;
         for field in fields {
-            writeln!(
-                self.out,
-                "value = 31 * value + {0}.GetHashCode();",
-                &field.name
-            )?;
+            if self.is_nullable_option(&field.value) {
+                writeln!(
+                    self.out,
+                    "value = 31 * value + ({0}?.GetHashCode() ?? 0);",
+                    &field.name
+                )?;
+            } else {
+                writeln!(
+                    self.out,
+                    "value = 31 * value + {0}.GetHashCode();",
+                    &field.name
+                )?;
+            }
         }
         writeln!(self.out, "return value;")?;
         self.out.unindent();
@@ -866,7 +1605,7 @@ return new Serde.ValueArray<{0}>(obj);
             writeln!(
                 self.out,
                 "public {0} Clone() => ({0})MemberwiseClone();\n",
-                name
+                class_name
             )?;
             writeln!(self.out, "object ICloneable.Clone() => Clone();\n")?;
         }
@@ -879,64 +1618,142 @@ return new Serde.ValueArray<{0}>(obj);
         writeln!(self.out, "}}")
     }
 
-    fn output_enum_container(
+    fn output_serialize_method(
         &mut self,
-        name: &str,
-        variants: &BTreeMap<u32, Named<VariantFormat>>,
+        fn_mods: &str,
+        variant_index: Option<u32>,
+        fields: &[Named<Format>],
     ) -> Result<()> {
-        writeln!(self.out)?;
-        self.output_comment(name)?;
         writeln!(
             self.out,
-            "public abstract class {0}: IEquatable<{0}>, ICloneable {{",
-            name
+            "\npublic {}void Serialize{}({}{}){} {{",
+            fn_mods,
+            self.generic_parameters(),
+            self.serializer_parameter(),
+            self.quote_serialize_parameters(&self.type_parameters, "F"),
+            // Overrides inherit the constraints of the base method.
+            if fn_mods.is_empty() {
+                self.generic_constraints()
+            } else {
+                ""
+            }
         )?;
-        let reserved_names = variants
-            .values()
-            .map(|v| v.name.as_str())
-            .collect::<Vec<_>>();
-        self.enter_class(name, &reserved_names);
-
-        // Serialize/Deserialize
-        if self.generator.config.serialization {
+        self.out.indent();
+        writeln!(self.out, "serializer.increase_container_depth();")?;
+        if let Some(index) = variant_index {
+            writeln!(self.out, "serializer.serialize_variant_index({});", index)?;
+        }
+        for field in fields {
             writeln!(
                 self.out,
-                "\npublic abstract void Serialize(Serde.ISerializer serializer);"
-            )?;
-            write!(
-                self.out,
-                "\npublic static {} Deserialize(Serde.IDeserializer deserializer) {{",
-                name
+                "{}",
+                self.quote_serialize_value(&field.name, &field.value)
             )?;
-            self.out.indent();
+        }
+        writeln!(self.out, "serializer.decrease_container_depth();")?;
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_deserialize_method(
+        &mut self,
+        variant_index: Option<u32>,
+        name: &str,
+        fields: &[Named<Format>],
+    ) -> Result<()> {
+        writeln!(
+            self.out,
+            "\n{} {} {}{}({}{}){} {{",
+            if variant_index.is_none() {
+                "public static"
+            } else {
+                "internal static"
+            },
+            name,
+            if variant_index.is_none() {
+                "Deserialize"
+            } else {
+                "Load"
+            },
+            self.generic_parameters(),
+            self.deserializer_parameter(),
+            self.quote_deserialize_parameters(&self.type_parameters, "F"),
+            self.generic_constraints()
+        )?;
+        self.out.indent();
+        writeln!(self.out, "deserializer.increase_container_depth();")?;
+        writeln!(
+            self.out,
+            "{0} obj = new {0}(\n\t{1});",
+            name,
+            fields
+                .iter()
+                .map(|f| self.quote_deserialize(&f.value))
+                .collect::<Vec<_>>()
+                .join(",\n\t")
+        )?;
+        writeln!(self.out, "deserializer.decrease_container_depth();")?;
+        writeln!(self.out, "return obj;")?;
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_enum_container(
+        &mut self,
+        name: &str,
+        variants: &BTreeMap<u32, Named<VariantFormat>>,
+    ) -> Result<()> {
+        let class_name = format!("{}{}", name, self.quote_type_parameters());
+        let constraints = Self::quote_type_constraints(&self.type_parameters);
+        writeln!(self.out)?;
+        self.output_comment(name)?;
+        self.output_json_converter_attribute(name)?;
+        let is_record = self.generator.type_declarations != TypeDeclarations::Classes;
+        if is_record {
             writeln!(
                 self.out,
-                r#"
-int index = deserializer.deserialize_variant_index();
-switch (index) {{"#,
+                "public abstract record {}{} {{",
+                class_name, constraints
             )?;
-            self.out.indent();
-            for (index, variant) in variants {
-                writeln!(
-                    self.out,
-                    "case {}: return {}.Load(deserializer);",
-                    index, variant.name,
-                )?;
-            }
+        } else {
             writeln!(
                 self.out,
-                r#"default: throw new Serde.DeserializationException("Unknown variant index for {}: " + index);"#,
-                name,
+                "public abstract class {0}: IEquatable<{0}>, ICloneable{1} {{",
+                class_name, constraints
             )?;
-            self.out.unindent();
-            writeln!(self.out, "}}")?;
-            self.out.unindent();
-            writeln!(self.out, "}}")?;
+        }
+        let reserved_names = variants
+            .values()
+            .map(|v| v.name.as_str())
+            .collect::<Vec<_>>();
+        self.enter_class(name, &reserved_names);
 
+        // Serialize/Deserialize
+        if self.generator.config.serialization {
+            self.output_enum_serialization_methods(name, variants)?;
             for encoding in &self.generator.config.encodings {
                 self.output_class_serialize_for_encoding(*encoding)?;
-                self.output_class_deserialize_for_encoding(name, *encoding)?;
+                self.output_class_deserialize_for_encoding(&class_name, *encoding)?;
             }
+            if self.generator.span_serializers {
+                self.span = true;
+                self.output_enum_serialization_methods(name, variants)?;
+                for encoding in &self.generator.config.encodings {
+                    self.output_span_serialize_for_encoding(None, *encoding)?;
+                    self.output_span_deserialize_for_encoding(&class_name, *encoding)?;
+                }
+                self.span = false;
+            }
+        }
+        if self.fingerprint.is_some() {
+            writeln!(self.out)?;
+            self.output_fingerprint()?;
+        }
+
+        if is_record {
+            self.output_variants(&class_name, variants)?;
+            self.leave_class(&reserved_names);
+            return writeln!(self.out, "}}\n");
         }
 
         // HashCode
@@ -955,13 +1772,14 @@ switch (index) {{"#,
         writeln!(self.out, "}}")?;
 
         // Equals
+        let q = self.nullable_mark();
         writeln!(
             self.out,
-            "public override bool Equals(object obj) => obj is {} other && Equals(other);\n",
-            name
+            "public override bool Equals(object{} obj) => obj is {} other && Equals(other);\n",
+            q, class_name
         )?;
 
-        writeln!(self.out, "public bool Equals({} other) {{", name)?;
+        writeln!(self.out, "public bool Equals({}{} other) {{", class_name, q)?;
         self.out.indent();
         writeln!(self.out, "if (other == null) return false;")?;
         writeln!(self.out, "if (ReferenceEquals(this, other)) return true;")?;
@@ -990,23 +1808,76 @@ switch (index) {{"#,
         writeln!(
             self.out,
             "public {0} Clone() => ({0})MemberwiseClone();\n",
-            name
+            class_name
         )?;
         writeln!(self.out, "object ICloneable.Clone() => Clone();\n")?;
 
-        self.output_variants(name, variants)?;
+        self.output_variants(&class_name, variants)?;
         self.leave_class(&reserved_names);
         writeln!(self.out, "}}\n")
     }
 
-    fn output_cstyle_enum(
+    fn output_enum_serialization_methods(
         &mut self,
         name: &str,
         variants: &BTreeMap<u32, Named<VariantFormat>>,
     ) -> Result<()> {
-        writeln!(self.out)?;
-        self.output_comment(name)?;
-        writeln!(self.out, "public enum {} {{", name)?;
+        writeln!(
+            self.out,
+            "\npublic abstract void Serialize{}({}{}){};",
+            self.generic_parameters(),
+            self.serializer_parameter(),
+            self.quote_serialize_parameters(&self.type_parameters, "F"),
+            self.generic_constraints()
+        )?;
+        write!(
+            self.out,
+            "\npublic static {}{} Deserialize{}({}{}){} {{",
+            name,
+            self.quote_type_parameters(),
+            self.generic_parameters(),
+            self.deserializer_parameter(),
+            self.quote_deserialize_parameters(&self.type_parameters, "F"),
+            self.generic_constraints()
+        )?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            r#"
+int index = deserializer.deserialize_variant_index();
+switch (index) {{"#,
+        )?;
+        self.out.indent();
+        for (index, variant) in variants {
+            writeln!(
+                self.out,
+                "case {}: return {}.Load({}{});",
+                index,
+                variant.name,
+                self.deserializer_argument(),
+                Self::quote_function_arguments("deserialize", &self.type_parameters)
+            )?;
+        }
+        writeln!(
+            self.out,
+            r#"default: throw new Serde.DeserializationException("Unknown variant index for {}: " + index);"#,
+            name,
+        )?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        self.out.unindent();
+        writeln!(self.out, "}}")
+    }
+
+    fn output_cstyle_enum(
+        &mut self,
+        name: &str,
+        variants: &BTreeMap<u32, Named<VariantFormat>>,
+    ) -> Result<()> {
+        writeln!(self.out)?;
+        self.output_comment(name)?;
+        self.output_json_converter_attribute(name)?;
+        writeln!(self.out, "public enum {} {{", name)?;
         self.out.indent();
         for (index, variant) in variants {
             writeln!(self.out, "{} = {},", variant.name, index)?;
@@ -1014,32 +1885,17 @@ switch (index) {{"#,
         self.out.unindent();
         writeln!(self.out, "}}")?;
 
-        if self.generator.config.serialization {
+        // Enums cannot have members: methods and constants go to an extension class.
+        let has_extensions = self.generator.config.serialization || self.fingerprint.is_some();
+        if has_extensions {
             let ext_name = format!("{}Extensions", name.to_camel_case());
             writeln!(self.out, "public static class {} {{", ext_name)?;
             self.enter_class(&ext_name, &[]);
+            self.output_fingerprint()?;
+        }
 
-            writeln!(
-                self.out,
-                r#"
-public static void Serialize(this {0} value, Serde.ISerializer serializer) {{
-    serializer.increase_container_depth();
-    serializer.serialize_variant_index((int)value);
-    serializer.decrease_container_depth();
-}}
-
-public static {0} Deserialize(Serde.IDeserializer deserializer) {{
-    deserializer.increase_container_depth();
-    int index = deserializer.deserialize_variant_index();
-    if (!Enum.IsDefined(typeof({0}), index))
-        throw new Serde.DeserializationException("Unknown variant index for {}: " + index);
-    {0} value = ({0})index;
-    deserializer.decrease_container_depth();
-    return value;
-}}"#,
-                name
-            )?;
-
+        if self.generator.config.serialization {
+            self.output_cstyle_enum_serialization_methods(name)?;
             for encoding in &self.generator.config.encodings {
                 writeln!(
                     self.out,
@@ -1055,6 +1911,18 @@ public static byte[] {0}Serialize(this {1} value)  {{
                 self.output_class_deserialize_for_encoding(name, *encoding)?;
             }
 
+            if self.generator.span_serializers {
+                self.span = true;
+                self.output_cstyle_enum_serialization_methods(name)?;
+                for encoding in &self.generator.config.encodings {
+                    self.output_span_serialize_for_encoding(Some(name), *encoding)?;
+                    self.output_span_deserialize_for_encoding(name, *encoding)?;
+                }
+                self.span = false;
+            }
+        }
+
+        if has_extensions {
             self.leave_class(&[]);
             writeln!(self.out, "}}")?;
         }
@@ -1062,24 +1930,66 @@ public static byte[] {0}Serialize(this {1} value)  {{
         Ok(())
     }
 
+    fn output_fingerprint(&mut self) -> Result<()> {
+        if let Some(fingerprint) = self.fingerprint {
+            writeln!(
+                self.out,
+                "public const ulong FINGERPRINT = {:#018x}UL;",
+                fingerprint
+            )?;
+        }
+        Ok(())
+    }
+
+    fn output_cstyle_enum_serialization_methods(&mut self, name: &str) -> Result<()> {
+        writeln!(
+            self.out,
+            r#"
+public static void Serialize{1}(this {0} value, {2}){4} {{
+    serializer.increase_container_depth();
+    serializer.serialize_variant_index((int)value);
+    serializer.decrease_container_depth();
+}}
+
+public static {0} Deserialize{1}({3}){4} {{
+    deserializer.increase_container_depth();
+    int index = deserializer.deserialize_variant_index();
+    if (!Enum.IsDefined(typeof({0}), index))
+        throw new Serde.DeserializationException("Unknown variant index for {0}: " + index);
+    {0} value = ({0})index;
+    deserializer.decrease_container_depth();
+    return value;
+}}"#,
+            name,
+            self.generic_parameters(),
+            self.serializer_parameter(),
+            self.deserializer_parameter(),
+            self.generic_constraints()
+        )
+    }
+
     fn output_class_serialize_for_encoding(&mut self, encoding: Encoding) -> Result<()> {
+        let parameters = self.quote_serialize_parameters(&self.type_parameters, "F");
         writeln!(
             self.out,
             r#"
-public int {0}Serialize(byte[] outputBuffer) => {0}Serialize(new ArraySegment<byte>(outputBuffer));
+public int {0}Serialize(byte[] outputBuffer{1}) => {0}Serialize(new ArraySegment<byte>(outputBuffer){2});
 
-public int {0}Serialize(ArraySegment<byte> outputBuffer) {{
+public int {0}Serialize(ArraySegment<byte> outputBuffer{1}) {{
     Serde.ISerializer serializer = new {0}.{0}Serializer(outputBuffer);
-    Serialize(serializer);
+    Serialize(serializer{2});
     return serializer.get_buffer_offset();
 }}
 
-public byte[] {0}Serialize()  {{
+public byte[] {0}Serialize({3})  {{
     Serde.ISerializer serializer = new {0}.{0}Serializer();
-    Serialize(serializer);
+    Serialize(serializer{2});
     return serializer.get_bytes();
 }}"#,
-            encoding.name().to_camel_case()
+            encoding.name().to_camel_case(),
+            parameters,
+            Self::quote_function_arguments("serialize", &self.type_parameters),
+            parameters.trim_start_matches(", "),
         )
     }
 
@@ -1091,41 +2001,104 @@ public byte[] {0}Serialize()  {{
         writeln!(
             self.out,
             r#"
-public static {0} {1}Deserialize(byte[] input) => {1}Deserialize(new ArraySegment<byte>(input));
+public static {0} {1}Deserialize(byte[] input{2}) => {1}Deserialize(new ArraySegment<byte>(input){3});
 
-public static {0} {1}Deserialize(ArraySegment<byte> input) {{
+public static {0} {1}Deserialize(ArraySegment<byte> input{2}) {{
     if (input == null) {{
          throw new Serde.DeserializationException("Cannot deserialize null array");
     }}
     Serde.IDeserializer deserializer = new {1}.{1}Deserializer(input);
-    {0} value = Deserialize(deserializer);
+    {0} value = Deserialize(deserializer{3});
     if (deserializer.get_buffer_offset() < input.Count) {{
          throw new Serde.DeserializationException("Some input bytes were not read");
     }}
     return value;
 }}"#,
             name,
-            encoding.name().to_camel_case()
+            encoding.name().to_camel_case(),
+            self.quote_deserialize_parameters(&self.type_parameters, "F"),
+            Self::quote_function_arguments("deserialize", &self.type_parameters),
+        )
+    }
+
+    /// Span-based entry points of the given encoding, either as instance methods or as extension
+    /// methods of the given C-style enum.
+    fn output_span_serialize_for_encoding(
+        &mut self,
+        cstyle_enum_name: Option<&str>,
+        encoding: Encoding,
+    ) -> Result<()> {
+        let (modifiers, receiver, call) = match cstyle_enum_name {
+            Some(name) => (
+                "static ",
+                format!("this {} value, ", name),
+                "Serialize(value, ref serializer)".to_string(),
+            ),
+            None => (
+                "",
+                String::new(),
+                format!(
+                    "Serialize(ref serializer{})",
+                    Self::quote_function_arguments("serialize", &self.type_parameters)
+                ),
+            ),
+        };
+        let span_format = format!("{0}.{0}Format", encoding.name().to_camel_case());
+        writeln!(
+            self.out,
+            r#"
+public {1}int {0}Serialize({2}Span<byte> output{4}) {{
+    var serializer = new Serde.SpanSerializer<{0}.{0}Format>(output);
+    {3};
+    return serializer.get_buffer_offset();
+}}
+
+public {1}int {0}Serialize({2}IBufferWriter<byte> output{4}) {{
+    var serializer = new Serde.SpanSerializer<{0}.{0}Format>(output);
+    {3};
+    return serializer.Flush();
+}}"#,
+            encoding.name().to_camel_case(),
+            modifiers,
+            receiver,
+            call,
+            self.quote_serialize_parameters(&self.type_parameters, &span_format),
+        )
+    }
+
+    fn output_span_deserialize_for_encoding(
+        &mut self,
+        name: &str,
+        encoding: Encoding,
+    ) -> Result<()> {
+        writeln!(
+            self.out,
+            r#"
+public static {0} {1}Deserialize(ReadOnlySpan<byte> input{2}) {{
+    var deserializer = new Serde.SpanDeserializer<{1}.{1}Format>(input);
+    {0} value = Deserialize(ref deserializer{3});
+    if (deserializer.get_buffer_offset() < input.Length) {{
+         throw new Serde.DeserializationException("Some input bytes were not read");
+    }}
+    return value;
+}}"#,
+            name,
+            encoding.name().to_camel_case(),
+            self.quote_deserialize_parameters(
+                &self.type_parameters,
+                &format!("{0}.{0}Format", encoding.name().to_camel_case())
+            ),
+            Self::quote_function_arguments("deserialize", &self.type_parameters),
         )
     }
 
     fn output_container(&mut self, name: &str, format: &ContainerFormat) -> Result<()> {
         use ContainerFormat::*;
-        let fields = match format {
-            UnitStruct => Vec::new(),
-            NewTypeStruct(format) => vec![Named {
-                name: "value".to_string(),
-                value: format.as_ref().clone(),
-            }],
-            TupleStruct(formats) => formats
-                .iter()
-                .enumerate()
-                .map(|(i, f)| Named {
-                    name: format!("field{}", i),
-                    value: f.clone(),
-                })
-                .collect::<Vec<_>>(),
-            Struct(fields) => fields.clone(),
+        let variant = match format {
+            UnitStruct => VariantFormat::Unit,
+            NewTypeStruct(format) => VariantFormat::NewType(format.clone()),
+            TupleStruct(formats) => VariantFormat::Tuple(formats.clone()),
+            Struct(fields) => VariantFormat::Struct(fields.clone()),
             Enum(variants) => {
                 if variants
                     .iter()
@@ -1133,89 +2106,985 @@ public static {0} {1}Deserialize(ArraySegment<byte> input) {{
                     && self.cstyle_enum_names.contains(&name.into())
                 {
                     self.output_cstyle_enum(name, variants)?;
+                    if self.generator.json_converters {
+                        self.output_cstyle_enum_json_converter(name, variants)?;
+                    }
                 } else {
                     self.output_enum_container(name, variants)?;
+                    if self.generator.json_converters {
+                        self.output_enum_json_converter(name, variants)?;
+                    }
                 }
                 return Ok(());
             }
+            Generic { parameters, format } => {
+                self.type_parameters = parameters.clone();
+                return self.output_container(name, format);
+            }
         };
-        self.output_struct_or_variant_container(None, None, name, &fields)
+        let fields = Self::variant_fields(&variant);
+        self.output_struct_or_variant_container(None, None, name, &fields)?;
+        if self.generator.json_converters {
+            self.output_struct_json_converter(name, &variant)?;
+        }
+        Ok(())
     }
-}
 
-/// Installer for generated source files in C#.
-pub struct Installer {
-    install_dir: PathBuf,
-}
+    fn output_json_converter_attribute(&mut self, name: &str) -> Result<()> {
+        if self.generator.json_converters {
+            writeln!(self.out, "[JsonConverter(typeof({}JsonConverter))]", name)?;
+        }
+        Ok(())
+    }
 
-impl Installer {
-    pub fn new(install_dir: PathBuf) -> Self {
-        Installer { install_dir }
+    fn quote_json_write(&self, value: &str, format: &Format) -> String {
+        use Format::*;
+        match format {
+            TypeName(_) => format!("JsonSerializer.Serialize(writer, {}, options);", value),
+            Unit => "writer.WriteNullValue();".to_string(),
+            Bool => format!("writer.WriteBooleanValue({});", value),
+            I8 | I16 | I32 | I64 | U8 | U16 | U32 | U64 | F32 | F64 => {
+                format!("writer.WriteNumberValue({});", value)
+            }
+            I128 | U128 => format!(
+                "writer.WriteRawValue({}.ToString(CultureInfo.InvariantCulture));",
+                value
+            ),
+            Char => format!("writer.WriteStringValue({}.ToString());", value),
+            Str => format!("writer.WriteStringValue({});", value),
+            _ => format!(
+                "{}.write_{}(writer, {}, options);",
+                self.quote_qualified_name("JsonTraitHelpers"),
+                self.helper_name(format),
+                value
+            ),
+        }
     }
 
-    fn install_runtime(
-        &self,
-        source_dir: include_dir::Dir,
-        path: &str,
-    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
-        let dir_path = self.install_dir.join(path);
-        std::fs::create_dir_all(&dir_path)?;
-        for entry in source_dir.files() {
-            let mut file = std::fs::File::create(dir_path.join(entry.path()))?;
-            file.write_all(entry.contents())?;
+    /// Same as `quote_json_write` for a type argument (see `quote_type_argument`).
+    fn quote_json_write_argument(&self, value: &str, format: &Format) -> String {
+        match format {
+            Format::Option(_) => format!(
+                "{}.write_{}(writer, {}, options);",
+                self.quote_qualified_name("JsonTraitHelpers"),
+                common::mangle_type(format),
+                value
+            ),
+            _ => self.quote_json_write(value, format),
         }
-        Ok(())
     }
-}
 
-impl crate::SourceInstaller for Installer {
-    type Error = Box<dyn std::error::Error>;
+    fn quote_json_read(&self, format: &Format) -> String {
+        use Format::*;
+        match format {
+            TypeName(name) => format!(
+                "JsonSerializer.Deserialize<{}>(ref reader, options)!",
+                self.quote_qualified_name(name)
+            ),
+            Bool => "reader.GetBoolean()".to_string(),
+            I8 => "reader.GetSByte()".to_string(),
+            I16 => "reader.GetInt16()".to_string(),
+            I32 => "reader.GetInt32()".to_string(),
+            I64 => "reader.GetInt64()".to_string(),
+            U8 => "reader.GetByte()".to_string(),
+            U16 => "reader.GetUInt16()".to_string(),
+            U32 => "reader.GetUInt32()".to_string(),
+            U64 => "reader.GetUInt64()".to_string(),
+            F32 => "reader.GetSingle()".to_string(),
+            F64 => "reader.GetDouble()".to_string(),
+            // Other primitive types (e.g. `Bytes`) use the fixed helpers of `JsonTraitHelpers`.
+            _ => format!(
+                "{}.read_{}(ref reader, options)",
+                self.quote_qualified_name("JsonTraitHelpers"),
+                self.helper_name(format),
+            ),
+        }
+    }
 
-    fn install_module(
-        &self,
-        config: &CodeGeneratorConfig,
-        registry: &Registry,
-    ) -> std::result::Result<(), Self::Error> {
-        let name = config.module_name.clone();
-        let generator = CodeGenerator::new(config);
-        let dir_path = generator.write_source_files(self.install_dir.clone(), registry)?;
+    /// Same as `quote_json_read` for a type argument (see `quote_type_argument`).
+    fn quote_json_read_argument(&self, format: &Format) -> String {
+        match format {
+            Format::Option(_) => format!(
+                "{}.read_{}(ref reader, options)",
+                self.quote_qualified_name("JsonTraitHelpers"),
+                common::mangle_type(format),
+            ),
+            _ => self.quote_json_read(format),
+        }
+    }
 
-        let back_path: String = std::iter::repeat("..\\".to_string())
-            .take(dir_path.strip_prefix(&self.install_dir)?.iter().count())
-            .collect();
-        let mut deps = vec!["Serde".to_string()];
-        for encoding in &config.encodings {
-            deps.push(encoding.name().to_camel_case());
+    /// Convert a map key to a JSON property name, like serde_json does.
+    fn quote_json_key_to_string(key: &str, format: &Format) -> Option<String> {
+        use Format::*;
+        match format {
+            Str => Some(key.to_string()),
+            Char => Some(format!("{}.ToString()", key)),
+            Bool => Some(format!("({} ? \"true\" : \"false\")", key)),
+            I8 | I16 | I32 | I64 | I128 | U8 | U16 | U32 | U64 | U128 => {
+                Some(format!("{}.ToString(CultureInfo.InvariantCulture)", key))
+            }
+            _ => None,
         }
-        let deps: String = deps
-            .iter()
-            .map(|d| {
-                format!(
-                    "      <ProjectReference Include=\"{1}{0}\\{0}.csproj\" />\n",
-                    d, back_path
-                )
-            })
-            .collect();
+    }
 
-        let mut proj = std::fs::File::create(dir_path.join(name + ".csproj"))?;
-        write!(
-            proj,
-            r#"
-<Project Sdk="Microsoft.NET.Sdk">
-    <PropertyGroup>
-       <TargetFramework>netstandard2.0</TargetFramework>
-       <LangVersion>7.2</LangVersion>
-    </PropertyGroup>
-    <ItemGroup>
-      <PackageReference Include="System.Memory" Version="4.5.4" />
-      <PackageReference Include="System.ValueTuple" Version="4.5.0" />
-    </ItemGroup>
-    <ItemGroup>
-{}    </ItemGroup>
-</Project>
-"#,
-            deps
-        )?;
+    /// Parse a map key from a JSON property name.
+    fn quote_json_key_from_string(&self, name: &str, format: &Format) -> Option<String> {
+        use Format::*;
+        match format {
+            Str => Some(name.to_string()),
+            Char => Some(format!("char.Parse({})", name)),
+            Bool => Some(format!("bool.Parse({})", name)),
+            I8 | I16 | I32 | I64 | I128 | U8 | U16 | U32 | U64 | U128 => Some(format!(
+                "{}.Parse({}, NumberStyles.Integer, CultureInfo.InvariantCulture)",
+                self.quote_type(format),
+                name
+            )),
+            _ => None,
+        }
+    }
+
+    fn output_json_write_content(&mut self, value: &str, variant: &VariantFormat) -> Result<()> {
+        use VariantFormat::*;
+        match variant {
+            Unit => writeln!(self.out, "writer.WriteNullValue();"),
+            NewType(format) => writeln!(
+                self.out,
+                "{}",
+                self.quote_json_write(&format!("{}.value", value), format)
+            ),
+            Tuple(formats) => {
+                writeln!(self.out, "writer.WriteStartArray();")?;
+                for (index, format) in formats.iter().enumerate() {
+                    writeln!(
+                        self.out,
+                        "{}",
+                        self.quote_json_write(&format!("{}.field{}", value, index), format)
+                    )?;
+                }
+                writeln!(self.out, "writer.WriteEndArray();")
+            }
+            Struct(fields) => {
+                writeln!(self.out, "writer.WriteStartObject();")?;
+                for field in fields {
+                    writeln!(self.out, "writer.WritePropertyName(\"{}\");", field.name)?;
+                    writeln!(
+                        self.out,
+                        "{}",
+                        self.quote_json_write(&format!("{}.{}", value, field.name), &field.value)
+                    )?;
+                }
+                writeln!(self.out, "writer.WriteEndObject();")
+            }
+            Variable(_) => panic!("incorrect value"),
+        }
+    }
+
+    fn output_json_read_content(
+        &mut self,
+        class_name: &str,
+        variant: &VariantFormat,
+    ) -> Result<()> {
+        use VariantFormat::*;
+        match variant {
+            Unit => {
+                writeln!(
+                    self.out,
+                    r#"if (reader.TokenType != JsonTokenType.Null) throw new JsonException("Expected null for {0}");
+return new {0}();"#,
+                    class_name
+                )
+            }
+            NewType(format) => writeln!(
+                self.out,
+                "return new {}({});",
+                class_name,
+                self.quote_json_read(format)
+            ),
+            Tuple(formats) => {
+                writeln!(
+                    self.out,
+                    r#"if (reader.TokenType != JsonTokenType.StartArray) throw new JsonException("Expected an array for {}");"#,
+                    class_name
+                )?;
+                for (index, format) in formats.iter().enumerate() {
+                    writeln!(self.out, "reader.Read();")?;
+                    writeln!(
+                        self.out,
+                        "var field{} = {};",
+                        index,
+                        self.quote_json_read(format)
+                    )?;
+                }
+                writeln!(
+                    self.out,
+                    r#"reader.Read();
+if (reader.TokenType != JsonTokenType.EndArray) throw new JsonException("Expected {} elements for {}");
+return new {}({});"#,
+                    formats.len(),
+                    class_name,
+                    class_name,
+                    (0..formats.len())
+                        .map(|i| format!("field{}", i))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
+            Struct(fields) => {
+                writeln!(
+                    self.out,
+                    r#"if (reader.TokenType != JsonTokenType.StartObject) throw new JsonException("Expected an object for {}");"#,
+                    class_name
+                )?;
+                for field in fields {
+                    writeln!(
+                        self.out,
+                        "{} field_{} = default!;",
+                        self.quote_type(&field.value),
+                        field.name
+                    )?;
+                    writeln!(self.out, "bool has_{} = false;", field.name)?;
+                }
+                writeln!(
+                    self.out,
+                    r#"while (reader.Read() && reader.TokenType != JsonTokenType.EndObject) {{
+    string property = reader.GetString()!;
+    reader.Read();
+    switch (property) {{"#
+                )?;
+                self.out.indent();
+                self.out.indent();
+                for field in fields {
+                    writeln!(
+                        self.out,
+                        "case \"{0}\": field_{0} = {1}; has_{0} = true; break;",
+                        field.name,
+                        self.quote_json_read(&field.value)
+                    )?;
+                }
+                writeln!(self.out, "default: reader.Skip(); break;")?;
+                self.out.unindent();
+                writeln!(self.out, "}}")?;
+                self.out.unindent();
+                writeln!(self.out, "}}")?;
+                for field in fields {
+                    // Like serde_json, missing optional fields are read as `None`.
+                    if !matches!(field.value, Format::Option(_)) {
+                        writeln!(
+                            self.out,
+                            r#"if (!has_{0}) throw new JsonException("Missing field {0} in {1}");"#,
+                            field.name, class_name
+                        )?;
+                    }
+                }
+                writeln!(
+                    self.out,
+                    "return new {}({});",
+                    class_name,
+                    fields
+                        .iter()
+                        .map(|f| format!("field_{}", f.name))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )
+            }
+            Variable(_) => panic!("incorrect value"),
+        }
+    }
+
+    fn output_json_converter_start(&mut self, name: &str) -> Result<String> {
+        let converter_name = format!("{}JsonConverter", name);
+        writeln!(
+            self.out,
+            "\npublic sealed class {0}: JsonConverter<{1}> {{",
+            converter_name, name
+        )?;
+        self.enter_class(&converter_name, &[]);
+        Ok(converter_name)
+    }
+
+    fn output_struct_json_converter(&mut self, name: &str, variant: &VariantFormat) -> Result<()> {
+        self.output_json_converter_start(name)?;
+        let is_reference_type = !self.record_struct_names.contains(name);
+        if is_reference_type {
+            // Unit structs and some newtype structs are serialized as `null`.
+            writeln!(self.out, "public override bool HandleNull => true;\n")?;
+        }
+        writeln!(
+            self.out,
+            "public override {} Read(ref Utf8JsonReader reader, Type typeToConvert, JsonSerializerOptions options) {{",
+            name
+        )?;
+        self.out.indent();
+        self.output_json_read_content(name, variant)?;
+        self.out.unindent();
+        writeln!(
+            self.out,
+            "}}\n\npublic override void Write(Utf8JsonWriter writer, {} value, JsonSerializerOptions options) {{",
+            name
+        )?;
+        self.out.indent();
+        if is_reference_type {
+            writeln!(
+                self.out,
+                "if (value == null) {{ writer.WriteNullValue(); return; }}"
+            )?;
+        }
+        self.output_json_write_content("value", variant)?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        self.leave_class(&[]);
+        writeln!(self.out, "}}")
+    }
+
+    fn output_enum_json_converter(
+        &mut self,
+        name: &str,
+        variants: &BTreeMap<u32, Named<VariantFormat>>,
+    ) -> Result<()> {
+        self.output_json_converter_start(name)?;
+        // Read
+        writeln!(
+            self.out,
+            r#"public override {0} Read(ref Utf8JsonReader reader, Type typeToConvert, JsonSerializerOptions options) {{
+    if (reader.TokenType == JsonTokenType.String) {{
+        string name = reader.GetString()!;
+        switch (name) {{"#,
+            name
+        )?;
+        self.out.indent();
+        self.out.indent();
+        self.out.indent();
+        for variant in variants.values() {
+            if variant.value == VariantFormat::Unit {
+                writeln!(
+                    self.out,
+                    "case \"{0}\": return new {1}.{0}();",
+                    variant.name, name
+                )?;
+            }
+        }
+        writeln!(
+            self.out,
+            r#"default: throw new JsonException("Unknown unit variant for {}: " + name);"#,
+            name
+        )?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        self.out.unindent();
+        writeln!(
+            self.out,
+            r#"}}
+if (reader.TokenType != JsonTokenType.StartObject) throw new JsonException("Expected a string or an object for {0}");
+reader.Read();
+string variant = reader.GetString()!;
+reader.Read();
+{0} value;
+switch (variant) {{"#,
+            name
+        )?;
+        self.out.indent();
+        for variant in variants.values() {
+            writeln!(
+                self.out,
+                "case \"{0}\": value = Read{0}(ref reader, options); break;",
+                variant.name
+            )?;
+        }
+        writeln!(
+            self.out,
+            r#"default: throw new JsonException("Unknown variant for {}: " + variant);"#,
+            name
+        )?;
+        self.out.unindent();
+        writeln!(
+            self.out,
+            r#"}}
+reader.Read();
+if (reader.TokenType != JsonTokenType.EndObject) throw new JsonException("Expected a single variant for {}");
+return value;"#,
+            name
+        )?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+
+        // Write
+        writeln!(
+            self.out,
+            r#"
+public override void Write(Utf8JsonWriter writer, {} value, JsonSerializerOptions options) {{
+    switch (value) {{"#,
+            name
+        )?;
+        self.out.indent();
+        self.out.indent();
+        for variant in variants.values() {
+            if variant.value == VariantFormat::Unit {
+                writeln!(
+                    self.out,
+                    "case {1}.{0} _: writer.WriteStringValue(\"{0}\"); break;",
+                    variant.name, name
+                )?;
+            } else {
+                writeln!(
+                    self.out,
+                    r#"case {1}.{0} x:
+    writer.WriteStartObject();
+    writer.WritePropertyName("{0}");
+    Write{0}(writer, x, options);
+    writer.WriteEndObject();
+    break;"#,
+                    variant.name, name
+                )?;
+            }
+        }
+        writeln!(
+            self.out,
+            r#"default: throw new InvalidOperationException("Unknown variant type");"#
+        )?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+
+        // Variants
+        for variant in variants.values() {
+            let class_name = format!("{}.{}", name, variant.name);
+            writeln!(
+                self.out,
+                "\nstatic {} Read{}(ref Utf8JsonReader reader, JsonSerializerOptions options) {{",
+                class_name, variant.name
+            )?;
+            self.out.indent();
+            self.output_json_read_content(&class_name, &variant.value)?;
+            self.out.unindent();
+            writeln!(self.out, "}}")?;
+            if variant.value != VariantFormat::Unit {
+                writeln!(
+                    self.out,
+                    "\nstatic void Write{}(Utf8JsonWriter writer, {} value, JsonSerializerOptions options) {{",
+                    variant.name, class_name
+                )?;
+                self.out.indent();
+                self.output_json_write_content("value", &variant.value)?;
+                self.out.unindent();
+                writeln!(self.out, "}}")?;
+            }
+        }
+        self.leave_class(&[]);
+        writeln!(self.out, "}}")
+    }
+
+    fn output_cstyle_enum_json_converter(
+        &mut self,
+        name: &str,
+        variants: &BTreeMap<u32, Named<VariantFormat>>,
+    ) -> Result<()> {
+        self.output_json_converter_start(name)?;
+        writeln!(
+            self.out,
+            r#"public override {} Read(ref Utf8JsonReader reader, Type typeToConvert, JsonSerializerOptions options) {{
+    if (reader.TokenType != JsonTokenType.String) throw new JsonException("Expected a string for {0}");
+    string name = reader.GetString()!;
+    switch (name) {{"#,
+            name
+        )?;
+        self.out.indent();
+        self.out.indent();
+        for variant in variants.values() {
+            writeln!(
+                self.out,
+                "case \"{0}\": return {1}.{0};",
+                variant.name, name
+            )?;
+        }
+        writeln!(
+            self.out,
+            r#"default: throw new JsonException("Unknown variant for {}: " + name);"#,
+            name
+        )?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        self.out.unindent();
+        writeln!(
+            self.out,
+            r#"}}
+
+public override void Write(Utf8JsonWriter writer, {} value, JsonSerializerOptions options) {{
+    switch (value) {{"#,
+            name
+        )?;
+        self.out.indent();
+        self.out.indent();
+        for variant in variants.values() {
+            writeln!(
+                self.out,
+                "case {1}.{0}: writer.WriteStringValue(\"{0}\"); break;",
+                variant.name, name
+            )?;
+        }
+        writeln!(
+            self.out,
+            r#"default: throw new InvalidOperationException("Unknown value for {}: " + value);"#,
+            name
+        )?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        self.leave_class(&[]);
+        writeln!(self.out, "}}")
+    }
+
+    fn output_json_trait_helpers(&mut self, registry: &Registry) -> Result<()> {
+        let subtypes = self.helper_subtypes(registry);
+        writeln!(self.out, "static class JsonTraitHelpers {{")?;
+        let reserved_names = &[];
+        self.enter_class("JsonTraitHelpers", reserved_names);
+        writeln!(
+            self.out,
+            r#"public static Serde.Unit read_unit(ref Utf8JsonReader reader, JsonSerializerOptions options) {{
+    if (reader.TokenType != JsonTokenType.Null) throw new JsonException("Expected null");
+    return new Serde.Unit();
+}}
+
+public static string read_str(ref Utf8JsonReader reader, JsonSerializerOptions options) {{
+    if (reader.TokenType != JsonTokenType.String) throw new JsonException("Expected a string");
+    return reader.GetString()!;
+}}
+
+public static char read_char(ref Utf8JsonReader reader, JsonSerializerOptions options) {{
+    string value = read_str(ref reader, options);
+    if (value.Length != 1) throw new JsonException("Expected a single character");
+    return value[0];
+}}
+
+public static void write_bytes(Utf8JsonWriter writer, Serde.ValueArray<byte> value, JsonSerializerOptions options) {{
+    writer.WriteStartArray();
+    foreach (var item in value) {{
+        writer.WriteNumberValue(item);
+    }}
+    writer.WriteEndArray();
+}}
+
+public static Serde.ValueArray<byte> read_bytes(ref Utf8JsonReader reader, JsonSerializerOptions options) {{
+    if (reader.TokenType != JsonTokenType.StartArray) throw new JsonException("Expected an array of bytes");
+    var obj = new List<byte>();
+    while (reader.Read() && reader.TokenType != JsonTokenType.EndArray) {{
+        obj.Add(reader.GetByte());
+    }}
+    return new Serde.ValueArray<byte>(obj.ToArray());
+}}
+
+public static BigInteger read_i128(ref Utf8JsonReader reader, JsonSerializerOptions options) {{
+    BigInteger value = read_big_integer(ref reader);
+    if (value < -(BigInteger.One << 127) || value >= (BigInteger.One << 127)) throw new JsonException("Invalid value for i128: " + value);
+    return value;
+}}
+
+public static BigInteger read_u128(ref Utf8JsonReader reader, JsonSerializerOptions options) {{
+    BigInteger value = read_big_integer(ref reader);
+    if (value.Sign < 0 || value >= (BigInteger.One << 128)) throw new JsonException("Invalid value for u128: " + value);
+    return value;
+}}
+
+static BigInteger read_big_integer(ref Utf8JsonReader reader) {{
+    if (reader.TokenType != JsonTokenType.Number) throw new JsonException("Expected a number");
+    byte[] bytes = reader.HasValueSequence ? System.Buffers.BuffersExtensions.ToArray(reader.ValueSequence) : reader.ValueSpan.ToArray();
+    return BigInteger.Parse(Encoding.UTF8.GetString(bytes), NumberStyles.Integer, CultureInfo.InvariantCulture);
+}}
+"#
+        )?;
+        for (name, (subtype, is_field)) in &subtypes {
+            self.output_json_write_helper(name, subtype, *is_field)?;
+            self.output_json_read_helper(name, subtype, *is_field)?;
+        }
+        self.leave_class(reserved_names);
+        writeln!(self.out, "}}\n")
+    }
+
+    fn output_json_write_helper(
+        &mut self,
+        name: &str,
+        format0: &Format,
+        is_field: bool,
+    ) -> Result<()> {
+        use Format::*;
+
+        let nullable = is_field && self.is_nullable_option(format0);
+        write!(
+            self.out,
+            "public static void write_{}(Utf8JsonWriter writer, {} value, JsonSerializerOptions options) {{",
+            name,
+            if nullable {
+                self.quote_type(format0)
+            } else {
+                self.quote_type_argument(format0)
+            }
+        )?;
+        self.out.indent();
+        match format0 {
+            Option(format) => {
+                write!(
+                    self.out,
+                    r#"
+if ({}) {{
+    {}
+}} else {{
+    writer.WriteNullValue();
+}}
+"#,
+                    if nullable {
+                        "value is {} val"
+                    } else {
+                        "value.IsSome(out var val)"
+                    },
+                    self.quote_json_write_argument("val", format)
+                )?;
+            }
+
+            Seq(format)
+            | TupleArray {
+                content: format, ..
+            } => {
+                write!(
+                    self.out,
+                    r#"
+writer.WriteStartArray();
+foreach (var item in value) {{
+    {}
+}}
+writer.WriteEndArray();
+"#,
+                    self.quote_json_write_argument("item", format)
+                )?;
+            }
+
+            Map { key, value } => match Self::quote_json_key_to_string("entry.Key", key) {
+                Some(key_string) => {
+                    write!(
+                        self.out,
+                        r#"
+writer.WriteStartObject();
+foreach (KeyValuePair<{}, {}> entry in value) {{
+    writer.WritePropertyName({});
+    {}
+}}
+writer.WriteEndObject();
+"#,
+                        self.quote_type_argument(key),
+                        self.quote_type_argument(value),
+                        key_string,
+                        self.quote_json_write_argument("entry.Value", value)
+                    )?;
+                }
+                None => {
+                    write!(
+                        self.out,
+                        r#"
+throw new NotSupportedException("JSON maps require string, integer, bool or char keys");
+"#
+                    )?;
+                }
+            },
+
+            Tuple(formats) => {
+                writeln!(self.out, "\nwriter.WriteStartArray();")?;
+                for (index, format) in formats.iter().enumerate() {
+                    let expr = format!("value.Item{}", index + 1);
+                    writeln!(self.out, "{}", self.quote_json_write(&expr, format))?;
+                }
+                writeln!(self.out, "writer.WriteEndArray();")?;
+            }
+
+            _ => panic!("unexpected case"),
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}\n")
+    }
+
+    fn output_json_read_helper(
+        &mut self,
+        name: &str,
+        format0: &Format,
+        is_field: bool,
+    ) -> Result<()> {
+        use Format::*;
+
+        let nullable = is_field && self.is_nullable_option(format0);
+        write!(
+            self.out,
+            "public static {} read_{}(ref Utf8JsonReader reader, JsonSerializerOptions options) {{",
+            if nullable {
+                self.quote_type(format0)
+            } else {
+                self.quote_type_argument(format0)
+            },
+            name,
+        )?;
+        self.out.indent();
+        match format0 {
+            Option(format) if nullable => {
+                write!(
+                    self.out,
+                    r#"
+if (reader.TokenType == JsonTokenType.Null) {{
+    return null;
+}} else {{
+    return {};
+}}
+"#,
+                    self.quote_json_read_argument(format),
+                )?;
+            }
+
+            Option(format) => {
+                write!(
+                    self.out,
+                    r#"
+if (reader.TokenType == JsonTokenType.Null) {{
+    return Serde.Option<{0}>.None;
+}} else {{
+    return Serde.Option<{0}>.Some({1});
+}}
+"#,
+                    self.quote_type_argument(format),
+                    self.quote_json_read_argument(format),
+                )?;
+            }
+
+            Seq(format) => {
+                write!(
+                    self.out,
+                    r#"
+if (reader.TokenType != JsonTokenType.StartArray) throw new JsonException("Expected an array");
+var obj = new List<{0}>();
+while (reader.Read() && reader.TokenType != JsonTokenType.EndArray) {{
+    obj.Add({1});
+}}
+return new Serde.ValueArray<{0}>(obj.ToArray());
+"#,
+                    self.quote_type_argument(format),
+                    self.quote_json_read_argument(format)
+                )?;
+            }
+
+            TupleArray { content, size } => {
+                write!(
+                    self.out,
+                    r#"
+if (reader.TokenType != JsonTokenType.StartArray) throw new JsonException("Expected an array");
+var obj = new List<{0}>();
+while (reader.Read() && reader.TokenType != JsonTokenType.EndArray) {{
+    obj.Add({1});
+}}
+if (obj.Count != {2}) throw new JsonException("Invalid length for fixed-size array: " + obj.Count + " instead of " + {2});
+return new Serde.ValueArray<{0}>(obj.ToArray());
+"#,
+                    self.quote_type_argument(content),
+                    self.quote_json_read_argument(content),
+                    size
+                )?;
+            }
+
+            Map { key, value } => match self.quote_json_key_from_string("property", key) {
+                Some(parsed_key) => {
+                    write!(
+                        self.out,
+                        r#"
+if (reader.TokenType != JsonTokenType.StartObject) throw new JsonException("Expected an object");
+var obj = new Dictionary<{0}, {1}>();
+while (reader.Read() && reader.TokenType != JsonTokenType.EndObject) {{
+    string property = reader.GetString()!;
+    reader.Read();
+    obj[{2}] = {3};
+}}
+return new Serde.ValueDictionary<{0}, {1}>(obj);
+"#,
+                        self.quote_type_argument(key),
+                        self.quote_type_argument(value),
+                        parsed_key,
+                        self.quote_json_read_argument(value),
+                    )?;
+                }
+                None => {
+                    write!(
+                        self.out,
+                        r#"
+throw new NotSupportedException("JSON maps require string, integer, bool or char keys");
+"#
+                    )?;
+                }
+            },
+
+            Tuple(formats) => {
+                writeln!(
+                    self.out,
+                    r#"
+if (reader.TokenType != JsonTokenType.StartArray) throw new JsonException("Expected an array");"#
+                )?;
+                for (index, format) in formats.iter().enumerate() {
+                    writeln!(
+                        self.out,
+                        "reader.Read();\nvar item{} = {};",
+                        index + 1,
+                        self.quote_json_read(format)
+                    )?;
+                }
+                writeln!(
+                    self.out,
+                    r#"reader.Read();
+if (reader.TokenType != JsonTokenType.EndArray) throw new JsonException("Expected {} elements");
+return ({});"#,
+                    formats.len(),
+                    (1..=formats.len())
+                        .map(|i| format!("item{}", i))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )?;
+            }
+
+            _ => panic!("unexpected case"),
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}\n")
+    }
+}
+
+/// Installer for generated source files in C#.
+pub struct Installer {
+    install_dir: PathBuf,
+    type_declarations: TypeDeclarations,
+    nullable_annotations: bool,
+    json_converters: bool,
+    span_serializers: bool,
+}
+
+impl Installer {
+    pub fn new(install_dir: PathBuf) -> Self {
+        Installer {
+            install_dir,
+            type_declarations: TypeDeclarations::Classes,
+            nullable_annotations: false,
+            json_converters: false,
+            span_serializers: false,
+        }
+    }
+
+    /// How to declare structs and enums in installed modules.
+    pub fn with_type_declarations(mut self, type_declarations: TypeDeclarations) -> Self {
+        self.type_declarations = type_declarations;
+        self
+    }
+
+    /// Whether to install modules with `#nullable enable` and `T?` options.
+    pub fn with_nullable_annotations(mut self, nullable_annotations: bool) -> Self {
+        self.nullable_annotations = nullable_annotations;
+        self
+    }
+
+    /// Whether to install modules with `System.Text.Json` converters.
+    pub fn with_json_converters(mut self, json_converters: bool) -> Self {
+        self.json_converters = json_converters;
+        self
+    }
+
+    /// Whether to install modules with `Span<byte>`-based (de)serialization methods.
+    pub fn with_span_serializers(mut self, span_serializers: bool) -> Self {
+        self.span_serializers = span_serializers;
+        self
+    }
+
+    /// Whether installed modules require .NET 6 and C# 10 rather than .NET Standard 2.0.
+    fn requires_net6(&self) -> bool {
+        self.type_declarations != TypeDeclarations::Classes
+            || self.nullable_annotations
+            || self.json_converters
+    }
+
+    fn install_runtime(&self, source_dir: include_dir::Dir, path: &str) -> crate::Result<()> {
+        let dir_path = self.install_dir.join(path);
+        std::fs::create_dir_all(&dir_path)?;
+        for entry in source_dir.files() {
+            let mut file = std::fs::File::create(dir_path.join(entry.path()))?;
+            file.write_all(entry.contents())?;
+        }
+        Ok(())
+    }
+}
+
+impl crate::SourceInstaller for Installer {
+    type Error = crate::Error;
+
+    fn install_module(
+        &self,
+        config: &CodeGeneratorConfig,
+        registry: &Registry,
+    ) -> std::result::Result<(), Self::Error> {
+        let name = config.module_name.clone();
+        let generator = CodeGenerator::new(config)
+            .with_type_declarations(self.type_declarations)
+            .with_nullable_annotations(self.nullable_annotations)
+            .with_json_converters(self.json_converters)
+            .with_span_serializers(self.span_serializers);
+        let dir_path = generator.write_source_files(self.install_dir.clone(), registry)?;
+
+        let back_path: String = std::iter::repeat("..\\".to_string())
+            .take(
+                dir_path
+                    .strip_prefix(&self.install_dir)
+                    .map_or(0, |path| path.iter().count()),
+            )
+            .collect();
+        let mut deps = vec!["Serde".to_string()];
+        for encoding in &config.encodings {
+            deps.push(encoding.name().to_camel_case());
+        }
+        let deps: String = deps
+            .iter()
+            .map(|d| {
+                format!(
+                    "      <ProjectReference Include=\"{1}{0}\\{0}.csproj\" />\n",
+                    d, back_path
+                )
+            })
+            .collect();
+
+        let mut proj = std::fs::File::create(dir_path.join(name + ".csproj"))?;
+        if self.requires_net6() {
+            write!(
+                proj,
+                r#"
+<Project Sdk="Microsoft.NET.Sdk">
+    <PropertyGroup>
+       <TargetFramework>net6.0</TargetFramework>
+       <LangVersion>10.0</LangVersion>
+    </PropertyGroup>
+    <ItemGroup>
+{}    </ItemGroup>
+</Project>
+"#,
+                deps
+            )?;
+        } else {
+            write!(
+                proj,
+                r#"
+<Project Sdk="Microsoft.NET.Sdk">
+    <PropertyGroup>
+       <TargetFramework>netstandard2.0</TargetFramework>
+       <LangVersion>7.2</LangVersion>
+    </PropertyGroup>
+    <ItemGroup>
+      <PackageReference Include="System.Memory" Version="4.5.4" />
+      <PackageReference Include="System.ValueTuple" Version="4.5.0" />
+    </ItemGroup>
+    <ItemGroup>
+{}    </ItemGroup>
+</Project>
+"#,
+                deps
+            )?;
+        }
 
         Ok(())
     }
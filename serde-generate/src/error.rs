@@ -0,0 +1,46 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use thiserror::Error;
+
+/// Result type used by code generators and source installers.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// Error type used by code generators and source installers.
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("{language} does not support {option}")]
+    UnsupportedOption {
+        language: &'static str,
+        option: &'static str,
+    },
+    #[error(
+        "Incomplete format in container `{container}`{}: make sure that all types were traced",
+        .field.as_ref().map(|f| format!(" (field `{}`)", f)).unwrap_or_default()
+    )]
+    UnknownFormat {
+        container: String,
+        field: Option<String>,
+    },
+    #[error("In container `{container}`, the name of field `{field}` is reserved {reason}")]
+    ReservedName {
+        container: String,
+        field: String,
+        reason: &'static str,
+    },
+    #[error("Container `{0}` does not exist in the registry")]
+    UnknownContainer(String),
+    #[error("Unexpected generic definition in container `{0}`")]
+    UnexpectedGeneric(String),
+    #[error("{language} does not support enums without variants (container `{container}`)")]
+    EmptyEnum {
+        language: &'static str,
+        container: String,
+    },
+    #[error("Invalid renaming: {0}")]
+    InvalidRenaming(String),
+    #[error(transparent)]
+    Reflection(#[from] serde_reflection::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
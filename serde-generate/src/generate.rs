@@ -7,18 +7,23 @@
 //! cargo run --bin serdegen -- --help
 //! '''
 
+use serde::Deserialize;
 use serde_generate::{
-    cpp, csharp, golang, java, python3, rust, typescript, CodeGeneratorConfig, Encoding,
-    SourceInstaller,
+    c, cpp, csharp, golang, java, lint, python3, rust, transform, typescript, CodeGeneratorConfig,
+    Encoding, SourceInstaller,
 };
 use serde_reflection::Registry;
-use std::path::PathBuf;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
 use structopt::{clap::arg_enum, StructOpt};
 
 arg_enum! {
 #[derive(Debug, StructOpt)]
 enum Language {
     Python3,
+    C,
     Cpp,
     Rust,
     Java,
@@ -47,9 +52,16 @@ struct Options {
     #[structopt(parse(from_os_str))]
     input: Option<PathBuf>,
 
-    /// Language for code generation.
-    #[structopt(long, possible_values = &Language::variants(), case_insensitive = true, default_value = "Python3")]
-    language: Language,
+    /// Path to a YAML configuration file, possibly listing several targets. Command-line
+    /// arguments take precedence over the values of the file, e.g. `--use-json=false` turns
+    /// off a flag set in the file. The language, target directory, module name and runtimes
+    /// cannot be given on the command line when the file lists several targets.
+    #[structopt(long, parse(from_os_str))]
+    config: Option<PathBuf>,
+
+    /// Language for code generation (default: Python3).
+    #[structopt(long, possible_values = &Language::variants(), case_insensitive = true)]
+    language: Option<Language>,
 
     /// Directory where to write generated modules (otherwise print code on stdout).
     #[structopt(long)]
@@ -72,113 +84,781 @@ struct Options {
 
     /// Translate enums without variant data (c-style enums) into their equivalent in the target language,
     /// if the target language and the generator code support them.
-    #[structopt(long)]
-    use_c_style_enums: bool,
-}
-
-fn get_codegen_config<'a, I>(name: String, runtimes: I, c_style_enums: bool) -> CodeGeneratorConfig
-where
-    I: IntoIterator<Item = &'a Runtime>,
-{
-    let mut encodings = Vec::new();
-    for runtime in runtimes {
-        match runtime {
-            Runtime::Bincode => {
-                encodings.push(Encoding::Bincode);
+    #[structopt(long, require_equals = true)]
+    use_c_style_enums: Option<Option<bool>>,
+
+    /// Emit the fingerprint of each (non-generic) container as a constant `FINGERPRINT`
+    /// (or the closest equivalent in the target language).
+    #[structopt(long, require_equals = true)]
+    with_fingerprints: Option<Option<bool>>,
+
+    /// Generate pydantic models instead of dataclasses (Python).
+    #[structopt(long, require_equals = true)]
+    use_pydantic_models: Option<Option<bool>>,
+
+    /// Generate plain interfaces, discriminated unions and standalone (de)serialization
+    /// functions instead of classes (TypeScript).
+    #[structopt(long, require_equals = true)]
+    use_discriminated_unions: Option<Option<bool>>,
+
+    /// Map 64-bit and 128-bit integers to `number` (with range checks) instead of `bigint`
+    /// (TypeScript).
+    #[structopt(long, require_equals = true)]
+    use_numbers_for_big_integers: Option<Option<bool>>,
+
+    /// Generate `json:"..."` tags and JSON methods (Go) or `System.Text.Json` converters (C#)
+    /// following the data layout of serde_json.
+    #[structopt(long, require_equals = true)]
+    use_json: Option<Option<bool>>,
+
+    /// Generate a visitor interface and an exhaustive type switch for each enum (Go).
+    #[structopt(long, require_equals = true)]
+    use_enum_visitors: Option<Option<bool>>,
+
+    /// Use the generic helpers of the serde runtime instead of generating a helper for each
+    /// composite type (Go >= 1.18).
+    #[structopt(long, require_equals = true)]
+    use_generic_helpers: Option<Option<bool>>,
+
+    /// Emit a forward-declaration header, one header per container and a source file with
+    /// out-of-line definitions (C++).
+    #[structopt(long, require_equals = true)]
+    use_split_sources: Option<Option<bool>>,
+
+    /// Use `std::pmr` containers and strings (C++).
+    #[structopt(long, require_equals = true)]
+    use_pmr_containers: Option<Option<bool>>,
+
+    /// Return `serde::expected` values instead of throwing exceptions, for code compiled
+    /// with `-fno-exceptions` (C++).
+    #[structopt(long, require_equals = true)]
+    use_expected_results: Option<Option<bool>>,
+
+    /// Generate records instead of classes (C# >= 9).
+    #[structopt(long, require_equals = true)]
+    use_records: Option<Option<bool>>,
+
+    /// Generate records, with `readonly record struct` for non-recursive structs (C# >= 10).
+    #[structopt(long, require_equals = true)]
+    use_record_structs: Option<Option<bool>>,
+
+    /// Emit `#nullable enable` and represent optional fields as `T?` (C#).
+    #[structopt(long, require_equals = true)]
+    use_nullable_annotations: Option<Option<bool>>,
+
+    /// Also generate allocation-free (de)serialization methods for `Span<byte>`,
+    /// `ReadOnlySpan<byte>` and `IBufferWriter<byte>` (C#).
+    #[structopt(long, require_equals = true)]
+    use_span_serializers: Option<Option<bool>>,
+
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Extract and rename a part of the Serde formats, then print the resulting YAML.
+    Transform(TransformOptions),
+    /// Report the problems of the Serde formats that would break generated code, e.g. reserved
+    /// words used as field names. Exit with code 1 if problems are found.
+    Check(CheckOptions),
+}
+
+#[derive(Debug, StructOpt)]
+struct TransformOptions {
+    /// Path to the YAML-encoded Serde formats.
+    #[structopt(parse(from_os_str))]
+    input: PathBuf,
+
+    /// Container to keep together with its dependencies (by default, all containers are kept).
+    /// Other containers are dropped.
+    #[structopt(long = "root", number_of_values = 1)]
+    roots: Vec<String>,
+
+    /// Rename a container and the references to it, e.g. `--rename MyStruct=TheirStruct`.
+    #[structopt(long, number_of_values = 1)]
+    rename: Vec<String>,
+
+    /// Rename a field, e.g. `--rename-field MyStruct.my_field=their_field`.
+    #[structopt(long, number_of_values = 1)]
+    rename_field: Vec<String>,
+
+    /// Rename a variant, e.g. `--rename-variant MyEnum.MyVariant=TheirVariant`.
+    #[structopt(long, number_of_values = 1)]
+    rename_variant: Vec<String>,
+
+    /// Path where to write the resulting formats (otherwise print them on stdout).
+    #[structopt(long, parse(from_os_str))]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, StructOpt)]
+struct CheckOptions {
+    /// Path to the YAML-encoded Serde formats.
+    #[structopt(parse(from_os_str))]
+    input: PathBuf,
+
+    /// Language to check (by default, all languages are checked).
+    #[structopt(
+        long = "language",
+        possible_values = &Language::variants(),
+        case_insensitive = true,
+        number_of_values = 1
+    )]
+    languages: Vec<Language>,
+
+    /// Runtimes to check, e.g. `bcs` to report the formats not supported by BCS.
+    #[structopt(long, possible_values = &Runtime::variants(), case_insensitive = true)]
+    with_runtimes: Vec<Runtime>,
+}
+
+/// Content of a configuration file (see `--config`).
+///
+/// Paths are relative to the directory of the file. Qualified names used as keys of `comments`
+/// and `custom_code` are separated with dots (e.g. "my_module.MyStruct"). Boolean flags of the
+/// command line are available in the section of the corresponding language, without the
+/// `use_` prefix.
+///
+/// A file may also contain a list `targets`, e.g. to generate code in several languages. Each
+/// target is merged into the rest of the file and is then processed as a separate configuration.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Config {
+    input: Option<PathBuf>,
+    language: Option<String>,
+    target_source_dir: Option<PathBuf>,
+    with_runtimes: Vec<String>,
+    module_name: Option<String>,
+    serde_package_name: Option<String>,
+    serialization: Option<bool>,
+    c_style_enums: bool,
+    fingerprints: bool,
+    external_definitions: BTreeMap<String, Vec<String>>,
+    comments: BTreeMap<String, String>,
+    custom_code: BTreeMap<String, String>,
+    rust: RustConfig,
+    python3: Python3Config,
+    cpp: CppConfig,
+    go: GoConfig,
+    typescript: TypeScriptConfig,
+    csharp: CSharpConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct RustConfig {
+    derive_macros: Option<Vec<String>>,
+    custom_derive_block: Option<String>,
+    track_visibility: Option<bool>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct Python3Config {
+    pydantic_models: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CppConfig {
+    split_sources: bool,
+    pmr_containers: bool,
+    expected_results: bool,
+    type_mapping: CppTypeMappingConfig,
+}
+
+/// Overrides of `cpp::TypeMapping`. Includes replace the default ones if not empty.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CppTypeMappingConfig {
+    seq: Option<String>,
+    map: Option<String>,
+    str: Option<String>,
+    bytes: Option<String>,
+    option: Option<String>,
+    boxed: Option<String>,
+    includes: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct GoConfig {
+    json: bool,
+    enum_visitors: bool,
+    generic_helpers: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct TypeScriptConfig {
+    discriminated_unions: bool,
+    numbers_for_big_integers: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct CSharpConfig {
+    records: bool,
+    record_structs: bool,
+    nullable_annotations: bool,
+    json: bool,
+    span_serializers: bool,
+}
+
+/// Errors reported by `serdegen`.
+#[derive(Debug, thiserror::Error)]
+enum CliError {
+    /// Invalid command-line arguments or configuration file.
+    #[error("{0}")]
+    Usage(String),
+    /// Input file that cannot be read or does not contain a valid registry.
+    #[error("{0}")]
+    Input(String),
+    /// Failure of a code generator or a source installer.
+    #[error(transparent)]
+    Generation(#[from] serde_generate::Error),
+}
+
+impl CliError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Usage(_) => 2,
+            CliError::Input(_) => 3,
+            CliError::Generation(_) => 4,
+        }
+    }
+}
+
+type Result<T> = std::result::Result<T, CliError>;
+
+/// Merge the YAML value `overlay` into `base`. Mappings are merged recursively.
+fn merge_yaml(base: &mut serde_yaml::Value, overlay: serde_yaml::Value) {
+    match (base, overlay) {
+        (serde_yaml::Value::Mapping(base), serde_yaml::Value::Mapping(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(entry) => merge_yaml(entry, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
             }
-            Runtime::Bcs => {
-                encodings.push(Encoding::Bcs);
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+impl Config {
+    /// Read the configurations of all the targets defined in a file.
+    fn read(path: &Path) -> Result<Vec<Self>> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| CliError::Usage(format!("Failed to read {}: {}", path.display(), e)))?;
+        let mut value: serde_yaml::Value = serde_yaml::from_str(content.as_str())
+            .map_err(|e| CliError::Usage(format!("Failed to parse {}: {}", path.display(), e)))?;
+        let targets = match &mut value {
+            serde_yaml::Value::Mapping(mapping) => mapping.remove(&"targets".into()),
+            _ => None,
+        };
+        let values = match targets {
+            None => vec![value],
+            Some(serde_yaml::Value::Sequence(targets)) => targets
+                .into_iter()
+                .map(|target| {
+                    let mut value = value.clone();
+                    merge_yaml(&mut value, target);
+                    value
+                })
+                .collect(),
+            Some(_) => {
+                return Err(CliError::Usage(format!(
+                    "Invalid {}: `targets` must be a list",
+                    path.display()
+                )))
+            }
+        };
+        let base_dir = path.parent().unwrap_or_else(|| Path::new(""));
+        values
+            .into_iter()
+            .map(|value| {
+                let mut config: Config = serde_yaml::from_value(value)
+                    .map_err(|e| CliError::Usage(format!("Invalid {}: {}", path.display(), e)))?;
+                config.input = config.input.map(|input| base_dir.join(input));
+                config.target_source_dir = config.target_source_dir.map(|dir| base_dir.join(dir));
+                Ok(config)
+            })
+            .collect()
+    }
+
+    /// Merge the command-line arguments into the configuration.
+    fn merge_options(&mut self, options: &Options) {
+        // A flag given without a value (e.g. `--use-json`) is set to `true`.
+        fn override_flag(flag: &mut bool, option: Option<Option<bool>>) {
+            if let Some(value) = option {
+                *flag = value.unwrap_or(true);
+            }
+        }
+
+        if options.input.is_some() {
+            self.input = options.input.clone();
+        }
+        if let Some(language) = &options.language {
+            self.language = Some(language.to_string());
+        }
+        if options.target_source_dir.is_some() {
+            self.target_source_dir = options.target_source_dir.clone();
+        }
+        if !options.with_runtimes.is_empty() {
+            self.with_runtimes = options
+                .with_runtimes
+                .iter()
+                .map(|runtime| runtime.to_string())
+                .collect();
+        }
+        if options.module_name.is_some() {
+            self.module_name = options.module_name.clone();
+        }
+        if options.serde_package_name.is_some() {
+            self.serde_package_name = options.serde_package_name.clone();
+        }
+        override_flag(&mut self.c_style_enums, options.use_c_style_enums);
+        override_flag(&mut self.fingerprints, options.with_fingerprints);
+        override_flag(
+            &mut self.python3.pydantic_models,
+            options.use_pydantic_models,
+        );
+        override_flag(
+            &mut self.typescript.discriminated_unions,
+            options.use_discriminated_unions,
+        );
+        override_flag(
+            &mut self.typescript.numbers_for_big_integers,
+            options.use_numbers_for_big_integers,
+        );
+        override_flag(&mut self.go.json, options.use_json);
+        override_flag(&mut self.go.enum_visitors, options.use_enum_visitors);
+        override_flag(&mut self.go.generic_helpers, options.use_generic_helpers);
+        override_flag(&mut self.cpp.split_sources, options.use_split_sources);
+        override_flag(&mut self.cpp.pmr_containers, options.use_pmr_containers);
+        override_flag(&mut self.cpp.expected_results, options.use_expected_results);
+        override_flag(&mut self.csharp.records, options.use_records);
+        override_flag(&mut self.csharp.record_structs, options.use_record_structs);
+        override_flag(
+            &mut self.csharp.nullable_annotations,
+            options.use_nullable_annotations,
+        );
+        override_flag(&mut self.csharp.json, options.use_json);
+        override_flag(
+            &mut self.csharp.span_serializers,
+            options.use_span_serializers,
+        );
+    }
+
+    fn language(&self) -> Result<Language> {
+        match &self.language {
+            None => Ok(Language::Python3),
+            Some(language) => language
+                .parse()
+                .map_err(|e| CliError::Usage(format!("Unknown language `{}` ({})", language, e))),
+        }
+    }
+
+    fn runtimes(&self) -> Result<std::collections::BTreeSet<Runtime>> {
+        self.with_runtimes
+            .iter()
+            .map(|runtime| {
+                runtime
+                    .parse()
+                    .map_err(|e| CliError::Usage(format!("Unknown runtime `{}` ({})", runtime, e)))
+            })
+            .collect()
+    }
+
+    /// A short description of the target, for error messages.
+    fn description(&self) -> String {
+        let language = self.language.as_deref().unwrap_or("Python3");
+        match &self.target_source_dir {
+            None => language.to_string(),
+            Some(dir) => format!("{} in {}", language, dir.display()),
+        }
+    }
+
+    fn get_codegen_config<'a, I>(&self, name: String, runtimes: I) -> CodeGeneratorConfig
+    where
+        I: IntoIterator<Item = &'a Runtime>,
+    {
+        let mut encodings = Vec::new();
+        for runtime in runtimes {
+            match runtime {
+                Runtime::Bincode => {
+                    encodings.push(Encoding::Bincode);
+                }
+                Runtime::Bcs => {
+                    encodings.push(Encoding::Bcs);
+                }
+                _ => (),
             }
-            _ => (),
         }
+        let qualified_names = |map: &BTreeMap<String, String>| {
+            map.iter()
+                .map(|(name, value)| {
+                    let name = name.split('.').map(String::from).collect::<Vec<_>>();
+                    (name, value.clone())
+                })
+                .collect()
+        };
+        CodeGeneratorConfig::new(name)
+            .with_serialization(self.serialization.unwrap_or(true))
+            .with_encodings(encodings)
+            .with_c_style_enums(self.c_style_enums)
+            .with_fingerprints(self.fingerprints)
+            .with_external_definitions(self.external_definitions.clone())
+            .with_comments(qualified_names(&self.comments))
+            .with_custom_code(qualified_names(&self.custom_code))
+    }
+
+    fn cpp_type_mapping(&self) -> cpp::TypeMapping {
+        let config = &self.cpp.type_mapping;
+        let mut type_mapping = if self.cpp.pmr_containers {
+            cpp::TypeMapping::std_pmr()
+        } else {
+            cpp::TypeMapping::default()
+        };
+        if !config.includes.is_empty() {
+            type_mapping = type_mapping.with_includes(config.includes.clone());
+        }
+        if let Some(seq) = &config.seq {
+            type_mapping = type_mapping.with_seq(seq.clone());
+        }
+        if let Some(map) = &config.map {
+            type_mapping = type_mapping.with_map(map.clone());
+        }
+        if let Some(str) = &config.str {
+            type_mapping = type_mapping.with_str(str.clone());
+        }
+        if let Some(bytes) = &config.bytes {
+            type_mapping = type_mapping.with_bytes(bytes.clone());
+        }
+        if let Some(option) = &config.option {
+            type_mapping = type_mapping.with_option(option.clone());
+        }
+        if let Some(boxed) = &config.boxed {
+            type_mapping = type_mapping.with_boxed(boxed.clone());
+        }
+        type_mapping
     }
-    CodeGeneratorConfig::new(name)
-        .with_encodings(encodings)
-        .with_c_style_enums(c_style_enums)
 }
 
 fn main() {
     let options = Options::from_args();
-    let serde_package_name_opt = options.serde_package_name.clone();
-    let named_registry_opt = match &options.input {
+    match &options.command {
+        Some(Command::Transform(transform_options)) => {
+            if let Err(error) = transform(transform_options) {
+                eprintln!("Error: {}", error);
+                std::process::exit(error.exit_code());
+            }
+            return;
+        }
+        Some(Command::Check(check_options)) => match check(check_options) {
+            Ok(true) => return,
+            Ok(false) => std::process::exit(1),
+            Err(error) => {
+                eprintln!("Error: {}", error);
+                std::process::exit(error.exit_code());
+            }
+        },
+        None => (),
+    }
+    let configs = match &options.config {
+        None => vec![Config::default()],
+        Some(path) => match Config::read(path) {
+            Ok(configs) => configs,
+            Err(error) => {
+                eprintln!("Error: {}", error);
+                std::process::exit(error.exit_code());
+            }
+        },
+    };
+    let num_targets = configs.len();
+    if num_targets > 1
+        && (options.language.is_some()
+            || options.target_source_dir.is_some()
+            || options.module_name.is_some()
+            || !options.with_runtimes.is_empty())
+    {
+        let error = CliError::Usage(
+            "`--language`, `--target-source-dir`, `--module-name` and `--with-runtimes` cannot \
+             be used with a configuration file listing several targets"
+                .to_string(),
+        );
+        eprintln!("Error: {}", error);
+        std::process::exit(error.exit_code());
+    }
+    let mut exit_code = 0;
+    for mut config in configs {
+        config.merge_options(&options);
+        let description = config.description();
+        let result = if num_targets > 1 && config.target_source_dir.is_none() {
+            Err(CliError::Usage(
+                "Generating several targets requires `target_source_dir`".to_string(),
+            ))
+        } else {
+            run(config)
+        };
+        if let Err(error) = result {
+            eprintln!("Error while generating {}: {}", description, error);
+            if exit_code == 0 {
+                exit_code = error.exit_code();
+            }
+        }
+    }
+    if exit_code != 0 {
+        std::process::exit(exit_code);
+    }
+}
+
+fn read_registry(input: &Path) -> Result<Registry> {
+    let content = std::fs::read_to_string(input)
+        .map_err(|e| CliError::Input(format!("Failed to read {}: {}", input.display(), e)))?;
+    serde_yaml::from_str::<Registry>(content.as_str())
+        .map_err(|e| CliError::Input(format!("Failed to parse {}: {}", input.display(), e)))
+}
+
+/// Parse an argument of the form `NAME=NEW_NAME`.
+fn parse_renaming(argument: &str) -> Result<(String, String)> {
+    match argument.split_once('=') {
+        Some((name, new_name)) if !name.is_empty() && !new_name.is_empty() => {
+            Ok((name.to_string(), new_name.to_string()))
+        }
+        _ => Err(CliError::Usage(format!(
+            "Invalid renaming `{}` (expected `NAME=NEW_NAME`)",
+            argument
+        ))),
+    }
+}
+
+/// Parse an argument of the form `CONTAINER.NAME=NEW_NAME`.
+fn parse_qualified_renaming(argument: &str) -> Result<(String, String, String)> {
+    let (name, new_name) = parse_renaming(argument)?;
+    match name.split_once('.') {
+        Some((container, name)) if !container.is_empty() && !name.is_empty() => {
+            Ok((container.to_string(), name.to_string(), new_name))
+        }
+        _ => Err(CliError::Usage(format!(
+            "Invalid renaming `{}` (expected `CONTAINER.NAME=NEW_NAME`)",
+            argument
+        ))),
+    }
+}
+
+fn transform(options: &TransformOptions) -> Result<()> {
+    let mut renaming = transform::Renaming::new();
+    for argument in &options.rename {
+        let (name, new_name) = parse_renaming(argument)?;
+        renaming = renaming.with_container(name, new_name);
+    }
+    for argument in &options.rename_field {
+        let (container, name, new_name) = parse_qualified_renaming(argument)?;
+        renaming = renaming.with_field(container, name, new_name);
+    }
+    for argument in &options.rename_variant {
+        let (container, name, new_name) = parse_qualified_renaming(argument)?;
+        renaming = renaming.with_variant(container, name, new_name);
+    }
+
+    let mut registry = read_registry(&options.input)?;
+    if !options.roots.is_empty() {
+        transform::prune(&mut registry, &options.roots)?;
+    }
+    let registry = transform::rename(&registry, &renaming)?;
+    let content = serde_yaml::to_string(&registry).expect("registries should serialize to YAML");
+    match &options.output {
+        None => print!("{}", content),
+        Some(path) => std::fs::write(path, content).map_err(serde_generate::Error::Io)?,
+    }
+    Ok(())
+}
+
+/// Print the lints of the registry. Return whether the registry is free of problems.
+fn check(options: &CheckOptions) -> Result<bool> {
+    let to_lint_language = |language: &Language| match language {
+        Language::Python3 => lint::Language::Python3,
+        Language::C => lint::Language::C,
+        Language::Cpp => lint::Language::Cpp,
+        Language::Rust => lint::Language::Rust,
+        Language::Java => lint::Language::Java,
+        Language::Go => lint::Language::Go,
+        Language::TypeScript => lint::Language::TypeScript,
+        Language::CSharp => lint::Language::CSharp,
+    };
+    let languages = if options.languages.is_empty() {
+        Language::variants()
+            .iter()
+            .map(|name| to_lint_language(&name.parse().expect("variants should parse")))
+            .collect::<Vec<_>>()
+    } else {
+        options.languages.iter().map(to_lint_language).collect()
+    };
+    let encodings = options
+        .with_runtimes
+        .iter()
+        .filter_map(|runtime| match runtime {
+            Runtime::Bincode => Some(Encoding::Bincode),
+            Runtime::Bcs => Some(Encoding::Bcs),
+            Runtime::Serde => None,
+        })
+        .collect::<Vec<_>>();
+
+    let registry = read_registry(&options.input)?;
+    let lints = lint::lint(&registry, &languages, &encodings);
+    for lint in &lints {
+        println!("{}", lint);
+    }
+    Ok(lints.is_empty())
+}
+
+fn run(config: Config) -> Result<()> {
+    let language = config.language()?;
+    let runtimes = config.runtimes()?;
+    let serde_package_name_opt = config.serde_package_name.clone();
+    let big_integer_mode = if config.typescript.numbers_for_big_integers {
+        typescript::BigIntegerMode::Number
+    } else {
+        typescript::BigIntegerMode::BigInt
+    };
+    let type_declarations = if config.csharp.record_structs {
+        csharp::TypeDeclarations::RecordStructs
+    } else if config.csharp.records {
+        csharp::TypeDeclarations::Records
+    } else {
+        csharp::TypeDeclarations::Classes
+    };
+    let type_mapping = config.cpp_type_mapping();
+    let track_visibility = config.rust.track_visibility.unwrap_or(true);
+    let named_registry_opt = match &config.input {
         None => None,
         Some(input) => {
-            let name = options.module_name.clone().unwrap_or_else(|| {
-                input
+            let name = match &config.module_name {
+                Some(name) => name.clone(),
+                None => input
                     .file_stem()
-                    .expect("failed to deduce module name from input path")
+                    .ok_or_else(|| {
+                        CliError::Usage("Failed to deduce module name from input path".to_string())
+                    })?
                     .to_string_lossy()
-                    .into_owned()
-            });
-            let content = std::fs::read_to_string(input).expect("input file must be readable");
-            let registry = serde_yaml::from_str::<Registry>(content.as_str()).unwrap();
-            Some((registry, name))
+                    .into_owned(),
+            };
+            Some((read_registry(input)?, name))
         }
     };
-    let runtimes: std::collections::BTreeSet<_> = options.with_runtimes.into_iter().collect();
 
-    match options.target_source_dir {
+    match config.target_source_dir.clone() {
         None => {
             if let Some((registry, name)) = named_registry_opt {
-                let config = get_codegen_config(name, &runtimes, options.use_c_style_enums);
+                let codegen_config = config.get_codegen_config(name, &runtimes);
 
                 let stdout = std::io::stdout();
                 let mut out = stdout.lock();
-                match options.language {
-                    Language::Python3 => python3::CodeGenerator::new(&config)
+                match language {
+                    Language::Python3 => python3::CodeGenerator::new(&codegen_config)
                         .with_serde_package_name(serde_package_name_opt)
-                        .output(&mut out, &registry)
-                        .unwrap(),
-                    Language::Rust => rust::CodeGenerator::new(&config)
-                        .output(&mut out, &registry)
-                        .unwrap(),
-                    Language::Cpp => cpp::CodeGenerator::new(&config)
-                        .output(&mut out, &registry)
-                        .unwrap(),
-                    Language::Go => golang::CodeGenerator::new(&config)
-                        .output(&mut out, &registry)
-                        .unwrap(),
+                        .with_pydantic_models(config.python3.pydantic_models)
+                        .output(&mut out, &registry)?,
+                    Language::Rust => {
+                        let mut generator = rust::CodeGenerator::new(&codegen_config)
+                            .with_custom_derive_block(config.rust.custom_derive_block.clone())
+                            .with_track_visibility(track_visibility);
+                        if let Some(derive_macros) = &config.rust.derive_macros {
+                            generator = generator.with_derive_macros(derive_macros.clone());
+                        }
+                        generator.output(&mut out, &registry)?
+                    }
+                    Language::C => {
+                        c::CodeGenerator::new(&codegen_config).output(&mut out, &registry)?
+                    }
+                    Language::Cpp => cpp::CodeGenerator::new(&codegen_config)
+                        .with_type_mapping(type_mapping)
+                        .with_expected_results(config.cpp.expected_results)
+                        .output(&mut out, &registry)?,
+                    Language::Go => golang::CodeGenerator::new(&codegen_config)
+                        .with_json(config.go.json)
+                        .with_enum_visitors(config.go.enum_visitors)
+                        .with_generic_helpers(config.go.generic_helpers)
+                        .output(&mut out, &registry)?,
                     Language::Java => {
-                        panic!("Code generation in Java requires `--target-source-dir`")
+                        return Err(CliError::Usage(
+                            "Code generation in Java requires `--target-source-dir`".to_string(),
+                        ))
                     }
-                    Language::TypeScript => typescript::CodeGenerator::new(&config)
-                        .output(&mut out, &registry)
-                        .unwrap(),
+                    Language::TypeScript => typescript::CodeGenerator::new(&codegen_config)
+                        .with_discriminated_unions(config.typescript.discriminated_unions)
+                        .with_big_integer_mode(big_integer_mode)
+                        .output(&mut out, &registry)?,
                     Language::CSharp => {
-                        panic!("Code generation in C# requires `--target-source-dir`")
+                        return Err(CliError::Usage(
+                            "Code generation in C# requires `--target-source-dir`".to_string(),
+                        ))
                     }
                 }
             }
         }
 
         Some(install_dir) => {
-            let installer: Box<dyn SourceInstaller<Error = Box<dyn std::error::Error>>> =
-                match options.language {
-                    Language::Python3 => {
-                        Box::new(python3::Installer::new(install_dir, serde_package_name_opt))
+            let installer: Box<dyn SourceInstaller<Error = serde_generate::Error>> = match language
+            {
+                Language::Python3 => Box::new(
+                    python3::Installer::new(install_dir, serde_package_name_opt)
+                        .with_pydantic_models(config.python3.pydantic_models),
+                ),
+                Language::Rust => {
+                    let mut installer = rust::Installer::new(install_dir)
+                        .with_custom_derive_block(config.rust.custom_derive_block.clone())
+                        .with_track_visibility(track_visibility);
+                    if let Some(derive_macros) = &config.rust.derive_macros {
+                        installer = installer.with_derive_macros(derive_macros.clone());
                     }
-                    Language::Rust => Box::new(rust::Installer::new(install_dir)),
-                    Language::Cpp => Box::new(cpp::Installer::new(install_dir)),
-                    Language::Java => Box::new(java::Installer::new(install_dir)),
-                    Language::Go => {
-                        Box::new(golang::Installer::new(install_dir, serde_package_name_opt))
-                    }
-                    Language::TypeScript => Box::new(typescript::Installer::new(install_dir)),
-                    Language::CSharp => Box::new(csharp::Installer::new(install_dir)),
-                };
+                    Box::new(installer)
+                }
+                Language::C => Box::new(c::Installer::new(install_dir)),
+                Language::Cpp => Box::new(
+                    cpp::Installer::new(install_dir)
+                        .with_split_sources(config.cpp.split_sources)
+                        .with_type_mapping(type_mapping)
+                        .with_expected_results(config.cpp.expected_results),
+                ),
+                Language::Java => Box::new(java::Installer::new(install_dir)),
+                Language::Go => Box::new(
+                    golang::Installer::new(install_dir, serde_package_name_opt)
+                        .with_json(config.go.json)
+                        .with_enum_visitors(config.go.enum_visitors)
+                        .with_generic_helpers(config.go.generic_helpers),
+                ),
+                Language::TypeScript => Box::new(
+                    typescript::Installer::new(install_dir)
+                        .with_discriminated_unions(config.typescript.discriminated_unions)
+                        .with_big_integer_mode(big_integer_mode),
+                ),
+                Language::CSharp => Box::new(
+                    csharp::Installer::new(install_dir)
+                        .with_type_declarations(type_declarations)
+                        .with_nullable_annotations(config.csharp.nullable_annotations)
+                        .with_json_converters(config.csharp.json)
+                        .with_span_serializers(config.csharp.span_serializers),
+                ),
+            };
 
             if let Some((registry, name)) = named_registry_opt {
-                let config = get_codegen_config(name, &runtimes, options.use_c_style_enums);
-                installer.install_module(&config, &registry).unwrap();
+                let codegen_config = config.get_codegen_config(name, &runtimes);
+                installer.install_module(&codegen_config, &registry)?;
             }
 
             for runtime in runtimes {
                 match runtime {
-                    Runtime::Serde => installer.install_serde_runtime().unwrap(),
-                    Runtime::Bincode => installer.install_bincode_runtime().unwrap(),
-                    Runtime::Bcs => installer.install_bcs_runtime().unwrap(),
+                    Runtime::Serde => installer.install_serde_runtime()?,
+                    Runtime::Bincode => installer.install_bincode_runtime()?,
+                    Runtime::Bcs => installer.install_bcs_runtime()?,
                 }
             }
         }
     }
+    Ok(())
 }
@@ -4,13 +4,13 @@
 use crate::{
     common,
     indent::{IndentConfig, IndentedWriter},
-    CodeGeneratorConfig, Encoding,
+    CodeGeneratorConfig, Encoding, Error, Result,
 };
 use heck::CamelCase;
 use serde_reflection::{ContainerFormat, Format, FormatHolder, Named, Registry, VariantFormat};
 use std::{
-    collections::{BTreeMap, HashMap},
-    io::{Result, Write},
+    collections::{BTreeMap, HashMap, HashSet},
+    io::Write,
     path::PathBuf,
 };
 
@@ -24,6 +24,13 @@ pub struct CodeGenerator<'a> {
     /// Mapping from external type names to fully-qualified class names (e.g. "MyClass" -> "com.my_org.my_package.MyClass").
     /// Derived from `config.external_definitions`.
     external_qualified_names: HashMap<String, String>,
+    /// Whether to emit `json:"..."` tags and JSON methods following the data layout of serde_json.
+    json: bool,
+    /// Whether to emit a visitor interface and an exhaustive type switch for each enum.
+    enum_visitors: bool,
+    /// Whether to use the generic helpers of the serde runtime (Go >= 1.18) instead of generating
+    /// (de)serialization helpers for each composite type.
+    generic_helpers: bool,
 }
 
 /// Shared state for the code generation of a Go source file.
@@ -34,14 +41,15 @@ struct GoEmitter<'a, T> {
     generator: &'a CodeGenerator<'a>,
     /// Current namespace (e.g. vec!["com", "my_org", "my_package", "MyClass"])
     current_namespace: Vec<String>,
+    /// Names of the enums defined in the registry.
+    enums: HashSet<String>,
+    /// Fingerprints of the containers, if requested.
+    fingerprints: BTreeMap<String, u64>,
 }
 
 impl<'a> CodeGenerator<'a> {
     /// Create a Go code generator for the given config.
     pub fn new(config: &'a CodeGeneratorConfig) -> Self {
-        if config.c_style_enums {
-            panic!("Go does not support generating c-style enums");
-        }
         let mut external_qualified_names = HashMap::new();
         for (namespace, names) in &config.external_definitions {
             let package_name = {
@@ -63,6 +71,9 @@ impl<'a> CodeGenerator<'a> {
                 "github.com/novifinancial/serde-reflection/serde-generate/runtime/golang"
                     .to_string(),
             external_qualified_names,
+            json: false,
+            enum_visitors: false,
+            generic_helpers: false,
         }
     }
 
@@ -72,8 +83,40 @@ impl<'a> CodeGenerator<'a> {
         self
     }
 
+    /// Whether to emit `json:"..."` tags matching serde names, as well as `MarshalJSON` and
+    /// `UnmarshalJSON` methods whenever the default behavior of `encoding/json` differs from
+    /// serde_json (e.g. externally-tagged enums, tuples, bytes, chars, missing non-optional fields).
+    /// Values of an enum `Foo` are decoded with the generated function `UnmarshalJSONFoo`.
+    pub fn with_json(mut self, json: bool) -> Self {
+        self.json = json;
+        self
+    }
+
+    /// Whether to emit an interface `FooVisitor` and a function `VisitFoo` dispatching on the
+    /// variants of each enum `Foo`. Adding a variant makes existing visitors fail to compile.
+    pub fn with_enum_visitors(mut self, enum_visitors: bool) -> Self {
+        self.enum_visitors = enum_visitors;
+        self
+    }
+
+    /// Whether to (de)serialize options, sequences, maps, and arrays with the generic functions
+    /// of the `generics` runtime module (e.g. `generics.SerializeSeq`), instead of generating a
+    /// helper function for each composite type. Requires Go >= 1.18. The encoding of values is
+    /// unchanged.
+    pub fn with_generic_helpers(mut self, generic_helpers: bool) -> Self {
+        self.generic_helpers = generic_helpers;
+        self
+    }
+
     /// Output class definitions for `registry`.
-    pub fn output(&self, out: &mut dyn Write, registry: &Registry) -> Result<()> {
+    pub fn output(&self, out: &mut dyn Write, registry: &Registry) -> crate::Result<()> {
+        if self.config.c_style_enums {
+            return Err(Error::UnsupportedOption {
+                language: "Go",
+                option: "c-style enums",
+            });
+        }
+        let registry = &common::check_and_monomorphize_registry(registry)?;
         let current_namespace = self
             .config
             .module_name
@@ -86,6 +129,14 @@ impl<'a> CodeGenerator<'a> {
             out: IndentedWriter::new(out, IndentConfig::Tab),
             generator: self,
             current_namespace,
+            enums: registry
+                .iter()
+                .filter_map(|(name, format)| match format {
+                    ContainerFormat::Enum(_) => Some(name.clone()),
+                    _ => None,
+                })
+                .collect(),
+            fingerprints: common::get_fingerprints(self.config, registry)?,
         };
 
         emitter.output_preamble(registry)?;
@@ -94,10 +145,14 @@ impl<'a> CodeGenerator<'a> {
             emitter.output_container(name, format)?;
         }
 
-        if self.config.serialization {
+        if self.config.serialization && !self.generic_helpers {
             emitter.output_trait_helpers(registry)?;
         }
 
+        if self.json {
+            emitter.output_json_helpers(registry)?;
+        }
+
         Ok(())
     }
 }
@@ -118,14 +173,29 @@ where
         }
         writeln!(self.out, "import (")?;
         self.out.indent();
-        if self.generator.config.serialization
-            && (Self::has_enum(registry) || !self.generator.config.encodings.is_empty())
+        if self.generator.json {
+            writeln!(self.out, "\"encoding/json\"")?;
+        }
+        if (self.generator.config.serialization
+            && (Self::has_enum(registry) || !self.generator.config.encodings.is_empty()))
+            || self.generator.json
+            || (self.generator.enum_visitors && Self::has_enum(registry))
         {
             writeln!(self.out, "\"fmt\"")?;
         }
         if self.generator.config.serialization || Self::has_int128(registry) {
             writeln!(self.out, "\"{}/serde\"", self.generator.serde_module_path)?;
         }
+        if self.generator.config.serialization
+            && self.generator.generic_helpers
+            && Self::has_generic_helpers(registry)
+        {
+            writeln!(
+                self.out,
+                "\"{}/generics\"",
+                self.generator.serde_module_path
+            )?;
+        }
         if self.generator.config.serialization {
             for encoding in &self.generator.config.encodings {
                 writeln!(
@@ -141,6 +211,10 @@ where
         }
         self.out.unindent();
         writeln!(self.out, ")\n")?;
+        if self.generator.json {
+            // Depending on the registry, JSON methods may not need these packages.
+            writeln!(self.out, "var _ = json.Marshal\nvar _ = fmt.Errorf\n")?;
+        }
         Ok(())
     }
 
@@ -162,6 +236,27 @@ where
         false
     }
 
+    fn has_generic_helpers(registry: &Registry) -> bool {
+        for format in registry.values() {
+            if format
+                .visit(&mut |f| match f {
+                    Format::Option(_)
+                    | Format::Seq(_)
+                    | Format::Map { .. }
+                    | Format::TupleArray { .. } => {
+                        // Interrupt the visit if we find a format handled by a generic helper.
+                        Err(serde_reflection::Error::Custom(String::new()))
+                    }
+                    _ => Ok(()),
+                })
+                .is_err()
+            {
+                return true;
+            }
+        }
+        false
+    }
+
     fn has_enum(registry: &Registry) -> bool {
         for format in registry.values() {
             if let ContainerFormat::Enum(_) = format {
@@ -190,6 +285,17 @@ where
         Ok(())
     }
 
+    fn output_fingerprint(&mut self, name: &str) -> std::io::Result<()> {
+        if let Some(fingerprint) = self.fingerprints.get(name) {
+            writeln!(
+                self.out,
+                "\nconst {}Fingerprint uint64 = {:#018x}",
+                name, fingerprint
+            )?;
+        }
+        Ok(())
+    }
+
     fn output_custom_code(&mut self, name: &str) -> std::io::Result<()> {
         let mut path = self.current_namespace.clone();
         path.push(name.to_string());
@@ -237,7 +343,7 @@ where
             ),
             TupleArray { content, size } => format!("[{}]{}", size, self.quote_type(content)),
 
-            Variable(_) => panic!("unexpected value"),
+            Variable(_) | Parameter(_) | TypeApp { .. } => panic!("unexpected value"),
         }
     }
 
@@ -280,7 +386,39 @@ where
 
     fn quote_serialize_value(&self, value: &str, format: &Format) -> String {
         use Format::*;
-        let expr = match format {
+        if self.generator.generic_helpers {
+            match format {
+                Tuple(formats) => {
+                    return formats
+                        .iter()
+                        .enumerate()
+                        .map(|(i, f)| {
+                            self.quote_serialize_value(&format!("{}.Field{}", value, i), f)
+                        })
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                }
+                TupleArray { content, size: _ } => {
+                    return format!(
+                        "for _, item := range {} {{ {} }}",
+                        value,
+                        self.quote_serialize_value("item", content)
+                    )
+                }
+                _ => (),
+            }
+        }
+        format!(
+            "if err := {}; err != nil {{ return err }}",
+            self.quote_serialize_expr(value, format)
+        )
+    }
+
+    /// Go expression of type `error` serializing `value`. Tuples and arrays are not supported
+    /// when using generic helpers.
+    fn quote_serialize_expr(&self, value: &str, format: &Format) -> String {
+        use Format::*;
+        match format {
             TypeName(_) => format!("{}.Serialize(serializer)", value),
             Unit => format!("serializer.SerializeUnit({})", value),
             Bool => format!("serializer.SerializeBool({})", value),
@@ -299,18 +437,134 @@ where
             Char => format!("serializer.SerializeChar({})", value),
             Str => format!("serializer.SerializeStr({})", value),
             Bytes => format!("serializer.SerializeBytes({})", value),
+            Option(format) if self.generator.generic_helpers => format!(
+                "generics.SerializeOption(serializer, {}, {})",
+                value,
+                self.quote_serialize_function(format)
+            ),
+            Seq(format) if self.generator.generic_helpers => format!(
+                "generics.SerializeSeq(serializer, {}, {})",
+                value,
+                self.quote_serialize_function(format)
+            ),
+            Map { key, value: val } if self.generator.generic_helpers => format!(
+                "generics.SerializeMap(serializer, {}, {}, {})",
+                value,
+                self.quote_serialize_function(key),
+                self.quote_serialize_function(val)
+            ),
             _ => format!(
                 "serialize_{}({}, serializer)",
                 common::mangle_type(format),
                 value
             ),
-        };
-        format!("if err := {}; err != nil {{ return err }}", expr)
+        }
+    }
+
+    /// Go function of type `func(serde.Serializer, T) error` serializing values of type `format`.
+    fn quote_serialize_function(&self, format: &Format) -> String {
+        match Self::primitive_name(format) {
+            Some(name) => format!("serde.Serializer.Serialize{}", name),
+            None => match format {
+                Format::Tuple(_) | Format::TupleArray { .. } => format!(
+                    "func(serializer serde.Serializer, value {}) error {{ {}; return nil }}",
+                    self.quote_type(format),
+                    self.quote_serialize_value("value", format)
+                ),
+                _ => format!(
+                    "func(serializer serde.Serializer, value {}) error {{ return {} }}",
+                    self.quote_type(format),
+                    self.quote_serialize_expr("value", format)
+                ),
+            },
+        }
+    }
+
+    /// Go function of type `func(serde.Deserializer) (T, error)` deserializing values of type `format`.
+    fn quote_deserialize_function(&self, format: &Format) -> String {
+        match format {
+            Format::TypeName(name) => format!("Deserialize{}", self.quote_qualified_name(name)),
+            _ => match Self::primitive_name(format) {
+                Some(name) => format!("serde.Deserializer.Deserialize{}", name),
+                None => match format {
+                    Format::Tuple(_) | Format::TupleArray { .. } => format!(
+                        "func(deserializer serde.Deserializer) ({0}, error) {{ var obj {0}; {1}; return obj, nil }}",
+                        self.quote_type(format),
+                        self.quote_deserialize(format, "obj", "obj")
+                    ),
+                    _ => format!(
+                        "func(deserializer serde.Deserializer) ({}, error) {{ return {} }}",
+                        self.quote_type(format),
+                        self.quote_deserialize_expr(format)
+                    ),
+                },
+            },
+        }
+    }
+
+    /// Suffix of the methods of `serde.Serializer` and `serde.Deserializer` handling `format`, if any.
+    fn primitive_name(format: &Format) -> Option<&'static str> {
+        use Format::*;
+        match format {
+            Unit => Some("Unit"),
+            Bool => Some("Bool"),
+            I8 => Some("I8"),
+            I16 => Some("I16"),
+            I32 => Some("I32"),
+            I64 => Some("I64"),
+            I128 => Some("I128"),
+            U8 => Some("U8"),
+            U16 => Some("U16"),
+            U32 => Some("U32"),
+            U64 => Some("U64"),
+            U128 => Some("U128"),
+            F32 => Some("F32"),
+            F64 => Some("F64"),
+            Char => Some("Char"),
+            Str => Some("Str"),
+            Bytes => Some("Bytes"),
+            _ => None,
+        }
     }
 
     fn quote_deserialize(&self, format: &Format, dest: &str, fail: &str) -> String {
         use Format::*;
-        let expr = match format {
+        if self.generator.generic_helpers {
+            match format {
+                Tuple(formats) => {
+                    return formats
+                        .iter()
+                        .enumerate()
+                        .map(|(i, f)| {
+                            self.quote_deserialize(f, &format!("{}.Field{}", dest, i), fail)
+                        })
+                        .collect::<Vec<_>>()
+                        .join("; ")
+                }
+                TupleArray { content, size: _ } => {
+                    return format!(
+                        "if err := generics.DeserializeArray(deserializer, {}[:], {}); err != nil {{ return {}, err }}",
+                        dest,
+                        self.quote_deserialize_function(content),
+                        fail
+                    )
+                }
+                _ => (),
+            }
+        }
+        format!(
+            "if val, err := {}; err == nil {{ {} = val }} else {{ return {}, err }}",
+            self.quote_deserialize_expr(format),
+            dest,
+            fail
+        )
+    }
+
+    /// Go expression of type `(T, error)` deserializing a value of type `format`. Tuples and arrays
+    /// are not supported when using generic helpers.
+    fn quote_deserialize_expr(&self, format: &Format) -> String {
+        use Format::*;
+        match format {
             TypeName(name) => format!(
                 "Deserialize{}(deserializer)",
                 self.quote_qualified_name(name)
@@ -332,12 +586,21 @@ where
             Char => "deserializer.DeserializeChar()".to_string(),
             Str => "deserializer.DeserializeStr()".to_string(),
             Bytes => "deserializer.DeserializeBytes()".to_string(),
+            Option(format) if self.generator.generic_helpers => format!(
+                "generics.DeserializeOption(deserializer, {})",
+                self.quote_deserialize_function(format)
+            ),
+            Seq(format) if self.generator.generic_helpers => format!(
+                "generics.DeserializeSeq(deserializer, {})",
+                self.quote_deserialize_function(format)
+            ),
+            Map { key, value } if self.generator.generic_helpers => format!(
+                "generics.DeserializeMap(deserializer, {}, {})",
+                self.quote_deserialize_function(key),
+                self.quote_deserialize_function(value)
+            ),
             _ => format!("deserialize_{}(deserializer)", common::mangle_type(format)),
-        };
-        format!(
-            "if val, err := {}; err == nil {{ {} = val }} else {{ return {}, err }}",
-            expr, dest, fail
-        )
+        }
     }
 
     fn output_serialization_helper(&mut self, name: &str, format0: &Format) -> Result<()> {
@@ -423,7 +686,8 @@ for _, item := range(value) {{
         }
         writeln!(self.out, "return nil")?;
         self.out.unindent();
-        writeln!(self.out, "}}\n")
+        writeln!(self.out, "}}\n")?;
+        Ok(())
     }
 
     fn output_deserialization_helper(&mut self, name: &str, format0: &Format) -> Result<()> {
@@ -540,7 +804,8 @@ return obj, nil
             _ => panic!("unexpected case"),
         }
         self.out.unindent();
-        writeln!(self.out, "}}\n")
+        writeln!(self.out, "}}\n")?;
+        Ok(())
     }
 
     fn output_variant(
@@ -551,30 +816,56 @@ return obj, nil
         variant: &VariantFormat,
     ) -> Result<()> {
         use VariantFormat::*;
-        let fields = match variant {
+        let go_name = name.to_camel_case();
+        match variant {
+            NewType(format) if !Self::is_wrapped_new_type(format) => {
+                self.output_struct_or_variant_new_type_container(
+                    Some(base),
+                    Some(index),
+                    &go_name,
+                    format,
+                )?;
+            }
+            _ => {
+                let fields = Self::variant_fields(variant);
+                self.output_struct_or_variant_container(
+                    Some(base),
+                    Some(index),
+                    &go_name,
+                    &fields,
+                )?;
+            }
+        }
+        if self.generator.json {
+            self.output_variant_json_method(&format!("{}__{}", base, go_name), name, variant)?;
+        }
+        // Custom code
+        self.output_custom_code(&go_name)?;
+        Ok(())
+    }
+
+    // We cannot define a "new type" (e.g. `type Foo Bar`) out of a typename `Bar` because `Bar`
+    // could point to a Go interface. This would make `Foo` an interface as well. Interfaces can't be used
+    // as structs (e.g. they cannot have methods).
+    //
+    // Similarly, option types are compiled as pointers but `type Foo *Bar` would prevent `Foo` from being a
+    // valid pointer receiver.
+    //
+    // In these cases, the content is wrapped in a struct with a single field `Value`.
+    fn is_wrapped_new_type(format: &Format) -> bool {
+        matches!(format, Format::TypeName(_) | Format::Option(_))
+    }
+
+    /// Fields of the Go struct used for a variant (or a container laid out as a variant).
+    /// Names are the serde names; Go names are obtained with `to_camel_case`.
+    fn variant_fields(variant: &VariantFormat) -> Vec<Named<Format>> {
+        use VariantFormat::*;
+        match variant {
             Unit => Vec::new(),
-            NewType(format) => match format.as_ref() {
-                // We cannot define a "new type" (e.g. `type Foo Bar`) out of a typename `Bar` because `Bar`
-                // could point to a Go interface. This would make `Foo` an interface as well. Interfaces can't be used
-                // as structs (e.g. they cannot have methods).
-                //
-                // Similarly, option types are compiled as pointers but `type Foo *Bar` would prevent `Foo` from being a
-                // valid pointer receiver.
-                Format::TypeName(_) | Format::Option(_) => vec![Named {
-                    name: "Value".to_string(),
-                    value: format.as_ref().clone(),
-                }],
-                // Other cases are fine.
-                _ => {
-                    self.output_struct_or_variant_new_type_container(
-                        Some(base),
-                        Some(index),
-                        name,
-                        format,
-                    )?;
-                    return Ok(());
-                }
-            },
+            NewType(format) => vec![Named {
+                name: "Value".to_string(),
+                value: format.as_ref().clone(),
+            }],
             Tuple(formats) => formats
                 .iter()
                 .enumerate()
@@ -583,16 +874,9 @@ return obj, nil
                     value: f.clone(),
                 })
                 .collect(),
-            Struct(fields) => fields
-                .iter()
-                .map(|f| Named {
-                    name: f.name.to_camel_case(),
-                    value: f.value.clone(),
-                })
-                .collect(),
+            Struct(fields) => fields.clone(),
             Variable(_) => panic!("incorrect value"),
-        };
-        self.output_struct_or_variant_container(Some(base), Some(index), name, &fields)
+        }
     }
 
     fn output_struct_or_variant_container(
@@ -612,8 +896,19 @@ return obj, nil
         writeln!(self.out, "type {} struct {{", full_name)?;
         self.enter_class(name);
         for field in fields {
-            self.output_comment(&field.name)?;
-            writeln!(self.out, "{} {}", field.name, self.quote_type(&field.value))?;
+            let field_name = field.name.to_camel_case();
+            self.output_comment(&field_name)?;
+            if self.generator.json {
+                writeln!(
+                    self.out,
+                    "{} {} `json:\"{}\"`",
+                    field_name,
+                    self.quote_type(&field.value),
+                    field.name
+                )?;
+            } else {
+                writeln!(self.out, "{} {}", field_name, self.quote_type(&field.value))?;
+            }
         }
         self.leave_class();
         writeln!(self.out, "}}")?;
@@ -642,7 +937,10 @@ return obj, nil
                 writeln!(
                     self.out,
                     "{}",
-                    self.quote_serialize_value(&format!("obj.{}", &field.name), &field.value)
+                    self.quote_serialize_value(
+                        &format!("obj.{}", field.name.to_camel_case()),
+                        &field.value
+                    )
                 )?;
             }
             writeln!(self.out, "serializer.DecreaseContainerDepth()")?;
@@ -676,7 +974,11 @@ return obj, nil
                 writeln!(
                     self.out,
                     "{}",
-                    self.quote_deserialize(&field.value, &format!("obj.{}", field.name), "obj")
+                    self.quote_deserialize(
+                        &field.value,
+                        &format!("obj.{}", field.name.to_camel_case()),
+                        "obj"
+                    )
                 )?;
             }
             writeln!(self.out, "deserializer.DecreaseContainerDepth()")?;
@@ -690,8 +992,6 @@ return obj, nil
                 }
             }
         }
-        // Custom code
-        self.output_custom_code(name)?;
         Ok(())
     }
 
@@ -781,8 +1081,6 @@ return obj, nil
                 }
             }
         }
-        // Custom code
-        self.output_custom_code(name)?;
         Ok(())
     }
 
@@ -805,7 +1103,8 @@ func (obj *{0}) {2}Serialize() ([]byte, error) {{
             name,
             encoding.name(),
             encoding.name().to_camel_case()
-        )
+        )?;
+        Ok(())
     }
 
     fn output_struct_deserialize_for_encoding(
@@ -831,7 +1130,8 @@ func {2}Deserialize{0}(input []byte) ({0}, error) {{
             name,
             encoding.name(),
             encoding.name().to_camel_case(),
-        )
+        )?;
+        Ok(())
     }
 
     fn output_enum_container(
@@ -883,7 +1183,9 @@ switch index {{"#,
 		return nil, err
 	}}
 "#,
-                    index, name, variant.name
+                    index,
+                    name,
+                    variant.name.to_camel_case()
                 )?;
             }
             writeln!(
@@ -901,63 +1203,633 @@ switch index {{"#,
             }
         }
 
+        if self.generator.json {
+            self.output_enum_json_function(name, variants)?;
+        }
+        if self.generator.enum_visitors {
+            self.output_enum_visitor(name, variants)?;
+        }
+
         for (index, variant) in variants {
             self.output_variant(name, *index, &variant.name, &variant.value)?;
         }
         self.current_namespace.pop();
+        self.output_fingerprint(name)?;
         // Custom code
         self.output_custom_code(name)?;
         Ok(())
     }
 
-    fn output_container(&mut self, name: &str, format: &ContainerFormat) -> Result<()> {
-        use ContainerFormat::*;
-        let fields = match format {
-            UnitStruct => Vec::new(),
-            NewTypeStruct(format) => match format.as_ref() {
-                // See comment in `output_variant`.
-                Format::TypeName(_) | Format::Option(_) => vec![Named {
-                    name: "Value".to_string(),
-                    value: format.as_ref().clone(),
-                }],
-                _ => {
-                    self.output_struct_or_variant_new_type_container(None, None, name, format)?;
-                    return Ok(());
+    /// Whether the JSON encoding of `format` requires generated code: either the default behavior
+    /// of `encoding/json` differs from serde_json, or the format contains enums, which are Go
+    /// interfaces and cannot be decoded by `encoding/json`.
+    fn needs_json_helper(&self, format: &Format) -> bool {
+        format
+            .visit(&mut |f| match f {
+                Format::Unit | Format::Char | Format::Bytes | Format::Tuple(_) => {
+                    // Interrupt the visit.
+                    Err(serde_reflection::Error::Custom(String::new()))
                 }
-            },
-            TupleStruct(formats) => formats
-                .iter()
-                .enumerate()
-                .map(|(i, f)| Named {
-                    name: format!("Field{}", i),
-                    value: f.clone(),
-                })
-                .collect(),
+                Format::TypeName(name) if self.enums.contains(name) => {
+                    Err(serde_reflection::Error::Custom(String::new()))
+                }
+                _ => Ok(()),
+            })
+            .is_err()
+    }
+
+    /// Go expression for a value that `json.Marshal` encodes like serde_json would encode `value`.
+    fn quote_json_value(&self, value: &str, format: &Format) -> String {
+        match format {
+            Format::TypeName(_) => value.to_string(),
+            _ if self.needs_json_helper(format) => {
+                format!("marshal_json_{}({})", common::mangle_type(format), value)
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    /// Go statement decoding the JSON bytes `data` into `dest`. The error is returned using `ret`
+    /// (e.g. "return " or "return nil, ").
+    fn quote_json_decode(&self, format: &Format, data: &str, dest: &str, ret: &str) -> String {
+        match format {
+            Format::TypeName(name) if self.enums.contains(name) => format!(
+                "if val, err := UnmarshalJSON{}({}); err == nil {{ {} = val }} else {{ {}err }}",
+                name, data, dest, ret
+            ),
+            Format::TypeName(_) => format!(
+                "if err := json.Unmarshal({}, &{}); err != nil {{ {}err }}",
+                data, dest, ret
+            ),
+            _ if self.needs_json_helper(format) => format!(
+                "if val, err := unmarshal_json_{}({}); err == nil {{ {} = val }} else {{ {}err }}",
+                common::mangle_type(format),
+                data,
+                dest,
+                ret
+            ),
+            _ => format!(
+                "if err := json.Unmarshal({}, &{}); err != nil {{ {}err }}",
+                data, dest, ret
+            ),
+        }
+    }
+
+    /// Go expression for the JSON content of a container or variant `obj` (without the variant name).
+    fn quote_json_content(&self, variant: &VariantFormat) -> String {
+        use VariantFormat::*;
+        match variant {
+            Unit => "nil".to_string(),
+            NewType(format) if Self::is_wrapped_new_type(format) => {
+                self.quote_json_value("obj.Value", format)
+            }
+            NewType(format) => {
+                self.quote_json_value(&format!("(({})(obj))", self.quote_type(format)), format)
+            }
+            Tuple(formats) => format!(
+                "[]interface{{}}{{{}}}",
+                formats
+                    .iter()
+                    .enumerate()
+                    .map(|(i, f)| self.quote_json_value(&format!("obj.Field{}", i), f))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            // Anonymous structs preserve the order of the fields.
+            Struct(fields) => format!(
+                "struct {{{}}}{{{}}}",
+                fields
+                    .iter()
+                    .map(|f| format!(
+                        "{} interface{{}} `json:\"{}\"`",
+                        f.name.to_camel_case(),
+                        f.name
+                    ))
+                    .collect::<Vec<_>>()
+                    .join("; "),
+                fields
+                    .iter()
+                    .map(|f| self
+                        .quote_json_value(&format!("obj.{}", f.name.to_camel_case()), &f.value))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            Variable(_) => panic!("incorrect value"),
+        }
+    }
+
+    /// Decode the JSON content `data` of a container or variant `obj` of type `name`.
+    /// `obj_ref` is the expression used to assign `obj` as a whole (e.g. "*obj" for a receiver).
+    fn output_json_decode_content(
+        &mut self,
+        name: &str,
+        variant: &VariantFormat,
+        data: &str,
+        obj_ref: &str,
+        ret: &str,
+    ) -> Result<()> {
+        use VariantFormat::*;
+        match variant {
+            Unit => (),
+            NewType(format) if Self::is_wrapped_new_type(format) => {
+                writeln!(
+                    self.out,
+                    "{}",
+                    self.quote_json_decode(format, data, "obj.Value", ret)
+                )?;
+            }
+            NewType(format) => {
+                writeln!(self.out, "var inner {}", self.quote_type(format))?;
+                writeln!(
+                    self.out,
+                    "{}",
+                    self.quote_json_decode(format, data, "inner", ret)
+                )?;
+                writeln!(self.out, "{} = {}(inner)", obj_ref, name)?;
+            }
+            Tuple(formats) => {
+                writeln!(self.out, "var items []json.RawMessage")?;
+                writeln!(
+                    self.out,
+                    "if err := json.Unmarshal({}, &items); err != nil {{ {}err }}",
+                    data, ret
+                )?;
+                writeln!(
+                    self.out,
+                    "if len(items) != {0} {{ {1}fmt.Errorf(\"Expected {0} items for {2}, got %d\", len(items)) }}",
+                    formats.len(),
+                    ret,
+                    name
+                )?;
+                for (i, format) in formats.iter().enumerate() {
+                    writeln!(
+                        self.out,
+                        "{}",
+                        self.quote_json_decode(
+                            format,
+                            &format!("items[{}]", i),
+                            &format!("obj.Field{}", i),
+                            ret
+                        )
+                    )?;
+                }
+            }
+            Struct(fields) => {
+                writeln!(self.out, "var fields map[string]json.RawMessage")?;
+                writeln!(
+                    self.out,
+                    "if err := json.Unmarshal({}, &fields); err != nil {{ {}err }}",
+                    data, ret
+                )?;
+                for field in fields {
+                    let decode = self.quote_json_decode(
+                        &field.value,
+                        "value",
+                        &format!("obj.{}", field.name.to_camel_case()),
+                        ret,
+                    );
+                    // Like serde_json, only optional fields may be missing.
+                    if let Format::Option(_) = &field.value {
+                        writeln!(
+                            self.out,
+                            "if value, ok := fields[\"{}\"]; ok {{ {} }}",
+                            field.name, decode
+                        )?;
+                    } else {
+                        writeln!(
+                            self.out,
+                            "if value, ok := fields[\"{0}\"]; !ok {{ {1}fmt.Errorf(\"Missing field {0} for {2}\") }} else {{ {3} }}",
+                            field.name, ret, name, decode
+                        )?;
+                    }
+                }
+            }
+            Variable(_) => panic!("incorrect value"),
+        }
+        Ok(())
+    }
+
+    fn output_container_json_methods(&mut self, name: &str, variant: &VariantFormat) -> Result<()> {
+        use VariantFormat::*;
+        let needs_methods = match variant {
+            Unit | Tuple(_) => true,
+            NewType(format) => Self::is_wrapped_new_type(format) || self.needs_json_helper(format),
+            // `encoding/json` silently accepts missing fields.
             Struct(fields) => fields
                 .iter()
-                .map(|f| Named {
-                    name: f.name.to_camel_case(),
-                    value: f.value.clone(),
+                .any(|f| self.needs_json_helper(&f.value) || !matches!(f.value, Format::Option(_))),
+            Variable(_) => panic!("incorrect value"),
+        };
+        if !needs_methods {
+            // The JSON tags are enough.
+            return Ok(());
+        }
+        writeln!(
+            self.out,
+            "\nfunc (obj {}) MarshalJSON() ([]byte, error) {{",
+            name
+        )?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            "return json.Marshal({})",
+            self.quote_json_content(variant)
+        )?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+
+        if let Unit = variant {
+            // `encoding/json` already accepts `null` for empty structs.
+            return Ok(());
+        }
+        writeln!(
+            self.out,
+            "\nfunc (obj *{}) UnmarshalJSON(data []byte) error {{",
+            name
+        )?;
+        self.out.indent();
+        self.output_json_decode_content(name, variant, "data", "*obj", "return ")?;
+        writeln!(self.out, "return nil")?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        Ok(())
+    }
+
+    // Variants use the externally-tagged layout of serde_json: `"Name"` for unit variants,
+    // `{"Name": content}` otherwise.
+    fn output_variant_json_method(
+        &mut self,
+        full_name: &str,
+        variant_name: &str,
+        variant: &VariantFormat,
+    ) -> Result<()> {
+        writeln!(
+            self.out,
+            "\nfunc (obj {}) MarshalJSON() ([]byte, error) {{",
+            full_name
+        )?;
+        self.out.indent();
+        match variant {
+            VariantFormat::Unit => writeln!(self.out, "return json.Marshal(\"{}\")", variant_name)?,
+            _ => writeln!(
+                self.out,
+                "return json.Marshal(struct {{ Value interface{{}} `json:\"{}\"` }}{{{}}})",
+                variant_name,
+                self.quote_json_content(variant)
+            )?,
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        Ok(())
+    }
+
+    fn output_enum_json_function(
+        &mut self,
+        name: &str,
+        variants: &BTreeMap<u32, Named<VariantFormat>>,
+    ) -> Result<()> {
+        let has_content = variants
+            .values()
+            .any(|v| !matches!(v.value, VariantFormat::Unit));
+        writeln!(
+            self.out,
+            "\nfunc UnmarshalJSON{0}(data []byte) ({0}, error) {{",
+            name
+        )?;
+        self.out.indent();
+        writeln!(self.out, "var name string")?;
+        if has_content {
+            writeln!(self.out, "var content json.RawMessage")?;
+        }
+        writeln!(
+            self.out,
+            r#"if err := json.Unmarshal(data, &name); err != nil {{
+	var fields map[string]json.RawMessage
+	if err := json.Unmarshal(data, &fields); err != nil {{ return nil, err }}
+	if len(fields) != 1 {{ return nil, fmt.Errorf("Expected a single variant for {}") }}"#,
+            name
+        )?;
+        if has_content {
+            writeln!(
+                self.out,
+                "\tfor key, value := range fields {{ name = key; content = value }}"
+            )?;
+        } else {
+            writeln!(self.out, "\tfor key := range fields {{ name = key }}")?;
+        }
+        writeln!(self.out, "}}\n")?;
+        writeln!(self.out, "switch name {{")?;
+        for variant in variants.values() {
+            let full_name = format!("{}__{}", name, variant.name.to_camel_case());
+            writeln!(self.out, "case \"{}\":", variant.name)?;
+            self.out.indent();
+            writeln!(self.out, "var obj {}", full_name)?;
+            self.output_json_decode_content(
+                &full_name,
+                &variant.value,
+                "content",
+                "obj",
+                "return nil, ",
+            )?;
+            writeln!(self.out, "return &obj, nil")?;
+            self.out.unindent();
+        }
+        writeln!(
+            self.out,
+            "default:\n\treturn nil, fmt.Errorf(\"Unknown variant name for {}: %s\", name)",
+            name
+        )?;
+        writeln!(self.out, "}}")?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        Ok(())
+    }
+
+    fn output_enum_visitor(
+        &mut self,
+        name: &str,
+        variants: &BTreeMap<u32, Named<VariantFormat>>,
+    ) -> Result<()> {
+        writeln!(self.out, "\ntype {}Visitor interface {{", name)?;
+        self.out.indent();
+        for variant in variants.values() {
+            let variant_name = variant.name.to_camel_case();
+            writeln!(
+                self.out,
+                "Visit{}(value *{}__{}) error",
+                variant_name, name, variant_name
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+
+        writeln!(
+            self.out,
+            "\nfunc Visit{0}(value {0}, visitor {0}Visitor) error {{",
+            name
+        )?;
+        self.out.indent();
+        if variants.is_empty() {
+            writeln!(self.out, "switch value.(type) {{")?;
+        } else {
+            writeln!(self.out, "switch v := value.(type) {{")?;
+        }
+        for variant in variants.values() {
+            let variant_name = variant.name.to_camel_case();
+            writeln!(
+                self.out,
+                "case *{}__{}:\n\treturn visitor.Visit{}(v)",
+                name, variant_name, variant_name
+            )?;
+        }
+        writeln!(
+            self.out,
+            "default:\n\treturn fmt.Errorf(\"Unknown variant for {}: %T\", value)",
+            name
+        )?;
+        writeln!(self.out, "}}")?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        Ok(())
+    }
+
+    fn output_json_helpers(&mut self, registry: &Registry) -> Result<()> {
+        let mut subtypes = BTreeMap::new();
+        for format in registry.values() {
+            format
+                .visit(&mut |f| {
+                    if !matches!(f, Format::TypeName(_)) && self.needs_json_helper(f) {
+                        subtypes.insert(common::mangle_type(f), f.clone());
+                    }
+                    Ok(())
                 })
-                .collect(),
-            Enum(variants) => {
-                let variants = variants
+                .unwrap();
+        }
+        for (mangled_name, subtype) in &subtypes {
+            self.output_json_marshal_helper(mangled_name, subtype)?;
+            self.output_json_unmarshal_helper(mangled_name, subtype)?;
+        }
+        Ok(())
+    }
+
+    fn output_json_marshal_helper(&mut self, name: &str, format0: &Format) -> Result<()> {
+        use Format::*;
+
+        write!(
+            self.out,
+            "func marshal_json_{}(value {}) interface{{}} {{",
+            name,
+            self.quote_type(format0)
+        )?;
+        self.out.indent();
+        match format0 {
+            Unit => write!(self.out, "\nreturn nil")?,
+            Char => write!(self.out, "\nreturn string(value)")?,
+            // serde_json encodes bytes as arrays of numbers.
+            Bytes => write!(
+                self.out,
+                r#"
+items := make([]int, len(value))
+for i, item := range value {{ items[i] = int(item) }}
+return items"#
+            )?,
+            Option(format) => write!(
+                self.out,
+                r#"
+if value == nil {{ return nil }}
+return {}"#,
+                self.quote_json_value("*value", format)
+            )?,
+            Seq(format)
+            | TupleArray {
+                content: format, ..
+            } => write!(
+                self.out,
+                r#"
+items := make([]interface{{}}, len(value))
+for i, item := range value {{ items[i] = {} }}
+return items"#,
+                self.quote_json_value("item", format)
+            )?,
+            Map { key, value } => write!(
+                self.out,
+                r#"
+entries := make(map[{}]interface{{}}, len(value))
+for key, item := range value {{ entries[key] = {} }}
+return entries"#,
+                self.quote_type(key),
+                self.quote_json_value("item", value)
+            )?,
+            Tuple(formats) => write!(
+                self.out,
+                "\nreturn []interface{{}}{{{}}}",
+                formats
                     .iter()
-                    .map(|(i, f)| {
-                        (
-                            *i,
-                            Named {
-                                name: f.name.to_camel_case(),
-                                value: f.value.clone(),
-                            },
+                    .enumerate()
+                    .map(|(i, f)| self.quote_json_value(&format!("value.Field{}", i), f))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?,
+            _ => panic!("unexpected case"),
+        }
+        self.out.unindent();
+        writeln!(self.out, "\n}}\n")?;
+        Ok(())
+    }
+
+    fn output_json_unmarshal_helper(&mut self, name: &str, format0: &Format) -> Result<()> {
+        use Format::*;
+
+        write!(
+            self.out,
+            "func unmarshal_json_{}(data []byte) ({}, error) {{",
+            name,
+            self.quote_type(format0),
+        )?;
+        self.out.indent();
+        writeln!(self.out, "\nvar obj {}", self.quote_type(format0))?;
+        match format0 {
+            Unit => (),
+            Char => write!(
+                self.out,
+                r#"var text string
+if err := json.Unmarshal(data, &text); err != nil {{ return obj, err }}
+runes := []rune(text)
+if len(runes) != 1 {{ return obj, fmt.Errorf("Expected a single character, got %q", text) }}
+obj = runes[0]
+"#
+            )?,
+            Bytes => write!(
+                self.out,
+                r#"var items []uint16
+if err := json.Unmarshal(data, &items); err != nil {{ return obj, err }}
+obj = make([]byte, len(items))
+for i, item := range items {{
+	if item > 255 {{ return obj, fmt.Errorf("Invalid byte value: %d", item) }}
+	obj[i] = byte(item)
+}}
+"#
+            )?,
+            Option(format) => write!(
+                self.out,
+                r#"if string(data) == "null" {{ return obj, nil }}
+obj = new({})
+{}
+"#,
+                self.quote_type(format),
+                self.quote_json_decode(format, "data", "*obj", "return obj, ")
+            )?,
+            Seq(format) => write!(
+                self.out,
+                r#"var items []json.RawMessage
+if err := json.Unmarshal(data, &items); err != nil {{ return obj, err }}
+obj = make({}, len(items))
+for i, item := range items {{
+	{}
+}}
+"#,
+                self.quote_type(format0),
+                self.quote_json_decode(format, "item", "obj[i]", "return obj, ")
+            )?,
+            TupleArray { content, size } => write!(
+                self.out,
+                r#"var items []json.RawMessage
+if err := json.Unmarshal(data, &items); err != nil {{ return obj, err }}
+if len(items) != {0} {{ return obj, fmt.Errorf("Expected {0} items, got %d", len(items)) }}
+for i, item := range items {{
+	{1}
+}}
+"#,
+                size,
+                self.quote_json_decode(content, "item", "obj[i]", "return obj, ")
+            )?,
+            Map { key, value } => {
+                // `encoding/json` only supports strings and integers as keys.
+                let decode_key = match key.as_ref() {
+                    Str => "key = name".to_string(),
+                    _ => "if err := json.Unmarshal([]byte(name), &key); err != nil { return obj, err }"
+                        .to_string(),
+                };
+                write!(
+                    self.out,
+                    r#"var entries map[string]json.RawMessage
+if err := json.Unmarshal(data, &entries); err != nil {{ return obj, err }}
+obj = make({}, len(entries))
+for name, item := range entries {{
+	var key {}
+	{}
+	var value {}
+	{}
+	obj[key] = value
+}}
+"#,
+                    self.quote_type(format0),
+                    self.quote_type(key),
+                    decode_key,
+                    self.quote_type(value),
+                    self.quote_json_decode(value, "item", "value", "return obj, ")
+                )?
+            }
+            Tuple(formats) => {
+                write!(
+                    self.out,
+                    r#"var items []json.RawMessage
+if err := json.Unmarshal(data, &items); err != nil {{ return obj, err }}
+if len(items) != {0} {{ return obj, fmt.Errorf("Expected {0} items, got %d", len(items)) }}
+"#,
+                    formats.len()
+                )?;
+                for (i, format) in formats.iter().enumerate() {
+                    writeln!(
+                        self.out,
+                        "{}",
+                        self.quote_json_decode(
+                            format,
+                            &format!("items[{}]", i),
+                            &format!("obj.Field{}", i),
+                            "return obj, "
                         )
-                    })
-                    .collect();
-                self.output_enum_container(name, &variants)?;
+                    )?;
+                }
+            }
+            _ => panic!("unexpected case"),
+        }
+        writeln!(self.out, "return obj, nil")?;
+        self.out.unindent();
+        writeln!(self.out, "}}\n")?;
+        Ok(())
+    }
+
+    fn output_container(&mut self, name: &str, format: &ContainerFormat) -> Result<()> {
+        use ContainerFormat::*;
+        // Containers are laid out like enum variants.
+        let variant = match format {
+            UnitStruct => VariantFormat::Unit,
+            NewTypeStruct(format) => VariantFormat::NewType(format.clone()),
+            TupleStruct(formats) => VariantFormat::Tuple(formats.clone()),
+            Struct(fields) => VariantFormat::Struct(fields.clone()),
+            Enum(variants) => {
+                self.output_enum_container(name, variants)?;
                 return Ok(());
             }
+            Generic { .. } => return Err(Error::UnexpectedGeneric(name.to_string())),
         };
-        self.output_struct_or_variant_container(None, None, name, &fields)
+        match &variant {
+            VariantFormat::NewType(format) if !Self::is_wrapped_new_type(format) => {
+                self.output_struct_or_variant_new_type_container(None, None, name, format)?;
+            }
+            _ => {
+                let fields = Self::variant_fields(&variant);
+                self.output_struct_or_variant_container(None, None, name, &fields)?;
+            }
+        }
+        if self.generator.json {
+            self.output_container_json_methods(name, &variant)?;
+        }
+        self.output_fingerprint(name)?;
+        // Custom code
+        self.output_custom_code(name)?;
+        Ok(())
     }
 }
 
@@ -965,6 +1837,9 @@ switch index {{"#,
 pub struct Installer {
     install_dir: PathBuf,
     serde_module_path: Option<String>,
+    json: bool,
+    enum_visitors: bool,
+    generic_helpers: bool,
 }
 
 impl Installer {
@@ -972,9 +1847,30 @@ impl Installer {
         Installer {
             install_dir,
             serde_module_path,
+            json: false,
+            enum_visitors: false,
+            generic_helpers: false,
         }
     }
 
+    /// See `CodeGenerator::with_json`.
+    pub fn with_json(mut self, json: bool) -> Self {
+        self.json = json;
+        self
+    }
+
+    /// See `CodeGenerator::with_enum_visitors`.
+    pub fn with_enum_visitors(mut self, enum_visitors: bool) -> Self {
+        self.enum_visitors = enum_visitors;
+        self
+    }
+
+    /// See `CodeGenerator::with_generic_helpers`.
+    pub fn with_generic_helpers(mut self, generic_helpers: bool) -> Self {
+        self.generic_helpers = generic_helpers;
+        self
+    }
+
     fn runtime_installation_message(&self, name: &str) {
         eprintln!(
             "Not installing sources for published package {}{}",
@@ -988,7 +1884,7 @@ impl Installer {
 }
 
 impl crate::SourceInstaller for Installer {
-    type Error = Box<dyn std::error::Error>;
+    type Error = crate::Error;
 
     fn install_module(
         &self,
@@ -1000,7 +1896,10 @@ impl crate::SourceInstaller for Installer {
         let source_path = dir_path.join("lib.go");
         let mut file = std::fs::File::create(source_path)?;
 
-        let mut generator = CodeGenerator::new(config);
+        let mut generator = CodeGenerator::new(config)
+            .with_json(self.json)
+            .with_enum_visitors(self.enum_visitors)
+            .with_generic_helpers(self.generic_helpers);
         if let Some(path) = &self.serde_module_path {
             generator = generator.with_serde_module_path(path.clone());
         }
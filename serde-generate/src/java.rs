@@ -4,7 +4,7 @@
 use crate::{
     common,
     indent::{IndentConfig, IndentedWriter},
-    CodeGeneratorConfig, Encoding,
+    CodeGeneratorConfig, Encoding, Error,
 };
 use heck::CamelCase;
 use include_dir::include_dir as include_directory;
@@ -37,14 +37,15 @@ struct JavaEmitter<'a, T> {
     /// * We count multiplicities to allow inplace backtracking.
     /// * Names in the registry are assumed to never clash.
     current_reserved_names: HashMap<String, usize>,
+    /// Fingerprint of the container defined in the current file, if requested.
+    fingerprint: Option<u64>,
+    /// Type parameters of the generic container defined in the current file (if any).
+    type_parameters: Vec<String>,
 }
 
 impl<'a> CodeGenerator<'a> {
     /// Create a Java code generator for the given config.
     pub fn new(config: &'a CodeGeneratorConfig) -> Self {
-        if config.c_style_enums {
-            panic!("Java does not support generating c-style enums");
-        }
         let mut external_qualified_names = HashMap::new();
         for (namespace, names) in &config.external_definitions {
             for name in names {
@@ -65,7 +66,14 @@ impl<'a> CodeGenerator<'a> {
         &self,
         install_dir: std::path::PathBuf,
         registry: &Registry,
-    ) -> Result<()> {
+    ) -> crate::Result<()> {
+        if self.config.c_style_enums {
+            return Err(Error::UnsupportedOption {
+                language: "Java",
+                option: "c-style enums",
+            });
+        }
+        common::check_registry(registry)?;
         let current_namespace = self
             .config
             .module_name
@@ -79,8 +87,16 @@ impl<'a> CodeGenerator<'a> {
         }
         std::fs::create_dir_all(&dir_path)?;
 
+        let fingerprints = common::get_fingerprints(self.config, registry)?;
+        Self::check_fingerprint_names(registry, &fingerprints)?;
         for (name, format) in registry {
-            self.write_container_class(&dir_path, current_namespace.clone(), name, format)?;
+            self.write_container_class(
+                &dir_path,
+                current_namespace.clone(),
+                name,
+                format,
+                fingerprints.get(name).copied(),
+            )?;
         }
         if self.config.serialization {
             self.write_helper_class(&dir_path, current_namespace, registry)?;
@@ -88,12 +104,42 @@ impl<'a> CodeGenerator<'a> {
         Ok(())
     }
 
+    /// The constant `FINGERPRINT` is declared in the class of the container, next to its fields
+    /// and the classes of its variants.
+    fn check_fingerprint_names(
+        registry: &Registry,
+        fingerprints: &BTreeMap<String, u64>,
+    ) -> crate::Result<()> {
+        for name in fingerprints.keys() {
+            let clashing_name = match &registry[name] {
+                ContainerFormat::Struct(fields) => fields
+                    .iter()
+                    .map(|f| &f.name)
+                    .find(|name| *name == "FINGERPRINT"),
+                ContainerFormat::Enum(variants) => variants
+                    .values()
+                    .map(|v| &v.name)
+                    .find(|name| *name == "FINGERPRINT"),
+                _ => None,
+            };
+            if let Some(field) = clashing_name {
+                return Err(Error::ReservedName {
+                    container: name.clone(),
+                    field: field.clone(),
+                    reason: "for fingerprints in Java",
+                });
+            }
+        }
+        Ok(())
+    }
+
     fn write_container_class(
         &self,
         dir_path: &std::path::Path,
         current_namespace: Vec<String>,
         name: &str,
         format: &ContainerFormat,
+        fingerprint: Option<u64>,
     ) -> Result<()> {
         let mut file = std::fs::File::create(dir_path.join(name.to_string() + ".java"))?;
         let mut emitter = JavaEmitter {
@@ -101,6 +147,8 @@ impl<'a> CodeGenerator<'a> {
             generator: self,
             current_namespace,
             current_reserved_names: HashMap::new(),
+            fingerprint,
+            type_parameters: Vec::new(),
         };
 
         emitter.output_preamble()?;
@@ -119,6 +167,8 @@ impl<'a> CodeGenerator<'a> {
             generator: self,
             current_namespace,
             current_reserved_names: HashMap::new(),
+            fingerprint: None,
+            type_parameters: Vec::new(),
         };
 
         emitter.output_preamble()?;
@@ -226,10 +276,108 @@ where
                 size,
                 self.quote_type(content)
             ),
+            Parameter(name) => name.to_string(),
+            TypeApp { name, arguments } => format!(
+                "{}<{}>",
+                self.quote_qualified_name(name),
+                self.quote_types(arguments)
+            ),
             Variable(_) => panic!("unexpected value"),
         }
     }
 
+    /// Type parameters of a generic class, e.g. `<T0, T1>`.
+    fn quote_type_parameters(parameters: &[String]) -> String {
+        if parameters.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", parameters.join(", "))
+        }
+    }
+
+    /// Type parameters of a generic static method, e.g. `<T0, T1> `.
+    fn quote_method_type_parameters(parameters: &[String]) -> String {
+        if parameters.is_empty() {
+            String::new()
+        } else {
+            format!("<{}> ", parameters.join(", "))
+        }
+    }
+
+    /// Extra arguments of generic serialization methods: one `com.novi.serde.Serialize`
+    /// function per type parameter.
+    fn quote_serialize_parameters(parameters: &[String]) -> String {
+        parameters
+            .iter()
+            .map(|p| format!(", com.novi.serde.Serialize<{0}> serialize{0}", p))
+            .collect()
+    }
+
+    /// Extra arguments of generic deserialization methods: one `com.novi.serde.Deserialize`
+    /// function per type parameter.
+    fn quote_deserialize_parameters(parameters: &[String]) -> String {
+        parameters
+            .iter()
+            .map(|p| format!(", com.novi.serde.Deserialize<{0}> deserialize{0}", p))
+            .collect()
+    }
+
+    /// Serialization functions passed along to generic methods for the given type parameters.
+    fn quote_serialize_arguments(parameters: &[String]) -> String {
+        parameters
+            .iter()
+            .map(|p| format!(", serialize{}", p))
+            .collect()
+    }
+
+    /// Deserialization functions passed along to generic methods for the given type parameters.
+    fn quote_deserialize_arguments(parameters: &[String]) -> String {
+        parameters
+            .iter()
+            .map(|p| format!(", deserialize{}", p))
+            .collect()
+    }
+
+    /// Serialization function (of type `com.novi.serde.Serialize`) for a type argument.
+    fn quote_serialize_function(&self, format: &Format) -> String {
+        if let Format::Parameter(name) = format {
+            return format!("serialize{}", name);
+        }
+        let parameters = common::type_parameters(format);
+        let helpers = self.quote_qualified_name("TraitHelpers");
+        let mangled_name = common::mangle_type(format);
+        if parameters.is_empty() {
+            format!("{}::serialize_{}", helpers, mangled_name)
+        } else {
+            format!(
+                "(v, s) -> {}.serialize_{}(v, s{})",
+                helpers,
+                mangled_name,
+                Self::quote_serialize_arguments(&parameters)
+            )
+        }
+    }
+
+    /// Deserialization function (of type `com.novi.serde.Deserialize`) for a type argument.
+    fn quote_deserialize_function(&self, format: &Format) -> String {
+        if let Format::Parameter(name) = format {
+            return format!("deserialize{}", name);
+        }
+        let parameters = common::type_parameters(format);
+        let helpers = self.quote_qualified_name("TraitHelpers");
+        let mangled_name = common::mangle_type(format);
+        if parameters.is_empty() {
+            format!("{}::deserialize_{}", helpers, mangled_name)
+        } else {
+            format!(
+                "d -> {}.deserialize_{}(d{})",
+                helpers,
+                mangled_name,
+                Self::quote_deserialize_arguments(&parameters)
+            )
+        }
+    }
+
     fn enter_class(&mut self, name: &str, reserved_subclass_names: &[&str]) {
         self.out.indent();
         self.current_namespace.push(name.to_string());
@@ -270,6 +418,14 @@ where
                     if Self::needs_helper(f) {
                         subtypes.insert(common::mangle_type(f), f.clone());
                     }
+                    // Type arguments of generic classes are passed as helper functions.
+                    if let Format::TypeApp { arguments, .. } = f {
+                        for argument in arguments {
+                            if !matches!(argument, Format::Parameter(_)) {
+                                subtypes.insert(common::mangle_type(argument), argument.clone());
+                            }
+                        }
+                    }
                     Ok(())
                 })
                 .unwrap();
@@ -314,11 +470,21 @@ where
             Char => format!("serializer.serialize_char({});", value),
             Str => format!("serializer.serialize_str({});", value),
             Bytes => format!("serializer.serialize_bytes({});", value),
+            Parameter(name) => format!("serialize{}.serialize({}, serializer);", name, value),
+            TypeApp { arguments, .. } => format!(
+                "{}.serialize(serializer{});",
+                value,
+                arguments
+                    .iter()
+                    .map(|f| format!(", {}", self.quote_serialize_function(f)))
+                    .collect::<String>()
+            ),
             _ => format!(
-                "{}.serialize_{}({}, serializer);",
+                "{}.serialize_{}({}, serializer{});",
                 self.quote_qualified_name("TraitHelpers"),
                 common::mangle_type(format),
-                value
+                value,
+                Self::quote_serialize_arguments(&common::type_parameters(format))
             ),
         }
     }
@@ -347,10 +513,20 @@ where
             Char => "deserializer.deserialize_char()".to_string(),
             Str => "deserializer.deserialize_str()".to_string(),
             Bytes => "deserializer.deserialize_bytes()".to_string(),
+            Parameter(name) => format!("deserialize{}.deserialize(deserializer)", name),
+            TypeApp { name, arguments } => format!(
+                "{}.deserialize(deserializer{})",
+                self.quote_qualified_name(name),
+                arguments
+                    .iter()
+                    .map(|f| format!(", {}", self.quote_deserialize_function(f)))
+                    .collect::<String>()
+            ),
             _ => format!(
-                "{}.deserialize_{}(deserializer)",
+                "{}.deserialize_{}(deserializer{})",
                 self.quote_qualified_name("TraitHelpers"),
                 common::mangle_type(format),
+                Self::quote_deserialize_arguments(&common::type_parameters(format))
             ),
         }
     }
@@ -358,11 +534,14 @@ where
     fn output_serialization_helper(&mut self, name: &str, format0: &Format) -> Result<()> {
         use Format::*;
 
+        let parameters = common::type_parameters(format0);
         write!(
             self.out,
-            "static void serialize_{}({} value, com.novi.serde.Serializer serializer) throws com.novi.serde.SerializationError {{",
+            "static {}void serialize_{}({} value, com.novi.serde.Serializer serializer{}) throws com.novi.serde.SerializationError {{",
+            Self::quote_method_type_parameters(&parameters),
             name,
-            self.quote_type(format0)
+            self.quote_type(format0),
+            Self::quote_serialize_parameters(&parameters)
         )?;
         self.out.indent();
         match format0 {
@@ -441,7 +620,13 @@ for ({1} item : value) {{
                 )?;
             }
 
-            _ => panic!("unexpected case"),
+            _ => {
+                write!(
+                    self.out,
+                    "\n{}\n",
+                    self.quote_serialize_value("value", format0)
+                )?;
+            }
         }
         self.out.unindent();
         writeln!(self.out, "}}\n")
@@ -450,12 +635,15 @@ for ({1} item : value) {{
     fn output_deserialization_helper(&mut self, name: &str, format0: &Format) -> Result<()> {
         use Format::*;
 
+        let parameters = common::type_parameters(format0);
         write!(
-        self.out,
-        "static {} deserialize_{}(com.novi.serde.Deserializer deserializer) throws com.novi.serde.DeserializationError {{",
-        self.quote_type(format0),
-        name,
-    )?;
+            self.out,
+            "static {}{} deserialize_{}(com.novi.serde.Deserializer deserializer{}) throws com.novi.serde.DeserializationError {{",
+            Self::quote_method_type_parameters(&parameters),
+            self.quote_type(format0),
+            name,
+            Self::quote_deserialize_parameters(&parameters)
+        )?;
         self.out.indent();
         match format0 {
             Option(format) => {
@@ -552,7 +740,9 @@ return obj;
                 )?;
             }
 
-            _ => panic!("unexpected case"),
+            _ => {
+                write!(self.out, "\nreturn {};\n", self.quote_deserialize(format0))?;
+            }
         }
         self.out.unindent();
         writeln!(self.out, "}}\n")
@@ -604,18 +794,19 @@ return obj;
         name: &str,
         fields: &[Named<Format>],
     ) -> Result<()> {
+        let generics = Self::quote_type_parameters(&self.type_parameters);
         // Beginning of class
         writeln!(self.out)?;
         if let Some(base) = variant_base {
             self.output_comment(name)?;
             writeln!(
                 self.out,
-                "public static final class {} extends {} {{",
-                name, base
+                "public static final class {0}{2} extends {1}{2} {{",
+                name, base, generics
             )?;
         } else {
             self.output_comment(name)?;
-            writeln!(self.out, "public final class {} {{", name)?;
+            writeln!(self.out, "public final class {}{} {{", name, generics)?;
         }
         let reserved_names = &["Builder"];
         self.enter_class(name, reserved_names);
@@ -660,7 +851,8 @@ return obj;
         if self.generator.config.serialization {
             writeln!(
                 self.out,
-                "\npublic void serialize(com.novi.serde.Serializer serializer) throws com.novi.serde.SerializationError {{",
+                "\npublic void serialize(com.novi.serde.Serializer serializer{}) throws com.novi.serde.SerializationError {{",
+                Self::quote_serialize_parameters(&self.type_parameters),
             )?;
             self.out.indent();
             writeln!(self.out, "serializer.increase_container_depth();")?;
@@ -686,22 +878,28 @@ return obj;
         }
         // Deserialize (struct) or Load (variant)
         if self.generator.config.serialization {
-            if variant_index.is_none() {
-                writeln!(
-                    self.out,
-                    "\npublic static {} deserialize(com.novi.serde.Deserializer deserializer) throws com.novi.serde.DeserializationError {{",
-                    name,
-                )?;
+            let (modifiers, method) = if variant_index.is_none() {
+                ("public static", "deserialize")
             } else {
-                writeln!(
-                    self.out,
-                    "\nstatic {} load(com.novi.serde.Deserializer deserializer) throws com.novi.serde.DeserializationError {{",
-                    name,
-                )?;
-            }
+                ("static", "load")
+            };
+            writeln!(
+                self.out,
+                "\n{} {}{}{} {}(com.novi.serde.Deserializer deserializer{}) throws com.novi.serde.DeserializationError {{",
+                modifiers,
+                Self::quote_method_type_parameters(&self.type_parameters),
+                name,
+                generics,
+                method,
+                Self::quote_deserialize_parameters(&self.type_parameters),
+            )?;
             self.out.indent();
             writeln!(self.out, "deserializer.increase_container_depth();")?;
-            writeln!(self.out, "Builder builder = new Builder();")?;
+            if self.type_parameters.is_empty() {
+                writeln!(self.out, "Builder builder = new Builder();")?;
+            } else {
+                writeln!(self.out, "Builder{} builder = new Builder<>();", generics)?;
+            }
             for field in fields {
                 writeln!(
                     self.out,
@@ -730,8 +928,9 @@ return obj;
 if (this == obj) return true;
 if (obj == null) return false;
 if (getClass() != obj.getClass()) return false;
-{0} other = ({0}) obj;"#,
+{0}{1} other = ({0}{1}) obj;"#,
             name,
+            Self::quote_type_parameters(&vec!["?".to_string(); self.type_parameters.len()]),
         )?;
         for field in fields {
             writeln!(
@@ -759,6 +958,10 @@ if (getClass() != obj.getClass()) return false;
         writeln!(self.out, "}}")?;
         // Builder
         self.output_struct_or_variant_container_builder(name, fields)?;
+        // Fingerprint
+        if variant_base.is_none() {
+            self.output_fingerprint()?;
+        }
         // Custom code
         self.output_custom_code()?;
         // End of class
@@ -766,14 +969,31 @@ if (getClass() != obj.getClass()) return false;
         writeln!(self.out, "}}")
     }
 
+    fn output_fingerprint(&mut self) -> Result<()> {
+        if let Some(fingerprint) = self.fingerprint {
+            writeln!(
+                self.out,
+                "\npublic static final long FINGERPRINT = {:#018x}L;",
+                fingerprint
+            )?;
+        }
+        Ok(())
+    }
+
     fn output_struct_or_variant_container_builder(
         &mut self,
         name: &str,
         fields: &[Named<Format>],
     ) -> Result<()> {
+        let generics = Self::quote_type_parameters(&self.type_parameters);
+        let diamond = if self.type_parameters.is_empty() {
+            ""
+        } else {
+            "<>"
+        };
         // Beginning of builder class
         writeln!(self.out)?;
-        writeln!(self.out, "public static final class Builder {{")?;
+        writeln!(self.out, "public static final class Builder{} {{", generics)?;
         let reserved_names = &[];
         self.enter_class("Builder", reserved_names);
         // Fields
@@ -791,8 +1011,8 @@ if (getClass() != obj.getClass()) return false;
         // Finalization
         writeln!(
             self.out,
-            r#"public {0} build() {{
-    return new {0}({1}
+            r#"public {0}{2} build() {{
+    return new {0}{3}({1}
     );
 }}"#,
             name,
@@ -800,7 +1020,9 @@ if (getClass() != obj.getClass()) return false;
                 .iter()
                 .map(|f| format!("\n        {}", f.name))
                 .collect::<Vec<_>>()
-                .join(",")
+                .join(","),
+            generics,
+            diamond,
         )?;
         // Custom code
         self.output_custom_code()?;
@@ -814,9 +1036,10 @@ if (getClass() != obj.getClass()) return false;
         name: &str,
         variants: &BTreeMap<u32, Named<VariantFormat>>,
     ) -> Result<()> {
+        let generics = Self::quote_type_parameters(&self.type_parameters);
         writeln!(self.out)?;
         self.output_comment(name)?;
-        writeln!(self.out, "public abstract class {} {{", name)?;
+        writeln!(self.out, "public abstract class {}{} {{", name, generics)?;
         let reserved_names = variants
             .values()
             .map(|v| v.name.as_str())
@@ -825,12 +1048,16 @@ if (getClass() != obj.getClass()) return false;
         if self.generator.config.serialization {
             writeln!(
                 self.out,
-                "\nabstract public void serialize(com.novi.serde.Serializer serializer) throws com.novi.serde.SerializationError;"
+                "\nabstract public void serialize(com.novi.serde.Serializer serializer{}) throws com.novi.serde.SerializationError;",
+                Self::quote_serialize_parameters(&self.type_parameters),
             )?;
             write!(
                 self.out,
-                "\npublic static {} deserialize(com.novi.serde.Deserializer deserializer) throws com.novi.serde.DeserializationError {{",
-                name
+                "\npublic static {}{}{} deserialize(com.novi.serde.Deserializer deserializer{}) throws com.novi.serde.DeserializationError {{",
+                Self::quote_method_type_parameters(&self.type_parameters),
+                name,
+                generics,
+                Self::quote_deserialize_parameters(&self.type_parameters),
             )?;
             self.out.indent();
             writeln!(
@@ -843,8 +1070,10 @@ switch (index) {{"#,
             for (index, variant) in variants {
                 writeln!(
                     self.out,
-                    "case {}: return {}.load(deserializer);",
-                    index, variant.name,
+                    "case {}: return {}.load(deserializer{});",
+                    index,
+                    variant.name,
+                    Self::quote_deserialize_arguments(&self.type_parameters),
                 )?;
             }
             writeln!(
@@ -863,6 +1092,7 @@ switch (index) {{"#,
             }
         }
 
+        self.output_fingerprint()?;
         self.output_variants(name, variants)?;
         self.leave_class(&reserved_names);
         writeln!(self.out, "}}\n")
@@ -872,13 +1102,15 @@ switch (index) {{"#,
         writeln!(
             self.out,
             r#"
-public byte[] {0}Serialize() throws com.novi.serde.SerializationError {{
+public byte[] {0}Serialize({2}) throws com.novi.serde.SerializationError {{
     com.novi.serde.Serializer serializer = new com.novi.{0}.{1}Serializer();
-    serialize(serializer);
+    serialize(serializer{3});
     return serializer.get_bytes();
 }}"#,
             encoding.name(),
-            encoding.name().to_camel_case()
+            encoding.name().to_camel_case(),
+            Self::quote_serialize_parameters(&self.type_parameters).trim_start_matches(", "),
+            Self::quote_serialize_arguments(&self.type_parameters),
         )
     }
 
@@ -890,12 +1122,12 @@ public byte[] {0}Serialize() throws com.novi.serde.SerializationError {{
         writeln!(
             self.out,
             r#"
-public static {0} {1}Deserialize(byte[] input) throws com.novi.serde.DeserializationError {{
+public static {3}{0}{4} {1}Deserialize(byte[] input{5}) throws com.novi.serde.DeserializationError {{
     if (input == null) {{
          throw new com.novi.serde.DeserializationError("Cannot deserialize null array");
     }}
     com.novi.serde.Deserializer deserializer = new com.novi.{1}.{2}Deserializer(input);
-    {0} value = deserialize(deserializer);
+    {0}{4} value = deserialize(deserializer{6});
     if (deserializer.get_buffer_offset() < input.length) {{
          throw new com.novi.serde.DeserializationError("Some input bytes were not read");
     }}
@@ -903,7 +1135,11 @@ public static {0} {1}Deserialize(byte[] input) throws com.novi.serde.Deserializa
 }}"#,
             name,
             encoding.name(),
-            encoding.name().to_camel_case()
+            encoding.name().to_camel_case(),
+            Self::quote_method_type_parameters(&self.type_parameters),
+            Self::quote_type_parameters(&self.type_parameters),
+            Self::quote_deserialize_parameters(&self.type_parameters),
+            Self::quote_deserialize_arguments(&self.type_parameters),
         )
     }
 
@@ -928,6 +1164,10 @@ public static {0} {1}Deserialize(byte[] input) throws com.novi.serde.Deserializa
                 self.output_enum_container(name, variants)?;
                 return Ok(());
             }
+            Generic { parameters, format } => {
+                self.type_parameters = parameters.clone();
+                return self.output_container(name, format);
+            }
         };
         self.output_struct_or_variant_container(None, None, name, &fields)
     }
@@ -943,11 +1183,7 @@ impl Installer {
         Installer { install_dir }
     }
 
-    fn install_runtime(
-        &self,
-        source_dir: include_dir::Dir,
-        path: &str,
-    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    fn install_runtime(&self, source_dir: include_dir::Dir, path: &str) -> crate::Result<()> {
         let dir_path = self.install_dir.join(path);
         std::fs::create_dir_all(&dir_path)?;
         for entry in source_dir.files() {
@@ -959,7 +1195,7 @@ impl Installer {
 }
 
 impl crate::SourceInstaller for Installer {
-    type Error = Box<dyn std::error::Error>;
+    type Error = crate::Error;
 
     fn install_module(
         &self,
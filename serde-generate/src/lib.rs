@@ -12,14 +12,26 @@
 //!
 //! * C++ 17
 //! * Java 8
-//! * Python 3 (requires numpy >= 1.20.1)
+//! * Python 3 (requires numpy >= 1.20.1; optional native accelerator, see `serde_binary/build_speedups.py`)
 //! * Rust 2018
-//! * Go >= 1.14
-//! * C# (NetCoreApp >= 2.1)
+//! * Go >= 1.14 (optional `encoding/json` support following the layout of serde_json; optional generic helpers from the `generics` runtime module with Go >= 1.18)
+//! * C# (NetCoreApp >= 2.1; optional records, nullable annotations and `System.Text.Json` converters with .NET 6)
 //!
 //! The following languages are partially supported and still considered under development:
 //!
 //! * TypeScript > 3.2 (make sure to enable `esnext.BigInt` and `dom` at tsconfig.json -> lib)
+//! * C 99 (header-only, without dynamic memory allocation)
+//!
+//! Generic containers (see `TracerConfig::recognize_generics` in `serde-reflection`) are
+//! emitted as generic definitions in Rust, Java, C# and TypeScript, and as class templates in C++.
+//! In Java, C# and TypeScript, the (de)serialization of type parameters is passed as extra
+//! function arguments, e.g. `Tree.deserialize(deserializer, deserializeT0)`. Python, Go, C, and
+//! C# with JSON converters receive one definition per instance, named after
+//! `serde_reflection::mangled_name`.
+//!
+//! With `CodeGeneratorConfig::with_fingerprints`, the fingerprint of each non-generic container
+//! (see `serde_reflection::fingerprint`) is emitted as a constant, e.g. `Foo::FINGERPRINT` in Rust,
+//! C++, Java, Python, and C#, `FooFingerprint` in Go and TypeScript, and `<module>_Foo_FINGERPRINT` in C.
 //!
 //! ## Supported Encodings
 //!
@@ -32,6 +44,20 @@
 //! * [BCS](https://github.com/diem/bcs) (short for Binary Canonical Serialization, the main format used
 //!   in the [Diem blockchain](https://github.com/diem/diem)).
 //!
+//! The module [`conformance`] helps check that the generated code agrees with Rust on
+//! a given set of samples: for each target language whose toolchain is installed, the samples
+//! are decoded then re-encoded, and the results are compared with the original bytes.
+//! ```no_run
+//! # use serde_generate::{conformance::Harness, Encoding};
+//! # fn check(registry: &serde_reflection::Registry, samples: &[Vec<u8>]) -> serde_generate::Result<()> {
+//! let report = Harness::new(registry, "MyStruct".to_string(), Encoding::Bcs).run(samples)?;
+//! assert!(report.is_success(), "{}", report);
+//! # Ok(())
+//! # }
+//! ```
+//! The harness supports all the target languages. Languages whose toolchain or runtime
+//! dependencies (e.g. `numpy` for Python) are missing are reported as skipped.
+//!
 //! ## Quick Start with Python and Bincode
 //!
 //! In the following example, we transfer a `Test` value from Rust to Python using [`bincode`](https://docs.rs/bincode/1.3.1/bincode/).
@@ -46,7 +72,7 @@
 //!     b: (u32, u32),
 //! }
 //!
-//! # fn main() -> Result<(), std::io::Error> {
+//! # fn main() -> Result<(), serde_generate::Error> {
 //! // Obtain the Serde format of `Test`. (In practice, formats are more likely read from a file.)
 //! let mut tracer = Tracer::new(TracerConfig::default());
 //! tracer.trace_simple_type::<Test>().unwrap();
@@ -110,6 +136,58 @@
 //!
 //! See the help message of the tool with `--help` for more options.
 //!
+//! Options may also be read from a YAML configuration file with `--config`. Besides the options
+//! of the command line, the file gives access to external definitions, comments, custom code,
+//! and builder options such as the derive macros of the Rust generator:
+//! ```yaml
+//! input: test.yaml
+//! language: rust
+//! module_name: test
+//! target_source_dir: generated
+//! comments:
+//!   test.MyStruct: "Some documentation"
+//! rust:
+//!   derive_macros: ["Clone", "Debug", "PartialEq"]
+//! ```
+//! Paths are relative to the configuration file. Command-line arguments take precedence: boolean
+//! flags accept an optional value, e.g. `--use-json=false` turns off `json` in the file.
+//!
+//! A configuration file may also list several `targets`, e.g. to generate code in several languages
+//! from the same formats. Each target is merged into the rest of the file:
+//! ```yaml
+//! input: test.yaml
+//! module_name: test
+//! with_runtimes: [serde, bcs]
+//! targets:
+//!   - language: python3
+//!     target_source_dir: generated/python
+//!   - language: go
+//!     target_source_dir: generated/go
+//!     go:
+//!       json: true
+//! ```
+//! Errors are reported for each target and do not prevent the generation of the other targets.
+//! The options `--language`, `--target-source-dir`, `--module-name` and `--with-runtimes` are
+//! rejected when several targets are listed.
+//!
+//! The exit code of `serdegen` is 2 for invalid arguments or configuration files, 3 for input files
+//! that cannot be read or parsed, and 4 when code generation fails (e.g. for incomplete formats).
+//!
+//! To publish only a part of the formats under different names, the subcommand `transform` keeps the
+//! given roots and their dependencies, then renames containers, fields, and variants (see also the
+//! module [`transform`]):
+//! ```bash
+//! cargo run -p serde-generate -- transform test.yaml --root MyStruct --rename MyStruct=TheirStruct --rename-field MyStruct.x=y > their.yaml
+//! ```
+//!
+//! The subcommand `check` reports the problems of the formats that would break generated code up
+//! front, e.g. reserved words used as field names, names that clash with generated helper classes,
+//! recursive containers without finite values, or floats used with BCS (see also the module [`lint`]).
+//! The exit code is 1 if problems are found:
+//! ```bash
+//! cargo run -p serde-generate -- check test.yaml --language java --language python3 --with-runtimes bcs
+//! ```
+//!
 //! Note: Outside of this repository, you may install the tool with `cargo install serde-generate` then use `$HOME/.cargo/bin/serdegen`.
 
 /// Dependency analysis and topological sort for Serde formats.
@@ -117,6 +195,8 @@ pub mod analyzer;
 /// Utility function to generate indented text
 pub mod indent;
 
+/// Support for code-generation in C
+pub mod c;
 /// Support for code-generation in C++
 pub mod cpp;
 /// Support for code-generation in C#
@@ -132,6 +212,16 @@ pub mod rust;
 /// Support for code-generation in TypeScript/JavaScript
 pub mod typescript;
 
+/// Cross-language conformance tests for the generated code.
+pub mod conformance;
+/// Extraction, pruning, and renaming of registries.
+pub mod transform;
+
+/// Detection of registry problems that would break generated code.
+pub mod lint;
+/// Random values and targeted invalid inputs following the formats of a registry.
+pub mod samples;
+
 #[doc(hidden)]
 /// Utility functions to help testing code generators.
 pub mod test_utils;
@@ -140,5 +230,8 @@ pub mod test_utils;
 mod common;
 /// Common configuration objects and traits used in public APIs.
 mod config;
+/// Error type used in public APIs.
+mod error;
 
 pub use config::*;
+pub use error::{Error, Result};
@@ -0,0 +1,788 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::Encoding;
+use heck::CamelCase;
+use serde_reflection::{ContainerFormat, Format, FormatHolder, Named, Registry, VariantFormat};
+use std::collections::{BTreeMap, BTreeSet};
+
+type VariantFormats = BTreeMap<u32, Named<VariantFormat>>;
+
+/// Target languages of the code generators.
+#[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq)]
+pub enum Language {
+    C,
+    Cpp,
+    CSharp,
+    Go,
+    Java,
+    Python3,
+    Rust,
+    TypeScript,
+}
+
+impl Language {
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::C => "C",
+            Language::Cpp => "C++",
+            Language::CSharp => "C#",
+            Language::Go => "Go",
+            Language::Java => "Java",
+            Language::Python3 => "Python",
+            Language::Rust => "Rust",
+            Language::TypeScript => "TypeScript",
+        }
+    }
+
+    /// Reserved words that cannot be used as identifiers.
+    pub(crate) fn keywords(self) -> &'static [&'static str] {
+        match self {
+            Language::C => &[
+                "auto",
+                "break",
+                "case",
+                "char",
+                "const",
+                "continue",
+                "default",
+                "do",
+                "double",
+                "else",
+                "enum",
+                "extern",
+                "float",
+                "for",
+                "goto",
+                "if",
+                "inline",
+                "int",
+                "long",
+                "register",
+                "restrict",
+                "return",
+                "short",
+                "signed",
+                "sizeof",
+                "static",
+                "struct",
+                "switch",
+                "typedef",
+                "union",
+                "unsigned",
+                "void",
+                "volatile",
+                "while",
+                "_Bool",
+                "_Complex",
+                "_Imaginary",
+            ],
+            Language::Cpp => &[
+                "alignas",
+                "alignof",
+                "and",
+                "and_eq",
+                "asm",
+                "auto",
+                "bitand",
+                "bitor",
+                "bool",
+                "break",
+                "case",
+                "catch",
+                "char",
+                "char16_t",
+                "char32_t",
+                "class",
+                "compl",
+                "const",
+                "const_cast",
+                "constexpr",
+                "continue",
+                "decltype",
+                "default",
+                "delete",
+                "do",
+                "double",
+                "dynamic_cast",
+                "else",
+                "enum",
+                "explicit",
+                "export",
+                "extern",
+                "false",
+                "float",
+                "for",
+                "friend",
+                "goto",
+                "if",
+                "inline",
+                "int",
+                "long",
+                "mutable",
+                "namespace",
+                "new",
+                "noexcept",
+                "not",
+                "not_eq",
+                "nullptr",
+                "operator",
+                "or",
+                "or_eq",
+                "private",
+                "protected",
+                "public",
+                "register",
+                "reinterpret_cast",
+                "return",
+                "short",
+                "signed",
+                "sizeof",
+                "static",
+                "static_assert",
+                "static_cast",
+                "struct",
+                "switch",
+                "template",
+                "this",
+                "thread_local",
+                "throw",
+                "true",
+                "try",
+                "typedef",
+                "typeid",
+                "typename",
+                "union",
+                "unsigned",
+                "using",
+                "virtual",
+                "void",
+                "volatile",
+                "wchar_t",
+                "while",
+                "xor",
+                "xor_eq",
+            ],
+            Language::CSharp => &[
+                "abstract",
+                "as",
+                "base",
+                "bool",
+                "break",
+                "byte",
+                "case",
+                "catch",
+                "char",
+                "checked",
+                "class",
+                "const",
+                "continue",
+                "decimal",
+                "default",
+                "delegate",
+                "do",
+                "double",
+                "else",
+                "enum",
+                "event",
+                "explicit",
+                "extern",
+                "false",
+                "finally",
+                "fixed",
+                "float",
+                "for",
+                "foreach",
+                "goto",
+                "if",
+                "implicit",
+                "in",
+                "int",
+                "interface",
+                "internal",
+                "is",
+                "lock",
+                "long",
+                "namespace",
+                "new",
+                "null",
+                "object",
+                "operator",
+                "out",
+                "override",
+                "params",
+                "private",
+                "protected",
+                "public",
+                "readonly",
+                "ref",
+                "return",
+                "sbyte",
+                "sealed",
+                "short",
+                "sizeof",
+                "stackalloc",
+                "static",
+                "string",
+                "struct",
+                "switch",
+                "this",
+                "throw",
+                "true",
+                "try",
+                "typeof",
+                "uint",
+                "ulong",
+                "unchecked",
+                "unsafe",
+                "ushort",
+                "using",
+                "virtual",
+                "void",
+                "volatile",
+                "while",
+            ],
+            Language::Go => &[
+                "break",
+                "case",
+                "chan",
+                "const",
+                "continue",
+                "default",
+                "defer",
+                "else",
+                "fallthrough",
+                "for",
+                "func",
+                "go",
+                "goto",
+                "if",
+                "import",
+                "interface",
+                "map",
+                "package",
+                "range",
+                "return",
+                "select",
+                "struct",
+                "switch",
+                "type",
+                "var",
+            ],
+            Language::Java => &[
+                "abstract",
+                "assert",
+                "boolean",
+                "break",
+                "byte",
+                "case",
+                "catch",
+                "char",
+                "class",
+                "const",
+                "continue",
+                "default",
+                "do",
+                "double",
+                "else",
+                "enum",
+                "extends",
+                "false",
+                "final",
+                "finally",
+                "float",
+                "for",
+                "goto",
+                "if",
+                "implements",
+                "import",
+                "instanceof",
+                "int",
+                "interface",
+                "long",
+                "native",
+                "new",
+                "null",
+                "package",
+                "private",
+                "protected",
+                "public",
+                "return",
+                "short",
+                "static",
+                "strictfp",
+                "super",
+                "switch",
+                "synchronized",
+                "this",
+                "throw",
+                "throws",
+                "transient",
+                "true",
+                "try",
+                "void",
+                "volatile",
+                "while",
+            ],
+            Language::Python3 => &[
+                "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+                "continue", "def", "del", "elif", "else", "except", "finally", "for", "from",
+                "global", "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass",
+                "raise", "return", "try", "while", "with", "yield",
+            ],
+            Language::Rust => &[
+                "Self", "abstract", "as", "async", "await", "become", "box", "break", "const",
+                "continue", "crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn",
+                "for", "if", "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut",
+                "override", "priv", "pub", "ref", "return", "self", "static", "struct", "super",
+                "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual",
+                "where", "while", "yield",
+            ],
+            Language::TypeScript => &[
+                "break",
+                "case",
+                "catch",
+                "class",
+                "const",
+                "continue",
+                "debugger",
+                "default",
+                "delete",
+                "do",
+                "else",
+                "enum",
+                "export",
+                "extends",
+                "false",
+                "finally",
+                "for",
+                "function",
+                "if",
+                "implements",
+                "import",
+                "in",
+                "instanceof",
+                "interface",
+                "let",
+                "new",
+                "null",
+                "package",
+                "private",
+                "protected",
+                "public",
+                "return",
+                "static",
+                "super",
+                "switch",
+                "this",
+                "throw",
+                "true",
+                "try",
+                "typeof",
+                "var",
+                "void",
+                "while",
+                "with",
+                "yield",
+            ],
+        }
+    }
+
+    /// Whether the names of containers, fields, and variants (respectively) are used verbatim
+    /// as identifiers in the generated code.
+    fn verbatim_names(self) -> (bool, bool, bool) {
+        match self {
+            // Container names are qualified with the module name. Reserved words are escaped.
+            Language::C => (false, false, false),
+            Language::Cpp | Language::CSharp | Language::Java | Language::Rust => {
+                (true, true, true)
+            }
+            // Field names are converted to camel case and variant names are prefixed.
+            Language::Go => (true, false, false),
+            // Variant names are prefixed.
+            Language::Python3 | Language::TypeScript => (true, true, false),
+        }
+    }
+}
+
+/// Location of a problem in a registry.
+#[derive(Clone, Debug, PartialOrd, Ord, PartialEq, Eq)]
+pub struct Location {
+    pub container: String,
+    pub variant: Option<String>,
+    pub field: Option<String>,
+}
+
+impl Location {
+    fn new(container: &str, variant: Option<&str>, field: Option<&str>) -> Self {
+        Self {
+            container: container.to_string(),
+            variant: variant.map(String::from),
+            field: field.map(String::from),
+        }
+    }
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.container)?;
+        if let Some(variant) = &self.variant {
+            write!(f, "::{}", variant)?;
+        }
+        if let Some(field) = &self.field {
+            write!(f, ".{}", field)?;
+        }
+        Ok(())
+    }
+}
+
+/// A problem found in a registry that is likely to break generated code or (de)serialization.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lint {
+    pub location: Location,
+    /// The language concerned by the problem, if the problem is specific to one language.
+    pub language: Option<Language>,
+    pub message: String,
+}
+
+impl std::fmt::Display for Lint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.location, self.message)
+    }
+}
+
+/// A field or a variant of a container, as seen by the lints. Positional fields are named
+/// after their index.
+struct Member<'a> {
+    variant: Option<&'a str>,
+    field: Option<String>,
+    /// Whether `field` is the name of a named field.
+    is_named: bool,
+    /// Content of the field or the variant, if any.
+    format: Option<&'a Format>,
+    /// Whether the format of a variant is unknown.
+    is_incomplete: bool,
+}
+
+/// Report the problems of the registry that would break the code generated for the given
+/// languages, or the (de)serialization of values with the given encodings:
+/// * incomplete formats,
+/// * reserved words of the target languages used as names,
+/// * names that clash with the classes or types synthesized by the code generators,
+/// * recursive containers without finite values,
+/// * formats not supported by BCS (i.e. floats and chars).
+///
+/// Lints are sorted by location.
+pub fn lint(registry: &Registry, languages: &[Language], encodings: &[Encoding]) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    for (name, format) in registry {
+        let members = get_members(format);
+        check_formats(name, &members, encodings, &mut lints);
+        for language in languages {
+            check_reserved_words(*language, name, &members, &mut lints);
+            check_generated_names(*language, registry, name, format, &members, &mut lints);
+        }
+    }
+    check_finite_values(registry, &mut lints);
+    lints.sort_by(|a, b| a.location.cmp(&b.location));
+    lints
+}
+
+fn get_members(format: &ContainerFormat) -> Vec<Member<'_>> {
+    fn positional<'a>(variant: Option<&'a str>, formats: &'a [Format]) -> Vec<Member<'a>> {
+        formats
+            .iter()
+            .enumerate()
+            .map(|(index, format)| Member {
+                variant,
+                field: Some(index.to_string()),
+                is_named: false,
+                format: Some(format),
+                is_incomplete: false,
+            })
+            .collect()
+    }
+
+    fn named<'a>(variant: Option<&'a str>, fields: &'a [Named<Format>]) -> Vec<Member<'a>> {
+        fields
+            .iter()
+            .map(|field| Member {
+                variant,
+                field: Some(field.name.clone()),
+                is_named: true,
+                format: Some(&field.value),
+                is_incomplete: false,
+            })
+            .collect()
+    }
+
+    match format {
+        ContainerFormat::UnitStruct => Vec::new(),
+        ContainerFormat::NewTypeStruct(format) => positional(None, std::slice::from_ref(format)),
+        ContainerFormat::TupleStruct(formats) => positional(None, formats),
+        ContainerFormat::Struct(fields) => named(None, fields),
+        ContainerFormat::Enum(variants) => {
+            let mut members = Vec::new();
+            for variant in variants.values() {
+                let name = Some(variant.name.as_str());
+                // The variant itself.
+                members.push(Member {
+                    variant: name,
+                    field: None,
+                    is_named: false,
+                    format: match &variant.value {
+                        VariantFormat::NewType(format) => Some(format.as_ref()),
+                        _ => None,
+                    },
+                    is_incomplete: matches!(variant.value, VariantFormat::Variable(_)),
+                });
+                match &variant.value {
+                    VariantFormat::Tuple(formats) => members.extend(positional(name, formats)),
+                    VariantFormat::Struct(fields) => members.extend(named(name, fields)),
+                    _ => (),
+                }
+            }
+            members
+        }
+        ContainerFormat::Generic { format, .. } => get_members(format),
+    }
+}
+
+/// The variants of an enum, if the container is an enum (or a generic enum).
+fn get_variants(format: &ContainerFormat) -> Option<&VariantFormats> {
+    match format {
+        ContainerFormat::Enum(variants) => Some(variants),
+        ContainerFormat::Generic { format, .. } => get_variants(format),
+        _ => None,
+    }
+}
+
+/// Report incomplete formats and the formats that are not supported by the encodings.
+fn check_formats(name: &str, members: &[Member], encodings: &[Encoding], lints: &mut Vec<Lint>) {
+    for member in members {
+        let location = Location::new(name, member.variant, member.field.as_deref());
+        let mut unsupported = BTreeSet::new();
+        let complete = !member.is_incomplete
+            && member.format.map_or(true, |format| {
+                format
+                    .visit(&mut |format| {
+                        match format {
+                            Format::F32 => unsupported.insert("F32"),
+                            Format::F64 => unsupported.insert("F64"),
+                            Format::Char => unsupported.insert("CHAR"),
+                            _ => false,
+                        };
+                        Ok(())
+                    })
+                    .is_ok()
+            });
+        if !complete {
+            lints.push(Lint {
+                location: location.clone(),
+                language: None,
+                message: "incomplete format: make sure that all types were traced".to_string(),
+            });
+        }
+        if encodings.contains(&Encoding::Bcs) && !unsupported.is_empty() {
+            lints.push(Lint {
+                location,
+                language: None,
+                message: format!(
+                    "{} values are not supported by BCS",
+                    unsupported.into_iter().collect::<Vec<_>>().join(" and ")
+                ),
+            });
+        }
+    }
+}
+
+fn check_reserved_words(language: Language, name: &str, members: &[Member], lints: &mut Vec<Lint>) {
+    let (containers, fields, variants) = language.verbatim_names();
+    let mut report = |location: Location, word: &str| {
+        if language.keywords().contains(&word) {
+            lints.push(Lint {
+                location,
+                language: Some(language),
+                message: format!("`{}` is a reserved word in {}", word, language.name()),
+            });
+        }
+    };
+    if containers {
+        report(Location::new(name, None, None), name);
+    }
+    for member in members {
+        match (member.variant, &member.field) {
+            (Some(variant), None) if variants => {
+                report(Location::new(name, Some(variant), None), variant)
+            }
+            (variant, Some(field)) if fields && member.is_named => {
+                report(Location::new(name, variant, Some(field)), field)
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Report the names that clash with the definitions synthesized by the code generators (e.g.
+/// helper classes, or the classes of variants).
+fn check_generated_names(
+    language: Language,
+    registry: &Registry,
+    name: &str,
+    format: &ContainerFormat,
+    members: &[Member],
+    lints: &mut Vec<Lint>,
+) {
+    let mut report = |location: Location, message: String| {
+        lints.push(Lint {
+            location,
+            language: Some(language),
+            message: format!("{} in {}", message, language.name()),
+        });
+    };
+
+    let helpers: &[&str] = match language {
+        Language::CSharp => &["TraitHelpers", "JsonTraitHelpers"],
+        Language::Java => &["TraitHelpers", "Builder"],
+        Language::TypeScript => &["Helpers"],
+        _ => &[],
+    };
+    if helpers.contains(&name) {
+        report(
+            Location::new(name, None, None),
+            format!("`{}` clashes with a generated helper class", name),
+        );
+    }
+
+    let variants = get_variants(format);
+    for variant in variants.iter().flat_map(|variants| variants.values()) {
+        let variant = variant.name.as_str();
+        let location = Location::new(name, Some(variant), None);
+        // Classes of variants are nested in the class of the enum.
+        if matches!(language, Language::Cpp | Language::CSharp | Language::Java) && variant == name
+        {
+            report(
+                location.clone(),
+                format!("variant `{}` clashes with the enclosing class", variant),
+            );
+        }
+        if language == Language::Java && variant == "Builder" {
+            report(
+                location.clone(),
+                "`Builder` clashes with a generated builder class".to_string(),
+            );
+        }
+        // Types of variants are named after their enum.
+        let variant_type = match language {
+            Language::C => format!("{}_{}", name, variant),
+            Language::Go => format!("{}__{}", name, variant.to_camel_case()),
+            Language::Python3 => format!("{}__{}", name, variant),
+            Language::TypeScript => format!("{}Variant{}", name, variant),
+            _ => continue,
+        };
+        if registry.contains_key(&variant_type) {
+            report(
+                location,
+                format!(
+                    "the type generated for variant `{}` clashes with container `{}`",
+                    variant, variant_type
+                ),
+            );
+        }
+    }
+    if language == Language::C && variants.map_or(false, |variants| variants.is_empty()) {
+        report(
+            Location::new(name, None, None),
+            "enums without variants are not supported".to_string(),
+        );
+    }
+    if language == Language::C
+        && variants.is_some()
+        && registry.contains_key(&format!("{}_tag", name))
+    {
+        report(
+            Location::new(name, None, None),
+            format!(
+                "the tag type of the enum clashes with container `{}_tag`",
+                name
+            ),
+        );
+    }
+
+    // Members cannot be named after their class.
+    if matches!(language, Language::Cpp | Language::CSharp) {
+        for member in members.iter().filter(|member| member.is_named) {
+            let field = member.field.as_deref().unwrap_or_default();
+            if field == member.variant.unwrap_or(name) {
+                report(
+                    Location::new(name, member.variant, Some(field)),
+                    format!("field `{}` clashes with the enclosing class", field),
+                );
+            }
+        }
+    }
+}
+
+/// Report the containers whose values would all be infinite, e.g. `struct A { a: A }`, since
+/// recursion in containers must go through an `Option`, a sequence, or a map.
+fn check_finite_values(registry: &Registry, lints: &mut Vec<Lint>) {
+    fn is_finite(registry: &Registry, finite: &BTreeSet<&str>, format: &Format) -> bool {
+        use Format::*;
+        match format {
+            TypeName(name) | TypeApp { name, .. } => {
+                !registry.contains_key(name) || finite.contains(name.as_str())
+            }
+            Option(_) | Seq(_) | Map { .. } => true,
+            Tuple(formats) => formats.iter().all(|f| is_finite(registry, finite, f)),
+            TupleArray { content, size } => *size == 0 || is_finite(registry, finite, content),
+            // Incomplete formats are reported separately.
+            _ => true,
+        }
+    }
+
+    let mut finite = BTreeSet::new();
+    loop {
+        let mut changed = false;
+        for (name, format) in registry {
+            if finite.contains(name.as_str()) {
+                continue;
+            }
+            let members = get_members(format);
+            let is_member_finite = |member: &Member| {
+                member
+                    .format
+                    .map_or(true, |format| is_finite(registry, &finite, format))
+            };
+            let result = match get_variants(format) {
+                // One variant with finite values is enough. Empty enums are not reported.
+                Some(variants) => {
+                    variants.is_empty()
+                        || variants.values().any(|variant| {
+                            members
+                                .iter()
+                                .filter(|member| member.variant == Some(variant.name.as_str()))
+                                .all(is_member_finite)
+                        })
+                }
+                None => members.iter().all(is_member_finite),
+            };
+            if result {
+                finite.insert(name.as_str());
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    for name in registry.keys() {
+        if !finite.contains(name.as_str()) {
+            lints.push(Lint {
+                location: Location::new(name, None, None),
+                language: None,
+                message: "container has no finite values: recursion must go through an option, a sequence, or a map".to_string(),
+            });
+        }
+    }
+}
@@ -2,12 +2,13 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::{
+    common,
     indent::{IndentConfig, IndentedWriter},
-    CodeGeneratorConfig, Encoding,
+    CodeGeneratorConfig, Encoding, Error, Result,
 };
 use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
 use std::collections::{BTreeMap, HashMap};
-use std::io::{Result, Write};
+use std::io::Write;
 use std::path::PathBuf;
 
 /// Main configuration object for code-generation in Python.
@@ -16,6 +17,8 @@ pub struct CodeGenerator<'a> {
     config: &'a CodeGeneratorConfig,
     /// Whether the module providing Serde definitions is located within package.
     serde_package_name: Option<String>,
+    /// Whether to generate pydantic models instead of frozen dataclasses.
+    pydantic_models: bool,
     /// Mapping from external type names to suitably qualified names (e.g. "MyClass" -> "my_module.MyClass").
     /// Assumes suitable imports (e.g. "from my_package import my_module").
     /// Derived from `config.external_definitions`.
@@ -30,14 +33,13 @@ struct PythonEmitter<'a, T> {
     generator: &'a CodeGenerator<'a>,
     /// Current namespace (e.g. vec!["my_package", "my_module", "MyClass"])
     current_namespace: Vec<String>,
+    /// Fingerprints of the containers, if requested.
+    fingerprints: BTreeMap<String, u64>,
 }
 
 impl<'a> CodeGenerator<'a> {
     /// Create a Python code generator for the given config.
     pub fn new(config: &'a CodeGeneratorConfig) -> Self {
-        if config.c_style_enums {
-            panic!("Python 3 does not support generating c-style enums");
-        }
         let mut external_qualified_names = HashMap::new();
         for (module_path, names) in &config.external_definitions {
             let module = {
@@ -55,6 +57,7 @@ impl<'a> CodeGenerator<'a> {
         Self {
             config,
             serde_package_name: None,
+            pydantic_models: false,
             external_qualified_names,
         }
     }
@@ -65,8 +68,23 @@ impl<'a> CodeGenerator<'a> {
         self
     }
 
+    /// Whether to generate pydantic (v2) models instead of frozen dataclasses.
+    /// Integer fields are then validated against the range of their Serde format and
+    /// fixed-size arrays against their length.
+    pub fn with_pydantic_models(mut self, pydantic_models: bool) -> Self {
+        self.pydantic_models = pydantic_models;
+        self
+    }
+
     /// Write container definitions in Python.
-    pub fn output(&self, out: &mut dyn Write, registry: &Registry) -> Result<()> {
+    pub fn output(&self, out: &mut dyn Write, registry: &Registry) -> crate::Result<()> {
+        if self.config.c_style_enums {
+            return Err(Error::UnsupportedOption {
+                language: "Python 3",
+                option: "c-style enums",
+            });
+        }
+        let registry = &common::check_and_monomorphize_registry(registry)?;
         let current_namespace = self
             .config
             .module_name
@@ -77,6 +95,7 @@ impl<'a> CodeGenerator<'a> {
             out: IndentedWriter::new(out, IndentConfig::Space(4)),
             generator: self,
             current_namespace,
+            fingerprints: common::get_fingerprints(self.config, registry)?,
         };
         emitter.output_preamble()?;
         for (name, format) in registry {
@@ -105,13 +124,18 @@ where
             None => "".to_string(),
             Some(name) => format!("from {} ", name),
         };
+        let class_import = if self.generator.pydantic_models {
+            "import pydantic"
+        } else {
+            "from dataclasses import dataclass"
+        };
         writeln!(
             self.out,
             r#"# pyre-strict
-from dataclasses import dataclass
+{}
 import typing
 {}import serde_types as st"#,
-            from_serde_package,
+            class_import, from_serde_package,
         )?;
         for encoding in &self.generator.config.encodings {
             writeln!(self.out, "{}import {}", from_serde_package, encoding.name())?;
@@ -136,6 +160,11 @@ import typing
     }
 
     fn quote_type(&self, format: &Format) -> String {
+        if self.generator.pydantic_models {
+            if let Some(pydantic_type) = self.quote_pydantic_type(format) {
+                return pydantic_type;
+            }
+        }
         use Format::*;
         match format {
             TypeName(x) => self.quote_qualified_name(x),
@@ -170,10 +199,51 @@ import typing
                 self.quote_types(&vec![content.as_ref().clone(); *size])
             ), // Sadly, there are no fixed-size arrays in python.
 
-            Variable(_) => panic!("unexpected value"),
+            Variable(_) | Parameter(_) | TypeApp { .. } => panic!("unexpected value"),
         }
     }
 
+    /// Types of pydantic models differing from the dataclass ones. Primitive formats are
+    /// mapped to Python types annotated with the Serde type expected by the runtime first,
+    /// then validation constraints.
+    fn quote_pydantic_type(&self, format: &Format) -> Option<String> {
+        use Format::*;
+        fn int_type(
+            serde_type: &str,
+            min: impl std::fmt::Display,
+            max: impl std::fmt::Display,
+        ) -> String {
+            format!(
+                "typing.Annotated[int, {}, pydantic.Field(ge={}, le={})]",
+                serde_type, min, max
+            )
+        }
+        let pydantic_type = match format {
+            Unit => "typing.Annotated[None, st.unit]".into(),
+            I8 => int_type("st.int8", i8::MIN, i8::MAX),
+            I16 => int_type("st.int16", i16::MIN, i16::MAX),
+            I32 => int_type("st.int32", i32::MIN, i32::MAX),
+            I64 => int_type("st.int64", i64::MIN, i64::MAX),
+            I128 => int_type("st.int128", i128::MIN, i128::MAX),
+            U8 => int_type("st.uint8", u8::MIN, u8::MAX),
+            U16 => int_type("st.uint16", u16::MIN, u16::MAX),
+            U32 => int_type("st.uint32", u32::MIN, u32::MAX),
+            U64 => int_type("st.uint64", u64::MIN, u64::MAX),
+            U128 => int_type("st.uint128", u128::MIN, u128::MAX),
+            F32 => "typing.Annotated[float, st.float32]".into(),
+            F64 => "typing.Annotated[float, st.float64]".into(),
+            Char => "typing.Annotated[str, st.char, pydantic.Field(min_length=1, max_length=1)]"
+                .into(),
+            TupleArray { content, size } => format!(
+                "typing.Annotated[typing.Tuple[{}], pydantic.Field(min_length={1}, max_length={1})]",
+                self.quote_types(&vec![content.as_ref().clone(); *size]),
+                size
+            ),
+            _ => return None,
+        };
+        Some(pydantic_type)
+    }
+
     fn quote_types(&self, formats: &[Format]) -> String {
         formats
             .iter()
@@ -245,22 +315,31 @@ import typing
         };
 
         // Regarding comments, we pretend the namespace is `[module, base, name]`.
-        writeln!(
-            self.out,
-            "\n@dataclass(frozen=True)\nclass {0}__{1}({0}):",
-            base, name
-        )?;
+        if self.generator.pydantic_models {
+            writeln!(self.out, "\nclass {0}__{1}({0}):", base, name)?;
+        } else {
+            writeln!(
+                self.out,
+                "\n@dataclass(frozen=True)\nclass {0}__{1}({0}):",
+                base, name
+            )?;
+        }
         self.out.indent();
         self.output_comment(&name)?;
         if self.generator.config.serialization {
-            writeln!(self.out, "INDEX = {}  # type: int", index)?;
+            if self.generator.pydantic_models {
+                writeln!(self.out, "INDEX: typing.ClassVar[int] = {}", index)?;
+            } else {
+                writeln!(self.out, "INDEX = {}  # type: int", index)?;
+            }
         }
         self.current_namespace.push(name.to_string());
         self.output_fields(&fields)?;
         self.output_custom_code()?;
         self.current_namespace.pop();
         self.out.unindent();
-        writeln!(self.out)
+        writeln!(self.out)?;
+        Ok(())
     }
 
     fn output_enum_container(
@@ -268,23 +347,40 @@ import typing
         name: &str,
         variants: &BTreeMap<u32, Named<VariantFormat>>,
     ) -> Result<()> {
-        writeln!(self.out, "\nclass {}:", name)?;
+        if self.generator.pydantic_models {
+            writeln!(self.out, "\nclass {}(pydantic.BaseModel):", name)?;
+        } else {
+            writeln!(self.out, "\nclass {}:", name)?;
+        }
         self.out.indent();
         self.output_comment(&name)?;
+        self.output_pydantic_model_config()?;
+        self.output_fingerprint(name)?;
         self.current_namespace.push(name.to_string());
         if self.generator.config.serialization {
-            writeln!(
-                self.out,
-                "VARIANTS = []  # type: typing.Sequence[typing.Type[{}]]",
-                name
-            )?;
+            if self.generator.pydantic_models {
+                writeln!(
+                    self.out,
+                    "VARIANTS: typing.ClassVar[typing.Sequence[typing.Type[\"{}\"]]] = []",
+                    name
+                )?;
+            } else {
+                writeln!(
+                    self.out,
+                    "VARIANTS = []  # type: typing.Sequence[typing.Type[{}]]",
+                    name
+                )?;
+            }
             for encoding in &self.generator.config.encodings {
                 self.output_serialize_method_for_encoding(name, *encoding)?;
                 self.output_deserialize_method_for_encoding(name, *encoding)?;
             }
         }
         let wrote_custom_code = self.output_custom_code()?;
-        if !self.generator.config.serialization && !wrote_custom_code {
+        if !self.generator.config.serialization
+            && !self.generator.pydantic_models
+            && !wrote_custom_code
+        {
             writeln!(self.out, "pass")?;
         }
         writeln!(self.out)?;
@@ -310,6 +406,28 @@ import typing
         Ok(())
     }
 
+    fn output_fingerprint(&mut self, name: &str) -> Result<()> {
+        if let Some(fingerprint) = self.fingerprints.get(name) {
+            if self.generator.pydantic_models {
+                writeln!(
+                    self.out,
+                    "FINGERPRINT: typing.ClassVar[int] = {:#018x}",
+                    fingerprint
+                )?;
+            } else {
+                writeln!(self.out, "FINGERPRINT = {:#018x}  # type: int", fingerprint)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn output_pydantic_model_config(&mut self) -> Result<()> {
+        if self.generator.pydantic_models {
+            writeln!(self.out, "model_config = pydantic.ConfigDict(frozen=True)")?;
+        }
+        Ok(())
+    }
+
     fn output_serialize_method_for_encoding(
         &mut self,
         name: &str,
@@ -322,7 +440,8 @@ def {0}_serialize(self) -> bytes:
     return {0}.serialize(self, {1})"#,
             encoding.name(),
             name
-        )
+        )?;
+        Ok(())
     }
 
     fn output_deserialize_method_for_encoding(
@@ -341,7 +460,8 @@ def {0}_deserialize(input: bytes) -> '{1}':
     return v"#,
             encoding.name(),
             name
-        )
+        )?;
+        Ok(())
     }
 
     fn output_container(&mut self, name: &str, format: &ContainerFormat) -> Result<()> {
@@ -362,11 +482,18 @@ def {0}_deserialize(input: bytes) -> '{1}':
                 self.output_enum_container(name, variants)?;
                 return Ok(());
             }
+            Generic { .. } => return Err(Error::UnexpectedGeneric(name.to_string())),
         };
         // Struct case.
-        writeln!(self.out, "\n@dataclass(frozen=True)\nclass {}:", name)?;
+        if self.generator.pydantic_models {
+            writeln!(self.out, "\nclass {}(pydantic.BaseModel):", name)?;
+        } else {
+            writeln!(self.out, "\n@dataclass(frozen=True)\nclass {}:", name)?;
+        }
         self.out.indent();
         self.output_comment(name)?;
+        self.output_pydantic_model_config()?;
+        self.output_fingerprint(name)?;
         self.current_namespace.push(name.to_string());
         self.output_fields(&fields)?;
         for encoding in &self.generator.config.encodings {
@@ -376,7 +503,8 @@ def {0}_deserialize(input: bytes) -> '{1}':
         self.output_custom_code()?;
         self.current_namespace.pop();
         self.out.unindent();
-        writeln!(self.out)
+        writeln!(self.out)?;
+        Ok(())
     }
 }
 
@@ -384,6 +512,7 @@ def {0}_deserialize(input: bytes) -> '{1}':
 pub struct Installer {
     install_dir: PathBuf,
     serde_package_name: Option<String>,
+    pydantic_models: bool,
 }
 
 impl Installer {
@@ -391,13 +520,20 @@ impl Installer {
         Installer {
             install_dir,
             serde_package_name,
+            pydantic_models: false,
         }
     }
 
+    /// Whether to install pydantic models instead of frozen dataclasses.
+    pub fn with_pydantic_models(mut self, pydantic_models: bool) -> Self {
+        self.pydantic_models = pydantic_models;
+        self
+    }
+
     fn create_module_init_file(&self, name: &str) -> Result<std::fs::File> {
         let dir_path = self.install_dir.join(name);
         std::fs::create_dir_all(&dir_path)?;
-        std::fs::File::create(dir_path.join("__init__.py"))
+        Ok(std::fs::File::create(dir_path.join("__init__.py"))?)
     }
 
     fn fix_serde_package(&self, content: &str) -> String {
@@ -417,7 +553,7 @@ impl Installer {
 }
 
 impl crate::SourceInstaller for Installer {
-    type Error = Box<dyn std::error::Error>;
+    type Error = crate::Error;
 
     fn install_module(
         &self,
@@ -425,8 +561,9 @@ impl crate::SourceInstaller for Installer {
         registry: &Registry,
     ) -> std::result::Result<(), Self::Error> {
         let mut file = self.create_module_init_file(&config.module_name)?;
-        let generator =
-            CodeGenerator::new(config).with_serde_package_name(self.serde_package_name.clone());
+        let generator = CodeGenerator::new(config)
+            .with_serde_package_name(self.serde_package_name.clone())
+            .with_pydantic_models(self.pydantic_models);
         generator.output(&mut file, registry)?;
         Ok(())
     }
@@ -444,6 +581,16 @@ impl crate::SourceInstaller for Installer {
             "{}",
             self.fix_serde_package(include_str!("../runtime/python/serde_binary/__init__.py"))
         )?;
+        // Sources of the optional native module `serde_binary._speedups`.
+        let dir_path = self.install_dir.join("serde_binary");
+        std::fs::write(
+            dir_path.join("_speedups.c"),
+            include_str!("../runtime/python/serde_binary/_speedups.c"),
+        )?;
+        std::fs::write(
+            dir_path.join("build_speedups.py"),
+            include_str!("../runtime/python/serde_binary/build_speedups.py"),
+        )?;
         Ok(())
     }
 
@@ -2,14 +2,14 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::{
-    analyzer,
+    analyzer, common,
     indent::{IndentConfig, IndentedWriter},
-    CodeGeneratorConfig,
+    CodeGeneratorConfig, Error, Result,
 };
 use serde_reflection::{ContainerFormat, Format, Named, Registry, VariantFormat};
 use std::borrow::Cow;
 use std::collections::{BTreeMap, HashSet};
-use std::io::{Result, Write};
+use std::io::Write;
 use std::path::PathBuf;
 
 /// Main configuration object for code-generation in Rust.
@@ -34,6 +34,8 @@ struct RustEmitter<'a, T> {
     known_sizes: Cow<'a, HashSet<&'a str>>,
     /// Current namespace (e.g. vec!["my_package", "my_module", "MyClass"])
     current_namespace: Vec<String>,
+    /// Fingerprints of the containers, if requested.
+    fingerprints: BTreeMap<String, u64>,
 }
 
 impl<'a> CodeGenerator<'a> {
@@ -70,11 +72,8 @@ impl<'a> CodeGenerator<'a> {
     }
 
     /// Write container definitions in Rust.
-    pub fn output(
-        &self,
-        out: &mut dyn Write,
-        registry: &Registry,
-    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    pub fn output(&self, out: &mut dyn Write, registry: &Registry) -> crate::Result<()> {
+        common::check_registry(registry)?;
         let external_names = self
             .config
             .external_definitions
@@ -102,6 +101,7 @@ impl<'a> CodeGenerator<'a> {
             generator: self,
             known_sizes: Cow::Owned(known_sizes),
             current_namespace,
+            fingerprints: common::get_fingerprints(self.config, registry)?,
         };
 
         emitter.output_preamble()?;
@@ -117,12 +117,14 @@ impl<'a> CodeGenerator<'a> {
     pub fn quote_container_definitions(
         &self,
         registry: &Registry,
-    ) -> std::result::Result<BTreeMap<String, String>, Box<dyn std::error::Error>> {
+    ) -> crate::Result<BTreeMap<String, String>> {
+        common::check_registry(registry)?;
         let dependencies = analyzer::get_dependency_map(registry)?;
         let entries = analyzer::best_effort_topological_sort(&dependencies);
 
         let mut result = BTreeMap::new();
         let mut known_sizes = HashSet::new();
+        let fingerprints = common::get_fingerprints(self.config, registry)?;
         let current_namespace = self
             .config
             .module_name
@@ -138,6 +140,7 @@ impl<'a> CodeGenerator<'a> {
                     generator: self,
                     known_sizes: Cow::Borrowed(&known_sizes),
                     current_namespace: current_namespace.clone(),
+                    fingerprints: fingerprints.clone(),
                 };
                 let format = &registry[name];
                 emitter.output_container(name, format)?;
@@ -224,6 +227,16 @@ where
                 }
                 x.to_string()
             }
+            Parameter(x) => x.to_string(),
+            TypeApp { name, arguments } => {
+                let result = format!("{}<{}>", name, Self::quote_types(arguments, known_sizes));
+                if let Some(set) = known_sizes {
+                    if !set.contains(name.as_str()) {
+                        return format!("Box<{}>", result);
+                    }
+                }
+                result
+            }
             Unit => "()".into(),
             Bool => "bool".into(),
             I8 => "i8".into(),
@@ -290,19 +303,19 @@ where
         self.output_comment(name)?;
         use VariantFormat::*;
         match variant {
-            Unit => writeln!(self.out, "{},", name),
+            Unit => writeln!(self.out, "{},", name)?,
             NewType(format) => writeln!(
                 self.out,
                 "{}({}),",
                 name,
                 Self::quote_type(format, Some(&self.known_sizes))
-            ),
+            )?,
             Tuple(formats) => writeln!(
                 self.out,
                 "{}({}),",
                 name,
                 Self::quote_types(formats, Some(&self.known_sizes))
-            ),
+            )?,
             Struct(fields) => {
                 writeln!(self.out, "{} {{", name)?;
                 self.current_namespace.push(name.to_string());
@@ -310,10 +323,11 @@ where
                 self.output_fields(&[base, name], fields)?;
                 self.out.unindent();
                 self.current_namespace.pop();
-                writeln!(self.out, "}},")
+                writeln!(self.out, "}},")?;
             }
             Variable(_) => panic!("incorrect value"),
         }
+        Ok(())
     }
 
     fn output_variants(
@@ -346,15 +360,23 @@ where
         if self.generator.track_visibility {
             prefix.push_str("pub ");
         }
+        // Generic containers are declared with their type parameters, e.g. `Tree<T0>`.
+        let (header, format) = match format {
+            ContainerFormat::Generic { parameters, format } => (
+                format!("{}<{}>", name, parameters.join(", ")),
+                format.as_ref(),
+            ),
+            _ => (name.to_string(), format),
+        };
 
         use ContainerFormat::*;
         match format {
-            UnitStruct => writeln!(self.out, "{}struct {};\n", prefix, name)?,
+            UnitStruct => writeln!(self.out, "{}struct {};\n", prefix, header)?,
             NewTypeStruct(format) => writeln!(
                 self.out,
                 "{}struct {}({}{});\n",
                 prefix,
-                name,
+                header,
                 if self.generator.track_visibility {
                     "pub "
                 } else {
@@ -366,11 +388,11 @@ where
                 self.out,
                 "{}struct {}({});\n",
                 prefix,
-                name,
+                header,
                 Self::quote_types(formats, Some(&self.known_sizes))
             )?,
             Struct(fields) => {
-                writeln!(self.out, "{}struct {} {{", prefix, name)?;
+                writeln!(self.out, "{}struct {} {{", prefix, header)?;
                 self.current_namespace.push(name.to_string());
                 self.out.indent();
                 self.output_fields(&[name], fields)?;
@@ -379,7 +401,7 @@ where
                 writeln!(self.out, "}}\n")?;
             }
             Enum(variants) => {
-                writeln!(self.out, "{}enum {} {{", prefix, name)?;
+                writeln!(self.out, "{}enum {} {{", prefix, header)?;
                 self.current_namespace.push(name.to_string());
                 self.out.indent();
                 self.output_variants(name, variants)?;
@@ -387,19 +409,66 @@ where
                 self.current_namespace.pop();
                 writeln!(self.out, "}}\n")?;
             }
+            Generic { .. } => return Err(Error::UnexpectedGeneric(name.to_string())),
+        }
+        self.output_fingerprint(name)?;
+        self.output_custom_code(name)?;
+        Ok(())
+    }
+
+    fn output_fingerprint(&mut self, name: &str) -> Result<()> {
+        if let Some(fingerprint) = self.fingerprints.get(name) {
+            writeln!(
+                self.out,
+                "impl {} {{\n    {}const FINGERPRINT: u64 = {:#018x};\n}}\n",
+                name,
+                if self.generator.track_visibility {
+                    "pub "
+                } else {
+                    ""
+                },
+                fingerprint
+            )?;
         }
-        self.output_custom_code(name)
+        Ok(())
     }
 }
 
 /// Installer for generated source files in Rust.
 pub struct Installer {
     install_dir: PathBuf,
+    derive_macros: Option<Vec<String>>,
+    custom_derive_block: Option<String>,
+    track_visibility: bool,
 }
 
 impl Installer {
     pub fn new(install_dir: PathBuf) -> Self {
-        Installer { install_dir }
+        Installer {
+            install_dir,
+            derive_macros: None,
+            custom_derive_block: None,
+            track_visibility: true,
+        }
+    }
+
+    /// Which derive macros should be added (see `CodeGenerator::with_derive_macros`).
+    pub fn with_derive_macros(mut self, derive_macros: Vec<String>) -> Self {
+        self.derive_macros = Some(derive_macros);
+        self
+    }
+
+    /// Additional block of text added before each new container definition (see
+    /// `CodeGenerator::with_custom_derive_block`).
+    pub fn with_custom_derive_block(mut self, custom_derive_block: Option<String>) -> Self {
+        self.custom_derive_block = custom_derive_block;
+        self
+    }
+
+    /// Whether definitions and fields should be marked as `pub`.
+    pub fn with_track_visibility(mut self, track_visibility: bool) -> Self {
+        self.track_visibility = track_visibility;
+        self
     }
 
     fn runtime_installation_message(name: &str) {
@@ -408,14 +477,19 @@ impl Installer {
 }
 
 impl crate::SourceInstaller for Installer {
-    type Error = Box<dyn std::error::Error>;
+    type Error = crate::Error;
 
     fn install_module(
         &self,
         config: &CodeGeneratorConfig,
         registry: &Registry,
     ) -> std::result::Result<(), Self::Error> {
-        let generator = CodeGenerator::new(config);
+        let mut generator = CodeGenerator::new(config)
+            .with_custom_derive_block(self.custom_derive_block.clone())
+            .with_track_visibility(self.track_visibility);
+        if let Some(derive_macros) = &self.derive_macros {
+            generator = generator.with_derive_macros(derive_macros.clone());
+        }
         let (name, version) = {
             let parts = config.module_name.splitn(2, ':').collect::<Vec<_>>();
             if parts.len() >= 2 {
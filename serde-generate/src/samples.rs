@@ -0,0 +1,1088 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::Encoding;
+use serde::Serialize;
+use serde_reflection::{
+    ContainerFormat, Error, Format, FormatHolder, Registry, Result, Value, VariantFormat,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Configuration of the random values produced by a [`ValueGenerator`].
+#[derive(Clone, Debug)]
+pub struct ValueGeneratorConfig {
+    max_depth: usize,
+    max_length: usize,
+    has_canonical_maps: bool,
+    has_floats: bool,
+    has_chars: bool,
+}
+
+impl Default for ValueGeneratorConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_length: 4,
+            has_canonical_maps: true,
+            has_floats: true,
+            has_chars: true,
+        }
+    }
+}
+
+impl ValueGeneratorConfig {
+    /// Maximal nesting of containers, options, sequences and maps. Beyond this depth, we
+    /// generate the smallest possible values (e.g. empty sequences, enum variants ending recursions).
+    pub fn max_depth(mut self, value: usize) -> Self {
+        self.max_depth = value;
+        self
+    }
+
+    /// Maximal length of sequences, maps, strings and bytes.
+    pub fn max_length(mut self, value: usize) -> Self {
+        self.max_length = value;
+        self
+    }
+
+    /// Whether maps may contain more than one entry. Encodings that do not enforce the ordering
+    /// of map keys cannot be re-serialized reliably otherwise.
+    pub fn has_canonical_maps(mut self, value: bool) -> Self {
+        self.has_canonical_maps = value;
+        self
+    }
+
+    /// Whether to generate floats. Otherwise, optional floats are always `None`.
+    pub fn has_floats(mut self, value: bool) -> Self {
+        self.has_floats = value;
+        self
+    }
+
+    /// Whether to generate chars. Otherwise, optional chars are always `None`.
+    pub fn has_chars(mut self, value: bool) -> Self {
+        self.has_chars = value;
+        self
+    }
+}
+
+/// Generate random values following the formats of a registry.
+///
+/// ```rust
+/// use rand::SeedableRng;
+/// use serde_generate::{samples, Encoding};
+/// use serde_reflection::{Format, Samples, Tracer, TracerConfig};
+///
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Test {
+///     a: Vec<u64>,
+///     b: (u32, String),
+/// }
+///
+/// let mut tracer = Tracer::new(TracerConfig::default());
+/// tracer.trace_type::<Test>(&Samples::new()).unwrap();
+/// let registry = tracer.registry().unwrap();
+///
+/// let rng = rand::rngs::StdRng::seed_from_u64(0);
+/// let config = samples::ValueGeneratorConfig::default();
+/// let mut generator = samples::ValueGenerator::new(&registry, config, rng).unwrap();
+/// let format = Format::TypeName("Test".to_string());
+/// let value = generator.generate(&format).unwrap();
+/// let bytes = samples::serialize_value(&registry, Encoding::Bcs, &format, &value).unwrap();
+/// assert!(bcs::from_bytes::<Test>(&bytes).is_ok());
+/// ```
+pub struct ValueGenerator<'a, R> {
+    registry: &'a Registry,
+    config: ValueGeneratorConfig,
+    rng: R,
+    /// Minimal depth of the values of each container.
+    min_depths: BTreeMap<&'a str, usize>,
+}
+
+impl<'a, R: rand::Rng> ValueGenerator<'a, R> {
+    /// Create a generator for the given registry. Fails if some containers (e.g. recursive
+    /// structs without options or sequences) have no finite values. Generic containers must be
+    /// instantiated first with `serde_reflection::monomorphize`.
+    pub fn new(registry: &'a Registry, config: ValueGeneratorConfig, rng: R) -> Result<Self> {
+        if registry
+            .values()
+            .any(|format| matches!(format, ContainerFormat::Generic { .. }))
+        {
+            return Err(Error::NotSupported("generic containers"));
+        }
+        let mut min_depths = BTreeMap::new();
+        loop {
+            let mut changed = false;
+            for (name, format) in registry {
+                if let Some(depth) = Self::container_min_depth(&min_depths, format) {
+                    if min_depths.get(name.as_str()).map_or(true, |d| depth < *d) {
+                        min_depths.insert(name.as_str(), depth);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        if let Some(name) = registry
+            .keys()
+            .find(|name| !min_depths.contains_key(name.as_str()))
+        {
+            return Err(Error::Custom(format!(
+                "Container `{}` does not have finite values",
+                name
+            )));
+        }
+        Ok(Self {
+            registry,
+            config,
+            rng,
+            min_depths,
+        })
+    }
+
+    fn min_depth(min_depths: &BTreeMap<&str, usize>, format: &Format) -> Option<usize> {
+        use Format::*;
+        match format {
+            TypeName(name) => min_depths.get(name.as_str()).map(|depth| depth + 1),
+            Variable(_) => None,
+            Option(_) | Seq(_) | Map { .. } => Some(0),
+            Tuple(formats) => Self::max_min_depth(min_depths, formats),
+            TupleArray { content, size } => {
+                if *size == 0 {
+                    Some(0)
+                } else {
+                    Self::min_depth(min_depths, content)
+                }
+            }
+            _ => Some(0),
+        }
+    }
+
+    fn max_min_depth<'b, I>(min_depths: &BTreeMap<&str, usize>, formats: I) -> Option<usize>
+    where
+        I: IntoIterator<Item = &'b Format>,
+    {
+        formats.into_iter().try_fold(0, |result, format| {
+            Some(std::cmp::max(result, Self::min_depth(min_depths, format)?))
+        })
+    }
+
+    fn variant_min_depth(
+        min_depths: &BTreeMap<&str, usize>,
+        variant: &VariantFormat,
+    ) -> Option<usize> {
+        match variant {
+            VariantFormat::Variable(_) => None,
+            VariantFormat::Unit => Some(0),
+            VariantFormat::NewType(format) => Self::min_depth(min_depths, format),
+            VariantFormat::Tuple(formats) => Self::max_min_depth(min_depths, formats),
+            VariantFormat::Struct(fields) => {
+                Self::max_min_depth(min_depths, fields.iter().map(|f| &f.value))
+            }
+        }
+    }
+
+    fn container_min_depth(
+        min_depths: &BTreeMap<&str, usize>,
+        format: &ContainerFormat,
+    ) -> Option<usize> {
+        match format {
+            ContainerFormat::UnitStruct => Some(0),
+            ContainerFormat::NewTypeStruct(format) => Self::min_depth(min_depths, format),
+            ContainerFormat::TupleStruct(formats) => Self::max_min_depth(min_depths, formats),
+            ContainerFormat::Struct(fields) => {
+                Self::max_min_depth(min_depths, fields.iter().map(|f| &f.value))
+            }
+            ContainerFormat::Enum(variants) => variants
+                .values()
+                .filter_map(|v| Self::variant_min_depth(min_depths, &v.value))
+                .min(),
+            ContainerFormat::Generic { .. } => None,
+        }
+    }
+
+    /// Generate a random value of the given format.
+    pub fn generate(&mut self, format: &Format) -> Result<Value> {
+        self.generate_at_depth(format, 0)
+    }
+
+    fn generate_length(&mut self, depth: usize) -> usize {
+        if depth >= self.config.max_depth {
+            0
+        } else {
+            self.rng.gen_range(0..=self.config.max_length)
+        }
+    }
+
+    fn generate_values<'b, I>(&mut self, formats: I, depth: usize) -> Result<Value>
+    where
+        I: IntoIterator<Item = &'b Format>,
+    {
+        let values = formats
+            .into_iter()
+            .map(|format| self.generate_at_depth(format, depth))
+            .collect::<Result<_>>()?;
+        Ok(Value::Seq(values))
+    }
+
+    fn generate_at_depth(&mut self, format: &Format, depth: usize) -> Result<Value> {
+        use Format::*;
+        let value = match format {
+            Variable(_) => return Err(Error::UnknownFormat),
+            Parameter(_) | TypeApp { .. } => return Err(Error::NotSupported("generic formats")),
+            TypeName(name) => {
+                let container = self
+                    .registry
+                    .get(name)
+                    .ok_or_else(|| Error::Custom(format!("Missing container `{}`", name)))?;
+                self.generate_container(container, depth + 1)?
+            }
+            Unit => Value::Unit,
+            Bool => Value::Bool(self.rng.gen()),
+            I8 => Value::I8(self.rng.gen()),
+            I16 => Value::I16(self.rng.gen()),
+            I32 => Value::I32(self.rng.gen()),
+            I64 => Value::I64(self.rng.gen()),
+            I128 => Value::I128(self.rng.gen()),
+            U8 => Value::U8(self.rng.gen()),
+            U16 => Value::U16(self.rng.gen()),
+            U32 => Value::U32(self.rng.gen()),
+            U64 => Value::U64(self.rng.gen()),
+            U128 => Value::U128(self.rng.gen()),
+            F32 if self.config.has_floats => Value::F32(self.rng.gen_range(-1e6..1e6)),
+            F64 if self.config.has_floats => Value::F64(self.rng.gen_range(-1e12..1e12)),
+            Char if self.config.has_chars => Value::Char(self.rng.gen()),
+            F32 | F64 | Char => return Err(Error::NotSupported("floats and chars are disabled")),
+            Str => {
+                let length = self.generate_length(depth);
+                let value = (0..length)
+                    .map(|_| {
+                        if self.rng.gen_ratio(1, 4) {
+                            self.rng.gen::<char>()
+                        } else {
+                            self.rng.sample(rand::distributions::Alphanumeric) as char
+                        }
+                    })
+                    .collect();
+                Value::Str(value)
+            }
+            Bytes => {
+                let length = self.generate_length(depth);
+                Value::Bytes((0..length).map(|_| self.rng.gen()).collect())
+            }
+            Option(format) => {
+                let is_some = depth < self.config.max_depth
+                    && self.rng.gen()
+                    && match format.as_ref() {
+                        F32 | F64 => self.config.has_floats,
+                        Char => self.config.has_chars,
+                        _ => true,
+                    };
+                if is_some {
+                    let value = self.generate_at_depth(format, depth + 1)?;
+                    Value::Option(Some(Box::new(value)))
+                } else {
+                    Value::Option(None)
+                }
+            }
+            Seq(format) => {
+                let length = self.generate_length(depth);
+                self.generate_values(std::iter::repeat(format.as_ref()).take(length), depth + 1)?
+            }
+            Map { key, value } => {
+                let mut length = self.generate_length(depth);
+                if !self.config.has_canonical_maps {
+                    length = std::cmp::min(length, 1);
+                }
+                let mut keys = Vec::new();
+                for _ in 0..length {
+                    let key = self.generate_at_depth(key, depth + 1)?;
+                    if !keys.contains(&key) {
+                        keys.push(key);
+                    }
+                }
+                let mut entries = Vec::new();
+                for key in keys {
+                    entries.push(key);
+                    entries.push(self.generate_at_depth(value, depth + 1)?);
+                }
+                Value::Seq(entries)
+            }
+            Tuple(formats) => self.generate_values(formats, depth)?,
+            TupleArray { content, size } => {
+                self.generate_values(std::iter::repeat(content.as_ref()).take(*size), depth)?
+            }
+        };
+        Ok(value)
+    }
+
+    fn generate_container(&mut self, format: &ContainerFormat, depth: usize) -> Result<Value> {
+        let value = match format {
+            ContainerFormat::UnitStruct => Value::Unit,
+            ContainerFormat::NewTypeStruct(format) => self.generate_at_depth(format, depth)?,
+            ContainerFormat::TupleStruct(formats) => self.generate_values(formats, depth)?,
+            ContainerFormat::Struct(fields) => {
+                self.generate_values(fields.iter().map(|f| &f.value), depth)?
+            }
+            ContainerFormat::Enum(variants) => {
+                // Past the maximal depth, only select the variants that end recursions quickly.
+                let min_depths = &self.min_depths;
+                let candidates = variants
+                    .iter()
+                    .filter_map(|(index, variant)| {
+                        let min_depth = Self::variant_min_depth(min_depths, &variant.value)?;
+                        Some((*index, &variant.value, min_depth))
+                    })
+                    .collect::<Vec<_>>();
+                let smallest = candidates.iter().map(|(_, _, d)| *d).min().unwrap_or(0);
+                let candidates = candidates
+                    .into_iter()
+                    .filter(|(_, _, d)| depth < self.config.max_depth || *d == smallest)
+                    .collect::<Vec<_>>();
+                let (index, variant, _) = candidates[self.rng.gen_range(0..candidates.len())];
+                let value = match variant {
+                    VariantFormat::Variable(_) => return Err(Error::UnknownFormat),
+                    VariantFormat::Unit => Value::Unit,
+                    VariantFormat::NewType(format) => self.generate_at_depth(format, depth)?,
+                    VariantFormat::Tuple(formats) => self.generate_values(formats, depth)?,
+                    VariantFormat::Struct(fields) => {
+                        self.generate_values(fields.iter().map(|f| &f.value), depth)?
+                    }
+                };
+                Value::Variant(index, Box::new(value))
+            }
+            ContainerFormat::Generic { .. } => {
+                return Err(Error::NotSupported("generic containers"))
+            }
+        };
+        Ok(value)
+    }
+}
+
+/// Serialize a value of the given format, e.g. as produced by a [`ValueGenerator`], in a
+/// binary encoding.
+pub fn serialize_value(
+    registry: &Registry,
+    encoding: Encoding,
+    format: &Format,
+    value: &Value,
+) -> Result<Vec<u8>> {
+    let value = FormattedValue {
+        registry,
+        format,
+        value,
+    };
+    match encoding {
+        Encoding::Bcs => bcs::to_bytes(&value).map_err(|e| Error::Custom(e.to_string())),
+        Encoding::Bincode => bincode::serialize(&value).map_err(|e| Error::Custom(e.to_string())),
+    }
+}
+
+/// A value together with its format, serialized as such by Serde.
+/// Names of containers, fields and variants are left empty: this is only meant for binary
+/// encodings such as BCS and Bincode.
+struct FormattedValue<'a> {
+    registry: &'a Registry,
+    format: &'a Format,
+    value: &'a Value,
+}
+
+impl<'a> FormattedValue<'a> {
+    fn with(&self, format: &'a Format, value: &'a Value) -> Self {
+        Self {
+            registry: self.registry,
+            format,
+            value,
+        }
+    }
+}
+
+fn seq_values<E: serde::ser::Error>(value: &Value, len: usize) -> std::result::Result<&[Value], E> {
+    match value {
+        Value::Seq(values) if values.len() == len => Ok(values),
+        _ => Err(E::custom(format!(
+            "expected a sequence of {} values, got {:?}",
+            len, value
+        ))),
+    }
+}
+
+impl<'a> Serialize for FormattedValue<'a> {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::{
+            Error as _, SerializeMap, SerializeSeq, SerializeStruct, SerializeStructVariant,
+            SerializeTuple, SerializeTupleStruct, SerializeTupleVariant,
+        };
+
+        match (self.format, self.value) {
+            (Format::TypeName(name), value) => {
+                let container = self
+                    .registry
+                    .get(name)
+                    .ok_or_else(|| S::Error::custom(format!("missing container `{}`", name)))?;
+                match container {
+                    ContainerFormat::UnitStruct => serializer.serialize_unit_struct(""),
+                    ContainerFormat::NewTypeStruct(format) => {
+                        serializer.serialize_newtype_struct("", &self.with(format, value))
+                    }
+                    ContainerFormat::TupleStruct(formats) => {
+                        let values = seq_values(value, formats.len())?;
+                        let mut s = serializer.serialize_tuple_struct("", formats.len())?;
+                        for (format, value) in formats.iter().zip(values) {
+                            s.serialize_field(&self.with(format, value))?;
+                        }
+                        s.end()
+                    }
+                    ContainerFormat::Struct(fields) => {
+                        let values = seq_values(value, fields.len())?;
+                        let mut s = serializer.serialize_struct("", fields.len())?;
+                        for (field, value) in fields.iter().zip(values) {
+                            s.serialize_field("", &self.with(&field.value, value))?;
+                        }
+                        s.end()
+                    }
+                    ContainerFormat::Enum(variants) => {
+                        let (index, value) = match value {
+                            Value::Variant(index, value) => (*index, value.as_ref()),
+                            _ => return Err(S::Error::custom("expected a variant")),
+                        };
+                        let variant = variants.get(&index).ok_or_else(|| {
+                            S::Error::custom(format!("unknown variant index {}", index))
+                        })?;
+                        match &variant.value {
+                            VariantFormat::Variable(_) => {
+                                Err(S::Error::custom("unexpected variable"))
+                            }
+                            VariantFormat::Unit => serializer.serialize_unit_variant("", index, ""),
+                            VariantFormat::NewType(format) => serializer.serialize_newtype_variant(
+                                "",
+                                index,
+                                "",
+                                &self.with(format, value),
+                            ),
+                            VariantFormat::Tuple(formats) => {
+                                let values = seq_values(value, formats.len())?;
+                                let mut s = serializer.serialize_tuple_variant(
+                                    "",
+                                    index,
+                                    "",
+                                    formats.len(),
+                                )?;
+                                for (format, value) in formats.iter().zip(values) {
+                                    s.serialize_field(&self.with(format, value))?;
+                                }
+                                s.end()
+                            }
+                            VariantFormat::Struct(fields) => {
+                                let values = seq_values(value, fields.len())?;
+                                let mut s = serializer.serialize_struct_variant(
+                                    "",
+                                    index,
+                                    "",
+                                    fields.len(),
+                                )?;
+                                for (field, value) in fields.iter().zip(values) {
+                                    s.serialize_field("", &self.with(&field.value, value))?;
+                                }
+                                s.end()
+                            }
+                        }
+                    }
+                    ContainerFormat::Generic { .. } => {
+                        Err(S::Error::custom("unexpected generic container"))
+                    }
+                }
+            }
+            (Format::Unit, Value::Unit) => serializer.serialize_unit(),
+            (Format::Bool, Value::Bool(x)) => serializer.serialize_bool(*x),
+            (Format::I8, Value::I8(x)) => serializer.serialize_i8(*x),
+            (Format::I16, Value::I16(x)) => serializer.serialize_i16(*x),
+            (Format::I32, Value::I32(x)) => serializer.serialize_i32(*x),
+            (Format::I64, Value::I64(x)) => serializer.serialize_i64(*x),
+            (Format::I128, Value::I128(x)) => serializer.serialize_i128(*x),
+            (Format::U8, Value::U8(x)) => serializer.serialize_u8(*x),
+            (Format::U16, Value::U16(x)) => serializer.serialize_u16(*x),
+            (Format::U32, Value::U32(x)) => serializer.serialize_u32(*x),
+            (Format::U64, Value::U64(x)) => serializer.serialize_u64(*x),
+            (Format::U128, Value::U128(x)) => serializer.serialize_u128(*x),
+            (Format::F32, Value::F32(x)) => serializer.serialize_f32(*x),
+            (Format::F64, Value::F64(x)) => serializer.serialize_f64(*x),
+            (Format::Char, Value::Char(x)) => serializer.serialize_char(*x),
+            (Format::Str, Value::Str(x)) => serializer.serialize_str(x),
+            (Format::Bytes, Value::Bytes(x)) => serializer.serialize_bytes(x),
+            (Format::Option(_), Value::Option(None)) => serializer.serialize_none(),
+            (Format::Option(format), Value::Option(Some(value))) => {
+                serializer.serialize_some(&self.with(format, value))
+            }
+            (Format::Seq(format), Value::Seq(values)) => {
+                let mut s = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    s.serialize_element(&self.with(format, value))?;
+                }
+                s.end()
+            }
+            (Format::Map { key, value }, Value::Seq(values)) if values.len() % 2 == 0 => {
+                let mut s = serializer.serialize_map(Some(values.len() / 2))?;
+                for entry in values.chunks(2) {
+                    s.serialize_entry(&self.with(key, &entry[0]), &self.with(value, &entry[1]))?;
+                }
+                s.end()
+            }
+            (Format::Tuple(formats), value) => {
+                let values = seq_values(value, formats.len())?;
+                let mut s = serializer.serialize_tuple(formats.len())?;
+                for (format, value) in formats.iter().zip(values) {
+                    s.serialize_element(&self.with(format, value))?;
+                }
+                s.end()
+            }
+            (Format::TupleArray { content, size }, value) => {
+                let values = seq_values(value, *size)?;
+                let mut s = serializer.serialize_tuple(*size)?;
+                for value in values {
+                    s.serialize_element(&self.with(content, value))?;
+                }
+                s.end()
+            }
+            (format, value) => Err(S::Error::custom(format!(
+                "value {:?} does not match format {:?}",
+                value, format
+            ))),
+        }
+    }
+}
+
+/// Kinds of targeted invalid inputs produced by a [`Mutator`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Mutation {
+    /// An enum variant index out of range.
+    InvalidVariantIndex,
+    /// A string containing invalid UTF-8.
+    InvalidUtf8,
+    /// Map entries in decreasing order of serialized keys (BCS only).
+    UnsortedMapKeys,
+    /// A map entry repeated twice (BCS only).
+    DuplicateMapKeys,
+    /// A length or a variant index encoded in ULEB128 with a superfluous zero byte (BCS only).
+    NonCanonicalUleb128,
+    /// Containers nested beyond the maximal depth (BCS only).
+    ExcessiveDepth,
+}
+
+impl Mutation {
+    /// The mutations that produce invalid inputs for the given encoding.
+    pub fn all(encoding: Encoding) -> Vec<Mutation> {
+        match encoding {
+            Encoding::Bcs => vec![
+                Mutation::InvalidVariantIndex,
+                Mutation::InvalidUtf8,
+                Mutation::UnsortedMapKeys,
+                Mutation::DuplicateMapKeys,
+                Mutation::NonCanonicalUleb128,
+                Mutation::ExcessiveDepth,
+            ],
+            Encoding::Bincode => vec![Mutation::InvalidVariantIndex, Mutation::InvalidUtf8],
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mutation::InvalidVariantIndex => "invalid_variant_index",
+            Mutation::InvalidUtf8 => "invalid_utf8",
+            Mutation::UnsortedMapKeys => "unsorted_map_keys",
+            Mutation::DuplicateMapKeys => "duplicate_map_keys",
+            Mutation::NonCanonicalUleb128 => "non_canonical_uleb128",
+            Mutation::ExcessiveDepth => "excessive_depth",
+        }
+    }
+}
+
+/// An invalid input labeled with the mutation that produced it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NegativeSample {
+    pub mutation: Mutation,
+    pub bytes: Vec<u8>,
+}
+
+/// Generate invalid inputs by applying a [`Mutation`] to the serialization of random values.
+pub struct Mutator<'a, R> {
+    registry: &'a Registry,
+    encoding: Encoding,
+    generator: ValueGenerator<'a, R>,
+    /// Containers whose values may contain containers nested at any depth.
+    deep_containers: BTreeSet<&'a str>,
+}
+
+impl<'a, R: rand::Rng> Mutator<'a, R> {
+    /// How many random values to try before giving up on a mutation.
+    const MAX_ATTEMPTS: usize = 16;
+
+    pub fn new(
+        registry: &'a Registry,
+        encoding: Encoding,
+        config: ValueGeneratorConfig,
+        rng: R,
+    ) -> Result<Self> {
+        let generator = ValueGenerator::new(registry, config, rng)?;
+        let mut successors = BTreeMap::new();
+        for (name, format) in registry {
+            let mut names = BTreeSet::new();
+            format.visit(&mut |format| {
+                if let Format::TypeName(name) = format {
+                    names.insert(name.as_str());
+                }
+                Ok(())
+            })?;
+            successors.insert(name.as_str(), names);
+        }
+        let reachable = |name: &'a str| {
+            let mut seen = BTreeSet::new();
+            let mut stack = vec![name];
+            while let Some(name) = stack.pop() {
+                for next in successors.get(name).into_iter().flatten() {
+                    if seen.insert(*next) {
+                        stack.push(*next);
+                    }
+                }
+            }
+            seen
+        };
+        let reachables = registry
+            .keys()
+            .map(|name| (name.as_str(), reachable(name)))
+            .collect::<BTreeMap<_, _>>();
+        let deep_containers = reachables
+            .iter()
+            .filter(|(_, names)| names.iter().any(|name| reachables[name].contains(name)))
+            .map(|(name, _)| *name)
+            .collect();
+        Ok(Self {
+            registry,
+            encoding,
+            generator,
+            deep_containers,
+        })
+    }
+
+    /// Generate an invalid input for the given format. Return `None` if the mutation did not
+    /// apply to the random values that were tried.
+    pub fn generate(&mut self, format: &Format, mutation: Mutation) -> Result<Option<Vec<u8>>> {
+        if mutation == Mutation::ExcessiveDepth {
+            if self.encoding != Encoding::Bcs || !self.is_deep(format) {
+                return Ok(None);
+            }
+            let value = self.generate_deep_value(format, bcs::MAX_CONTAINER_DEPTH)?;
+            return Ok(Some(self.encode(format, &value, None)?.bytes));
+        }
+        for _ in 0..Self::MAX_ATTEMPTS {
+            let value = self.generator.generate(format)?;
+            let sites = self
+                .encode(format, &value, Some((mutation, usize::MAX)))?
+                .sites;
+            if sites > 0 {
+                let site = self.generator.rng.gen_range(0..sites);
+                let encoder = self.encode(format, &value, Some((mutation, site)))?;
+                return Ok(Some(encoder.bytes));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Generate up to `count` distinct invalid inputs for each mutation supported by the encoding.
+    pub fn generate_corpus(
+        &mut self,
+        format: &Format,
+        count: usize,
+    ) -> Result<Vec<NegativeSample>> {
+        let mut samples = Vec::new();
+        for mutation in Mutation::all(self.encoding) {
+            let mut seen = BTreeSet::new();
+            for _ in 0..count {
+                if let Some(bytes) = self.generate(format, mutation)? {
+                    if seen.insert(bytes.clone()) {
+                        samples.push(NegativeSample { mutation, bytes });
+                    }
+                }
+            }
+        }
+        Ok(samples)
+    }
+
+    fn encode(
+        &self,
+        format: &Format,
+        value: &Value,
+        mutation: Option<(Mutation, usize)>,
+    ) -> Result<Encoder<'a>> {
+        let mut encoder = Encoder {
+            registry: self.registry,
+            encoding: self.encoding,
+            mutation,
+            sites: 0,
+            bytes: Vec::new(),
+        };
+        encoder.encode(format, value)?;
+        Ok(encoder)
+    }
+
+    /// Whether values of the given format may contain containers nested at any depth.
+    fn is_deep(&self, format: &Format) -> bool {
+        let mut result = false;
+        format
+            .visit(&mut |format| {
+                if let Format::TypeName(name) = format {
+                    result |= self.deep_containers.contains(name.as_str());
+                }
+                Ok(())
+            })
+            .unwrap();
+        result
+    }
+
+    fn generate_minimal_value(&mut self, format: &Format) -> Result<Value> {
+        let depth = self.generator.config.max_depth;
+        self.generator.generate_at_depth(format, depth)
+    }
+
+    /// Generate the values of the given formats, with nested containers in the first format that
+    /// allows it.
+    fn generate_deep_values<'b, I>(&mut self, formats: I, depth: usize) -> Result<Value>
+    where
+        I: IntoIterator<Item = &'b Format>,
+    {
+        let mut is_done = false;
+        let mut values = Vec::new();
+        for format in formats {
+            if !is_done && self.is_deep(format) {
+                is_done = true;
+                values.push(self.generate_deep_value(format, depth)?);
+            } else {
+                values.push(self.generate_minimal_value(format)?);
+            }
+        }
+        Ok(Value::Seq(values))
+    }
+
+    /// Generate a value with more than `depth` nested containers, assuming that the format allows it.
+    fn generate_deep_value(&mut self, format: &Format, depth: usize) -> Result<Value> {
+        use Format::*;
+        let value =
+            match format {
+                TypeName(name) if depth > 0 && self.deep_containers.contains(name.as_str()) => {
+                    let depth = depth - 1;
+                    match &self.registry[name] {
+                        ContainerFormat::UnitStruct => Value::Unit,
+                        ContainerFormat::NewTypeStruct(format) => {
+                            self.generate_deep_value(format, depth)?
+                        }
+                        ContainerFormat::TupleStruct(formats) => {
+                            self.generate_deep_values(formats, depth)?
+                        }
+                        ContainerFormat::Struct(fields) => {
+                            self.generate_deep_values(fields.iter().map(|f| &f.value), depth)?
+                        }
+                        ContainerFormat::Enum(variants) => {
+                            let (index, variant) = variants
+                                .iter()
+                                .find(|(_, variant)| match &variant.value {
+                                    VariantFormat::NewType(format) => self.is_deep(format),
+                                    VariantFormat::Tuple(formats) => {
+                                        formats.iter().any(|f| self.is_deep(f))
+                                    }
+                                    VariantFormat::Struct(fields) => {
+                                        fields.iter().any(|f| self.is_deep(&f.value))
+                                    }
+                                    VariantFormat::Unit | VariantFormat::Variable(_) => false,
+                                })
+                                .expect("deep enums should have a deep variant");
+                            let value = match &variant.value {
+                                VariantFormat::NewType(format) => {
+                                    self.generate_deep_value(format, depth)?
+                                }
+                                VariantFormat::Tuple(formats) => {
+                                    self.generate_deep_values(formats, depth)?
+                                }
+                                VariantFormat::Struct(fields) => self
+                                    .generate_deep_values(fields.iter().map(|f| &f.value), depth)?,
+                                VariantFormat::Unit | VariantFormat::Variable(_) => unreachable!(),
+                            };
+                            Value::Variant(*index, Box::new(value))
+                        }
+                        ContainerFormat::Generic { .. } => {
+                            unreachable!("generic containers are not deep")
+                        }
+                    }
+                }
+                Option(format) if depth > 0 && self.is_deep(format) => {
+                    Value::Option(Some(Box::new(self.generate_deep_value(format, depth)?)))
+                }
+                Seq(format) if depth > 0 && self.is_deep(format) => {
+                    Value::Seq(vec![self.generate_deep_value(format, depth)?])
+                }
+                Map { key, value } if depth > 0 && (self.is_deep(key) || self.is_deep(value)) => {
+                    self.generate_deep_values(vec![key.as_ref(), value.as_ref()], depth)?
+                }
+                Tuple(formats) if depth > 0 => self.generate_deep_values(formats, depth)?,
+                TupleArray { content, size } if depth > 0 => self
+                    .generate_deep_values(std::iter::repeat(content.as_ref()).take(*size), depth)?,
+                _ => self.generate_minimal_value(format)?,
+            };
+        Ok(value)
+    }
+}
+
+/// Write each sample of a corpus in a separate file named after its mutation, e.g. for
+/// `cargo fuzz`.
+pub fn write_corpus(dir: &std::path::Path, samples: &[NegativeSample]) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for (index, sample) in samples.iter().enumerate() {
+        let path = dir.join(format!("{}-{:04}", sample.mutation.name(), index));
+        std::fs::write(path, &sample.bytes)?;
+    }
+    Ok(())
+}
+
+/// Serialize a value of a given format in a binary encoding, while applying a mutation
+/// at the given site (if any).
+struct Encoder<'a> {
+    registry: &'a Registry,
+    encoding: Encoding,
+    mutation: Option<(Mutation, usize)>,
+    /// How many places where the mutation may apply were seen so far.
+    sites: usize,
+    bytes: Vec<u8>,
+}
+
+impl<'a> Encoder<'a> {
+    /// Record a new site for the given mutation and return whether to apply the mutation here.
+    fn is_mutation_site(&mut self, mutation: Mutation) -> bool {
+        match self.mutation {
+            Some((m, site)) if m == mutation => {
+                let result = self.sites == site;
+                self.sites += 1;
+                result
+            }
+            _ => false,
+        }
+    }
+
+    fn encode_uleb128(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.bytes.push((value & 0x7f) as u8 | 0x80);
+            value >>= 7;
+        }
+        if self.is_mutation_site(Mutation::NonCanonicalUleb128) {
+            self.bytes.push(value as u8 | 0x80);
+            self.bytes.push(0);
+        } else {
+            self.bytes.push(value as u8);
+        }
+    }
+
+    fn encode_length(&mut self, length: usize) {
+        match self.encoding {
+            Encoding::Bcs => self.encode_uleb128(length as u64),
+            Encoding::Bincode => self.bytes.extend(&(length as u64).to_le_bytes()),
+        }
+    }
+
+    fn encode_variant_index(&mut self, index: u32) {
+        match self.encoding {
+            Encoding::Bcs => self.encode_uleb128(index as u64),
+            Encoding::Bincode => self.bytes.extend(&index.to_le_bytes()),
+        }
+    }
+
+    fn encode_values<'b, I>(&mut self, formats: I, value: &Value) -> Result<()>
+    where
+        I: ExactSizeIterator<Item = &'b Format>,
+    {
+        let values = seq_values::<Error>(value, formats.len())?;
+        for (format, value) in formats.zip(values) {
+            self.encode(format, value)?;
+        }
+        Ok(())
+    }
+
+    fn encode(&mut self, format: &Format, value: &Value) -> Result<()> {
+        match (format, value) {
+            (Format::TypeName(name), value) => {
+                let container = self
+                    .registry
+                    .get(name)
+                    .ok_or_else(|| Error::Custom(format!("Missing container `{}`", name)))?;
+                self.encode_container(container, value)?;
+            }
+            (Format::Unit, Value::Unit) => (),
+            (Format::Bool, Value::Bool(x)) => self.bytes.push(*x as u8),
+            (Format::I8, Value::I8(x)) => self.bytes.extend(&x.to_le_bytes()),
+            (Format::I16, Value::I16(x)) => self.bytes.extend(&x.to_le_bytes()),
+            (Format::I32, Value::I32(x)) => self.bytes.extend(&x.to_le_bytes()),
+            (Format::I64, Value::I64(x)) => self.bytes.extend(&x.to_le_bytes()),
+            (Format::I128, Value::I128(x)) => self.bytes.extend(&x.to_le_bytes()),
+            (Format::U8, Value::U8(x)) => self.bytes.push(*x),
+            (Format::U16, Value::U16(x)) => self.bytes.extend(&x.to_le_bytes()),
+            (Format::U32, Value::U32(x)) => self.bytes.extend(&x.to_le_bytes()),
+            (Format::U64, Value::U64(x)) => self.bytes.extend(&x.to_le_bytes()),
+            (Format::U128, Value::U128(x)) => self.bytes.extend(&x.to_le_bytes()),
+            (Format::F32, Value::F32(x)) => self.bytes.extend(&x.to_le_bytes()),
+            (Format::F64, Value::F64(x)) => self.bytes.extend(&x.to_le_bytes()),
+            (Format::Char, Value::Char(x)) => {
+                self.bytes.extend(x.encode_utf8(&mut [0; 4]).as_bytes())
+            }
+            (Format::Str, Value::Str(x)) => {
+                let mut content = x.as_bytes().to_vec();
+                if self.is_mutation_site(Mutation::InvalidUtf8) {
+                    content.push(0xff);
+                }
+                self.encode_length(content.len());
+                self.bytes.extend(content);
+            }
+            (Format::Bytes, Value::Bytes(x)) => {
+                self.encode_length(x.len());
+                self.bytes.extend(x);
+            }
+            (Format::Option(_), Value::Option(None)) => self.bytes.push(0),
+            (Format::Option(format), Value::Option(Some(value))) => {
+                self.bytes.push(1);
+                self.encode(format, value)?;
+            }
+            (Format::Seq(format), Value::Seq(values)) => {
+                self.encode_length(values.len());
+                for value in values {
+                    self.encode(format, value)?;
+                }
+            }
+            (Format::Map { key, value }, Value::Seq(values)) if values.len() % 2 == 0 => {
+                let mut entries = Vec::new();
+                for entry in values.chunks(2) {
+                    let bytes = std::mem::take(&mut self.bytes);
+                    self.encode(key, &entry[0])?;
+                    let key_bytes = std::mem::replace(&mut self.bytes, Vec::new());
+                    self.encode(value, &entry[1])?;
+                    let value_bytes = std::mem::replace(&mut self.bytes, bytes);
+                    entries.push((key_bytes, value_bytes));
+                }
+                if self.encoding == Encoding::Bcs {
+                    entries.sort();
+                    if entries.len() >= 2 && self.is_mutation_site(Mutation::UnsortedMapKeys) {
+                        entries.reverse();
+                    }
+                    if !entries.is_empty() && self.is_mutation_site(Mutation::DuplicateMapKeys) {
+                        entries.insert(0, entries[0].clone());
+                    }
+                }
+                self.encode_length(entries.len());
+                for (key_bytes, value_bytes) in entries {
+                    self.bytes.extend(key_bytes);
+                    self.bytes.extend(value_bytes);
+                }
+            }
+            (Format::Tuple(formats), value) => self.encode_values(formats.iter(), value)?,
+            (Format::TupleArray { content, size }, value) => {
+                self.encode_values(vec![content.as_ref(); *size].into_iter(), value)?
+            }
+            (format, value) => {
+                return Err(Error::Custom(format!(
+                    "Value {:?} does not match format {:?}",
+                    value, format
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    fn encode_container(&mut self, format: &ContainerFormat, value: &Value) -> Result<()> {
+        match format {
+            ContainerFormat::UnitStruct => (),
+            ContainerFormat::NewTypeStruct(format) => self.encode(format, value)?,
+            ContainerFormat::TupleStruct(formats) => self.encode_values(formats.iter(), value)?,
+            ContainerFormat::Struct(fields) => {
+                self.encode_values(fields.iter().map(|f| &f.value), value)?
+            }
+            ContainerFormat::Enum(variants) => {
+                let (index, value) = match value {
+                    Value::Variant(index, value) => (*index, value.as_ref()),
+                    _ => return Err(Error::Custom(format!("Expected a variant: {:?}", value))),
+                };
+                let variant = variants
+                    .get(&index)
+                    .ok_or_else(|| Error::Custom(format!("Unknown variant index {}", index)))?;
+                if self.is_mutation_site(Mutation::InvalidVariantIndex) {
+                    let end = variants.keys().last().map_or(0, |index| index + 1);
+                    self.encode_variant_index(end);
+                } else {
+                    self.encode_variant_index(index);
+                }
+                match &variant.value {
+                    VariantFormat::Variable(_) => return Err(Error::UnknownFormat),
+                    VariantFormat::Unit => (),
+                    VariantFormat::NewType(format) => self.encode(format, value)?,
+                    VariantFormat::Tuple(formats) => self.encode_values(formats.iter(), value)?,
+                    VariantFormat::Struct(fields) => {
+                        self.encode_values(fields.iter().map(|f| &f.value), value)?
+                    }
+                }
+            }
+            ContainerFormat::Generic { .. } => {
+                return Err(Error::NotSupported("generic containers"))
+            }
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_random_values_with_small_depth() {
+    use rand::SeedableRng;
+
+    let registry = crate::test_utils::get_registry().unwrap();
+    let format = Format::TypeName("SimpleList".to_string());
+    let config = ValueGeneratorConfig::default().max_depth(0);
+    let rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut generator = ValueGenerator::new(&registry, config, rng).unwrap();
+    assert_eq!(generator.generate(&format).unwrap(), Value::Option(None));
+}
+
+#[test]
+fn test_random_values_without_finite_values() {
+    let registry: Registry = serde_yaml::from_str(
+        r#"
+Node:
+  NEWTYPESTRUCT:
+    TYPENAME: Node
+"#,
+    )
+    .unwrap();
+    let rng = rand::rngs::mock::StepRng::new(0, 1);
+    assert!(ValueGenerator::new(&registry, ValueGeneratorConfig::default(), rng).is_err());
+}
+
+#[test]
+fn test_mutator_encoding_without_mutations() {
+    let registry = crate::test_utils::get_registry().unwrap();
+    let format = Format::TypeName("SerdeData".to_string());
+    use crate::test_utils::Runtime;
+
+    for &runtime in &[Runtime::Bcs, Runtime::Bincode] {
+        let rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(0);
+        let mut mutator = Mutator::new(
+            &registry,
+            runtime.into(),
+            runtime.value_generator_config(),
+            rng,
+        )
+        .unwrap();
+        for _ in 0..100 {
+            let value = mutator.generator.generate(&format).unwrap();
+            let bytes = mutator.encode(&format, &value, None).unwrap().bytes;
+            assert_eq!(
+                bytes,
+                runtime.serialize_value(&registry, &format, &value).unwrap()
+            );
+        }
+    }
+}
@@ -1,11 +1,14 @@
 // Copyright (c) Facebook, Inc. and its affiliates
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use crate::Encoding;
+use crate::{
+    samples::{Mutator, NegativeSample, ValueGenerator, ValueGeneratorConfig},
+    Encoding,
+};
 use maplit::btreemap;
 use serde::{Deserialize, Serialize};
 use serde_bytes::ByteBuf;
-use serde_reflection::{Registry, Result, Samples, Tracer, TracerConfig};
+use serde_reflection::{Error, Format, Registry, Result, Samples, Tracer, TracerConfig, Value};
 use std::collections::BTreeMap;
 
 // Simple data formats used to create and test values in each language.
@@ -136,6 +139,70 @@ pub fn get_registry() -> Result<Registry> {
     tracer.registry()
 }
 
+/// A registry with generic containers, as computed by `serde_reflection::generalize` from
+/// instances named after their type arguments, e.g. `Pair<u32, str>` (see
+/// `serde_name::generic_name`). The main container is `GenericData`.
+pub fn get_generic_registry() -> Result<Registry> {
+    let registry: Registry = serde_yaml::from_str(
+        r#"
+"Pair<u32, str>":
+  STRUCT:
+    - first: U32
+    - second: STR
+"Pair<str, Tree<u64>>":
+  STRUCT:
+    - first: STR
+    - second:
+        TYPENAME: "Tree<u64>"
+"Tree<u64>":
+  STRUCT:
+    - value: U64
+    - children:
+        SEQ:
+          TYPENAME: "Tree<u64>"
+"Tree<Option<str>>":
+  STRUCT:
+    - value:
+        OPTION: STR
+    - children:
+        SEQ:
+          TYPENAME: "Tree<Option<str>>"
+"Either<u8, Seq<u16>>":
+  ENUM:
+    0:
+      Left:
+        NEWTYPE: U8
+    1:
+      Right:
+        NEWTYPE:
+          SEQ: U16
+"Either<str, unit>":
+  ENUM:
+    0:
+      Left:
+        NEWTYPE: STR
+    1:
+      Right:
+        NEWTYPE: UNIT
+GenericData:
+  STRUCT:
+    - pair:
+        TYPENAME: "Pair<u32, str>"
+    - nested:
+        TYPENAME: "Pair<str, Tree<u64>>"
+    - tree:
+        TYPENAME: "Tree<Option<str>>"
+    - either:
+        TYPENAME: "Either<u8, Seq<u16>>"
+    - eithers:
+        SEQ:
+          TYPENAME: "Either<str, unit>"
+"#,
+    )
+    .map_err(|e| Error::Custom(e.to_string()))?;
+    serde_reflection::generalize(registry)
+}
+
 /// Manually generate sample values.
 /// Avoid maps with more than one element when `has_canonical_maps` is false so that
 /// we can test re-serialization.
@@ -378,6 +445,77 @@ impl Runtime {
         }
     }
 
+    /// Serialize a value of the given format, e.g. as produced by a [`ValueGenerator`].
+    pub fn serialize_value(
+        self,
+        registry: &Registry,
+        format: &Format,
+        value: &Value,
+    ) -> Result<Vec<u8>> {
+        crate::samples::serialize_value(registry, self.into(), format, value)
+    }
+
+    /// Configuration of the random values that can be tested with this encoding in all languages.
+    pub fn value_generator_config(self) -> ValueGeneratorConfig {
+        ValueGeneratorConfig::default()
+            .has_canonical_maps(self.has_canonical_maps())
+            .has_floats(self.has_floats())
+            .has_chars(false)
+    }
+
+    /// Serialize random values of type `SerdeData`. The same seed produces the same samples.
+    pub fn get_random_samples(self, seed: u64, count: usize) -> Vec<Vec<u8>> {
+        let registry = get_registry().unwrap();
+        self.get_random_samples_for(&registry, "SerdeData", seed, count)
+    }
+
+    /// Serialize random values of type `GenericData` (see `get_generic_registry`). The same
+    /// seed produces the same samples.
+    pub fn get_generic_random_samples(self, seed: u64, count: usize) -> Vec<Vec<u8>> {
+        let registry = serde_reflection::monomorphize(&get_generic_registry().unwrap()).unwrap();
+        self.get_random_samples_for(&registry, "GenericData", seed, count)
+    }
+
+    fn get_random_samples_for(
+        self,
+        registry: &Registry,
+        name: &str,
+        seed: u64,
+        count: usize,
+    ) -> Vec<Vec<u8>> {
+        let rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(seed);
+        let mut generator =
+            ValueGenerator::new(registry, self.value_generator_config(), rng).unwrap();
+        let format = Format::TypeName(name.to_string());
+        (0..count)
+            .map(|_| {
+                let value = generator.generate(&format).unwrap();
+                self.serialize_value(registry, &format, &value).unwrap()
+            })
+            .collect()
+    }
+
+    /// Generate targeted invalid inputs for the type `SerdeData`. The same seed produces the
+    /// same samples.
+    pub fn get_mutated_samples(self, seed: u64, count: usize) -> Vec<NegativeSample> {
+        // Values nested beyond the maximal depth of BCS may exceed the default stack size of
+        // test threads in debug builds.
+        std::thread::Builder::new()
+            .stack_size(64 << 20)
+            .spawn(move || {
+                let registry = get_registry().unwrap();
+                let rng = <rand::rngs::StdRng as rand::SeedableRng>::seed_from_u64(seed);
+                let mut mutator =
+                    Mutator::new(&registry, self.into(), self.value_generator_config(), rng)
+                        .unwrap();
+                let format = Format::TypeName("SerdeData".to_string());
+                mutator.generate_corpus(&format, count).unwrap()
+            })
+            .unwrap()
+            .join()
+            .unwrap()
+    }
+
     /// Serialize a value then add noise to the serialized bits repeatedly. Additionally return
     /// `true` if the deserialization of each modified bitstring should succeed.
     pub fn serialize_with_noise_and_deserialize<T>(self, value: &T) -> Vec<(Vec<u8>, bool)>
@@ -807,6 +945,23 @@ UnitStruct: UNITSTRUCT
     );
 }
 
+#[test]
+fn test_get_generic_registry() {
+    let registry = get_generic_registry().unwrap();
+    assert_eq!(
+        registry.keys().cloned().collect::<Vec<_>>(),
+        vec!["Either", "GenericData", "Pair", "Tree"]
+    );
+    for name in &["Either", "Pair", "Tree"] {
+        assert!(matches!(
+            registry[*name],
+            serde_reflection::ContainerFormat::Generic { .. }
+        ));
+    }
+    let samples = Runtime::Bcs.get_generic_random_samples(0, 10);
+    assert_eq!(samples.len(), 10);
+}
+
 #[test]
 fn test_bincode_get_sample_with_long_sequence() {
     test_get_sample_with_long_sequence(Runtime::Bincode);
@@ -961,3 +1116,71 @@ fn test_bcs_serialize_with_noise_and_deserialize() {
     // 1 for added incorrect 5-byte UTF8-like codepoint
     assert_eq!(samples.len(), value.len() + 3);
 }
+
+#[test]
+fn test_bincode_random_values() {
+    test_random_values(Runtime::Bincode);
+}
+
+#[test]
+fn test_bcs_random_values() {
+    test_random_values(Runtime::Bcs);
+}
+
+// Make sure that random values are serialized exactly like the corresponding Rust values.
+#[cfg(test)]
+fn test_random_values(runtime: Runtime) {
+    use rand::SeedableRng;
+    use serde::de::IntoDeserializer;
+
+    let registry = get_registry().unwrap();
+    let format = Format::TypeName("SerdeData".to_string());
+    // Unlike `BTreeMap`, Bincode does not sort map entries, hence the maps with at most one entry.
+    let config = runtime
+        .value_generator_config()
+        .has_chars(matches!(runtime, Runtime::Bincode));
+    let rng = rand::rngs::StdRng::seed_from_u64(0);
+    let mut generator = ValueGenerator::new(&registry, config, rng).unwrap();
+    for _ in 0..500 {
+        let value = generator.generate(&format).unwrap();
+        let bytes = runtime.serialize_value(&registry, &format, &value).unwrap();
+        let data = SerdeData::deserialize((&value).into_deserializer()).unwrap();
+        assert_eq!(runtime.serialize(&data), bytes);
+        assert_eq!(runtime.deserialize::<SerdeData>(&bytes), Some(data));
+    }
+    assert_eq!(
+        runtime.get_random_samples(1, 10),
+        runtime.get_random_samples(1, 10)
+    );
+}
+
+#[test]
+fn test_bcs_mutated_samples() {
+    test_mutated_samples(Runtime::Bcs);
+}
+
+#[test]
+fn test_bincode_mutated_samples() {
+    test_mutated_samples(Runtime::Bincode);
+}
+
+#[cfg(test)]
+fn test_mutated_samples(runtime: Runtime) {
+    use crate::samples::Mutation;
+
+    let samples = runtime.get_mutated_samples(0, 20);
+    for mutation in Mutation::all(runtime.into()) {
+        assert!(
+            samples.iter().any(|s| s.mutation == mutation),
+            "{:?}",
+            mutation
+        );
+    }
+    for sample in samples {
+        assert!(
+            runtime.deserialize::<SerdeData>(&sample.bytes).is_none(),
+            "{:?}",
+            sample
+        );
+    }
+}
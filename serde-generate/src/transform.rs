@@ -0,0 +1,261 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{analyzer, Error, Result};
+use serde_reflection::{ContainerFormat, Format, FormatHolder, Named, Registry, VariantFormat};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Compute the names of the containers reachable from the given roots, including the roots.
+pub fn get_reachable_containers<'a, S>(
+    registry: &'a Registry,
+    roots: &[S],
+) -> Result<BTreeSet<&'a str>>
+where
+    S: AsRef<str>,
+{
+    let dependencies = analyzer::get_dependency_map(registry)?;
+    let mut result = BTreeSet::new();
+    let mut queue = Vec::new();
+    for root in roots {
+        let (name, _) = registry
+            .get_key_value(root.as_ref())
+            .ok_or_else(|| Error::UnknownContainer(root.as_ref().to_string()))?;
+        queue.push(name.as_str());
+    }
+    while let Some(name) = queue.pop() {
+        if !result.insert(name) {
+            continue;
+        }
+        // References to names outside of the registry are left for code generators to report.
+        if let Some(children) = dependencies.get(name) {
+            queue.extend(children.iter().copied());
+        }
+    }
+    Ok(result)
+}
+
+/// Remove the containers that are not reachable from the given roots.
+pub fn prune<S>(registry: &mut Registry, roots: &[S]) -> Result<()>
+where
+    S: AsRef<str>,
+{
+    let reachable = get_reachable_containers(registry, roots)?;
+    let unreachable = registry
+        .keys()
+        .filter(|name| !reachable.contains(name.as_str()))
+        .cloned()
+        .collect::<Vec<_>>();
+    for name in unreachable {
+        registry.remove(&name);
+    }
+    Ok(())
+}
+
+/// Return a copy of the subgraph of the registry made of the given roots and their
+/// transitive dependencies.
+pub fn extract_subgraph<S>(registry: &Registry, roots: &[S]) -> Result<Registry>
+where
+    S: AsRef<str>,
+{
+    let reachable = get_reachable_containers(registry, roots)?;
+    Ok(registry
+        .iter()
+        .filter(|(name, _)| reachable.contains(name.as_str()))
+        .map(|(name, format)| (name.clone(), format.clone()))
+        .collect())
+}
+
+/// How to rename the containers, fields, and variants of a registry (see `rename`).
+/// Fields and variants are designated by the original name of their container.
+///
+/// Note that renaming fields and variants changes the data layout of self-describing
+/// encodings such as JSON, but not the one of Bincode or BCS.
+#[derive(Clone, Debug, Default)]
+pub struct Renaming {
+    containers: BTreeMap<String, String>,
+    fields: BTreeMap<String, BTreeMap<String, String>>,
+    variants: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Renaming {
+    /// Default renaming (i.e. nothing is renamed).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Rename the container `name`. All references to the container are rewritten.
+    pub fn with_container(mut self, name: String, new_name: String) -> Self {
+        self.containers.insert(name, new_name);
+        self
+    }
+
+    /// Rename a field of the container `container`. In the case of an enum, the field is
+    /// renamed in all the struct variants where it appears.
+    pub fn with_field(mut self, container: String, field: String, new_name: String) -> Self {
+        self.fields
+            .entry(container)
+            .or_default()
+            .insert(field, new_name);
+        self
+    }
+
+    /// Rename a variant of the enum `container`.
+    pub fn with_variant(mut self, container: String, variant: String, new_name: String) -> Self {
+        self.variants
+            .entry(container)
+            .or_default()
+            .insert(variant, new_name);
+        self
+    }
+
+    fn container_name<'a>(&'a self, name: &'a str) -> &'a str {
+        self.containers
+            .get(name)
+            .map(String::as_str)
+            .unwrap_or(name)
+    }
+}
+
+/// Return a copy of the registry where containers, fields, and variants are renamed, and
+/// references to containers (`Format::TypeName` and `Format::TypeApp`) are rewritten accordingly.
+///
+/// Renaming an unknown container, field, or variant is an error, and so is renaming two
+/// containers (or two fields or variants of the same container) to the same name.
+pub fn rename(registry: &Registry, renaming: &Renaming) -> Result<Registry> {
+    for name in renaming
+        .containers
+        .keys()
+        .chain(renaming.fields.keys())
+        .chain(renaming.variants.keys())
+    {
+        if !registry.contains_key(name) {
+            return Err(Error::UnknownContainer(name.clone()));
+        }
+    }
+
+    let mut result = Registry::new();
+    for (name, format) in registry {
+        let new_name = renaming.container_name(name).to_string();
+        let mut format = format.clone();
+        rename_references(&mut format, renaming)?;
+        if let Some(fields) = renaming.fields.get(name) {
+            rename_fields(name, &mut format, fields)?;
+        }
+        if let Some(variants) = renaming.variants.get(name) {
+            rename_variants(name, &mut format, variants)?;
+        }
+        if result.insert(new_name.clone(), format).is_some() {
+            return Err(Error::InvalidRenaming(format!(
+                "several containers are named `{}`",
+                new_name
+            )));
+        }
+    }
+    Ok(result)
+}
+
+fn rename_references(format: &mut ContainerFormat, renaming: &Renaming) -> Result<()> {
+    format.visit_mut(&mut |format| {
+        if let Format::TypeName(name) | Format::TypeApp { name, .. } = format {
+            *name = renaming.container_name(name).to_string();
+        }
+        Ok(())
+    })?;
+    Ok(())
+}
+
+fn rename_fields(
+    container: &str,
+    format: &mut ContainerFormat,
+    fields: &BTreeMap<String, String>,
+) -> Result<()> {
+    let mut renamed = BTreeSet::new();
+    match format {
+        ContainerFormat::Struct(named_formats) => {
+            rename_named(container, named_formats, fields, &mut renamed)?;
+        }
+        ContainerFormat::Enum(variants) => {
+            for variant in variants.values_mut() {
+                if let VariantFormat::Struct(named_formats) = &mut variant.value {
+                    rename_named(container, named_formats, fields, &mut renamed)?;
+                }
+            }
+        }
+        ContainerFormat::Generic { format, .. } => {
+            return rename_fields(container, format, fields);
+        }
+        ContainerFormat::UnitStruct
+        | ContainerFormat::NewTypeStruct(_)
+        | ContainerFormat::TupleStruct(_) => (),
+    }
+    match fields
+        .keys()
+        .find(|field| !renamed.contains(field.as_str()))
+    {
+        Some(field) => Err(Error::InvalidRenaming(format!(
+            "container `{}` has no field `{}`",
+            container, field
+        ))),
+        None => Ok(()),
+    }
+}
+
+fn rename_variants(
+    container: &str,
+    format: &mut ContainerFormat,
+    variants: &BTreeMap<String, String>,
+) -> Result<()> {
+    let mut renamed = BTreeSet::new();
+    match format {
+        ContainerFormat::Enum(named_formats) => {
+            rename_named(
+                container,
+                named_formats.values_mut(),
+                variants,
+                &mut renamed,
+            )?;
+        }
+        ContainerFormat::Generic { format, .. } => {
+            return rename_variants(container, format, variants);
+        }
+        _ => (),
+    }
+    match variants
+        .keys()
+        .find(|variant| !renamed.contains(variant.as_str()))
+    {
+        Some(variant) => Err(Error::InvalidRenaming(format!(
+            "container `{}` has no variant `{}`",
+            container, variant
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Rename a list of named values, recording the original names that were found. The new
+/// names must be distinct.
+fn rename_named<'a, 'b, T, I>(
+    container: &str,
+    named_values: I,
+    new_names: &'a BTreeMap<String, String>,
+    renamed: &mut BTreeSet<&'a str>,
+) -> Result<()>
+where
+    T: 'b,
+    I: IntoIterator<Item = &'b mut Named<T>>,
+{
+    let mut names = BTreeSet::new();
+    for named in named_values {
+        if let Some((name, new_name)) = new_names.get_key_value(&named.name) {
+            renamed.insert(name.as_str());
+            named.name = new_name.clone();
+        }
+        if !names.insert(named.name.clone()) {
+            return Err(Error::InvalidRenaming(format!(
+                "container `{}` uses the name `{}` twice",
+                container, named.name
+            )));
+        }
+    }
+    Ok(())
+}
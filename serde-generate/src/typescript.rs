@@ -13,7 +13,7 @@ use serde_reflection::{ContainerFormat, Format, FormatHolder, Named, Registry, V
 use crate::{
     common,
     indent::{IndentConfig, IndentedWriter},
-    CodeGeneratorConfig,
+    CodeGeneratorConfig, Error,
 };
 use heck::CamelCase;
 
@@ -26,6 +26,20 @@ pub struct CodeGenerator<'a> {
     external_qualified_names: HashMap<String, String>,
     /// vector of namespaces to import
     namespaces_to_import: Vec<String>,
+    /// Whether to emit plain interfaces and discriminated unions with standalone
+    /// (de)serialization functions instead of classes.
+    discriminated_unions: bool,
+    /// How to represent 64-bit and 128-bit integers.
+    big_integer_mode: BigIntegerMode,
+}
+
+/// How 64-bit and 128-bit integers (`U64`, `I64`, `U128`, `I128`) are represented in TypeScript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BigIntegerMode {
+    /// Native `bigint` values (default).
+    BigInt,
+    /// `number` values. The runtime checks that values are safe integers within range.
+    Number,
 }
 
 /// Shared state for the code generation of a TypeScript source file.
@@ -34,14 +48,15 @@ struct TypeScriptEmitter<'a, T> {
     out: IndentedWriter<T>,
     /// Generator.
     generator: &'a CodeGenerator<'a>,
+    /// Fingerprints of the containers, if requested.
+    fingerprints: BTreeMap<String, u64>,
+    /// Type parameters of the generic container being emitted (if any).
+    type_parameters: Vec<String>,
 }
 
 impl<'a> CodeGenerator<'a> {
     /// Create a TypeScript code generator for the given config.
     pub fn new(config: &'a CodeGeneratorConfig) -> Self {
-        if config.c_style_enums {
-            panic!("TypeScript does not support generating c-style enums");
-        }
         let mut external_qualified_names = HashMap::new();
         for (namespace, names) in &config.external_definitions {
             for name in names {
@@ -59,20 +74,55 @@ impl<'a> CodeGenerator<'a> {
                 .keys()
                 .map(|k| k.to_string())
                 .collect::<Vec<_>>(),
+            discriminated_unions: false,
+            big_integer_mode: BigIntegerMode::BigInt,
         }
     }
 
+    /// How to represent 64-bit and 128-bit integers.
+    pub fn with_big_integer_mode(mut self, big_integer_mode: BigIntegerMode) -> Self {
+        self.big_integer_mode = big_integer_mode;
+        self
+    }
+
+    /// Whether to emit plain interfaces and discriminated unions (e.g.
+    /// `type Foo = FooVariantA | FooVariantB` where each variant has a field `kind: "A"`)
+    /// instead of classes. Serialization then relies on standalone functions
+    /// `serializeFoo(value, serializer)` and `deserializeFoo(deserializer)`, which produce the
+    /// same bytes as the methods of the class-based output.
+    pub fn with_discriminated_unions(mut self, discriminated_unions: bool) -> Self {
+        self.discriminated_unions = discriminated_unions;
+        self
+    }
+
     /// Output class definitions for `registry` in a single source file.
-    pub fn output(&self, out: &mut dyn Write, registry: &Registry) -> Result<()> {
+    pub fn output(&self, out: &mut dyn Write, registry: &Registry) -> crate::Result<()> {
+        if self.config.c_style_enums {
+            return Err(Error::UnsupportedOption {
+                language: "TypeScript",
+                option: "c-style enums",
+            });
+        }
+        common::check_registry(registry)?;
+        if self.discriminated_unions {
+            Self::check_discriminated_unions(registry)?;
+        }
         let mut emitter = TypeScriptEmitter {
             out: IndentedWriter::new(out, IndentConfig::Space(2)),
             generator: self,
+            fingerprints: common::get_fingerprints(self.config, registry)?,
+            type_parameters: Vec::new(),
         };
 
         emitter.output_preamble()?;
 
         for (name, format) in registry {
-            emitter.output_container(name, format)?;
+            if self.discriminated_unions {
+                emitter.output_union_container(name, format)?;
+            } else {
+                emitter.output_container(name, format)?;
+            }
+            emitter.output_fingerprint(name)?;
         }
 
         if self.config.serialization {
@@ -81,6 +131,30 @@ impl<'a> CodeGenerator<'a> {
 
         Ok(())
     }
+
+    /// Variants of discriminated unions are tagged with a field `kind`.
+    fn check_discriminated_unions(registry: &Registry) -> crate::Result<()> {
+        for (name, format) in registry {
+            let format = match format {
+                ContainerFormat::Generic { format, .. } => format.as_ref(),
+                _ => format,
+            };
+            if let ContainerFormat::Enum(variants) = format {
+                for variant in variants.values() {
+                    if let VariantFormat::Struct(fields) = &variant.value {
+                        if fields.iter().any(|f| f.name == "kind") {
+                            return Err(Error::ReservedName {
+                                container: name.clone(),
+                                field: format!("{}.kind", variant.name),
+                                reason: "in discriminated unions",
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<'a, T> TypeScriptEmitter<'a, T>
@@ -125,9 +199,28 @@ import {{ Optional, Seq, Tuple, ListTuple, unit, bool, int8, int16, int32, int64
         Ok(())
     }
 
+    /// Compute a reference to the standalone function `prefix{name}` (e.g. `serializeFoo`).
+    fn quote_qualified_function(&self, prefix: &str, name: &str) -> String {
+        let qualified_name = self.quote_qualified_name(name);
+        match qualified_name.rfind('.') {
+            Some(pos) => format!(
+                "{}{}{}",
+                &qualified_name[..=pos],
+                prefix,
+                &qualified_name[pos + 1..]
+            ),
+            None => format!("{}{}", prefix, qualified_name),
+        }
+    }
+
     fn quote_type(&self, format: &Format) -> String {
         use Format::*;
         match format {
+            I64 | I128 | U64 | U128
+                if self.generator.big_integer_mode == BigIntegerMode::Number =>
+            {
+                "number".into()
+            }
             TypeName(x) => self.quote_qualified_name(x),
             Unit => "unit".into(),
             Bool => "bool".into(),
@@ -157,10 +250,78 @@ import {{ Optional, Seq, Tuple, ListTuple, unit, bool, int8, int16, int32, int64
                 content,
                 size: _size,
             } => format!("ListTuple<[{}]>", self.quote_type(content),),
+            Parameter(name) => name.to_string(),
+            TypeApp { name, arguments } => format!(
+                "{}<{}>",
+                self.quote_qualified_name(name),
+                self.quote_types(arguments, ", ")
+            ),
             Variable(_) => panic!("unexpected value"),
         }
     }
 
+    /// Type parameters of a generic definition, e.g. `<T0, T1>`.
+    fn quote_type_parameters(parameters: &[String]) -> String {
+        if parameters.is_empty() {
+            String::new()
+        } else {
+            format!("<{}>", parameters.join(", "))
+        }
+    }
+
+    /// Extra parameters of generic serialization functions: one function per type parameter.
+    fn quote_serialize_parameters(parameters: &[String]) -> String {
+        parameters
+            .iter()
+            .map(|p| {
+                format!(
+                    ", serialize{0}: (value: {0}, serializer: Serializer) => void",
+                    p
+                )
+            })
+            .collect()
+    }
+
+    /// Extra parameters of generic deserialization functions: one function per type parameter.
+    fn quote_deserialize_parameters(parameters: &[String]) -> String {
+        parameters
+            .iter()
+            .map(|p| format!(", deserialize{0}: (deserializer: Deserializer) => {0}", p))
+            .collect()
+    }
+
+    /// Functions passed along to generic functions for the given type parameters, e.g.
+    /// `, serializeT0, serializeT1`.
+    fn quote_function_arguments(prefix: &str, parameters: &[String]) -> String {
+        parameters
+            .iter()
+            .map(|p| format!(", {}{}", prefix, p))
+            .collect()
+    }
+
+    /// Serialization function for a type argument.
+    fn quote_serialize_function(&self, format: &Format) -> String {
+        match format {
+            Format::Parameter(name) => format!("serialize{}", name),
+            _ => format!(
+                "(value: {}, serializer: Serializer) => {{ {} }}",
+                self.quote_type(format),
+                self.quote_serialize_value("value", format, false)
+            ),
+        }
+    }
+
+    /// Deserialization function for a type argument.
+    fn quote_deserialize_function(&self, format: &Format) -> String {
+        match format {
+            Format::Parameter(name) => format!("deserialize{}", name),
+            _ => format!(
+                "(deserializer: Deserializer) => {}",
+                self.quote_deserialize(format)
+            ),
+        }
+    }
+
     fn quote_types(&self, formats: &[Format], sep: &str) -> String {
         formats
             .iter()
@@ -206,6 +367,12 @@ import {{ Optional, Seq, Tuple, ListTuple, unit, bool, int8, int16, int32, int64
         let this_str = if use_this { "this." } else { "" };
 
         match format {
+            TypeName(name) if self.generator.discriminated_unions => format!(
+                "{}({}{}, serializer);",
+                self.quote_qualified_function("serialize", name),
+                this_str,
+                value
+            ),
             TypeName(_) => format!("{}{}.serialize(serializer);", this_str, value),
             Unit => format!("serializer.serializeUnit({}{});", this_str, value),
             Bool => format!("serializer.serializeBool({}{});", this_str, value),
@@ -224,11 +391,30 @@ import {{ Optional, Seq, Tuple, ListTuple, unit, bool, int8, int16, int32, int64
             Char => format!("serializer.serializeChar({}{});", this_str, value),
             Str => format!("serializer.serializeStr({}{});", this_str, value),
             Bytes => format!("serializer.serializeBytes({}{});", this_str, value),
+            Parameter(name) => format!("serialize{}({}{}, serializer);", name, this_str, value),
+            TypeApp { name, arguments } => {
+                let functions = arguments
+                    .iter()
+                    .map(|f| format!(", {}", self.quote_serialize_function(f)))
+                    .collect::<String>();
+                if self.generator.discriminated_unions {
+                    format!(
+                        "{}({}{}, serializer{});",
+                        self.quote_qualified_function("serialize", name),
+                        this_str,
+                        value,
+                        functions
+                    )
+                } else {
+                    format!("{}{}.serialize(serializer{});", this_str, value, functions)
+                }
+            }
             _ => format!(
-                "Helpers.serialize{}({}{}, serializer);",
+                "Helpers.serialize{}({}{}, serializer{});",
                 common::mangle_type(format).to_camel_case(),
                 this_str,
-                value
+                value,
+                Self::quote_function_arguments("serialize", &common::type_parameters(format))
             ),
         }
     }
@@ -236,10 +422,22 @@ import {{ Optional, Seq, Tuple, ListTuple, unit, bool, int8, int16, int32, int64
     fn quote_deserialize(&self, format: &Format) -> String {
         use Format::*;
         match format {
+            TypeName(name) if self.generator.discriminated_unions => format!(
+                "{}(deserializer)",
+                self.quote_qualified_function("deserialize", name)
+            ),
             TypeName(name) => format!(
                 "{}.deserialize(deserializer)",
                 self.quote_qualified_name(name)
             ),
+            I64 | I128 | U64 | U128
+                if self.generator.big_integer_mode == BigIntegerMode::Number =>
+            {
+                format!(
+                    "deserializer.deserialize{}AsNumber()",
+                    common::mangle_type(format).to_uppercase()
+                )
+            }
             Unit => "deserializer.deserializeUnit()".to_string(),
             Bool => "deserializer.deserializeBool()".to_string(),
             I8 => "deserializer.deserializeI8()".to_string(),
@@ -257,9 +455,30 @@ import {{ Optional, Seq, Tuple, ListTuple, unit, bool, int8, int16, int32, int64
             Char => "deserializer.deserializeChar()".to_string(),
             Str => "deserializer.deserializeStr()".to_string(),
             Bytes => "deserializer.deserializeBytes()".to_string(),
+            Parameter(name) => format!("deserialize{}(deserializer)", name),
+            TypeApp { name, arguments } => {
+                let functions = arguments
+                    .iter()
+                    .map(|f| format!(", {}", self.quote_deserialize_function(f)))
+                    .collect::<String>();
+                if self.generator.discriminated_unions {
+                    format!(
+                        "{}(deserializer{})",
+                        self.quote_qualified_function("deserialize", name),
+                        functions
+                    )
+                } else {
+                    format!(
+                        "{}.deserialize(deserializer{})",
+                        self.quote_qualified_name(name),
+                        functions
+                    )
+                }
+            }
             _ => format!(
-                "Helpers.deserialize{}(deserializer)",
+                "Helpers.deserialize{}(deserializer{})",
                 common::mangle_type(format).to_camel_case(),
+                Self::quote_function_arguments("deserialize", &common::type_parameters(format))
             ),
         }
     }
@@ -267,11 +486,14 @@ import {{ Optional, Seq, Tuple, ListTuple, unit, bool, int8, int16, int32, int64
     fn output_serialization_helper(&mut self, name: &str, format0: &Format) -> Result<()> {
         use Format::*;
 
+        let parameters = common::type_parameters(format0);
         write!(
             self.out,
-            "static serialize{}(value: {}, serializer: Serializer): void {{",
+            "static serialize{}{}(value: {}, serializer: Serializer{}): void {{",
             name.to_camel_case(),
-            self.quote_type(format0)
+            Self::quote_type_parameters(&parameters),
+            self.quote_type(format0),
+            Self::quote_serialize_parameters(&parameters)
         )?;
         self.out.indent();
         match format0 {
@@ -358,10 +580,13 @@ value.forEach((item) =>{{
     fn output_deserialization_helper(&mut self, name: &str, format0: &Format) -> Result<()> {
         use Format::*;
 
+        let parameters = common::type_parameters(format0);
         write!(
             self.out,
-            "static deserialize{}(deserializer: Deserializer): {} {{",
+            "static deserialize{}{}(deserializer: Deserializer{}): {} {{",
             name.to_camel_case(),
+            Self::quote_type_parameters(&parameters),
+            Self::quote_deserialize_parameters(&parameters),
             self.quote_type(format0),
         )?;
         self.out.indent();
@@ -472,24 +697,7 @@ return list;
         name: &str,
         variant: &VariantFormat,
     ) -> Result<()> {
-        use VariantFormat::*;
-        let fields = match variant {
-            Unit => Vec::new(),
-            NewType(format) => vec![Named {
-                name: "value".to_string(),
-                value: format.as_ref().clone(),
-            }],
-            Tuple(formats) => formats
-                .iter()
-                .enumerate()
-                .map(|(i, f)| Named {
-                    name: format!("field{}", i),
-                    value: f.clone(),
-                })
-                .collect(),
-            Struct(fields) => fields.clone(),
-            Variable(_) => panic!("incorrect value"),
-        };
+        let fields = Self::variant_fields(variant);
         self.output_struct_or_variant_container(Some(base), Some(index), name, &fields)
     }
 
@@ -512,6 +720,7 @@ return list;
         fields: &[Named<Format>],
     ) -> Result<()> {
         let mut variant_base_name = format!("");
+        let generics = Self::quote_type_parameters(&self.type_parameters);
 
         // Beginning of class
         if let Some(base) = variant_base {
@@ -519,13 +728,13 @@ return list;
             self.output_comment(name)?;
             writeln!(
                 self.out,
-                "export class {0}Variant{1} extends {0} {{",
-                base, name
+                "export class {0}Variant{1}{2} extends {0}{2} {{",
+                base, name, generics
             )?;
             variant_base_name = format!("{0}Variant", base);
         } else {
             self.output_comment(name)?;
-            writeln!(self.out, "export class {} {{", name)?;
+            writeln!(self.out, "export class {}{} {{", name, generics)?;
         }
         if !fields.is_empty() {
             writeln!(self.out)?;
@@ -550,7 +759,8 @@ return list;
         if self.generator.config.serialization {
             writeln!(
                 self.out,
-                "public serialize(serializer: Serializer): void {{",
+                "public serialize(serializer: Serializer{}): void {{",
+                Self::quote_serialize_parameters(&self.type_parameters),
             )?;
             self.out.indent();
             if let Some(index) = variant_index {
@@ -568,19 +778,20 @@ return list;
         }
         // Deserialize (struct) or Load (variant)
         if self.generator.config.serialization {
-            if variant_index.is_none() {
-                writeln!(
-                    self.out,
-                    "static deserialize(deserializer: Deserializer): {} {{",
-                    name,
-                )?;
-            } else {
-                writeln!(
-                    self.out,
-                    "static load(deserializer: Deserializer): {}{} {{",
-                    variant_base_name, name,
-                )?;
-            }
+            writeln!(
+                self.out,
+                "static {}{}(deserializer: Deserializer{}): {}{}{} {{",
+                if variant_index.is_none() {
+                    "deserialize"
+                } else {
+                    "load"
+                },
+                generics,
+                Self::quote_deserialize_parameters(&self.type_parameters),
+                variant_base_name,
+                name,
+                generics,
+            )?;
             self.out.indent();
             for field in fields {
                 writeln!(
@@ -592,14 +803,15 @@ return list;
             }
             writeln!(
                 self.out,
-                r#"return new {0}{1}({2});"#,
+                r#"return new {0}{1}{3}({2});"#,
                 variant_base_name,
                 name,
                 fields
                     .iter()
                     .map(|f| f.name.to_string())
                     .collect::<Vec<_>>()
-                    .join(",")
+                    .join(","),
+                generics,
             )?;
             self.out.unindent();
             writeln!(self.out, "}}\n")?;
@@ -612,17 +824,21 @@ return list;
         name: &str,
         variants: &BTreeMap<u32, Named<VariantFormat>>,
     ) -> Result<()> {
+        let generics = Self::quote_type_parameters(&self.type_parameters);
         self.output_comment(name)?;
-        writeln!(self.out, "export abstract class {} {{", name)?;
+        writeln!(self.out, "export abstract class {}{} {{", name, generics)?;
         if self.generator.config.serialization {
             writeln!(
                 self.out,
-                "abstract serialize(serializer: Serializer): void;\n"
+                "abstract serialize(serializer: Serializer{}): void;\n",
+                Self::quote_serialize_parameters(&self.type_parameters),
             )?;
             write!(
                 self.out,
-                "static deserialize(deserializer: Deserializer): {} {{",
-                name
+                "static deserialize{1}(deserializer: Deserializer{2}): {0}{1} {{",
+                name,
+                generics,
+                Self::quote_deserialize_parameters(&self.type_parameters),
             )?;
             self.out.indent();
             writeln!(
@@ -635,8 +851,11 @@ switch (index) {{"#,
             for (index, variant) in variants {
                 writeln!(
                     self.out,
-                    "case {}: return {}Variant{}.load(deserializer);",
-                    index, name, variant.name,
+                    "case {}: return {}Variant{}.load(deserializer{});",
+                    index,
+                    name,
+                    variant.name,
+                    Self::quote_function_arguments("deserialize", &self.type_parameters),
                 )?;
             }
             writeln!(
@@ -654,6 +873,19 @@ switch (index) {{"#,
         Ok(())
     }
 
+    /// Fingerprints are 64-bit values, hence always `bigint` values regardless of the
+    /// representation chosen for 64-bit integers.
+    fn output_fingerprint(&mut self, name: &str) -> Result<()> {
+        if let Some(fingerprint) = self.fingerprints.get(name) {
+            writeln!(
+                self.out,
+                "export const {}Fingerprint: bigint = BigInt(\"{:#018x}\");",
+                name, fingerprint
+            )?;
+        }
+        Ok(())
+    }
+
     fn output_container(&mut self, name: &str, format: &ContainerFormat) -> Result<()> {
         use ContainerFormat::*;
         let fields = match format {
@@ -675,26 +907,276 @@ switch (index) {{"#,
                 self.output_enum_container(name, variants)?;
                 return Ok(());
             }
+            Generic { parameters, format } => {
+                self.type_parameters = parameters.clone();
+                self.output_container(name, format)?;
+                self.type_parameters.clear();
+                return Ok(());
+            }
         };
         self.output_struct_or_variant_container(None, None, name, &fields)
     }
+
+    fn variant_fields(variant: &VariantFormat) -> Vec<Named<Format>> {
+        use VariantFormat::*;
+        match variant {
+            Unit => Vec::new(),
+            NewType(format) => vec![Named {
+                name: "value".to_string(),
+                value: format.as_ref().clone(),
+            }],
+            Tuple(formats) => formats
+                .iter()
+                .enumerate()
+                .map(|(i, f)| Named {
+                    name: format!("field{}", i),
+                    value: f.clone(),
+                })
+                .collect(),
+            Struct(fields) => fields.clone(),
+            Variable(_) => panic!("incorrect value"),
+        }
+    }
+
+    fn output_interface(
+        &mut self,
+        name: &str,
+        kind: Option<&str>,
+        fields: &[Named<Format>],
+    ) -> Result<()> {
+        self.output_comment(name)?;
+        writeln!(
+            self.out,
+            "export interface {}{} {{",
+            name,
+            Self::quote_type_parameters(&self.type_parameters)
+        )?;
+        self.out.indent();
+        if let Some(kind) = kind {
+            writeln!(self.out, "kind: \"{}\";", kind)?;
+        }
+        for field in fields {
+            writeln!(
+                self.out,
+                "{}: {};",
+                field.name,
+                self.quote_type(&field.value)
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}\n")
+    }
+
+    /// Deserialize the given fields into local variables, then return an object literal.
+    fn output_deserialize_fields(
+        &mut self,
+        kind: Option<&str>,
+        fields: &[Named<Format>],
+    ) -> Result<()> {
+        let mut entries = Vec::new();
+        if let Some(kind) = kind {
+            entries.push(format!("kind: \"{}\"", kind));
+        }
+        for field in fields {
+            writeln!(
+                self.out,
+                "const {} = {};",
+                field.name,
+                self.quote_deserialize(&field.value)
+            )?;
+            entries.push(format!("{0}: {0}", field.name));
+        }
+        if entries.is_empty() {
+            writeln!(self.out, "return {{}};")
+        } else {
+            writeln!(self.out, "return {{ {} }};", entries.join(", "))
+        }
+    }
+
+    fn output_union_serialize_signature(&mut self, name: &str) -> Result<()> {
+        writeln!(
+            self.out,
+            "export function serialize{0}{1}(value: {0}{1}, serializer: Serializer{2}): void {{",
+            name,
+            Self::quote_type_parameters(&self.type_parameters),
+            Self::quote_serialize_parameters(&self.type_parameters),
+        )
+    }
+
+    fn output_union_deserialize_signature(&mut self, name: &str) -> Result<()> {
+        writeln!(
+            self.out,
+            "export function deserialize{0}{1}(deserializer: Deserializer{2}): {0}{1} {{",
+            name,
+            Self::quote_type_parameters(&self.type_parameters),
+            Self::quote_deserialize_parameters(&self.type_parameters),
+        )
+    }
+
+    fn output_union_container(&mut self, name: &str, format: &ContainerFormat) -> Result<()> {
+        use ContainerFormat::*;
+        let fields = match format {
+            UnitStruct => Vec::new(),
+            NewTypeStruct(format) => vec![Named {
+                name: "value".to_string(),
+                value: format.as_ref().clone(),
+            }],
+            TupleStruct(formats) => formats
+                .iter()
+                .enumerate()
+                .map(|(i, f)| Named {
+                    name: format!("field{}", i),
+                    value: f.clone(),
+                })
+                .collect::<Vec<_>>(),
+            Struct(fields) => fields.clone(),
+            Enum(variants) => {
+                self.output_union_enum_container(name, variants)?;
+                return Ok(());
+            }
+            Generic { parameters, format } => {
+                self.type_parameters = parameters.clone();
+                self.output_union_container(name, format)?;
+                self.type_parameters.clear();
+                return Ok(());
+            }
+        };
+        self.output_interface(name, None, &fields)?;
+        if !self.generator.config.serialization {
+            return Ok(());
+        }
+        self.output_union_serialize_signature(name)?;
+        self.out.indent();
+        for field in &fields {
+            writeln!(
+                self.out,
+                "{}",
+                self.quote_serialize_value(&format!("value.{}", field.name), &field.value, false)
+            )?;
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}\n")?;
+        self.output_union_deserialize_signature(name)?;
+        self.out.indent();
+        self.output_deserialize_fields(None, &fields)?;
+        self.out.unindent();
+        writeln!(self.out, "}}\n")
+    }
+
+    fn output_union_enum_container(
+        &mut self,
+        name: &str,
+        variants: &BTreeMap<u32, Named<VariantFormat>>,
+    ) -> Result<()> {
+        for variant in variants.values() {
+            self.output_interface(
+                &format!("{}Variant{}", name, variant.name),
+                Some(&variant.name),
+                &Self::variant_fields(&variant.value),
+            )?;
+        }
+        let generics = Self::quote_type_parameters(&self.type_parameters);
+        self.output_comment(name)?;
+        writeln!(
+            self.out,
+            "export type {}{} = {};\n",
+            name,
+            generics,
+            variants
+                .values()
+                .map(|v| format!("{}Variant{}{}", name, v.name, generics))
+                .collect::<Vec<_>>()
+                .join(" | ")
+        )?;
+        if !self.generator.config.serialization {
+            return Ok(());
+        }
+
+        self.output_union_serialize_signature(name)?;
+        self.out.indent();
+        writeln!(self.out, "switch (value.kind) {{")?;
+        self.out.indent();
+        for (index, variant) in variants {
+            writeln!(self.out, "case \"{}\":", variant.name)?;
+            self.out.indent();
+            writeln!(self.out, "serializer.serializeVariantIndex({});", index)?;
+            for field in Self::variant_fields(&variant.value) {
+                writeln!(
+                    self.out,
+                    "{}",
+                    self.quote_serialize_value(
+                        &format!("value.{}", field.name),
+                        &field.value,
+                        false
+                    )
+                )?;
+            }
+            writeln!(self.out, "break;")?;
+            self.out.unindent();
+        }
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        self.out.unindent();
+        writeln!(self.out, "}}\n")?;
+
+        self.output_union_deserialize_signature(name)?;
+        self.out.indent();
+        writeln!(
+            self.out,
+            "const index = deserializer.deserializeVariantIndex();\nswitch (index) {{"
+        )?;
+        self.out.indent();
+        for (index, variant) in variants {
+            writeln!(self.out, "case {}: {{", index)?;
+            self.out.indent();
+            self.output_deserialize_fields(
+                Some(&variant.name),
+                &Self::variant_fields(&variant.value),
+            )?;
+            self.out.unindent();
+            writeln!(self.out, "}}")?;
+        }
+        writeln!(
+            self.out,
+            "default: throw new Error(\"Unknown variant index for {}: \" + index);",
+            name,
+        )?;
+        self.out.unindent();
+        writeln!(self.out, "}}")?;
+        self.out.unindent();
+        writeln!(self.out, "}}\n")
+    }
 }
 
 /// Installer for generated source files in TypeScript.
 pub struct Installer {
     install_dir: PathBuf,
+    discriminated_unions: bool,
+    big_integer_mode: BigIntegerMode,
 }
 
 impl Installer {
     pub fn new(install_dir: PathBuf) -> Self {
-        Installer { install_dir }
+        Installer {
+            install_dir,
+            discriminated_unions: false,
+            big_integer_mode: BigIntegerMode::BigInt,
+        }
     }
 
-    fn install_runtime(
-        &self,
-        source_dir: include_dir::Dir,
-        path: &str,
-    ) -> std::result::Result<(), Box<dyn std::error::Error>> {
+    /// How to represent 64-bit and 128-bit integers in installed modules.
+    pub fn with_big_integer_mode(mut self, big_integer_mode: BigIntegerMode) -> Self {
+        self.big_integer_mode = big_integer_mode;
+        self
+    }
+
+    /// Whether to install discriminated unions instead of classes.
+    pub fn with_discriminated_unions(mut self, discriminated_unions: bool) -> Self {
+        self.discriminated_unions = discriminated_unions;
+        self
+    }
+
+    fn install_runtime(&self, source_dir: include_dir::Dir, path: &str) -> crate::Result<()> {
         let dir_path = self.install_dir.join(path);
         std::fs::create_dir_all(&dir_path)?;
         for entry in source_dir.files() {
@@ -706,7 +1188,7 @@ impl Installer {
 }
 
 impl crate::SourceInstaller for Installer {
-    type Error = Box<dyn std::error::Error>;
+    type Error = crate::Error;
 
     fn install_module(
         &self,
@@ -718,7 +1200,9 @@ impl crate::SourceInstaller for Installer {
         let source_path = dir_path.join("index.ts");
         let mut file = std::fs::File::create(source_path)?;
 
-        let generator = CodeGenerator::new(config);
+        let generator = CodeGenerator::new(config)
+            .with_discriminated_unions(self.discriminated_unions)
+            .with_big_integer_mode(self.big_integer_mode);
         generator.output(&mut file, registry)?;
         Ok(())
 
@@ -0,0 +1,251 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use serde_generate::{c, test_utils, CodeGeneratorConfig, Encoding};
+use serde_reflection::Registry;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::process::Command;
+use tempfile::{tempdir, TempDir};
+
+fn test_that_c_code_compiles_with_config(
+    config: &CodeGeneratorConfig,
+) -> (TempDir, std::path::PathBuf) {
+    let registry = test_utils::get_registry().unwrap();
+    test_that_c_code_compiles_with_config_and_registry(config, &registry)
+}
+
+fn test_that_c_code_compiles_with_config_and_registry(
+    config: &CodeGeneratorConfig,
+    registry: &Registry,
+) -> (TempDir, std::path::PathBuf) {
+    let dir = tempdir().unwrap();
+    let header_path = dir.path().join("test.h");
+    let mut header = File::create(&header_path).unwrap();
+
+    let generator = c::CodeGenerator::new(&config);
+    generator.output(&mut header, registry).unwrap();
+
+    let source_path = dir.path().join("test.c");
+    let mut source = File::create(&source_path).unwrap();
+    writeln!(
+        source,
+        r#"
+#include "bincode.h"
+#include "test.h"
+"#
+    )
+    .unwrap();
+
+    let status = Command::new("clang")
+        .arg("--std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-c")
+        .arg("-o")
+        .arg(dir.path().join("test.o"))
+        .arg("-I")
+        .arg("runtime/c")
+        .arg(&source_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    (dir, header_path)
+}
+
+#[test]
+fn test_that_c_code_compiles() {
+    let config = CodeGeneratorConfig::new("testing".to_string());
+    test_that_c_code_compiles_with_config(&config);
+}
+
+#[test]
+fn test_that_c_code_compiles_with_bcs() {
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![Encoding::Bcs]);
+    test_that_c_code_compiles_with_config(&config);
+}
+
+#[test]
+fn test_that_c_code_compiles_with_bincode() {
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![Encoding::Bincode]);
+    test_that_c_code_compiles_with_config(&config);
+}
+
+#[test]
+fn test_that_c_code_compiles_without_serialization() {
+    let config = CodeGeneratorConfig::new("testing".to_string()).with_serialization(false);
+    test_that_c_code_compiles_with_config(&config);
+}
+
+#[test]
+fn test_that_c_code_compiles_with_c_style_enums() {
+    let config = CodeGeneratorConfig::new("testing".to_string())
+        .with_encodings(vec![Encoding::Bcs])
+        .with_c_style_enums(true);
+    let (_dir, header_path) = test_that_c_code_compiles_with_config(&config);
+
+    let content = std::fs::read_to_string(&header_path).unwrap();
+    assert!(content.contains("typedef enum testing_CStyleEnum {"));
+}
+
+#[test]
+fn test_that_c_code_compiles_with_reserved_names_and_sparse_variants() {
+    let registry: Registry = serde_yaml::from_str(
+        r#"
+Foo:
+  STRUCT:
+    - int: U8
+    - default: BOOL
+Choice:
+  ENUM:
+    0:
+      register:
+        NEWTYPE: U8
+    5:
+      switch: UNIT
+Unit:
+  ENUM:
+    1:
+      A: UNIT
+    3:
+      B: UNIT
+"#,
+    )
+    .unwrap();
+    for c_style_enums in &[false, true] {
+        let config = CodeGeneratorConfig::new("testing".to_string())
+            .with_encodings(vec![Encoding::Bcs])
+            .with_c_style_enums(*c_style_enums);
+        let (_dir, header_path) =
+            test_that_c_code_compiles_with_config_and_registry(&config, &registry);
+
+        let content = std::fs::read_to_string(&header_path).unwrap();
+        assert!(content.contains("uint8_t int_;"));
+        assert!(content.contains("testing_Choice_register register_;"));
+        assert!(content.contains("testing_Choice__switch = 5,"));
+        assert!(content.contains("testing_Unit__B = 3,"));
+    }
+}
+
+#[test]
+fn test_that_c_enum_tags_do_not_collide() {
+    let registry: Registry = serde_yaml::from_str(
+        r#"
+FooBar:
+  ENUM:
+    0:
+      Baz: UNIT
+Foo:
+  ENUM:
+    0:
+      BarBaz: UNIT
+"#,
+    )
+    .unwrap();
+    let config = CodeGeneratorConfig::new("testing".to_string());
+    let (_dir, header_path) =
+        test_that_c_code_compiles_with_config_and_registry(&config, &registry);
+
+    let content = std::fs::read_to_string(&header_path).unwrap();
+    assert!(content.contains("testing_FooBar__Baz = 0,"));
+    assert!(content.contains("testing_Foo__BarBaz = 0,"));
+}
+
+#[test]
+fn test_that_c_enums_without_variants_are_rejected() {
+    let registry: Registry = serde_yaml::from_str("Empty:\n  ENUM: {}\n").unwrap();
+    let config = CodeGeneratorConfig::new("testing".to_string());
+    let generator = c::CodeGenerator::new(&config);
+    let error = generator.output(&mut Vec::new(), &registry).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "C does not support enums without variants (container `Empty`)"
+    );
+}
+
+#[test]
+fn test_that_c_code_compiles_with_comments() {
+    let comments = vec![
+        (
+            vec!["testing".to_string(), "SerdeData".to_string()],
+            "Some\ncomments".to_string(),
+        ),
+        (
+            vec![
+                "testing".to_string(),
+                "List".to_string(),
+                "Node".to_string(),
+            ],
+            "Some other comments".to_string(),
+        ),
+    ]
+    .into_iter()
+    .collect();
+    let config = CodeGeneratorConfig::new("testing".to_string()).with_comments(comments);
+
+    let (_dir, header_path) = test_that_c_code_compiles_with_config(&config);
+
+    // Comments were correctly generated.
+    let content = std::fs::read_to_string(&header_path).unwrap();
+    assert!(content.contains(
+        r#"
+// Some
+// comments
+struct testing_SerdeData {
+"#
+    ));
+    assert!(content.contains(
+        r#"
+// Some other comments
+struct testing_List_Node {
+"#
+    ));
+}
+
+#[test]
+fn test_c_code_with_external_definitions() {
+    let registry = test_utils::get_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let header_path = dir.path().join("test.h");
+    let mut header = File::create(&header_path).unwrap();
+
+    // Pretend that "Tree" is external.
+    let mut definitions = BTreeMap::new();
+    definitions.insert("pkg::foo".to_string(), vec!["Tree".to_string()]);
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_external_definitions(definitions);
+    let generator = c::CodeGenerator::new(&config);
+    generator.output(&mut header, &registry).unwrap();
+
+    let content = std::fs::read_to_string(&header_path).unwrap();
+    assert!(content.contains("pkg_foo_Tree"));
+    assert!(!content.contains("testing_Tree"));
+}
+
+#[test]
+fn test_that_c_code_compiles_with_custom_code() {
+    let custom_code = vec![
+        (
+            vec!["testing".to_string(), "SerdeData".to_string()],
+            "typedef testing_SerdeData testing_SerdeDataAlias;".to_string(),
+        ),
+        (
+            vec!["testing".to_string(), "Struct".to_string()],
+            "typedef testing_Struct testing_StructAlias;".to_string(),
+        ),
+    ]
+    .into_iter()
+    .collect();
+    let config = CodeGeneratorConfig::new("testing".to_string()).with_custom_code(custom_code);
+
+    let (_dir, header_path) = test_that_c_code_compiles_with_config(&config);
+
+    // Custom code was correctly generated.
+    let content = std::fs::read_to_string(&header_path).unwrap();
+    assert!(content.contains("testing_SerdeDataAlias"));
+    assert!(content.contains("testing_StructAlias"));
+}
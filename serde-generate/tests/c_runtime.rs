@@ -0,0 +1,339 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use serde_generate::{
+    c, samples, test_utils,
+    test_utils::{Choice, Runtime, Test},
+    CodeGeneratorConfig,
+};
+use std::fs::File;
+use std::io::Write;
+use std::process::Command;
+use tempfile::tempdir;
+
+#[test]
+fn test_c_bcs_runtime_on_simple_data() {
+    test_c_runtime_on_simple_data(Runtime::Bcs);
+}
+
+#[test]
+fn test_c_bincode_runtime_on_simple_data() {
+    test_c_runtime_on_simple_data(Runtime::Bincode);
+}
+
+fn quote_bytes(bytes: &[u8]) -> String {
+    format!(
+        "{{{}}}",
+        bytes
+            .iter()
+            .map(|x| format!("0x{:02x}", x))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+fn test_c_runtime_on_simple_data(runtime: Runtime) {
+    let registry = test_utils::get_simple_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let header_path = dir.path().join("test.h");
+    let mut header = File::create(&header_path).unwrap();
+
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![runtime.into()]);
+    let generator = c::CodeGenerator::new(&config);
+    generator.output(&mut header, &registry).unwrap();
+
+    let reference = runtime.serialize(&Test {
+        a: vec![4, 6],
+        b: (-3, 5),
+        c: Choice::C { x: 7 },
+    });
+
+    let source_path = dir.path().join("test.c");
+    let mut source = File::create(&source_path).unwrap();
+    writeln!(
+        source,
+        r#"
+#include <assert.h>
+#include "test.h"
+
+int main(void) {{
+    static uint8_t arena_buffer[4096];
+    serde_arena_t arena;
+    serde_arena_init(&arena, arena_buffer, sizeof(arena_buffer));
+
+    const uint8_t input[] = {0};
+    testing_Test value;
+    assert({1}(input, sizeof(input), &arena, &value) == SERDE_OK);
+    assert(value.a.len == 2 && value.a.data[0] == 4 && value.a.data[1] == 6);
+    assert(value.b.field0 == -3 && value.b.field1 == 5);
+    assert(value.c.tag == testing_Choice__C && value.c.value.C.x == 7);
+
+    uint32_t a[] = {{4, 6}};
+    testing_Test value2;
+    value2.a.data = a;
+    value2.a.len = 2;
+    value2.b.field0 = -3;
+    value2.b.field1 = 5;
+    value2.c.tag = testing_Choice__C;
+    value2.c.value.C.x = 7;
+
+    uint8_t output[sizeof(input)];
+    size_t length;
+    assert({2}(&value2, output, sizeof(output), &arena, &length) == SERDE_OK);
+    assert(length == sizeof(input) && memcmp(input, output, length) == 0);
+
+    // The output buffer is too small.
+    assert({2}(&value2, output, sizeof(output) - 1, &arena, &length) == SERDE_ERROR_BUFFER_TOO_SMALL);
+
+    // Trailing bytes are rejected.
+    uint8_t input2[sizeof(input) + 1];
+    memcpy(input2, input, sizeof(input));
+    input2[sizeof(input)] = 1;
+    assert({1}(input2, sizeof(input2), &arena, &value) == SERDE_ERROR_TRAILING_BYTES);
+    return 0;
+}}
+"#,
+        quote_bytes(&reference),
+        format!("testing_Test_{}_deserialize", runtime.name()),
+        format!("testing_Test_{}_serialize", runtime.name()),
+    )
+    .unwrap();
+
+    let status = Command::new("clang")
+        .arg("--std=c99")
+        .arg("-o")
+        .arg(dir.path().join("test"))
+        .arg("-I")
+        .arg("runtime/c")
+        .arg(source_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let status = Command::new(dir.path().join("test")).status().unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn test_c_bcs_runtime_on_supported_types() {
+    test_c_runtime_on_supported_types(Runtime::Bcs);
+}
+
+#[test]
+fn test_c_bincode_runtime_on_supported_types() {
+    test_c_runtime_on_supported_types(Runtime::Bincode);
+}
+
+fn quote_samples(samples: &[Vec<u8>]) -> String {
+    let mut result = String::new();
+    for (i, bytes) in samples.iter().enumerate() {
+        result += &format!(
+            "static const uint8_t sample{}[] = {};\n",
+            i,
+            quote_bytes(bytes)
+        );
+    }
+    result += &format!(
+        "static const sample_t samples[] = {{{}}};\n",
+        (0..samples.len())
+            .map(|i| format!("{{sample{0}, sizeof(sample{0})}}", i))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    result
+}
+
+fn test_c_runtime_on_supported_types(runtime: Runtime) {
+    let registry = test_utils::get_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let header_path = dir.path().join("test.h");
+    let mut header = File::create(&header_path).unwrap();
+
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![runtime.into()]);
+    let generator = c::CodeGenerator::new(&config);
+    generator.output(&mut header, &registry).unwrap();
+
+    // Skip the sample with a very long sequence: arenas are meant to be small.
+    let positive_samples = runtime.get_positive_samples_quick();
+    let negative_samples = runtime.get_negative_samples();
+
+    let source_path = dir.path().join("test.c");
+    let mut source = File::create(&source_path).unwrap();
+    writeln!(
+        source,
+        r#"
+#include <assert.h>
+#include <stdio.h>
+#include "test.h"
+
+typedef struct {{
+    const uint8_t *data;
+    size_t len;
+}} sample_t;
+
+static uint8_t arena_buffer[1 << 20];
+static uint8_t output[1 << 20];
+static uint8_t input2[1 << 20];
+
+static serde_error_t roundtrip(const uint8_t *input, size_t len) {{
+    serde_arena_t arena;
+    serde_arena_init(&arena, arena_buffer, sizeof(arena_buffer));
+    testing_SerdeData value;
+    size_t length;
+    SERDE_TRY({1}(input, len, &arena, &value));
+    SERDE_TRY({2}(&value, output, sizeof(output), &arena, &length));
+    // Re-serialization must be exact.
+    assert(length == len && memcmp(input, output, len) == 0);
+    return SERDE_OK;
+}}
+
+int main(void) {{
+    {{
+{0}
+        for (size_t k = 0; k < sizeof(samples) / sizeof(samples[0]); k++) {{
+            const uint8_t *input = samples[k].data;
+            size_t len = samples[k].len;
+            serde_error_t error = roundtrip(input, len);
+            if (error != SERDE_OK) {{
+                printf("Sample %zu failed to deserialize: %s\n", k, serde_error_message(error));
+                return 1;
+            }}
+
+            // Test simple mutations of the input.
+            for (size_t i = 0; i < len && i < 20; i++) {{
+                memcpy(input2, input, len);
+                input2[i] ^= 0x81;
+                roundtrip(input2, len);
+            }}
+        }}
+    }}
+    {{
+{3}
+        for (size_t k = 0; k < sizeof(samples) / sizeof(samples[0]); k++) {{
+            if (roundtrip(samples[k].data, samples[k].len) == SERDE_OK) {{
+                printf("Input should fail to deserialize:");
+                for (size_t i = 0; i < samples[k].len; i++) {{
+                    printf(" %d", samples[k].data[i]);
+                }}
+                printf("\n");
+                return 1;
+            }}
+        }}
+    }}
+    return 0;
+}}
+"#,
+        quote_samples(&positive_samples),
+        format!("testing_SerdeData_{}_deserialize", runtime.name()),
+        format!("testing_SerdeData_{}_serialize", runtime.name()),
+        quote_samples(&negative_samples),
+    )
+    .unwrap();
+
+    let status = Command::new("clang")
+        .arg("--std=c99")
+        .arg("-g")
+        .arg("-O2")
+        .arg("-o")
+        .arg(dir.path().join("test"))
+        .arg("-I")
+        .arg("runtime/c")
+        .arg(source_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let status = Command::new(dir.path().join("test")).status().unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn test_c_bcs_runtime_on_mutated_samples() {
+    test_c_runtime_on_mutated_samples(Runtime::Bcs);
+}
+
+#[test]
+fn test_c_bincode_runtime_on_mutated_samples() {
+    test_c_runtime_on_mutated_samples(Runtime::Bincode);
+}
+
+fn test_c_runtime_on_mutated_samples(runtime: Runtime) {
+    let registry = test_utils::get_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let header_path = dir.path().join("test.h");
+    let mut header = File::create(&header_path).unwrap();
+
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![runtime.into()]);
+    let generator = c::CodeGenerator::new(&config);
+    generator.output(&mut header, &registry).unwrap();
+
+    // Read the samples from files, as a fuzzer would.
+    let corpus_dir = dir.path().join("corpus");
+    let samples = runtime.get_mutated_samples(0, 10);
+    samples::write_corpus(&corpus_dir, &samples).unwrap();
+    let mut paths = std::fs::read_dir(&corpus_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert_eq!(paths.len(), samples.len());
+
+    let source_path = dir.path().join("test.c");
+    let mut source = File::create(&source_path).unwrap();
+    writeln!(
+        source,
+        r#"
+#include <stdio.h>
+#include "test.h"
+
+static uint8_t arena_buffer[1 << 20];
+static uint8_t input[1 << 20];
+static uint8_t output[1 << 20];
+
+int main(int argc, char **argv) {{
+    for (int k = 1; k < argc; k++) {{
+        FILE *file = fopen(argv[k], "rb");
+        if (file == NULL) {{
+            perror(argv[k]);
+            return 1;
+        }}
+        size_t len = fread(input, 1, sizeof(input), file);
+        fclose(file);
+
+        serde_arena_t arena;
+        serde_arena_init(&arena, arena_buffer, sizeof(arena_buffer));
+        testing_SerdeData value;
+        if ({0}(input, len, &arena, &value) == SERDE_OK) {{
+            printf("Input should fail to deserialize: %s\n", argv[k]);
+            return 1;
+        }}
+    }}
+    return 0;
+}}
+"#,
+        format!("testing_SerdeData_{}_deserialize", runtime.name()),
+    )
+    .unwrap();
+
+    let status = Command::new("clang")
+        .arg("--std=c99")
+        .arg("-g")
+        .arg("-O2")
+        .arg("-o")
+        .arg(dir.path().join("test"))
+        .arg("-I")
+        .arg("runtime/c")
+        .arg(source_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let status = Command::new(dir.path().join("test"))
+        .args(paths)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
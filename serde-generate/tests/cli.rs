@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use serde_generate::test_utils;
+use serde_reflection::Registry;
 use std::process::Command;
 use tempfile::tempdir;
 
@@ -134,6 +135,310 @@ fn test_that_installed_rust_code_compiles() {
     assert!(status.success());
 }
 
+#[test]
+fn test_that_installed_rust_code_compiles_with_config_file() {
+    let registry = test_utils::get_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let yaml_path = dir.path().join("test.yaml");
+    std::fs::write(yaml_path, serde_yaml::to_string(&registry).unwrap()).unwrap();
+    let config_path = dir.path().join("config.yaml");
+    std::fs::write(
+        config_path.clone(),
+        r##"
+input: test.yaml
+language: python3
+module_name: testing
+target_source_dir: out
+comments:
+  testing.SerdeData: "Some comments"
+rust:
+  derive_macros: ["Clone", "Debug"]
+  custom_derive_block: "#[allow(dead_code)]"
+"##,
+    )
+    .unwrap();
+
+    // Command-line arguments take precedence.
+    let status = Command::new("cargo")
+        .arg("run")
+        .arg("-p")
+        .arg("serde-generate")
+        .arg("--")
+        .arg("--config")
+        .arg(config_path)
+        .arg("--language")
+        .arg("rust")
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let content = std::fs::read_to_string(dir.path().join("out/testing/src/lib.rs")).unwrap();
+    assert!(content.contains("/// Some comments\n"));
+    assert!(
+        content.contains("#[derive(Clone, Debug, Serialize, Deserialize)]\n#[allow(dead_code)]\n")
+    );
+
+    // Use a stable `target` dir to avoid downloading and recompiling crates everytime.
+    let target_dir = std::env::current_dir().unwrap().join("../target");
+    let status = Command::new("cargo")
+        .current_dir(dir.path().join("out/testing"))
+        .arg("build")
+        .arg("--target-dir")
+        .arg(target_dir)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn test_that_config_file_targets_are_all_generated() {
+    let registry = test_utils::get_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let yaml_path = dir.path().join("test.yaml");
+    std::fs::write(yaml_path, serde_yaml::to_string(&registry).unwrap()).unwrap();
+    let config_path = dir.path().join("config.yaml");
+    std::fs::write(
+        config_path.clone(),
+        r#"
+input: test.yaml
+module_name: testing
+targets:
+  - language: python3
+    target_source_dir: python
+  - language: unknown
+    target_source_dir: unknown
+  - language: typescript
+    target_source_dir: typescript
+    typescript:
+      discriminated_unions: true
+  - language: go
+    target_source_dir: go
+  - language: java
+    target_source_dir: java
+"#,
+    )
+    .unwrap();
+
+    let output = Command::new("cargo")
+        .arg("run")
+        .arg("-p")
+        .arg("serde-generate")
+        .arg("--")
+        .arg("--config")
+        .arg(config_path)
+        .output()
+        .unwrap();
+    // The invalid target is reported without preventing the generation of other targets.
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Unknown language `unknown`"));
+    assert!(!stderr.contains("panicked"));
+
+    assert!(dir.path().join("python/testing/__init__.py").exists());
+    assert!(dir.path().join("go/testing/lib.go").exists());
+    assert!(dir.path().join("java/testing/SerdeData.java").exists());
+    let content = std::fs::read_to_string(dir.path().join("typescript/testing/index.ts")).unwrap();
+    assert!(content.contains("export type SerdeData ="));
+}
+
+#[test]
+fn test_that_registries_are_transformed() {
+    let registry = test_utils::get_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let yaml_path = dir.path().join("test.yaml");
+    std::fs::write(yaml_path.clone(), serde_yaml::to_string(&registry).unwrap()).unwrap();
+    let output_path = dir.path().join("output.yaml");
+
+    let status = Command::new("cargo")
+        .arg("run")
+        .arg("-p")
+        .arg("serde-generate")
+        .arg("--")
+        .arg("transform")
+        .arg(yaml_path.clone())
+        .arg("--root")
+        .arg("SimpleList")
+        .arg("--root")
+        .arg("CStyleEnum")
+        .arg("--rename")
+        .arg("SimpleList=MyList")
+        .arg("--rename-variant")
+        .arg("CStyleEnum.A=Z")
+        .arg("--output")
+        .arg(output_path.clone())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let content = std::fs::read_to_string(output_path).unwrap();
+    let registry = serde_yaml::from_str::<Registry>(&content).unwrap();
+    assert_eq!(
+        registry.keys().collect::<Vec<_>>(),
+        vec!["CStyleEnum", "MyList"]
+    );
+    assert!(content.contains("TYPENAME: MyList"));
+    assert!(content.contains("Z: UNIT"));
+
+    let output = Command::new("cargo")
+        .arg("run")
+        .arg("-p")
+        .arg("serde-generate")
+        .arg("--")
+        .arg("transform")
+        .arg(yaml_path)
+        .arg("--rename-field")
+        .arg("Struct=y")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Invalid renaming `Struct=y`"));
+}
+
+#[test]
+fn test_that_registries_are_checked() {
+    let registry = test_utils::get_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let yaml_path = dir.path().join("test.yaml");
+    std::fs::write(yaml_path.clone(), serde_yaml::to_string(&registry).unwrap()).unwrap();
+
+    let output = Command::new("cargo")
+        .arg("run")
+        .arg("-p")
+        .arg("serde-generate")
+        .arg("--")
+        .arg("check")
+        .arg(yaml_path.clone())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+
+    let output = Command::new("cargo")
+        .arg("run")
+        .arg("-p")
+        .arg("serde-generate")
+        .arg("--")
+        .arg("check")
+        .arg(yaml_path)
+        .arg("--language")
+        .arg("rust")
+        .arg("--with-runtimes")
+        .arg("bcs")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("PrimitiveTypes.f_f64: F64 values are not supported by BCS\n"));
+
+    let yaml_path = dir.path().join("keywords.yaml");
+    std::fs::write(
+        yaml_path.clone(),
+        r#"
+Foo:
+  STRUCT:
+    - class: U8
+"#,
+    )
+    .unwrap();
+    let output = Command::new("cargo")
+        .arg("run")
+        .arg("-p")
+        .arg("serde-generate")
+        .arg("--")
+        .arg("check")
+        .arg(yaml_path)
+        .arg("--language")
+        .arg("java")
+        .arg("--language")
+        .arg("rust")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Foo.class: `class` is a reserved word in Java\n"
+    );
+}
+
+#[test]
+fn test_that_errors_are_reported_with_exit_codes() {
+    let dir = tempdir().unwrap();
+    let yaml_path = dir.path().join("test.yaml");
+    std::fs::write(
+        yaml_path.clone(),
+        r#"
+Foo:
+  STRUCT:
+    - x: U8
+"#,
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        let output = Command::new("cargo")
+            .arg("run")
+            .arg("-p")
+            .arg("serde-generate")
+            .arg("--")
+            .args(args)
+            .output()
+            .unwrap();
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+        assert!(!stderr.contains("panicked"));
+        (output.status.code(), stderr)
+    };
+    let yaml_path = yaml_path.to_str().unwrap();
+
+    let (code, stderr) = run(&["--language", "java", yaml_path]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("Code generation in Java requires `--target-source-dir`"));
+
+    let missing_path = dir.path().join("missing.yaml");
+    let (code, stderr) = run(&[missing_path.to_str().unwrap()]);
+    assert_eq!(code, Some(3));
+    assert!(stderr.contains("Failed to read"));
+
+    let (code, stderr) = run(&["--language", "go", "--use-c-style-enums", yaml_path]);
+    assert_eq!(code, Some(4));
+    assert!(stderr.contains("Go does not support c-style enums"));
+
+    // Flags set in a configuration file can be turned off on the command line.
+    let config_path = dir.path().join("config.yaml");
+    std::fs::write(config_path.clone(), "c_style_enums: true\n").unwrap();
+    let config_path = config_path.to_str().unwrap();
+    let (code, stderr) = run(&["--config", config_path, "--language", "go", yaml_path]);
+    assert_eq!(code, Some(4));
+    assert!(stderr.contains("Go does not support c-style enums"));
+    let (code, _) = run(&[
+        "--config",
+        config_path,
+        "--language",
+        "go",
+        "--use-c-style-enums=false",
+        yaml_path,
+    ]);
+    assert_eq!(code, Some(0));
+
+    // Target-specific arguments are rejected when the file lists several targets.
+    std::fs::write(
+        config_path,
+        "targets:\n  - language: go\n    target_source_dir: go\n  - language: java\n    target_source_dir: java\n",
+    )
+    .unwrap();
+    let target_dir = dir.path().join("out");
+    let (code, stderr) = run(&[
+        "--config",
+        config_path,
+        "--target-source-dir",
+        target_dir.to_str().unwrap(),
+        yaml_path,
+    ]);
+    assert_eq!(code, Some(2));
+    assert!(stderr.contains("cannot be used with a configuration file listing several targets"));
+    assert!(!target_dir.exists());
+}
+
 #[test]
 fn test_that_installed_cpp_code_compiles() {
     let registry = test_utils::get_registry().unwrap();
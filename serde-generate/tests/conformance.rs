@@ -0,0 +1,88 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use serde_generate::{
+    conformance::{Harness, Language, Outcome},
+    test_utils,
+    test_utils::Runtime,
+    Encoding,
+};
+
+fn cargo_target_dir() -> std::path::PathBuf {
+    // Use a stable `target` dir to avoid downloading and recompiling crates everytime.
+    std::env::current_dir().unwrap().join("../target")
+}
+
+#[test]
+fn test_bcs_conformance_on_samples() {
+    test_conformance_on_samples(Runtime::Bcs);
+}
+
+#[test]
+fn test_bincode_conformance_on_samples() {
+    test_conformance_on_samples(Runtime::Bincode);
+}
+
+fn test_conformance_on_samples(runtime: Runtime) {
+    let registry = test_utils::get_registry().unwrap();
+    let mut samples = runtime.get_positive_samples_quick();
+    samples.extend(runtime.get_random_samples(0, 100));
+
+    let report = Harness::new(&registry, "SerdeData".to_string(), runtime.into())
+        .with_cargo_target_dir(cargo_target_dir())
+        .run(&samples)
+        .unwrap();
+    assert_eq!(report.outcomes.len(), Language::all().len());
+    assert!(report.is_success(), "{}", report);
+}
+
+#[test]
+fn test_that_failing_samples_are_reported() {
+    let registry = test_utils::get_registry().unwrap();
+    let runtime = Runtime::Bcs;
+    let mut samples = runtime.get_positive_samples_quick();
+    samples.truncate(2);
+    // Trailing bytes are rejected.
+    samples[1].push(0);
+
+    let report = Harness::new(&registry, "SerdeData".to_string(), runtime.into())
+        .with_languages(vec![Language::Rust])
+        .with_cargo_target_dir(cargo_target_dir())
+        .run(&samples)
+        .unwrap();
+    assert!(!report.is_success());
+    match &report.outcomes[&Language::Rust] {
+        Outcome::Failed(message) => {
+            assert!(!message.contains("sample 0:"));
+            assert!(message.contains("sample 1:"));
+            assert!(message.contains("1 failures"));
+        }
+        outcome => panic!("unexpected outcome: {:?}", outcome),
+    }
+    assert!(report.to_string().starts_with("rust: FAILED\n"));
+}
+
+#[test]
+fn test_that_unknown_containers_are_rejected() {
+    let registry = test_utils::get_registry().unwrap();
+    let error = Harness::new(&registry, "Unknown".to_string(), Encoding::Bcs)
+        .run(&[])
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Container `Unknown` does not exist in the registry"
+    );
+}
+
+#[test]
+fn test_conformance_on_generic_samples() {
+    let registry = test_utils::get_generic_registry().unwrap();
+    let runtime = Runtime::Bcs;
+    let samples = runtime.get_generic_random_samples(0, 50);
+
+    let report = Harness::new(&registry, "GenericData".to_string(), runtime.into())
+        .with_cargo_target_dir(cargo_target_dir())
+        .run(&samples)
+        .unwrap();
+    assert!(report.is_success(), "{}", report);
+}
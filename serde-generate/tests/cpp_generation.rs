@@ -1,7 +1,7 @@
 // Copyright (c) Facebook, Inc. and its affiliates
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use serde_generate::{cpp, test_utils, CodeGeneratorConfig, Encoding};
+use serde_generate::{cpp, test_utils, CodeGeneratorConfig, Encoding, SourceInstaller};
 use std::collections::BTreeMap;
 use std::fs::File;
 use std::io::Write;
@@ -10,13 +10,23 @@ use tempfile::{tempdir, TempDir};
 
 fn test_that_cpp_code_compiles_with_config(
     config: &CodeGeneratorConfig,
+) -> (TempDir, std::path::PathBuf) {
+    test_that_cpp_code_compiles_with_generator(&cpp::CodeGenerator::new(config), &[], &[])
+}
+
+fn test_that_cpp_code_compiles_with_generator(
+    generator: &cpp::CodeGenerator,
+    extra_headers: &[(&str, &str)],
+    compiler_args: &[&str],
 ) -> (TempDir, std::path::PathBuf) {
     let registry = test_utils::get_registry().unwrap();
     let dir = tempdir().unwrap();
+    for (name, content) in extra_headers {
+        std::fs::write(dir.path().join(name), content).unwrap();
+    }
     let header_path = dir.path().join("test.hpp");
     let mut header = File::create(&header_path).unwrap();
 
-    let generator = cpp::CodeGenerator::new(&config);
     generator.output(&mut header, &registry).unwrap();
 
     let source_path = dir.path().join("test.cpp");
@@ -37,6 +47,9 @@ fn test_that_cpp_code_compiles_with_config(
         .arg(dir.path().join("test.o"))
         .arg("-I")
         .arg("runtime/cpp")
+        .arg("-I")
+        .arg(dir.path())
+        .args(compiler_args)
         .arg(&source_path)
         .status()
         .unwrap();
@@ -51,6 +64,19 @@ fn test_that_cpp_code_compiles() {
     test_that_cpp_code_compiles_with_config(&config);
 }
 
+#[test]
+fn test_that_cpp_code_compiles_with_fingerprints() {
+    let config = CodeGeneratorConfig::new("testing".to_string()).with_fingerprints(true);
+    let (_dir, header_path) = test_that_cpp_code_compiles_with_config(&config);
+    let content = std::fs::read_to_string(header_path).unwrap();
+    let registry = test_utils::get_registry().unwrap();
+    let fingerprint = serde_reflection::fingerprint(&registry, "SerdeData").unwrap();
+    assert!(content.contains(&format!(
+        "static constexpr uint64_t FINGERPRINT = {:#018x}ull;",
+        fingerprint
+    )));
+}
+
 #[test]
 fn test_that_cpp_code_compiles_with_bcs() {
     let config =
@@ -71,6 +97,119 @@ fn test_that_cpp_code_compiles_without_serialization() {
     test_that_cpp_code_compiles_with_config(&config);
 }
 
+#[test]
+fn test_that_cpp_code_compiles_with_pmr_containers() {
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![Encoding::Bcs]);
+    let generator = cpp::CodeGenerator::new(&config).with_type_mapping(cpp::TypeMapping::std_pmr());
+    let (_dir, header_path) = test_that_cpp_code_compiles_with_generator(&generator, &[], &[]);
+    let content = std::fs::read_to_string(&header_path).unwrap();
+    assert!(content.contains("std::pmr::map<std::pmr::string, uint32_t>"));
+}
+
+#[test]
+fn test_that_cpp_code_compiles_with_expected_results() {
+    let config = CodeGeneratorConfig::new("testing".to_string())
+        .with_encodings(vec![Encoding::Bcs, Encoding::Bincode]);
+    let generator = cpp::CodeGenerator::new(&config).with_expected_results(true);
+    let (_dir, header_path) =
+        test_that_cpp_code_compiles_with_generator(&generator, &[], &["-fno-exceptions"]);
+    let content = std::fs::read_to_string(&header_path).unwrap();
+    assert!(content.contains("static serde::expected<SerdeData> bcsDeserialize("));
+    assert!(!content.contains("throw"));
+}
+
+// User-defined containers, registered with the hooks of `serde.hpp`.
+const CUSTOM_TYPES: &str = r#"
+#pragma once
+
+#include <deque>
+#include "serde.hpp"
+
+namespace custom {
+
+template <typename T>
+struct Seq : std::deque<T> {
+    using std::deque<T>::deque;
+};
+
+template <typename K, typename V>
+struct Map : std::map<K, V> {};
+
+struct Str : std::string {
+    using std::string::string;
+};
+
+template <typename T>
+class Opt {
+    std::optional<T> value_;
+
+  public:
+    using value_type = T;
+
+    Opt() = default;
+    Opt &operator=(T value) {
+        value_ = std::move(value);
+        return *this;
+    }
+    bool has_value() const { return value_.has_value(); }
+    const T &operator*() const { return *value_; }
+    friend bool operator==(const Opt &lhs, const Opt &rhs) {
+        return lhs.value_ == rhs.value_;
+    }
+};
+
+template <typename T>
+struct Box : serde::value_ptr<T> {
+    using serde::value_ptr<T>::value_ptr;
+};
+
+} // end of namespace custom
+
+namespace serde {
+
+template <typename T>
+struct is_sequence<custom::Seq<T>> : std::true_type {};
+
+template <typename K, typename V>
+struct is_map<custom::Map<K, V>> : std::true_type {};
+
+template <>
+struct is_string<custom::Str> : std::true_type {};
+
+template <typename T>
+struct is_optional<custom::Opt<T>> : std::true_type {};
+
+template <typename T>
+struct is_box<custom::Box<T>> : std::true_type {};
+
+} // end of namespace serde
+"#;
+
+#[test]
+fn test_that_cpp_code_compiles_with_custom_types() {
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![Encoding::Bcs]);
+    let type_mapping = cpp::TypeMapping::default()
+        .with_seq("custom::Seq".to_string())
+        .with_map("custom::Map".to_string())
+        .with_str("custom::Str".to_string())
+        .with_bytes("custom::Seq<uint8_t>".to_string())
+        .with_option("custom::Opt".to_string())
+        .with_boxed("custom::Box".to_string())
+        .with_includes(vec!["\"custom.hpp\"".to_string()]);
+    let generator = cpp::CodeGenerator::new(&config).with_type_mapping(type_mapping);
+    let (_dir, header_path) = test_that_cpp_code_compiles_with_generator(
+        &generator,
+        &[("custom.hpp", CUSTOM_TYPES)],
+        &[],
+    );
+    let content = std::fs::read_to_string(&header_path).unwrap();
+    assert!(content.contains("custom::Map<custom::Str, uint32_t>"));
+    assert!(content.contains("custom::Opt<custom::Box<testing::SimpleList>>"));
+    assert!(content.contains("custom::Seq<uint8_t>"));
+}
+
 #[test]
 fn test_that_cpp_code_compiles_with_comments() {
     let comments = vec![
@@ -156,6 +295,48 @@ fn test_that_cpp_code_compiles_with_custom_code() {
     assert!(content.contains("~Node"));
 }
 
+#[test]
+fn test_that_cpp_code_compiles_with_split_sources() {
+    let registry = test_utils::get_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![Encoding::Bcs]);
+    let installer = cpp::Installer::new(dir.path().to_path_buf()).with_split_sources(true);
+    installer.install_serde_runtime().unwrap();
+    installer.install_bcs_runtime().unwrap();
+    installer.install_module(&config, &registry).unwrap();
+
+    // Each header can be compiled on its own.
+    for name in ["fwd", "SerdeData", "List", "Tree"].iter() {
+        let status = Command::new("clang++")
+            .arg("--std=c++17")
+            .arg("-fsyntax-only")
+            .arg("-x")
+            .arg("c++-header")
+            .arg("-I")
+            .arg(dir.path())
+            .arg(dir.path().join("testing").join(format!("{}.hpp", name)))
+            .status()
+            .unwrap();
+        assert!(status.success());
+    }
+
+    let status = Command::new("clang++")
+        .arg("--std=c++17")
+        .arg("-c")
+        .arg("-o")
+        .arg(dir.path().join("testing.o"))
+        .arg("-I")
+        .arg(dir.path())
+        .arg(dir.path().join("testing.cpp"))
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let content = std::fs::read_to_string(dir.path().join("testing/SerdeData.hpp")).unwrap();
+    assert!(!content.contains("deserializer.increase_container_depth();"));
+}
+
 #[test]
 fn test_that_cpp_code_links() {
     let registry = test_utils::get_registry().unwrap();
@@ -232,3 +413,54 @@ int main() {{
         .unwrap();
     assert!(status.success());
 }
+
+#[test]
+fn test_that_cpp_code_compiles_with_generics() {
+    let registry = test_utils::get_generic_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let header_path = dir.path().join("test.hpp");
+    let mut header = File::create(&header_path).unwrap();
+
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![Encoding::Bcs]);
+    let generator = cpp::CodeGenerator::new(&config);
+    generator.output(&mut header, &registry).unwrap();
+
+    // Instantiate the class templates through the serialization of `GenericData`.
+    let source_path = dir.path().join("test.cpp");
+    let mut source = File::create(&source_path).unwrap();
+    writeln!(
+        source,
+        r#"
+#include "test.hpp"
+
+using namespace testing;
+
+bool round_trip(const GenericData &value) {{
+    auto bytes = value.bcsSerialize();
+    auto pair = Pair<uint32_t, std::string>{{1, "a"}}.bcsSerialize();
+    return GenericData::bcsDeserialize(bytes) == value && !pair.empty();
+}}
+"#
+    )
+    .unwrap();
+
+    let status = Command::new("clang++")
+        .arg("--std=c++17")
+        .arg("-c")
+        .arg("-o")
+        .arg(dir.path().join("test.o"))
+        .arg("-I")
+        .arg("runtime/cpp")
+        .arg("-I")
+        .arg(dir.path())
+        .arg(&source_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let content = std::fs::read_to_string(&header_path).unwrap();
+    assert!(content.contains("template <typename T0, typename T1>\n    struct Pair {"));
+    assert!(content.contains("std::vector<testing::Tree<T0>> children;"));
+    assert!(content.contains("testing::Either<uint8_t, std::vector<uint16_t>> either;"));
+}
@@ -98,12 +98,17 @@ int main() {{
 
 #[test]
 fn test_cpp_bcs_runtime_on_supported_types() {
-    test_cpp_runtime_on_supported_types(Runtime::Bcs);
+    test_cpp_runtime_on_supported_types(Runtime::Bcs, false);
 }
 
 #[test]
 fn test_cpp_bincode_runtime_on_supported_types() {
-    test_cpp_runtime_on_supported_types(Runtime::Bincode);
+    test_cpp_runtime_on_supported_types(Runtime::Bincode, false);
+}
+
+#[test]
+fn test_cpp_bcs_runtime_on_supported_types_with_split_sources() {
+    test_cpp_runtime_on_supported_types(Runtime::Bcs, true);
 }
 
 fn quote_bytes(bytes: &[u8]) -> String {
@@ -117,25 +122,63 @@ fn quote_bytes(bytes: &[u8]) -> String {
     )
 }
 
-fn test_cpp_runtime_on_supported_types(runtime: Runtime) {
+#[test]
+fn test_cpp_bcs_runtime_on_mutated_samples() {
+    test_cpp_runtime_on_mutated_samples(Runtime::Bcs);
+}
+
+#[test]
+fn test_cpp_bincode_runtime_on_mutated_samples() {
+    test_cpp_runtime_on_mutated_samples(Runtime::Bincode);
+}
+
+fn test_cpp_runtime_on_mutated_samples(runtime: Runtime) {
+    let negative_samples = runtime
+        .get_mutated_samples(0, 10)
+        .into_iter()
+        .map(|sample| sample.bytes)
+        .collect();
+    test_cpp_runtime_on_samples(runtime, Vec::new(), negative_samples, false);
+}
+
+fn test_cpp_runtime_on_supported_types(runtime: Runtime, split_sources: bool) {
+    test_cpp_runtime_on_samples(
+        runtime,
+        runtime.get_positive_samples(),
+        runtime.get_negative_samples(),
+        split_sources,
+    );
+}
+
+fn test_cpp_runtime_on_samples(
+    runtime: Runtime,
+    positive_samples: Vec<Vec<u8>>,
+    negative_samples: Vec<Vec<u8>>,
+    split_sources: bool,
+) {
     let registry = test_utils::get_registry().unwrap();
     let dir = tempdir().unwrap();
-    let header_path = dir.path().join("test.hpp");
-    let mut header = File::create(&header_path).unwrap();
 
     let config =
         CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![runtime.into()]);
     let generator = cpp::CodeGenerator::new(&config);
-    generator.output(&mut header, &registry).unwrap();
+    let mut sources = vec![dir.path().join("test.cpp")];
+    if split_sources {
+        generator
+            .write_source_files(dir.path().to_path_buf(), &registry)
+            .unwrap();
+        sources.push(dir.path().join("testing.cpp"));
+    } else {
+        let mut header = File::create(dir.path().join("testing.hpp")).unwrap();
+        generator.output(&mut header, &registry).unwrap();
+    }
 
-    let positive_encodings: Vec<_> = runtime
-        .get_positive_samples()
+    let positive_encodings: Vec<_> = positive_samples
         .iter()
         .map(|bytes| quote_bytes(bytes))
         .collect();
 
-    let negative_encodings: Vec<_> = runtime
-        .get_negative_samples()
+    let negative_encodings: Vec<_> = negative_samples
         .iter()
         .map(|bytes| quote_bytes(bytes))
         .collect();
@@ -149,7 +192,7 @@ fn test_cpp_runtime_on_supported_types(runtime: Runtime) {
 #include <exception>
 #include <iostream>
 #include <cassert>
-#include "test.hpp"
+#include "testing.hpp"
 
 using namespace testing;
 
@@ -219,7 +262,117 @@ int main() {{
         .arg(dir.path().join("test"))
         .arg("-I")
         .arg("runtime/cpp")
-        .arg(source_path)
+        .arg("-I")
+        .arg(dir.path())
+        .args(&sources)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let status = Command::new(dir.path().join("test")).status().unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn test_cpp_bcs_runtime_on_supported_types_without_exceptions() {
+    test_cpp_runtime_on_supported_types_without_exceptions(Runtime::Bcs);
+}
+
+#[test]
+fn test_cpp_bincode_runtime_on_supported_types_without_exceptions() {
+    test_cpp_runtime_on_supported_types_without_exceptions(Runtime::Bincode);
+}
+
+fn test_cpp_runtime_on_supported_types_without_exceptions(runtime: Runtime) {
+    let registry = test_utils::get_registry().unwrap();
+    let dir = tempdir().unwrap();
+
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![runtime.into()]);
+    let generator = cpp::CodeGenerator::new(&config).with_expected_results(true);
+    let mut header = File::create(dir.path().join("testing.hpp")).unwrap();
+    generator.output(&mut header, &registry).unwrap();
+
+    let positive_encodings: Vec<_> = runtime
+        .get_positive_samples()
+        .iter()
+        .map(|bytes| quote_bytes(bytes))
+        .collect();
+
+    let negative_encodings: Vec<_> = runtime
+        .get_negative_samples()
+        .iter()
+        .map(|bytes| quote_bytes(bytes))
+        .collect();
+
+    let source_path = dir.path().join("test.cpp");
+    let mut source = File::create(&source_path).unwrap();
+    writeln!(
+        source,
+        r#"
+#include <algorithm>
+#include <cstdio>
+#include "testing.hpp"
+
+using namespace testing;
+
+int main() {{
+    std::vector<std::vector<uint8_t>> positive_inputs = {{{0}}};
+    std::vector<std::vector<uint8_t>> negative_inputs = {{{1}}};
+
+    for (auto input: positive_inputs) {{
+        auto value = SerdeData::{2}Deserialize(input);
+        if (!value) {{
+            printf("Error: %s\n", value.error().what());
+            return 1;
+        }}
+        auto output = value->{2}Serialize();
+        if (!output || input != *output) {{
+            return 1;
+        }}
+
+        // Test simple mutations of the input.
+        for (int i = 0; i < std::min(input.size(), 20ul); i++) {{
+            auto input2 = input;
+            input2[i] ^= 0x81;
+            auto value2 = SerdeData::{2}Deserialize(input2);
+            if (value2 && *value2 == *value) {{
+                return 1;
+            }}
+        }}
+    }}
+
+    for (auto input: negative_inputs) {{
+        if (SerdeData::{2}Deserialize(input)) {{
+            printf("Input should fail to deserialize:");
+            for (auto x : input) {{
+                printf(" %d", x);
+            }}
+            printf("\n");
+            return 1;
+        }}
+    }}
+    return 0;
+}}
+"#,
+        positive_encodings.join(", "),
+        negative_encodings.join(", "),
+        runtime.name(),
+    )
+    .unwrap();
+
+    let status = Command::new("clang++")
+        .arg("--std=c++17")
+        .arg("-fno-exceptions")
+        .arg("-g")
+        .arg("-O3") // remove for debugging
+        .arg("-o")
+        .arg(dir.path().join("test"))
+        .arg("-I")
+        .arg("runtime/cpp")
+        .arg(&source_path)
+        .arg("-I")
+        .arg(dir.path())
         .status()
         .unwrap();
     assert!(status.success());
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use serde_generate::{csharp, test_utils, CodeGeneratorConfig, Encoding};
+use serde_reflection::Registry;
 use std::collections::BTreeMap;
 use std::process::Command;
 use std::sync::Mutex;
@@ -15,14 +16,31 @@ lazy_static::lazy_static! {
 fn test_that_csharp_code_compiles_with_config(
     config: &CodeGeneratorConfig,
 ) -> (TempDir, std::path::PathBuf) {
-    use serde_generate::SourceInstaller;
+    test_that_csharp_code_compiles_with_installer(config, |dir_path| {
+        csharp::Installer::new(dir_path)
+    })
+}
 
+fn test_that_csharp_code_compiles_with_installer(
+    config: &CodeGeneratorConfig,
+    make_installer: impl FnOnce(std::path::PathBuf) -> csharp::Installer,
+) -> (TempDir, std::path::PathBuf) {
     let registry = test_utils::get_registry().unwrap();
+    test_that_csharp_code_compiles_with_registry(&registry, config, make_installer)
+}
+
+fn test_that_csharp_code_compiles_with_registry(
+    registry: &Registry,
+    config: &CodeGeneratorConfig,
+    make_installer: impl FnOnce(std::path::PathBuf) -> csharp::Installer,
+) -> (TempDir, std::path::PathBuf) {
+    use serde_generate::SourceInstaller;
+
     let dir = tempdir().unwrap();
     let dir_path = dir.path().to_path_buf();
 
-    let installer = csharp::Installer::new(dir_path.clone());
-    installer.install_module(&config, &registry).unwrap();
+    let installer = make_installer(dir_path.clone());
+    installer.install_module(&config, registry).unwrap();
     installer.install_serde_runtime().unwrap();
     installer.install_bincode_runtime().unwrap();
     installer.install_bcs_runtime().unwrap();
@@ -80,6 +98,91 @@ fn test_that_csharp_code_compiles_with_bincode() {
     test_that_csharp_code_compiles_with_config(&config);
 }
 
+#[test]
+fn test_that_csharp_code_compiles_with_records() {
+    let config = CodeGeneratorConfig::new("Generated".to_string());
+    let (_dir, path) = test_that_csharp_code_compiles_with_installer(&config, |dir_path| {
+        csharp::Installer::new(dir_path).with_type_declarations(csharp::TypeDeclarations::Records)
+    });
+    let content = std::fs::read_to_string(path.join("SerdeData.cs")).unwrap();
+    assert!(content.contains("public abstract record SerdeData {"));
+    assert!(!content.contains("GetHashCode"));
+}
+
+#[test]
+fn test_that_csharp_code_compiles_with_record_structs_and_nullable_annotations() {
+    let config = CodeGeneratorConfig::new("Generated".to_string());
+    let (_dir, path) = test_that_csharp_code_compiles_with_installer(&config, |dir_path| {
+        csharp::Installer::new(dir_path)
+            .with_type_declarations(csharp::TypeDeclarations::RecordStructs)
+            .with_nullable_annotations(true)
+    });
+    let content = std::fs::read_to_string(path.join("Struct.cs")).unwrap();
+    assert!(content.starts_with("#nullable enable"));
+    assert!(content.contains("public readonly record struct Struct {"));
+    // Recursive structs remain reference types.
+    let content = std::fs::read_to_string(path.join("SimpleList.cs")).unwrap();
+    assert!(content.contains("public sealed record SimpleList {"));
+    assert!(content.contains("public SimpleList? value { get; init; }"));
+}
+
+#[test]
+fn test_that_csharp_code_compiles_with_span_serializers() {
+    let config = CodeGeneratorConfig::new("Generated".to_string())
+        .with_encodings(vec![Encoding::Bcs, Encoding::Bincode])
+        .with_c_style_enums(true);
+    let (_dir, path) = test_that_csharp_code_compiles_with_installer(&config, |dir_path| {
+        csharp::Installer::new(dir_path).with_span_serializers(true)
+    });
+    let content = std::fs::read_to_string(path.join("SerdeData.cs")).unwrap();
+    assert!(content.contains("public static SerdeData BcsDeserialize(ReadOnlySpan<byte> input) {"));
+    assert!(content.contains("public int BincodeSerialize(IBufferWriter<byte> output) {"));
+}
+
+#[test]
+fn test_that_csharp_code_compiles_with_generics() {
+    let registry = test_utils::get_generic_registry().unwrap();
+    let config = CodeGeneratorConfig::new("Generated".to_string())
+        .with_encodings(vec![Encoding::Bcs, Encoding::Bincode]);
+    let (_dir, path) =
+        test_that_csharp_code_compiles_with_registry(&registry, &config, |dir_path| {
+            csharp::Installer::new(dir_path).with_span_serializers(true)
+        });
+
+    // Generic containers are emitted as generic classes.
+    let content = std::fs::read_to_string(path.join("Pair.cs")).unwrap();
+    assert!(content.contains(
+        "public sealed class Pair<T0, T1>: IEquatable<Pair<T0, T1>>, ICloneable where T0 : IEquatable<T0> where T1 : IEquatable<T1> {"
+    ));
+    let content = std::fs::read_to_string(path.join("Either.cs")).unwrap();
+    assert!(content
+        .contains("public sealed class Left: Either<T0, T1>, IEquatable<Left>, ICloneable {"));
+    assert!(!path.join("Pair_u32_str.cs").exists());
+}
+
+#[test]
+fn test_that_csharp_code_compiles_with_json_converters() {
+    let config =
+        CodeGeneratorConfig::new("Generated".to_string()).with_encodings(vec![Encoding::Bcs]);
+    test_that_csharp_code_compiles_with_installer(&config, |dir_path| {
+        csharp::Installer::new(dir_path)
+            .with_nullable_annotations(true)
+            .with_json_converters(true)
+    });
+}
+
+#[test]
+fn test_that_csharp_code_compiles_with_records_json_converters_and_c_style_enums() {
+    let config = CodeGeneratorConfig::new("Generated".to_string())
+        .with_encodings(vec![Encoding::Bcs])
+        .with_c_style_enums(true);
+    test_that_csharp_code_compiles_with_installer(&config, |dir_path| {
+        csharp::Installer::new(dir_path)
+            .with_type_declarations(csharp::TypeDeclarations::RecordStructs)
+            .with_json_converters(true)
+    });
+}
+
 #[test]
 fn test_that_csharp_code_compiles_with_comments() {
     let comments = vec![(
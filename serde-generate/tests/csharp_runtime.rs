@@ -5,7 +5,7 @@ use heck::CamelCase;
 use serde_generate::{
     csharp, test_utils,
     test_utils::{Choice, Runtime, Test},
-    CodeGeneratorConfig, SourceInstaller,
+    CodeGeneratorConfig, Encoding, SourceInstaller,
 };
 use std::fs::File;
 use std::io::Write;
@@ -73,6 +73,8 @@ fn make_test_project(
     runtime: Runtime,
     test_name: &str,
     library_name: &str,
+    target_frameworks: &str,
+    lang_version: &str,
 ) -> std::io::Result<PathBuf> {
     let test_dir = tmp_dir.join(test_name.replace(".", "/"));
     std::fs::create_dir(&test_dir)?;
@@ -83,9 +85,9 @@ fn make_test_project(
 <Project Sdk="Microsoft.NET.Sdk">
 
   <PropertyGroup>
-    <TargetFrameworks>netcoreapp2.1;netcoreapp3.1</TargetFrameworks>
+    <TargetFrameworks>{2}</TargetFrameworks>
     <IsPackable>false</IsPackable>
-    <LangVersion>7.2</LangVersion>
+    <LangVersion>{3}</LangVersion>
   </PropertyGroup>
 
   <ItemGroup>
@@ -103,7 +105,9 @@ fn make_test_project(
 </Project>
 "#,
         runtime.name().to_camel_case(),
-        library_name
+        library_name,
+        target_frameworks,
+        lang_version
     )?;
     Ok(test_dir)
 }
@@ -129,7 +133,15 @@ fn test_csharp_bincode_runtime_on_simple_data() {
 
 fn test_csharp_runtime_on_simple_data(dir: PathBuf, runtime: Runtime) {
     let registry = test_utils::get_simple_registry().unwrap();
-    let test_dir = make_test_project(&dir, runtime, "Testing", "SimpleData").unwrap();
+    let test_dir = make_test_project(
+        &dir,
+        runtime,
+        "Testing",
+        "SimpleData",
+        "netcoreapp2.1;netcoreapp3.1",
+        "7.2",
+    )
+    .unwrap();
     let config =
         CodeGeneratorConfig::new("SimpleData".to_string()).with_encodings(vec![runtime.into()]);
 
@@ -198,16 +210,103 @@ namespace Testing {{
     run_nunit(&test_dir);
 }
 
+#[test]
+fn test_csharp_json_on_simple_data() {
+    let (dir, _tmp) = create_test_dir("test_csharp_json_on_simple_data");
+    let registry = test_utils::get_simple_registry().unwrap();
+    let test_dir = make_test_project(
+        &dir,
+        Runtime::Bcs,
+        "Testing",
+        "SimpleData",
+        "net6.0",
+        "10.0",
+    )
+    .unwrap();
+    let config =
+        CodeGeneratorConfig::new("SimpleData".to_string()).with_encodings(vec![Encoding::Bcs]);
+
+    let installer = csharp::Installer::new(dir)
+        .with_type_declarations(csharp::TypeDeclarations::RecordStructs)
+        .with_nullable_annotations(true)
+        .with_json_converters(true);
+    installer.install_serde_runtime().unwrap();
+    installer.install_bcs_runtime().unwrap();
+    installer.install_module(&config, &registry).unwrap();
+
+    let value = Test {
+        a: vec![4, 6],
+        b: (-3, 5),
+        c: Choice::C { x: 7 },
+    };
+    let reference = serde_json::to_string(&value).unwrap();
+
+    let mut source = File::create(&test_dir.join("TestRuntime.cs")).unwrap();
+    writeln!(
+        source,
+        r#"
+using System;
+using System.Text.Json;
+using NUnit.Framework;
+using Serde;
+using SimpleData;
+
+namespace Testing {{
+    [TestFixture]
+    public class TestJson {{
+        [Test]
+        public void TestRoundTrip() {{
+            string input = {0:?};
+
+            Test test = JsonSerializer.Deserialize<Test>(input);
+
+            var a = new ValueArray<uint>(new uint[] {{ 4, 6 }});
+            var b = ((long)-3, (ulong)5);
+            Choice c = new Choice.C((byte) 7);
+            Test test2 = new Test(a, b, c);
+
+            Assert.AreEqual(test, test2);
+            Assert.AreEqual(input, JsonSerializer.Serialize(test2));
+            Assert.AreEqual("\"A\"", JsonSerializer.Serialize<Choice>(new Choice.A()));
+            Assert.AreEqual(test2, Test.BcsDeserialize(test2.BcsSerialize()));
+
+            Assert.Throws<JsonException>(() => JsonSerializer.Deserialize<Test>("{{\"a\":[4,6]}}"));
+        }}
+    }}
+}}
+"#,
+        reference
+    )
+    .unwrap();
+
+    dotnet_build(&test_dir);
+    run_nunit(&test_dir);
+}
+
 #[test]
 fn test_csharp_bcs_runtime_on_supported_types() {
     let (dir, _tmp) = create_test_dir("test_csharp_bcs_runtime_on_supported_types");
-    test_csharp_runtime_on_supported_types(dir, Runtime::Bcs);
+    test_csharp_runtime_on_supported_types(dir, Runtime::Bcs, false);
 }
 
 #[test]
 fn test_csharp_bincode_runtime_on_supported_types() {
     let (dir, _tmp) = create_test_dir("test_csharp_bincode_runtime_on_supported_types");
-    test_csharp_runtime_on_supported_types(dir, Runtime::Bincode);
+    test_csharp_runtime_on_supported_types(dir, Runtime::Bincode, false);
+}
+
+#[test]
+fn test_csharp_bcs_runtime_on_supported_types_with_span_serializers() {
+    let (dir, _tmp) =
+        create_test_dir("test_csharp_bcs_runtime_on_supported_types_with_span_serializers");
+    test_csharp_runtime_on_supported_types(dir, Runtime::Bcs, true);
+}
+
+#[test]
+fn test_csharp_bincode_runtime_on_supported_types_with_span_serializers() {
+    let (dir, _tmp) =
+        create_test_dir("test_csharp_bincode_runtime_on_supported_types_with_span_serializers");
+    test_csharp_runtime_on_supported_types(dir, Runtime::Bincode, true);
 }
 
 fn quote_bytes(bytes: &[u8]) -> String {
@@ -221,12 +320,63 @@ fn quote_bytes(bytes: &[u8]) -> String {
     )
 }
 
-fn test_csharp_runtime_on_supported_types(dir: PathBuf, runtime: Runtime) {
+#[test]
+fn test_csharp_bcs_runtime_on_mutated_samples() {
+    let (dir, _tmp) = create_test_dir("test_csharp_bcs_runtime_on_mutated_samples");
+    test_csharp_runtime_on_mutated_samples(dir, Runtime::Bcs);
+}
+
+#[test]
+fn test_csharp_bincode_runtime_on_mutated_samples() {
+    let (dir, _tmp) = create_test_dir("test_csharp_bincode_runtime_on_mutated_samples");
+    test_csharp_runtime_on_mutated_samples(dir, Runtime::Bincode);
+}
+
+fn test_csharp_runtime_on_mutated_samples(dir: PathBuf, runtime: Runtime) {
+    let negative_samples = runtime
+        .get_mutated_samples(0, 10)
+        .into_iter()
+        .map(|sample| sample.bytes)
+        .collect();
+    test_csharp_runtime_on_samples(
+        dir,
+        runtime,
+        runtime.get_positive_samples_quick(),
+        negative_samples,
+        false,
+    );
+}
+
+fn test_csharp_runtime_on_supported_types(dir: PathBuf, runtime: Runtime, span_serializers: bool) {
+    test_csharp_runtime_on_samples(
+        dir,
+        runtime,
+        runtime.get_positive_samples_quick(),
+        runtime.get_negative_samples(),
+        span_serializers,
+    );
+}
+
+fn test_csharp_runtime_on_samples(
+    dir: PathBuf,
+    runtime: Runtime,
+    positive_samples: Vec<Vec<u8>>,
+    negative_samples: Vec<Vec<u8>>,
+    span_serializers: bool,
+) {
     let registry = test_utils::get_registry().unwrap();
-    let test_dir = make_test_project(&dir, runtime, "Testing", "Data").unwrap();
+    let test_dir = make_test_project(
+        &dir,
+        runtime,
+        "Testing",
+        "Data",
+        "netcoreapp2.1;netcoreapp3.1",
+        "7.2",
+    )
+    .unwrap();
     let config = CodeGeneratorConfig::new("Data".to_string()).with_encodings(vec![runtime.into()]);
 
-    let installer = csharp::Installer::new(dir);
+    let installer = csharp::Installer::new(dir).with_span_serializers(span_serializers);
     installer.install_serde_runtime().unwrap();
     match runtime {
         Runtime::Bincode => installer.install_bincode_runtime().unwrap(),
@@ -234,15 +384,77 @@ fn test_csharp_runtime_on_supported_types(dir: PathBuf, runtime: Runtime) {
     }
     installer.install_module(&config, &registry).unwrap();
 
-    let positive_encodings = runtime
-        .get_positive_samples_quick()
+    let span_tests = if span_serializers {
+        format!(
+            r#"
+        class TestBufferWriter : System.Buffers.IBufferWriter<byte> {{
+            byte[] buffer = new byte[1];
+            int written = 0;
+
+            public void Advance(int count) => written += count;
+
+            public Memory<byte> GetMemory(int sizeHint = 0) {{
+                Reserve(sizeHint);
+                return new Memory<byte>(buffer, written, buffer.Length - written);
+            }}
+
+            public Span<byte> GetSpan(int sizeHint = 0) {{
+                Reserve(sizeHint);
+                return new Span<byte>(buffer, written, buffer.Length - written);
+            }}
+
+            void Reserve(int sizeHint) {{
+                if (buffer.Length - written < Math.Max(sizeHint, 1)) {{
+                    Array.Resize(ref buffer, written + Math.Max(sizeHint, buffer.Length));
+                }}
+            }}
+
+            public byte[] ToArray() => buffer.Take(written).ToArray();
+        }}
+
+        [Test, TestCaseSource("TestPositiveInputs")]
+        public void TestSpanRoundTrip(byte[] input) {{
+            SerdeData test = SerdeData.{0}Deserialize(new ReadOnlySpan<byte>(input));
+            Assert.AreEqual(SerdeData.{0}Deserialize(input), test);
+            byte[] output = new byte[input.Length];
+            Assert.AreEqual(input.Length, test.{0}Serialize(new Span<byte>(output)));
+            CollectionAssert.AreEqual(input, output);
+            var writer = new TestBufferWriter();
+            Assert.AreEqual(input.Length, test.{0}Serialize(writer));
+            CollectionAssert.AreEqual(input, writer.ToArray());
+        }}
+
+        [Test, TestCaseSource("TestPositiveInputs")]
+        public void TestBufferWriterInterfacesRoundTrip(byte[] input) {{
+            var deserializer = new MemoryDeserializer<{0}Format>(input);
+            SerdeData test = SerdeData.Deserialize(deserializer);
+            Assert.AreEqual(input.Length, deserializer.get_buffer_offset());
+            Assert.AreEqual(SerdeData.{0}Deserialize(input), test);
+            var writer = new TestBufferWriter();
+            var serializer = new BufferWriterSerializer<{0}Format>(writer);
+            test.Serialize(serializer);
+            CollectionAssert.AreEqual(input, serializer.get_bytes());
+            Assert.AreEqual(input.Length, serializer.Flush());
+            CollectionAssert.AreEqual(input, writer.ToArray());
+        }}
+
+        [Test, TestCaseSource("TestNegativeInputs")]
+        public void TestSpanNegativeInputsFails(byte[] input) {{
+            Assert.Catch(() => SerdeData.{0}Deserialize(new ReadOnlySpan<byte>(input)));
+        }}"#,
+            runtime.name().to_camel_case()
+        )
+    } else {
+        String::new()
+    };
+
+    let positive_encodings = positive_samples
         .iter()
         .map(|bytes| quote_bytes(bytes))
         .collect::<Vec<_>>()
         .join("\n\t\t\t\t");
 
-    let negative_encodings = runtime
-        .get_negative_samples()
+    let negative_encodings = negative_samples
         .iter()
         .map(|bytes| quote_bytes(bytes))
         .collect::<Vec<_>>()
@@ -315,11 +527,13 @@ namespace Testing {{
         public void TestNegativeInputsFails(byte[] input) {{
             Assert.Catch(() => SerdeData.{2}Deserialize(input));
         }}
+{3}
     }}
 }}"#,
         positive_encodings,
         negative_encodings,
-        runtime.name().to_camel_case()
+        runtime.name().to_camel_case(),
+        span_tests
     )
     .unwrap();
 
@@ -30,23 +30,34 @@ fn get_empty_registry() -> Result<Registry> {
 fn test_that_golang_code_compiles_with_config(
     config: &CodeGeneratorConfig,
 ) -> (TempDir, std::path::PathBuf) {
-    test_that_golang_code_compiles_with_config_and_registry(config, &get_empty_registry().unwrap());
-    test_that_golang_code_compiles_with_config_and_registry(config, &get_small_registry().unwrap());
-    test_that_golang_code_compiles_with_config_and_registry(
-        config,
+    test_that_golang_code_compiles_with_generator(&golang::CodeGenerator::new(config))
+}
+
+fn test_that_golang_code_compiles_with_generator(
+    generator: &golang::CodeGenerator,
+) -> (TempDir, std::path::PathBuf) {
+    test_that_golang_code_compiles_with_generator_and_registry(
+        generator,
+        &get_empty_registry().unwrap(),
+    );
+    test_that_golang_code_compiles_with_generator_and_registry(
+        generator,
+        &get_small_registry().unwrap(),
+    );
+    test_that_golang_code_compiles_with_generator_and_registry(
+        generator,
         &test_utils::get_registry().unwrap(),
     )
 }
 
-fn test_that_golang_code_compiles_with_config_and_registry(
-    config: &CodeGeneratorConfig,
+fn test_that_golang_code_compiles_with_generator_and_registry(
+    generator: &golang::CodeGenerator,
     registry: &Registry,
 ) -> (TempDir, std::path::PathBuf) {
     let dir = tempdir().unwrap();
     let source_path = dir.path().join("test.go");
     let mut source = File::create(&source_path).unwrap();
 
-    let generator = golang::CodeGenerator::new(&config);
     generator.output(&mut source, &registry).unwrap();
 
     writeln!(&mut source, "func main() {{}}").unwrap();
@@ -74,6 +85,11 @@ fn test_that_golang_code_compiles_with_config_and_registry(
             "github.com/novifinancial/serde-reflection/serde-generate/runtime/golang={}",
             runtime_mod_path.to_str().unwrap()
         ))
+        .arg("-replace")
+        .arg(format!(
+            "github.com/novifinancial/serde-reflection/serde-generate/runtime/golang/generics={}",
+            runtime_mod_path.join("generics").to_str().unwrap()
+        ))
         .status()
         .unwrap();
     assert!(status.success());
@@ -188,3 +204,38 @@ fn test_that_golang_code_compiles_with_custom_code() {
     assert!(content.contains("// custom1"));
     assert!(content.contains("// custom2"));
 }
+
+#[test]
+fn test_that_golang_code_compiles_with_json_and_enum_visitors() {
+    let config = CodeGeneratorConfig::new("main".to_string()).with_encodings(vec![Encoding::Bcs]);
+    let generator = golang::CodeGenerator::new(&config)
+        .with_json(true)
+        .with_enum_visitors(true);
+    let (_dir, source_path) = test_that_golang_code_compiles_with_generator(&generator);
+    let content = std::fs::read_to_string(&source_path).unwrap();
+    assert!(content.contains("`json:\"f_string\"`"));
+    assert!(content.contains("func UnmarshalJSONSerdeData(data []byte) (SerdeData, error) {"));
+    assert!(
+        content.contains("func VisitSerdeData(value SerdeData, visitor SerdeDataVisitor) error {")
+    );
+}
+
+#[test]
+fn test_that_golang_code_compiles_with_json_and_enum_visitors_without_serialization() {
+    let config = CodeGeneratorConfig::new("main".to_string()).with_serialization(false);
+    let generator = golang::CodeGenerator::new(&config)
+        .with_json(true)
+        .with_enum_visitors(true);
+    test_that_golang_code_compiles_with_generator(&generator);
+}
+
+#[test]
+fn test_that_golang_code_compiles_with_generic_helpers() {
+    let config = CodeGeneratorConfig::new("main".to_string()).with_encodings(vec![Encoding::Bcs]);
+    let generator = golang::CodeGenerator::new(&config).with_generic_helpers(true);
+    let (_dir, source_path) = test_that_golang_code_compiles_with_generator(&generator);
+    // No helpers were generated for composite types.
+    let content = std::fs::read_to_string(&source_path).unwrap();
+    assert!(!content.contains("func serialize_"));
+    assert!(!content.contains("func deserialize_"));
+}
@@ -130,14 +130,130 @@ func main() {{
     assert!(status.success());
 }
 
+#[test]
+fn test_golang_json_on_simple_data() {
+    let registry = test_utils::get_simple_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let source_path = dir.path().join("test.go");
+    let mut source = File::create(&source_path).unwrap();
+
+    let config = CodeGeneratorConfig::new("main".to_string()).with_external_definitions(
+        vec![("github.com/google/go-cmp/cmp".to_string(), vec![])]
+            .into_iter()
+            .collect(),
+    );
+    let generator = golang::CodeGenerator::new(&config)
+        .with_json(true)
+        .with_enum_visitors(true);
+    generator.output(&mut source, &registry).unwrap();
+
+    let reference = serde_json::to_string(&Test {
+        a: vec![4, 6],
+        b: (-3, 5),
+        c: Choice::C { x: 7 },
+    })
+    .unwrap();
+
+    writeln!(
+        source,
+        r#"
+type testVisitor struct {{ x uint8 }}
+
+func (visitor *testVisitor) VisitA(value *Choice__A) error {{ return fmt.Errorf("unexpected variant") }}
+func (visitor *testVisitor) VisitB(value *Choice__B) error {{ return fmt.Errorf("unexpected variant") }}
+func (visitor *testVisitor) VisitC(value *Choice__C) error {{ visitor.x = value.X; return nil }}
+
+func main() {{
+	value := Test {{
+		A: []uint32{{ 4, 6 }},
+		B: struct {{ Field0 int64; Field1 uint64 }} {{ -3, 5 }},
+		C: &Choice__C {{ X: 7 }},
+	}}
+	output, err := json.Marshal(value)
+	if err != nil {{ panic(err) }}
+	if string(output) != {0:?} {{ panic(string(output)) }}
+
+	var value2 Test
+	if err := json.Unmarshal(output, &value2); err != nil {{ panic(err) }}
+	if !cmp.Equal(value, value2) {{ panic("value != value2") }}
+
+	for _, input := range []string{{ `"A"`, `{{"B":3}}` }} {{
+		choice, err := UnmarshalJSONChoice([]byte(input))
+		if err != nil {{ panic(err) }}
+		output, err := json.Marshal(choice)
+		if err != nil {{ panic(err) }}
+		if string(output) != input {{ panic(string(output)) }}
+	}}
+	if _, err := UnmarshalJSONChoice([]byte(`{{"D":3}}`)); err == nil {{ panic("was expecting an error") }}
+
+	// Like serde_json, missing fields are rejected.
+	var value3 Test
+	if err := json.Unmarshal([]byte(`{{}}`), &value3); err == nil {{ panic("was expecting an error") }}
+	if _, err := UnmarshalJSONChoice([]byte(`{{"C":{{}}}}`)); err == nil {{ panic("was expecting an error") }}
+
+	visitor := testVisitor {{}}
+	if err := VisitChoice(value2.C, &visitor); err != nil {{ panic(err) }}
+	if visitor.x != 7 {{ panic("incorrect visit") }}
+}}
+"#,
+        reference
+    )
+    .unwrap();
+
+    let status = Command::new("go")
+        .current_dir(dir.path())
+        .arg("mod")
+        .arg("init")
+        .arg("testing")
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let runtime_mod_path = std::env::current_exe()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .join("../../../serde-generate/runtime/golang");
+    let status = Command::new("go")
+        .current_dir(dir.path())
+        .arg("mod")
+        .arg("edit")
+        .arg("-replace")
+        .arg(format!(
+            "github.com/novifinancial/serde-reflection/serde-generate/runtime/golang={}",
+            runtime_mod_path.to_str().unwrap()
+        ))
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let status = Command::new("go")
+        .current_dir(dir.path())
+        .arg("run")
+        .arg(&source_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
 #[test]
 fn test_golang_bcs_runtime_on_supported_types() {
-    test_golang_runtime_on_supported_types(Runtime::Bcs);
+    test_golang_runtime_on_supported_types(Runtime::Bcs, false);
 }
 
 #[test]
 fn test_golang_bincode_runtime_on_supported_types() {
-    test_golang_runtime_on_supported_types(Runtime::Bincode);
+    test_golang_runtime_on_supported_types(Runtime::Bincode, false);
+}
+
+#[test]
+fn test_golang_bcs_runtime_with_generic_helpers_on_supported_types() {
+    test_golang_runtime_on_supported_types(Runtime::Bcs, true);
+}
+
+#[test]
+fn test_golang_bincode_runtime_with_generic_helpers_on_supported_types() {
+    test_golang_runtime_on_supported_types(Runtime::Bincode, true);
 }
 
 fn quote_bytes(bytes: &[u8]) -> String {
@@ -151,7 +267,40 @@ fn quote_bytes(bytes: &[u8]) -> String {
     )
 }
 
-fn test_golang_runtime_on_supported_types(runtime: Runtime) {
+#[test]
+fn test_golang_bcs_runtime_on_mutated_samples() {
+    test_golang_runtime_on_mutated_samples(Runtime::Bcs);
+}
+
+#[test]
+fn test_golang_bincode_runtime_on_mutated_samples() {
+    test_golang_runtime_on_mutated_samples(Runtime::Bincode);
+}
+
+fn test_golang_runtime_on_mutated_samples(runtime: Runtime) {
+    let negative_samples = runtime
+        .get_mutated_samples(0, 10)
+        .into_iter()
+        .map(|sample| sample.bytes)
+        .collect();
+    test_golang_runtime_on_samples(runtime, Vec::new(), negative_samples, false);
+}
+
+fn test_golang_runtime_on_supported_types(runtime: Runtime, generic_helpers: bool) {
+    test_golang_runtime_on_samples(
+        runtime,
+        runtime.get_positive_samples_quick(),
+        runtime.get_negative_samples(),
+        generic_helpers,
+    );
+}
+
+fn test_golang_runtime_on_samples(
+    runtime: Runtime,
+    positive_samples: Vec<Vec<u8>>,
+    negative_samples: Vec<Vec<u8>>,
+    generic_helpers: bool,
+) {
     let registry = test_utils::get_registry().unwrap();
     let dir = tempdir().unwrap();
     let source_path = dir.path().join("test.go");
@@ -164,18 +313,16 @@ fn test_golang_runtime_on_supported_types(runtime: Runtime) {
                 .into_iter()
                 .collect(),
         );
-    let generator = golang::CodeGenerator::new(&config);
+    let generator = golang::CodeGenerator::new(&config).with_generic_helpers(generic_helpers);
     generator.output(&mut source, &registry).unwrap();
 
-    let positive_encodings = runtime
-        .get_positive_samples_quick()
+    let positive_encodings = positive_samples
         .iter()
         .map(|bytes| quote_bytes(bytes))
         .collect::<Vec<_>>()
         .join(", ");
 
-    let negative_encodings = runtime
-        .get_negative_samples()
+    let negative_encodings = negative_samples
         .iter()
         .map(|bytes| quote_bytes(bytes))
         .collect::<Vec<_>>()
@@ -248,6 +395,11 @@ func main() {{
             "github.com/novifinancial/serde-reflection/serde-generate/runtime/golang={}",
             runtime_mod_path.to_str().unwrap()
         ))
+        .arg("-replace")
+        .arg(format!(
+            "github.com/novifinancial/serde-reflection/serde-generate/runtime/golang/generics={}",
+            runtime_mod_path.join("generics").to_str().unwrap()
+        ))
         .status()
         .unwrap();
     assert!(status.success());
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use serde_generate::{java, test_utils, CodeGeneratorConfig, Encoding};
+use serde_reflection::Registry;
 use std::collections::BTreeMap;
 use std::process::Command;
 use tempfile::{tempdir, TempDir};
@@ -10,11 +11,18 @@ fn test_that_java_code_compiles_with_config(
     config: &CodeGeneratorConfig,
 ) -> (TempDir, std::path::PathBuf) {
     let registry = test_utils::get_registry().unwrap();
+    test_that_java_code_compiles_with_registry(&registry, config)
+}
+
+fn test_that_java_code_compiles_with_registry(
+    registry: &Registry,
+    config: &CodeGeneratorConfig,
+) -> (TempDir, std::path::PathBuf) {
     let dir = tempdir().unwrap();
 
     let generator = java::CodeGenerator::new(&config);
     generator
-        .write_source_files(dir.path().to_path_buf(), &registry)
+        .write_source_files(dir.path().to_path_buf(), registry)
         .unwrap();
 
     let paths = std::iter::empty()
@@ -62,6 +70,60 @@ fn test_that_java_code_compiles_with_bincode() {
     test_that_java_code_compiles_with_config(&config);
 }
 
+#[test]
+fn test_that_java_code_compiles_with_fingerprints() {
+    let config = CodeGeneratorConfig::new("testing".to_string()).with_fingerprints(true);
+    let (_dir, path) = test_that_java_code_compiles_with_config(&config);
+    let content = std::fs::read_to_string(path.join("SerdeData.java")).unwrap();
+    let registry = test_utils::get_registry().unwrap();
+    let fingerprint = serde_reflection::fingerprint(&registry, "SerdeData").unwrap();
+    assert!(content.contains(&format!(
+        "public static final long FINGERPRINT = {:#018x}L;",
+        fingerprint
+    )));
+}
+
+#[test]
+fn test_that_fingerprint_names_are_rejected() {
+    let registry: Registry = serde_yaml::from_str(
+        r#"
+Foo:
+  STRUCT:
+    - FINGERPRINT: U64
+"#,
+    )
+    .unwrap();
+    let config = CodeGeneratorConfig::new("testing".to_string()).with_fingerprints(true);
+    let generator = java::CodeGenerator::new(&config);
+    let dir = tempdir().unwrap();
+    let error = generator
+        .write_source_files(dir.path().to_path_buf(), &registry)
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "In container `Foo`, the name of field `FINGERPRINT` is reserved for fingerprints in Java"
+    );
+
+    // Without fingerprints, the name is available.
+    let config = CodeGeneratorConfig::new("testing".to_string());
+    test_that_java_code_compiles_with_registry(&registry, &config);
+}
+
+#[test]
+fn test_that_java_code_compiles_with_generics() {
+    let registry = test_utils::get_generic_registry().unwrap();
+    let config = CodeGeneratorConfig::new("testing".to_string())
+        .with_encodings(vec![Encoding::Bcs, Encoding::Bincode]);
+    let (_dir, path) = test_that_java_code_compiles_with_registry(&registry, &config);
+
+    // Generic containers are emitted as generic classes.
+    let content = std::fs::read_to_string(path.join("Pair.java")).unwrap();
+    assert!(content.contains("public final class Pair<T0, T1> {"));
+    let content = std::fs::read_to_string(path.join("Either.java")).unwrap();
+    assert!(content.contains("public abstract class Either<T0, T1> {"));
+    assert!(!path.join("Pair_u32_str.java").exists());
+}
+
 #[test]
 fn test_that_java_code_compiles_with_comments() {
     let comments = vec![(
@@ -142,7 +142,50 @@ fn quote_bytes(bytes: &[u8]) -> String {
     )
 }
 
+#[test]
+fn test_java_bcs_runtime_on_random_values() {
+    let runtime = Runtime::Bcs;
+    test_java_runtime_on_samples(runtime, runtime.get_random_samples(0, 100), Vec::new());
+}
+
+#[test]
+fn test_java_bincode_runtime_on_random_values() {
+    let runtime = Runtime::Bincode;
+    test_java_runtime_on_samples(runtime, runtime.get_random_samples(0, 100), Vec::new());
+}
+
+#[test]
+fn test_java_bcs_runtime_on_mutated_samples() {
+    test_java_runtime_on_mutated_samples(Runtime::Bcs);
+}
+
+#[test]
+fn test_java_bincode_runtime_on_mutated_samples() {
+    test_java_runtime_on_mutated_samples(Runtime::Bincode);
+}
+
+fn test_java_runtime_on_mutated_samples(runtime: Runtime) {
+    let negative_samples = runtime
+        .get_mutated_samples(0, 10)
+        .into_iter()
+        .map(|sample| sample.bytes)
+        .collect();
+    test_java_runtime_on_samples(runtime, Vec::new(), negative_samples);
+}
+
 fn test_java_runtime_on_supported_types(runtime: Runtime) {
+    test_java_runtime_on_samples(
+        runtime,
+        runtime.get_positive_samples_quick(),
+        runtime.get_negative_samples(),
+    );
+}
+
+fn test_java_runtime_on_samples(
+    runtime: Runtime,
+    positive_samples: Vec<Vec<u8>>,
+    negative_samples: Vec<Vec<u8>>,
+) {
     let registry = test_utils::get_registry().unwrap();
     let dir = tempdir().unwrap();
 
@@ -153,14 +196,12 @@ fn test_java_runtime_on_supported_types(runtime: Runtime) {
         .write_source_files(dir.path().to_path_buf(), &registry)
         .unwrap();
 
-    let positive_encodings: Vec<_> = runtime
-        .get_positive_samples_quick()
+    let positive_encodings: Vec<_> = positive_samples
         .iter()
         .map(|bytes| quote_bytes(bytes))
         .collect();
 
-    let negative_encodings: Vec<_> = runtime
-        .get_negative_samples()
+    let negative_encodings: Vec<_> = negative_samples
         .iter()
         .map(|bytes| quote_bytes(bytes))
         .collect();
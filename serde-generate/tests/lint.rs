@@ -0,0 +1,150 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use serde_generate::{
+    lint::{lint, Language},
+    test_utils, Encoding,
+};
+use serde_reflection::{ContainerFormat, Format, Named, Registry};
+
+const ALL_LANGUAGES: &[Language] = &[
+    Language::C,
+    Language::Cpp,
+    Language::CSharp,
+    Language::Go,
+    Language::Java,
+    Language::Python3,
+    Language::Rust,
+    Language::TypeScript,
+];
+
+fn get_lints(registry: &Registry, languages: &[Language], encodings: &[Encoding]) -> Vec<String> {
+    lint(registry, languages, encodings)
+        .iter()
+        .map(|lint| lint.to_string())
+        .collect()
+}
+
+#[test]
+fn test_that_test_registries_have_no_lints() {
+    let registry = test_utils::get_registry().unwrap();
+    assert_eq!(
+        get_lints(&registry, ALL_LANGUAGES, &[Encoding::Bincode]),
+        Vec::<String>::new()
+    );
+    let registry = test_utils::get_generic_registry().unwrap();
+    assert_eq!(
+        get_lints(&registry, ALL_LANGUAGES, &[Encoding::Bincode]),
+        Vec::<String>::new()
+    );
+}
+
+#[test]
+fn test_lints_on_bcs_formats() {
+    let registry = test_utils::get_registry().unwrap();
+    let lints = get_lints(&registry, &[], &[Encoding::Bcs]);
+    assert!(
+        lints.contains(&"PrimitiveTypes.f_f32: F32 values are not supported by BCS".to_string())
+    );
+    assert!(
+        lints.contains(&"PrimitiveTypes.f_char: CHAR values are not supported by BCS".to_string())
+    );
+}
+
+#[test]
+fn test_lints_on_names() {
+    let registry: Registry = serde_yaml::from_str(
+        r#"
+Foo:
+  STRUCT:
+    - class: U8
+    - type: U8
+Choice:
+  ENUM:
+    0:
+      Choice: UNIT
+    1:
+      Builder:
+        STRUCT:
+          - Builder: U8
+Choice__Choice: UNITSTRUCT
+TraitHelpers: UNITSTRUCT
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        get_lints(&registry, &[Language::Java, Language::Python3], &[]),
+        vec![
+            "Choice::Builder: `Builder` clashes with a generated builder class in Java",
+            "Choice::Choice: variant `Choice` clashes with the enclosing class in Java",
+            "Choice::Choice: the type generated for variant `Choice` clashes with container `Choice__Choice` in Python",
+            "Foo.class: `class` is a reserved word in Java",
+            "Foo.class: `class` is a reserved word in Python",
+            "TraitHelpers: `TraitHelpers` clashes with a generated helper class in Java",
+        ]
+    );
+    assert_eq!(
+        get_lints(&registry, &[Language::Rust], &[]),
+        vec!["Foo.type: `type` is a reserved word in Rust"]
+    );
+
+    let registry: Registry = serde_yaml::from_str("Empty:\n  ENUM: {}\n").unwrap();
+    assert_eq!(
+        get_lints(&registry, &[Language::C], &[]),
+        vec!["Empty: enums without variants are not supported in C"]
+    );
+}
+
+#[test]
+fn test_lints_on_infinite_containers() {
+    let mut registry: Registry = serde_yaml::from_str(
+        r#"
+A:
+  STRUCT:
+    - b:
+        TYPENAME: B
+B:
+  NEWTYPESTRUCT:
+    TYPENAME: A
+C:
+  STRUCT:
+    - a:
+        OPTION:
+          TYPENAME: A
+List:
+  ENUM:
+    0:
+      Nil: UNIT
+    1:
+      Cons:
+        TUPLE:
+          - U8
+          - TYPENAME: List
+"#,
+    )
+    .unwrap();
+    registry.insert(
+        "Incomplete".to_string(),
+        ContainerFormat::Struct(vec![Named {
+            name: "x".to_string(),
+            value: Format::Seq(Box::new(Format::unknown())),
+        }]),
+    );
+    let lints = lint(&registry, &[], &[]);
+    assert_eq!(
+        lints
+            .iter()
+            .map(|lint| lint.location.to_string())
+            .collect::<Vec<_>>(),
+        vec!["A", "B", "Incomplete.x"]
+    );
+    assert!(lints.iter().all(|lint| lint.language.is_none()));
+    assert_eq!(
+        lints[0].message,
+        "container has no finite values: recursion must go through an option, a sequence, or a map"
+    );
+    assert_eq!(
+        lints[2].message,
+        "incomplete format: make sure that all types were traced"
+    );
+}
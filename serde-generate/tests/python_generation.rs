@@ -4,9 +4,24 @@
 use serde_generate::{python3, test_utils, CodeGeneratorConfig, Encoding, SourceInstaller};
 use std::collections::BTreeMap;
 use std::fs::File;
+use std::io::Write;
 use std::process::Command;
 use tempfile::{tempdir, TempDir};
 
+/// Pydantic models require pydantic >= 2 and `typing.Annotated` (Python >= 3.9). Tests of
+/// pydantic models are skipped when these are missing.
+fn pydantic_is_available() -> bool {
+    let available = Command::new("python3")
+        .arg("-c")
+        .arg("import typing, pydantic; typing.Annotated; assert int(pydantic.VERSION.split('.')[0]) >= 2")
+        .status()
+        .map_or(false, |status| status.success());
+    if !available {
+        eprintln!("Skipping test: pydantic >= 2 and Python >= 3.9 are required");
+    }
+    available
+}
+
 fn test_that_python_code_parses_with_config(
     config: &CodeGeneratorConfig,
 ) -> (TempDir, std::path::PathBuf) {
@@ -59,6 +74,54 @@ fn test_that_python_code_parses_with_bincode() {
     test_that_python_code_parses_with_config(&config);
 }
 
+#[test]
+fn test_that_python_code_with_pydantic_models_validates_values() {
+    if !pydantic_is_available() {
+        return;
+    }
+    let registry = test_utils::get_registry().unwrap();
+    let dir = tempdir().unwrap();
+
+    let source_path = dir.path().join("test.py");
+    let mut source = File::create(&source_path).unwrap();
+
+    let config = CodeGeneratorConfig::new("testing".to_string())
+        .with_encodings(vec![Encoding::Bcs, Encoding::Bincode]);
+    let generator = python3::CodeGenerator::new(&config).with_pydantic_models(true);
+    generator.output(&mut source, &registry).unwrap();
+    writeln!(
+        source,
+        r#"
+import pydantic
+
+assert issubclass(SerdeData, pydantic.BaseModel)
+SerdeData__TupleArray(value=(1, 2, 3))
+for value in [(1, 2), (1, 2, 3, 4), (1, 2, -1), (1, 2, 2**32)]:
+    try:
+        SerdeData__TupleArray(value=value)
+        assert False, value
+    except pydantic.ValidationError:
+        pass
+
+schema = SerdeData__TupleArray.model_json_schema()["properties"]["value"]
+assert schema["minItems"] == 3 and schema["maxItems"] == 3
+assert schema["prefixItems"][0] == {{"type": "integer", "minimum": 0, "maximum": 4294967295}}
+"#
+    )
+    .unwrap();
+
+    let python_path = format!(
+        "{}:runtime/python",
+        std::env::var("PYTHONPATH").unwrap_or_default()
+    );
+    let status = Command::new("python3")
+        .arg(&source_path)
+        .env("PYTHONPATH", python_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
 #[test]
 fn test_that_python_code_parses_with_comments() {
     let comments = vec![
@@ -208,6 +271,22 @@ fn test_that_installed_python_code_passes_pyre_check() {
     assert!(status.success());
 }
 
+#[test]
+fn test_that_c_style_enums_are_not_supported() {
+    let registry = test_utils::get_registry().unwrap();
+    let config = CodeGeneratorConfig::new("testing".to_string()).with_c_style_enums(true);
+    let generator = python3::CodeGenerator::new(&config);
+    let error = generator.output(&mut Vec::new(), &registry).unwrap_err();
+    assert!(matches!(
+        error,
+        serde_generate::Error::UnsupportedOption {
+            language: "Python 3",
+            ..
+        }
+    ));
+    assert_eq!(error.to_string(), "Python 3 does not support c-style enums");
+}
+
 #[test]
 fn test_python_autotest() {
     let status = Command::new("python3")
@@ -9,19 +9,89 @@ use serde_generate::{
 use std::fs::File;
 use std::io::Write;
 use std::process::Command;
-use tempfile::tempdir;
+use tempfile::{tempdir, TempDir};
+
+/// Pydantic models require pydantic >= 2 and `typing.Annotated` (Python >= 3.9). Tests of
+/// pydantic models are skipped when these are missing.
+fn pydantic_is_available() -> bool {
+    let available = Command::new("python3")
+        .arg("-c")
+        .arg("import typing, pydantic; typing.Annotated; assert int(pydantic.VERSION.split('.')[0]) >= 2")
+        .status()
+        .map_or(false, |status| status.success());
+    if !available {
+        eprintln!("Skipping test: pydantic >= 2 and Python >= 3.9 are required");
+    }
+    available
+}
+
+/// Prepare a Python command using either the pure Python runtime or the native module
+/// `serde_binary._speedups` (built in a temporary directory).
+fn python_command(with_speedups: bool) -> (Command, Option<TempDir>) {
+    let python_path = format!(
+        "{}:runtime/python",
+        std::env::var("PYTHONPATH").unwrap_or_default()
+    );
+    let mut command = Command::new("python3");
+    if !with_speedups {
+        command
+            .env("PYTHONPATH", python_path)
+            .env("SERDE_BINARY_NO_SPEEDUPS", "1");
+        return (command, None);
+    }
+
+    let dir = tempdir().unwrap();
+    std::fs::create_dir(dir.path().join("serde_binary")).unwrap();
+    std::fs::copy(
+        "runtime/python/serde_binary/__init__.py",
+        dir.path().join("serde_binary/__init__.py"),
+    )
+    .unwrap();
+    let status = Command::new("python3")
+        .arg("runtime/python/serde_binary/build_speedups.py")
+        .arg("--build-lib")
+        .arg(dir.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let python_path = format!("{}:{}", dir.path().to_string_lossy(), python_path);
+    let status = Command::new("python3")
+        .arg("-c")
+        .arg("import serde_binary; assert serde_binary._speedups is not None")
+        .env("PYTHONPATH", &python_path)
+        .env_remove("SERDE_BINARY_NO_SPEEDUPS")
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    command
+        .env("PYTHONPATH", python_path)
+        .env_remove("SERDE_BINARY_NO_SPEEDUPS");
+    (command, Some(dir))
+}
 
 #[test]
 fn test_python_bcs_runtime_on_simple_data() {
-    test_python_runtime_on_simple_data(Runtime::Bcs);
+    test_python_runtime_on_simple_data(Runtime::Bcs, false);
 }
 
 #[test]
 fn test_python_bincode_runtime_on_simple_data() {
-    test_python_runtime_on_simple_data(Runtime::Bincode);
+    test_python_runtime_on_simple_data(Runtime::Bincode, false);
 }
 
-fn test_python_runtime_on_simple_data(runtime: Runtime) {
+#[test]
+fn test_python_bcs_runtime_with_speedups_on_simple_data() {
+    test_python_runtime_on_simple_data(Runtime::Bcs, true);
+}
+
+#[test]
+fn test_python_bincode_runtime_with_speedups_on_simple_data() {
+    test_python_runtime_on_simple_data(Runtime::Bincode, true);
+}
+
+fn test_python_runtime_on_simple_data(runtime: Runtime, with_speedups: bool) {
     let registry = test_utils::get_simple_registry().unwrap();
     let dir = tempdir().unwrap();
     let source_path = dir.path().join("test.py");
@@ -67,26 +137,145 @@ assert seen_error
     )
     .unwrap();
 
-    let python_path = std::env::var("PYTHONPATH").unwrap_or_default() + ":runtime/python";
-    let status = Command::new("python3")
-        .arg(source_path)
-        .env("PYTHONPATH", python_path)
-        .status()
-        .unwrap();
+    let (mut command, _speedups_dir) = python_command(with_speedups);
+    let status = command.arg(source_path).status().unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn test_python_bcs_runtime_with_pydantic_models_on_simple_data() {
+    test_python_runtime_with_pydantic_models_on_simple_data(Runtime::Bcs);
+}
+
+#[test]
+fn test_python_bincode_runtime_with_pydantic_models_on_simple_data() {
+    test_python_runtime_with_pydantic_models_on_simple_data(Runtime::Bincode);
+}
+
+fn test_python_runtime_with_pydantic_models_on_simple_data(runtime: Runtime) {
+    if !pydantic_is_available() {
+        return;
+    }
+    let registry = test_utils::get_simple_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let source_path = dir.path().join("test.py");
+    let mut source = File::create(&source_path).unwrap();
+
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![runtime.into()]);
+    let generator = python3::CodeGenerator::new(&config).with_pydantic_models(true);
+    generator.output(&mut source, &registry).unwrap();
+
+    let reference = runtime.serialize(&Test {
+        a: vec![4, 6],
+        b: (3, 5),
+        c: Choice::C { x: 7 },
+    });
+    writeln!(
+        source,
+        r#"
+input = bytes({1:?})
+value = Test(a=[4, 6], b=(3, 5), c=Choice__C(x=7))
+
+s = value.{0}_serialize()
+assert s == input
+
+v = Test.{0}_deserialize(s)
+assert v == value
+assert v.c.x == 7
+assert type(v.c.x) is int
+
+for (a, b, x) in [([-1], (3, 5), 7), ([4], (-2**63 - 1, 5), 7), ([4], (3, 2**64), 7), ([4], (3, 5), 256)]:
+    seen_error = False
+    try:
+        Test(a=a, b=b, c=Choice__C(x=x))
+    except pydantic.ValidationError:
+        seen_error = True
+    assert seen_error
+"#,
+        runtime.name(),
+        reference,
+    )
+    .unwrap();
+
+    let (mut command, _speedups_dir) = python_command(false);
+    let status = command.arg(source_path).status().unwrap();
     assert!(status.success());
 }
 
 #[test]
 fn test_python_bcs_runtime_on_supported_types() {
-    test_python_runtime_on_supported_types(Runtime::Bcs);
+    test_python_runtime_on_supported_types(Runtime::Bcs, false, false);
 }
 
 #[test]
 fn test_python_bincode_runtime_on_supported_types() {
-    test_python_runtime_on_supported_types(Runtime::Bincode);
+    test_python_runtime_on_supported_types(Runtime::Bincode, false, false);
+}
+
+#[test]
+fn test_python_bcs_runtime_with_speedups_on_supported_types() {
+    test_python_runtime_on_supported_types(Runtime::Bcs, true, false);
+}
+
+#[test]
+fn test_python_bincode_runtime_with_speedups_on_supported_types() {
+    test_python_runtime_on_supported_types(Runtime::Bincode, true, false);
 }
 
-fn test_python_runtime_on_supported_types(runtime: Runtime) {
+#[test]
+fn test_python_bcs_runtime_with_pydantic_models_on_supported_types() {
+    test_python_runtime_on_supported_types(Runtime::Bcs, false, true);
+}
+
+#[test]
+fn test_python_bincode_runtime_with_pydantic_models_on_supported_types() {
+    test_python_runtime_on_supported_types(Runtime::Bincode, false, true);
+}
+
+#[test]
+fn test_python_bcs_runtime_on_mutated_samples() {
+    test_python_runtime_on_mutated_samples(Runtime::Bcs);
+}
+
+#[test]
+fn test_python_bincode_runtime_on_mutated_samples() {
+    test_python_runtime_on_mutated_samples(Runtime::Bincode);
+}
+
+fn test_python_runtime_on_mutated_samples(runtime: Runtime) {
+    let negative_samples = runtime
+        .get_mutated_samples(0, 10)
+        .into_iter()
+        .map(|sample| sample.bytes)
+        .collect();
+    test_python_runtime_on_samples(runtime, Vec::new(), negative_samples, false, false);
+}
+
+fn test_python_runtime_on_supported_types(
+    runtime: Runtime,
+    with_speedups: bool,
+    pydantic_models: bool,
+) {
+    if pydantic_models && !pydantic_is_available() {
+        return;
+    }
+    test_python_runtime_on_samples(
+        runtime,
+        runtime.get_positive_samples_quick(),
+        runtime.get_negative_samples(),
+        with_speedups,
+        pydantic_models,
+    );
+}
+
+fn test_python_runtime_on_samples(
+    runtime: Runtime,
+    positive_encodings: Vec<Vec<u8>>,
+    negative_encodings: Vec<Vec<u8>>,
+    with_speedups: bool,
+    pydantic_models: bool,
+) {
     let registry = test_utils::get_registry().unwrap();
     let dir = tempdir().unwrap();
     let source_path = dir.path().join("test.py");
@@ -94,12 +283,9 @@ fn test_python_runtime_on_supported_types(runtime: Runtime) {
 
     let config =
         CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![runtime.into()]);
-    let generator = python3::CodeGenerator::new(&config);
+    let generator = python3::CodeGenerator::new(&config).with_pydantic_models(pydantic_models);
     generator.output(&mut source, &registry).unwrap();
 
-    let positive_encodings: Vec<_> = runtime.get_positive_samples_quick();
-    let negative_encodings: Vec<_> = runtime.get_negative_samples();
-
     writeln!(
         source,
         r#"
@@ -146,14 +332,7 @@ for encoding in negative_encodings:
     )
     .unwrap();
 
-    let python_path = format!(
-        "{}:runtime/python",
-        std::env::var("PYTHONPATH").unwrap_or_default()
-    );
-    let status = Command::new("python3")
-        .arg(&source_path)
-        .env("PYTHONPATH", python_path)
-        .status()
-        .unwrap();
+    let (mut command, _speedups_dir) = python_command(with_speedups);
+    let status = command.arg(&source_path).status().unwrap();
     assert!(status.success());
 }
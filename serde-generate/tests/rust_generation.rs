@@ -1,7 +1,9 @@
 // Copyright (c) Facebook, Inc. and its affiliates
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+use serde::Serialize;
 use serde_generate::{rust, test_utils, CodeGeneratorConfig};
+use serde_reflection::{Samples, Tracer, TracerConfig};
 use std::fs::File;
 use std::io::Write;
 use std::process::Command;
@@ -168,3 +170,119 @@ fn test_that_rust_code_compiles_with_custom_code() {
     let content = std::fs::read_to_string(&source_path).unwrap();
     assert!(content.contains("// custom code\n"));
 }
+
+#[test]
+fn test_that_incomplete_formats_are_reported() {
+    #[derive(Serialize)]
+    struct Foo {
+        a: u32,
+        b: Option<u64>,
+    }
+
+    // The content of `b` is unknown after tracing only `b: None`.
+    let mut tracer = Tracer::new(TracerConfig::default());
+    let mut samples = Samples::new();
+    tracer
+        .trace_value(&mut samples, &Foo { a: 1, b: None })
+        .unwrap();
+    let registry = tracer.registry_unchecked();
+
+    let config = CodeGeneratorConfig::new("testing".to_string());
+    let generator = rust::CodeGenerator::new(&config);
+    let mut source = Vec::new();
+    match generator.output(&mut source, &registry) {
+        Err(serde_generate::Error::UnknownFormat { container, field }) => {
+            assert_eq!(container, "Foo");
+            assert_eq!(field, Some("b".to_string()));
+        }
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn test_that_nested_generic_definitions_are_rejected() {
+    let registry: serde_reflection::Registry = serde_yaml::from_str(
+        r#"
+Foo:
+  GENERIC:
+    PARAMETERS: [T0]
+    FORMAT:
+      GENERIC:
+        PARAMETERS: [T1]
+        FORMAT:
+          NEWTYPESTRUCT:
+            PARAMETER: T1
+"#,
+    )
+    .unwrap();
+    let config = CodeGeneratorConfig::new("testing".to_string());
+    let generator = rust::CodeGenerator::new(&config);
+    match generator.output(&mut Vec::new(), &registry) {
+        Err(serde_generate::Error::UnexpectedGeneric(container)) => assert_eq!(container, "Foo"),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn test_that_rust_code_compiles_with_generics() {
+    let registry = test_utils::get_generic_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let source_path = dir.path().join("test.rs");
+    let mut source = File::create(&source_path).unwrap();
+
+    let config = CodeGeneratorConfig::new("testing".to_string()).with_serialization(false);
+    let generator = rust::CodeGenerator::new(&config);
+    generator.output(&mut source, &registry).unwrap();
+
+    let status = Command::new("rustc")
+        .current_dir(dir.path())
+        .arg("--crate-type")
+        .arg("lib")
+        .arg("--edition")
+        .arg("2018")
+        .arg(&source_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let content = std::fs::read_to_string(&source_path).unwrap();
+    assert!(content.contains("pub struct Tree<T0> {"));
+    assert!(content.contains("pub children: Vec<Tree<T0>>,"));
+    assert!(content.contains("pub enum Either<T0, T1> {"));
+    assert!(content.contains("pub nested: Pair<String, Tree<u64>>,"));
+}
+
+#[test]
+fn test_that_rust_code_compiles_with_fingerprints() {
+    let registry = test_utils::get_generic_registry().unwrap();
+    let dir = tempdir().unwrap();
+    let source_path = dir.path().join("test.rs");
+    let mut source = File::create(&source_path).unwrap();
+
+    let config = CodeGeneratorConfig::new("testing".to_string())
+        .with_serialization(false)
+        .with_fingerprints(true);
+    let generator = rust::CodeGenerator::new(&config);
+    generator.output(&mut source, &registry).unwrap();
+
+    let status = Command::new("rustc")
+        .current_dir(dir.path())
+        .arg("--crate-type")
+        .arg("lib")
+        .arg("--edition")
+        .arg("2018")
+        .arg(&source_path)
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let content = std::fs::read_to_string(&source_path).unwrap();
+    let fingerprint = serde_reflection::fingerprint(&registry, "GenericData").unwrap();
+    assert!(content.contains(&format!(
+        "impl GenericData {{\n    pub const FINGERPRINT: u64 = {:#018x};\n}}\n",
+        fingerprint
+    )));
+    // Generic containers have no fingerprint.
+    assert!(!content.contains("impl<T0> Tree<T0> {"));
+    assert_eq!(content.matches("FINGERPRINT").count(), 1);
+}
@@ -0,0 +1,146 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use serde_generate::{test_utils, transform, Error};
+use serde_reflection::Registry;
+
+fn get_small_registry() -> Registry {
+    serde_yaml::from_str(
+        r#"
+Foo:
+  STRUCT:
+    - x: U8
+    - choice:
+        OPTION:
+          TYPENAME: Choice
+Choice:
+  ENUM:
+    0:
+      A:
+        STRUCT:
+          - x: U8
+          - foo:
+              TYPENAME: Foo
+    1:
+      B: UNIT
+Other:
+  NEWTYPESTRUCT:
+    TYPENAME: Choice
+"#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn test_extract_subgraph() {
+    let registry = get_small_registry();
+    let subgraph = transform::extract_subgraph(&registry, &["Foo"]).unwrap();
+    assert_eq!(subgraph.keys().collect::<Vec<_>>(), vec!["Choice", "Foo"]);
+
+    let mut pruned = registry.clone();
+    transform::prune(&mut pruned, &["Choice"]).unwrap();
+    assert_eq!(pruned, subgraph);
+
+    let subgraph = transform::extract_subgraph(&registry, &["Other"]).unwrap();
+    assert_eq!(subgraph, registry);
+
+    match transform::extract_subgraph(&registry, &["Unknown"]) {
+        Err(Error::UnknownContainer(name)) => assert_eq!(name, "Unknown"),
+        result => panic!("unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn test_extract_subgraph_with_generics() {
+    let registry = test_utils::get_generic_registry().unwrap();
+    let subgraph = transform::extract_subgraph(&registry, &["Pair"]).unwrap();
+    assert_eq!(subgraph.keys().collect::<Vec<_>>(), vec!["Pair"]);
+    let subgraph = transform::extract_subgraph(&registry, &["GenericData"]).unwrap();
+    assert_eq!(subgraph, registry);
+}
+
+#[test]
+fn test_rename() {
+    let registry = get_small_registry();
+    let renaming = transform::Renaming::new()
+        .with_container("Choice".to_string(), "TheirChoice".to_string())
+        .with_field("Choice".to_string(), "x".to_string(), "y".to_string())
+        .with_variant("Choice".to_string(), "B".to_string(), "C".to_string());
+    let registry = transform::rename(&registry, &renaming).unwrap();
+    assert_eq!(
+        serde_yaml::to_string(&registry).unwrap(),
+        r#"---
+Foo:
+  STRUCT:
+    - x: U8
+    - choice:
+        OPTION:
+          TYPENAME: TheirChoice
+Other:
+  NEWTYPESTRUCT:
+    TYPENAME: TheirChoice
+TheirChoice:
+  ENUM:
+    0:
+      A:
+        STRUCT:
+          - y: U8
+          - foo:
+              TYPENAME: Foo
+    1:
+      C: UNIT
+"#
+    );
+}
+
+#[test]
+fn test_rename_with_generics() {
+    let registry = test_utils::get_generic_registry().unwrap();
+    let renaming = transform::Renaming::new()
+        .with_container("Tree".to_string(), "Node".to_string())
+        .with_field("Tree".to_string(), "value".to_string(), "data".to_string());
+    let registry = transform::rename(&registry, &renaming).unwrap();
+    assert!(!registry.contains_key("Tree"));
+    let content = serde_yaml::to_string(&registry["Node"]).unwrap();
+    assert!(content.contains("- data:\n"));
+    assert!(content.contains("NAME: Node\n"));
+    let content = serde_yaml::to_string(&registry["GenericData"]).unwrap();
+    assert!(!content.contains("Tree"));
+}
+
+#[test]
+fn test_invalid_renamings() {
+    let registry = get_small_registry();
+    let check = |renaming: transform::Renaming, expected: &str| {
+        let error = transform::rename(&registry, &renaming).unwrap_err();
+        assert_eq!(error.to_string(), expected);
+    };
+    check(
+        transform::Renaming::new().with_container("Unknown".to_string(), "X".to_string()),
+        "Container `Unknown` does not exist in the registry",
+    );
+    check(
+        transform::Renaming::new().with_container("Foo".to_string(), "Other".to_string()),
+        "Invalid renaming: several containers are named `Other`",
+    );
+    check(
+        transform::Renaming::new().with_field("Foo".to_string(), "y".to_string(), "z".to_string()),
+        "Invalid renaming: container `Foo` has no field `y`",
+    );
+    check(
+        transform::Renaming::new().with_field(
+            "Foo".to_string(),
+            "x".to_string(),
+            "choice".to_string(),
+        ),
+        "Invalid renaming: container `Foo` uses the name `choice` twice",
+    );
+    check(
+        transform::Renaming::new().with_variant(
+            "Foo".to_string(),
+            "A".to_string(),
+            "B".to_string(),
+        ),
+        "Invalid renaming: container `Foo` has no variant `A`",
+    );
+}
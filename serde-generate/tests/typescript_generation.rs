@@ -59,15 +59,27 @@ fn write_package_tsconfig_json_for_test_build(path: std::path::PathBuf) -> Resul
 
 fn test_that_ts_code_compiles_with_config(
     config: &CodeGeneratorConfig,
+) -> (TempDir, std::path::PathBuf) {
+    test_that_ts_code_compiles_with_generator(&typescript::CodeGenerator::new(&config))
+}
+
+fn test_that_ts_code_compiles_with_generator(
+    generator: &typescript::CodeGenerator,
 ) -> (TempDir, std::path::PathBuf) {
     let registry = test_utils::get_registry().unwrap();
+    test_that_ts_code_compiles_with_registry(generator, &registry)
+}
+
+fn test_that_ts_code_compiles_with_registry(
+    generator: &typescript::CodeGenerator,
+    registry: &serde_reflection::Registry,
+) -> (TempDir, std::path::PathBuf) {
     let dir = tempdir().unwrap();
     make_output_file(&dir);
     let source_path = dir.path().join("testing").join("test.ts");
     let mut source = File::create(&source_path).unwrap();
 
-    let generator = typescript::CodeGenerator::new(&config);
-    generator.output(&mut source, &registry).unwrap();
+    generator.output(&mut source, registry).unwrap();
     let _result = write_package_tsconfig_json_for_test_build(dir.path().to_path_buf());
 
     let installer = typescript::Installer::new(dir.path().to_path_buf());
@@ -131,3 +143,72 @@ fn test_that_ts_code_compiles_with_comments() {
 "#
     ));
 }
+
+#[test]
+fn test_that_ts_code_compiles_with_discriminated_unions() {
+    let config = CodeGeneratorConfig::new("testing".to_string());
+    let generator = typescript::CodeGenerator::new(&config).with_discriminated_unions(true);
+    let (_dir, path) = test_that_ts_code_compiles_with_generator(&generator);
+
+    let content = std::fs::read_to_string(path.join("test.ts")).unwrap();
+    assert!(content.contains("export type List = ListVariantEmpty | ListVariantNode;"));
+    assert!(content.contains("export function serializeSerdeData("));
+    assert!(!content.contains("class SerdeData"));
+}
+
+#[test]
+fn test_that_kind_fields_are_rejected_in_discriminated_unions() {
+    let registry: serde_reflection::Registry = serde_yaml::from_str(
+        r#"
+Shape:
+  ENUM:
+    0:
+      Circle:
+        STRUCT:
+          - kind: STR
+          - radius: U32
+"#,
+    )
+    .unwrap();
+    let config = CodeGeneratorConfig::new("testing".to_string());
+    let generator = typescript::CodeGenerator::new(&config).with_discriminated_unions(true);
+    let error = generator.output(&mut Vec::new(), &registry).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "In container `Shape`, the name of field `Circle.kind` is reserved in discriminated unions"
+    );
+}
+
+#[test]
+fn test_that_ts_code_compiles_with_numbers_for_big_integers() {
+    let config = CodeGeneratorConfig::new("testing".to_string());
+    let generator = typescript::CodeGenerator::new(&config)
+        .with_big_integer_mode(typescript::BigIntegerMode::Number);
+    let (_dir, path) = test_that_ts_code_compiles_with_generator(&generator);
+
+    let content = std::fs::read_to_string(path.join("test.ts")).unwrap();
+    assert!(content.contains("deserializer.deserializeU64AsNumber()"));
+}
+
+#[test]
+fn test_that_ts_code_compiles_with_generics() {
+    let registry = test_utils::get_generic_registry().unwrap();
+    let config =
+        CodeGeneratorConfig::new("testing".to_string()).with_encodings(vec![Encoding::Bcs]);
+    let generator = typescript::CodeGenerator::new(&config);
+    let (_dir, path) = test_that_ts_code_compiles_with_registry(&generator, &registry);
+
+    let content = std::fs::read_to_string(path.join("test.ts")).unwrap();
+    assert!(content.contains("export class Pair<T0, T1> {"));
+    assert!(content.contains("export abstract class Either<T0, T1> {"));
+    assert!(!content.contains("Pair_u32_str"));
+
+    let generator = typescript::CodeGenerator::new(&config).with_discriminated_unions(true);
+    let (_dir, path) = test_that_ts_code_compiles_with_registry(&generator, &registry);
+
+    let content = std::fs::read_to_string(path.join("test.ts")).unwrap();
+    assert!(content.contains(
+        "export type Either<T0, T1> = EitherVariantLeft<T0, T1> | EitherVariantRight<T0, T1>;"
+    ));
+    assert!(content.contains("export function serializePair<T0, T1>("));
+}
@@ -0,0 +1,198 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use serde_generate::{
+    test_utils, test_utils::Runtime, typescript, typescript::BigIntegerMode, CodeGeneratorConfig,
+    SourceInstaller,
+};
+use std::fs::File;
+use std::io::Write;
+use std::process::Command;
+use tempfile::tempdir;
+
+fn write_package_tsconfig_json_for_test_build(path: &std::path::Path) {
+    let mut package_json = File::create(path.join("package.json")).unwrap();
+    writeln!(
+        package_json,
+        r#"
+{{
+  "name": "tmpCode",
+  "version": "1.0.0",
+  "scripts": {{
+    "build": "tsc"
+  }},
+  "devDependencies": {{
+    "@types/node": "12.12.2",
+    "typescript": "^3.9.6"
+  }}
+}}
+"#
+    )
+    .unwrap();
+    let mut tsconfig_json = File::create(path.join("tsconfig.json")).unwrap();
+    writeln!(
+        tsconfig_json,
+        r#"
+{{
+  "compilerOptions": {{
+    "target": "es6",
+    "module": "commonjs",
+    "outDir": "./dist",
+    "strict": true,
+    "esModuleInterop": true,
+    "skipLibCheck": true,
+    "lib": ["es6", "esnext.BigInt", "dom"]
+  }},
+  "include": ["testing/*.ts"]
+}}
+"#
+    )
+    .unwrap();
+}
+
+/// Import statements and expressions creating a serializer (resp. a deserializer from `input`).
+fn runtime_code(runtime: Runtime) -> (&'static str, &'static str, &'static str) {
+    match runtime {
+        Runtime::Bcs => (
+            r#"import { BcsSerializer } from '../bcs/bcsSerializer';
+import { BcsDeserializer } from '../bcs/bcsDeserializer';"#,
+            "new BcsSerializer()",
+            "new BcsDeserializer(input)",
+        ),
+        Runtime::Bincode => (
+            // The constructors of the binary runtime are protected.
+            r#"import { BincodeSerializer, BincodeDeserializer } from '../bincode';
+class TestSerializer extends BincodeSerializer {
+  constructor() { super(); }
+}
+class TestDeserializer extends BincodeDeserializer {
+  constructor(data: Uint8Array) { super(data); }
+}"#,
+            "new TestSerializer()",
+            "new TestDeserializer(input)",
+        ),
+    }
+}
+
+#[test]
+fn test_typescript_bcs_discriminated_unions_match_classes() {
+    test_typescript_discriminated_unions_match_classes(Runtime::Bcs, BigIntegerMode::BigInt);
+}
+
+#[test]
+fn test_typescript_bincode_discriminated_unions_match_classes() {
+    test_typescript_discriminated_unions_match_classes(Runtime::Bincode, BigIntegerMode::BigInt);
+}
+
+#[test]
+fn test_typescript_bcs_discriminated_unions_match_classes_with_numbers() {
+    test_typescript_discriminated_unions_match_classes(Runtime::Bcs, BigIntegerMode::Number);
+}
+
+#[test]
+fn test_typescript_bincode_discriminated_unions_match_classes_with_numbers() {
+    test_typescript_discriminated_unions_match_classes(Runtime::Bincode, BigIntegerMode::Number);
+}
+
+fn test_typescript_discriminated_unions_match_classes(
+    runtime: Runtime,
+    big_integer_mode: BigIntegerMode,
+) {
+    let registry = test_utils::get_registry().unwrap();
+    let dir = tempdir().unwrap();
+    std::fs::create_dir_all(dir.path().join("testing")).unwrap();
+    write_package_tsconfig_json_for_test_build(dir.path());
+
+    let config = CodeGeneratorConfig::new("testing".to_string());
+    let mut source = File::create(dir.path().join("testing/classes.ts")).unwrap();
+    typescript::CodeGenerator::new(&config)
+        .with_big_integer_mode(big_integer_mode)
+        .output(&mut source, &registry)
+        .unwrap();
+    let mut source = File::create(dir.path().join("testing/unions.ts")).unwrap();
+    typescript::CodeGenerator::new(&config)
+        .with_big_integer_mode(big_integer_mode)
+        .with_discriminated_unions(true)
+        .output(&mut source, &registry)
+        .unwrap();
+
+    let installer = typescript::Installer::new(dir.path().to_path_buf());
+    installer.install_serde_runtime().unwrap();
+    installer.install_bincode_runtime().unwrap();
+    installer.install_bcs_runtime().unwrap();
+
+    let (imports, new_serializer, new_deserializer) = runtime_code(runtime);
+    let encodings = runtime.get_positive_samples_quick();
+    let mut main = File::create(dir.path().join("testing/main.ts")).unwrap();
+    writeln!(
+        main,
+        r#"
+{0}
+import * as Classes from './classes';
+import * as Unions from './unions';
+
+function withClasses(input: Uint8Array): Uint8Array {{
+  const value = Classes.SerdeData.deserialize({2});
+  const serializer = {1};
+  value.serialize(serializer);
+  return serializer.getBytes();
+}}
+
+function withUnions(input: Uint8Array): Uint8Array {{
+  const value = Unions.deserializeSerdeData({2});
+  const serializer = {1};
+  Unions.serializeSerdeData(value, serializer);
+  return serializer.getBytes();
+}}
+
+function tryOrError(f: (input: Uint8Array) => Uint8Array, input: Uint8Array): string {{
+  try {{
+    return f(input).toString();
+  }} catch (e) {{
+    return "error";
+  }}
+}}
+
+const encodings: number[][] = {3:?};
+let successes = 0;
+for (const encoding of encodings) {{
+  const input = new Uint8Array(encoding);
+  const expected = tryOrError(withClasses, input);
+  const actual = tryOrError(withUnions, input);
+  if (expected !== actual) {{
+    throw new Error("Mismatch on input " + encoding + ": " + expected + " vs " + actual);
+  }}
+  if (expected !== "error") {{
+    successes++;
+  }}
+}}
+if (successes == 0) {{
+  throw new Error("No input was successfully deserialized");
+}}
+"#,
+        imports, new_serializer, new_deserializer, encodings
+    )
+    .unwrap();
+
+    let status = Command::new("npm")
+        .arg("install")
+        .current_dir(dir.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let status = Command::new("npm")
+        .arg("run")
+        .arg("build")
+        .current_dir(dir.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let status = Command::new("node")
+        .arg("dist/testing/main.js")
+        .current_dir(dir.path())
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
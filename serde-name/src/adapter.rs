@@ -0,0 +1,302 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use serde::{de::Visitor, ser::Serialize};
+use std::{collections::BTreeSet, sync::Mutex};
+
+lazy_static::lazy_static! {
+    static ref NAMES: Mutex<BTreeSet<&'static str>> = Mutex::new(BTreeSet::new());
+}
+
+/// Return a static copy of the given name, e.g. a mangled name to be used with
+/// `SerializeNameAdapter` and `DeserializeNameAdapter`. Each distinct name is allocated only once.
+pub fn intern_name(name: String) -> &'static str {
+    let mut names = NAMES.lock().unwrap();
+    if let Some(name) = names.get(name.as_str()) {
+        return name;
+    }
+    let name = Box::leak(name.into_boxed_str());
+    names.insert(name);
+    name
+}
+
+/// A `serde::Serializer` that overrides the Serde name of the (top-level) container being serialized.
+pub struct SerializeNameAdapter<S> {
+    inner: S,
+    name: &'static str,
+}
+
+impl<S> SerializeNameAdapter<S> {
+    pub fn new(inner: S, name: &'static str) -> Self {
+        Self { inner, name }
+    }
+}
+
+macro_rules! forward_serialize {
+    ($method:ident, $type:ty) => {
+        fn $method(self, value: $type) -> Result<S::Ok, S::Error> {
+            self.inner.$method(value)
+        }
+    };
+}
+
+impl<S> serde::ser::Serializer for SerializeNameAdapter<S>
+where
+    S: serde::ser::Serializer,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+    type SerializeSeq = S::SerializeSeq;
+    type SerializeTuple = S::SerializeTuple;
+    type SerializeTupleStruct = S::SerializeTupleStruct;
+    type SerializeTupleVariant = S::SerializeTupleVariant;
+    type SerializeMap = S::SerializeMap;
+    type SerializeStruct = S::SerializeStruct;
+    type SerializeStructVariant = S::SerializeStructVariant;
+
+    forward_serialize!(serialize_bool, bool);
+    forward_serialize!(serialize_i8, i8);
+    forward_serialize!(serialize_i16, i16);
+    forward_serialize!(serialize_i32, i32);
+    forward_serialize!(serialize_i64, i64);
+    forward_serialize!(serialize_i128, i128);
+    forward_serialize!(serialize_u8, u8);
+    forward_serialize!(serialize_u16, u16);
+    forward_serialize!(serialize_u32, u32);
+    forward_serialize!(serialize_u64, u64);
+    forward_serialize!(serialize_u128, u128);
+    forward_serialize!(serialize_f32, f32);
+    forward_serialize!(serialize_f64, f64);
+    forward_serialize!(serialize_char, char);
+    forward_serialize!(serialize_str, &str);
+    forward_serialize!(serialize_bytes, &[u8]);
+
+    fn serialize_none(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_none()
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner.serialize_some(value)
+    }
+
+    fn serialize_unit(self) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit()
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<S::Ok, S::Error> {
+        self.inner.serialize_unit_struct(self.name)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<S::Ok, S::Error> {
+        self.inner
+            .serialize_unit_variant(self.name, variant_index, variant)
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner.serialize_newtype_struct(self.name, value)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<S::Ok, S::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.inner
+            .serialize_newtype_variant(self.name, variant_index, variant, value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<S::SerializeSeq, S::Error> {
+        self.inner.serialize_seq(len)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<S::SerializeTuple, S::Error> {
+        self.inner.serialize_tuple(len)
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<S::SerializeTupleStruct, S::Error> {
+        self.inner.serialize_tuple_struct(self.name, len)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<S::SerializeTupleVariant, S::Error> {
+        self.inner
+            .serialize_tuple_variant(self.name, variant_index, variant, len)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<S::SerializeMap, S::Error> {
+        self.inner.serialize_map(len)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<S::SerializeStruct, S::Error> {
+        self.inner.serialize_struct(self.name, len)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<S::SerializeStructVariant, S::Error> {
+        self.inner
+            .serialize_struct_variant(self.name, variant_index, variant, len)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
+
+/// A `serde::Deserializer` that overrides the Serde name of the (top-level) container being deserialized.
+pub struct DeserializeNameAdapter<D> {
+    inner: D,
+    name: &'static str,
+}
+
+impl<D> DeserializeNameAdapter<D> {
+    pub fn new(inner: D, name: &'static str) -> Self {
+        Self { inner, name }
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($method:ident) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, D::Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.inner.$method(visitor)
+        }
+    };
+}
+
+impl<'de, D> serde::de::Deserializer<'de> for DeserializeNameAdapter<D>
+where
+    D: serde::de::Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    forward_deserialize!(deserialize_any);
+    forward_deserialize!(deserialize_bool);
+    forward_deserialize!(deserialize_i8);
+    forward_deserialize!(deserialize_i16);
+    forward_deserialize!(deserialize_i32);
+    forward_deserialize!(deserialize_i64);
+    forward_deserialize!(deserialize_i128);
+    forward_deserialize!(deserialize_u8);
+    forward_deserialize!(deserialize_u16);
+    forward_deserialize!(deserialize_u32);
+    forward_deserialize!(deserialize_u64);
+    forward_deserialize!(deserialize_u128);
+    forward_deserialize!(deserialize_f32);
+    forward_deserialize!(deserialize_f64);
+    forward_deserialize!(deserialize_char);
+    forward_deserialize!(deserialize_str);
+    forward_deserialize!(deserialize_string);
+    forward_deserialize!(deserialize_bytes);
+    forward_deserialize!(deserialize_byte_buf);
+    forward_deserialize!(deserialize_option);
+    forward_deserialize!(deserialize_unit);
+    forward_deserialize!(deserialize_seq);
+    forward_deserialize!(deserialize_map);
+    forward_deserialize!(deserialize_identifier);
+    forward_deserialize!(deserialize_ignored_any);
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_unit_struct(self.name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_newtype_struct(self.name, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_tuple_struct(self.name, len, visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_struct(self.name, fields, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, D::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.inner.deserialize_enum(self.name, variants, visitor)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        self.inner.is_human_readable()
+    }
+}
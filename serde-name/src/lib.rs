@@ -21,30 +21,128 @@
 //! assert_eq!(trace_name::<Bar>(), Some("ABC"));
 //! assert_eq!(trace_name::<Option<Bar>>(), None);
 //! ```
+//!
+//! The names of fields and variants can be obtained in the same way:
+//! ```rust
+//! # use serde::Deserialize;
+//! # use serde_name::trace_container_names;
+//! # #[derive(Deserialize)]
+//! # struct Foo {
+//! #   bar: Bar,
+//! # }
+//! # #[derive(Deserialize)]
+//! # #[serde(rename = "ABC")]
+//! # enum Bar { A, B, C }
+//! assert_eq!(trace_container_names::<Foo>().unwrap().fields(), Some(&["bar"][..]));
+//! assert_eq!(trace_container_names::<Bar>().unwrap().variants(), Some(&["A", "B", "C"][..]));
+//! ```
+//!
+//! The function `trace_shape` also describes Serde wrappers such as options, sequences, maps
+//! and tuples. This is useful to compute a distinct name for each instantiation of a generic
+//! container:
+//! ```rust
+//! # use serde::Deserialize;
+//! # use serde_name::{generic_name, mangle_generic_name, trace_shape};
+//! # #[derive(Deserialize)]
+//! # #[serde(rename = "ABC")]
+//! # enum Bar { A, B, C }
+//! let shape = trace_shape::<Option<Vec<Bar>>>();
+//! assert_eq!(shape.to_string(), "Option<Seq<ABC>>");
+//! assert_eq!(mangle_generic_name("Tree", &[shape.clone()]), "Tree_Option_Seq_ABC");
+//! assert_eq!(generic_name("Tree", &[shape]), "Tree<Option<Seq<ABC>>>");
+//! ```
+//! Such names may then replace the Serde name of a container thanks to `SerializeNameAdapter`
+//! and `DeserializeNameAdapter`, so that e.g. `Tree<u32>` and `Tree<String>` are recorded as
+//! distinct containers by `serde-reflection`. Names computed by `generic_name` further allow
+//! `serde-reflection` to recover a single generic container `Tree` when tracing is configured
+//! with `TracerConfig::recognize_generics`.
 
 use serde::de::Visitor;
 use thiserror::Error;
 
+/// (De)serializers overriding the Serde names of containers.
+mod adapter;
+/// Structured names of types, including Serde wrappers such as options and sequences.
+mod shape;
+
+pub use adapter::{intern_name, DeserializeNameAdapter, SerializeNameAdapter};
+pub use shape::{generic_name, mangle_generic_name, trace_shape, Shape};
+
 /// Compute the Serde name of a container.
 pub fn trace_name<'de, T>() -> Option<&'static str>
+where
+    T: serde::de::Deserialize<'de>,
+{
+    trace_container_names::<T>().map(|names| names.name())
+}
+
+/// Compute the Serde name of a container together with the names of its fields or variants,
+/// as given by Serde to `deserialize_struct` or `deserialize_enum`. In particular, skipped fields
+/// and variants are omitted.
+pub fn trace_container_names<'de, T>() -> Option<ContainerNames>
 where
     T: serde::de::Deserialize<'de>,
 {
     match T::deserialize(SerdeName) {
-        Err(SerdeNameError(name)) => name,
+        Err(SerdeNameError(names)) => names,
         _ => unreachable!(),
     }
 }
 
+/// The Serde names of a container and of its fields or variants.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ContainerNames {
+    UnitStruct(&'static str),
+    NewTypeStruct(&'static str),
+    TupleStruct(&'static str, usize),
+    Struct {
+        name: &'static str,
+        fields: &'static [&'static str],
+    },
+    Enum {
+        name: &'static str,
+        variants: &'static [&'static str],
+    },
+}
+
+impl ContainerNames {
+    /// The name of the container.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContainerNames::UnitStruct(name)
+            | ContainerNames::NewTypeStruct(name)
+            | ContainerNames::TupleStruct(name, _)
+            | ContainerNames::Struct { name, .. }
+            | ContainerNames::Enum { name, .. } => name,
+        }
+    }
+
+    /// The names of the fields, if the container is a struct with named fields.
+    pub fn fields(&self) -> Option<&'static [&'static str]> {
+        match self {
+            ContainerNames::Struct { fields, .. } => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// The names of the variants, if the container is an enum.
+    pub fn variants(&self) -> Option<&'static [&'static str]> {
+        match self {
+            ContainerNames::Enum { variants, .. } => Some(variants),
+            _ => None,
+        }
+    }
+}
+
 /// Minimal instrumented implementation of `serde::de::Deserializer`
-/// This always returns a `SerdeNameError` as soon as we have learnt the name
+/// This always returns a `SerdeNameError` as soon as we have learnt the names
 /// of the type (or the absence of name) from Serde.
 struct SerdeName;
 
 /// Custom error value used to report the result of the analysis.
 #[derive(Clone, Debug, Error, PartialEq)]
 #[error("{0:?}")]
-struct SerdeNameError(Option<&'static str>);
+struct SerdeNameError(Option<ContainerNames>);
 
 impl serde::de::Error for SerdeNameError {
     fn custom<T: std::fmt::Display>(_msg: T) -> Self {
@@ -111,7 +209,7 @@ impl<'de> serde::de::Deserializer<'de> for SerdeName {
     where
         V: Visitor<'de>,
     {
-        Err(SerdeNameError(Some(name)))
+        Err(SerdeNameError(Some(ContainerNames::UnitStruct(name))))
     }
 
     fn deserialize_newtype_struct<V>(
@@ -122,43 +220,49 @@ impl<'de> serde::de::Deserializer<'de> for SerdeName {
     where
         V: Visitor<'de>,
     {
-        Err(SerdeNameError(Some(name)))
+        Err(SerdeNameError(Some(ContainerNames::NewTypeStruct(name))))
     }
 
     fn deserialize_tuple_struct<V>(
         self,
         name: &'static str,
-        _len: usize,
+        len: usize,
         _visitor: V,
     ) -> std::result::Result<V::Value, SerdeNameError>
     where
         V: Visitor<'de>,
     {
-        Err(SerdeNameError(Some(name)))
+        Err(SerdeNameError(Some(ContainerNames::TupleStruct(name, len))))
     }
 
     fn deserialize_struct<V>(
         self,
         name: &'static str,
-        _fields: &'static [&'static str],
+        fields: &'static [&'static str],
         _visitor: V,
     ) -> std::result::Result<V::Value, SerdeNameError>
     where
         V: Visitor<'de>,
     {
-        Err(SerdeNameError(Some(name)))
+        Err(SerdeNameError(Some(ContainerNames::Struct {
+            name,
+            fields,
+        })))
     }
 
     fn deserialize_enum<V>(
         self,
         name: &'static str,
-        _variants: &'static [&'static str],
+        variants: &'static [&'static str],
         _visitor: V,
     ) -> std::result::Result<V::Value, SerdeNameError>
     where
         V: Visitor<'de>,
     {
-        Err(SerdeNameError(Some(name)))
+        Err(SerdeNameError(Some(ContainerNames::Enum {
+            name,
+            variants,
+        })))
     }
 
     fn is_human_readable(&self) -> bool {
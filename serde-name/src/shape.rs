@@ -0,0 +1,678 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use serde::de::{DeserializeSeed, IntoDeserializer, Visitor};
+use std::fmt;
+use thiserror::Error;
+
+/// The structure of a Rust type as seen by Serde, down to the names of containers.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Shape {
+    /// A struct or an enum, designated by its Serde name.
+    Named(&'static str),
+    /// A primitive type of the Serde data model, e.g. `u64`, `str`, `bytes` or `unit`.
+    Primitive(&'static str),
+    Option(Box<Shape>),
+    Seq(Box<Shape>),
+    Map {
+        key: Box<Shape>,
+        value: Box<Shape>,
+    },
+    Tuple(Vec<Shape>),
+    /// The shape could not be determined, e.g. because the type implements `Deserialize`
+    /// with `deserialize_any`, or because no value of a previous tuple element could be built.
+    Unknown,
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Shape::Named(name) | Shape::Primitive(name) => write!(f, "{}", name),
+            Shape::Option(shape) => write!(f, "Option<{}>", shape),
+            Shape::Seq(shape) => write!(f, "Seq<{}>", shape),
+            Shape::Map { key, value } => write!(f, "Map<{}, {}>", key, value),
+            Shape::Tuple(shapes) => {
+                write!(f, "(")?;
+                for (i, shape) in shapes.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", shape)?;
+                }
+                write!(f, ")")
+            }
+            Shape::Unknown => write!(f, "?"),
+        }
+    }
+}
+
+impl Shape {
+    /// A name made of letters, digits and underscores that identifies the shape, e.g.
+    /// `Option_u32` or `Map_str_Seq_Foo`.
+    ///
+    /// Names are stable as long as the Serde names of the containers do not change. Since every
+    /// wrapper has a fixed number of arguments (tuples start with their length), distinct shapes
+    /// have distinct mangled names unless the names of containers contain underscores.
+    pub fn mangled_name(&self) -> String {
+        match self {
+            Shape::Named(name) | Shape::Primitive(name) => name.to_string(),
+            Shape::Option(shape) => format!("Option_{}", shape.mangled_name()),
+            Shape::Seq(shape) => format!("Seq_{}", shape.mangled_name()),
+            Shape::Map { key, value } => {
+                format!("Map_{}_{}", key.mangled_name(), value.mangled_name())
+            }
+            Shape::Tuple(shapes) => {
+                let mut name = format!("Tuple{}", shapes.len());
+                for shape in shapes {
+                    name.push('_');
+                    name.push_str(&shape.mangled_name());
+                }
+                name
+            }
+            Shape::Unknown => "Unknown".to_string(),
+        }
+    }
+}
+
+/// Compute the name of an instantiation of a generic container from its base name and the
+/// shapes of its type arguments, e.g. `Tree_u32` for `Tree<u32>`.
+///
+/// The result is meant to replace the Serde name of the container (see `SerializeNameAdapter`
+/// and `DeserializeNameAdapter`) so that several instantiations may be traced in the same
+/// registry.
+pub fn mangle_generic_name(name: &str, arguments: &[Shape]) -> String {
+    let mut result = name.to_string();
+    for argument in arguments {
+        result.push('_');
+        result.push_str(&argument.mangled_name());
+    }
+    result
+}
+
+/// Compute the name of an instantiation of a generic container from its base name and the
+/// shapes of its type arguments, e.g. `Tree<Seq<str>>` for `Tree<Vec<String>>`.
+///
+/// Unlike `mangle_generic_name`, the result keeps the structure of the type arguments so that
+/// a tracer configured with `recognize_generics` can recover a single generic container from
+/// several instances.
+pub fn generic_name(name: &str, arguments: &[Shape]) -> String {
+    let arguments = arguments
+        .iter()
+        .map(|argument| argument.to_string())
+        .collect::<Vec<_>>();
+    format!("{}<{}>", name, arguments.join(", "))
+}
+
+/// Compute the shape of a type.
+pub fn trace_shape<'de, T>() -> Shape
+where
+    T: serde::de::Deserialize<'de>,
+{
+    shape_at::<T>(Vec::new())
+}
+
+/// Compute the shape of the sub-term of `T` designated by `path`, i.e. by the indices of the
+/// components to follow (0 for options and sequences, 0 or 1 for the key or the value of maps).
+fn shape_at<'de, T>(path: Vec<usize>) -> Shape
+where
+    T: serde::de::Deserialize<'de>,
+{
+    match T::deserialize(ShapeTracer { path: &path }) {
+        Err(ShapeError::Found(steps, leaf)) => {
+            let steps = steps.get(path.len()..).unwrap_or_default();
+            shape_from_steps::<T>(path, steps, leaf)
+        }
+        _ => Shape::Unknown,
+    }
+}
+
+/// Reconstruct a shape from the steps of a trace, tracing other components as needed.
+fn shape_from_steps<'de, T>(mut path: Vec<usize>, steps: &[Step], leaf: Shape) -> Shape
+where
+    T: serde::de::Deserialize<'de>,
+{
+    let (step, steps) = match steps.split_first() {
+        None => return leaf,
+        Some(x) => x,
+    };
+    let other = |i: usize| {
+        let mut path = path.clone();
+        path.push(i);
+        shape_at::<T>(path)
+    };
+    let others = match step {
+        Step::Option | Step::Seq => Vec::new(),
+        Step::Map => vec![other(1)],
+        Step::Tuple(len) => (1..*len).map(other).collect(),
+    };
+    path.push(0);
+    let first = shape_from_steps::<T>(path, steps, leaf);
+    match step {
+        Step::Option => Shape::Option(Box::new(first)),
+        Step::Seq => Shape::Seq(Box::new(first)),
+        Step::Map => Shape::Map {
+            key: Box::new(first),
+            value: Box::new(others.into_iter().next().unwrap()),
+        },
+        Step::Tuple(_) => Shape::Tuple(std::iter::once(first).chain(others).collect()),
+    }
+}
+
+/// Wrappers traversed while tracing a shape.
+#[derive(Clone, Debug, PartialEq)]
+enum Step {
+    Option,
+    Seq,
+    Map,
+    Tuple(usize),
+}
+
+/// Custom error value used to report the result of the analysis.
+#[derive(Clone, Debug, Error, PartialEq)]
+enum ShapeError {
+    /// The steps followed from the root of the type and the shape found at the end.
+    #[error("{0:?} {1:?}")]
+    Found(Vec<Step>, Shape),
+    #[error("failed to build a value")]
+    Failed,
+}
+
+impl serde::de::Error for ShapeError {
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        ShapeError::Failed
+    }
+}
+
+/// Wrap the error of a component into the error of the enclosing wrapper.
+fn wrap<T>(step: Step, result: Result<T, ShapeError>) -> Result<T, ShapeError> {
+    match result {
+        Err(ShapeError::Found(mut steps, leaf)) => {
+            steps.insert(0, step);
+            Err(ShapeError::Found(steps, leaf))
+        }
+        _ => Err(ShapeError::Failed),
+    }
+}
+
+/// Instrumented implementation of `serde::de::Deserializer` that always returns a `ShapeError`
+/// as soon as the leaf of the given path is reached.
+struct ShapeTracer<'a> {
+    path: &'a [usize],
+}
+
+impl<'a> ShapeTracer<'a> {
+    fn split(&self) -> (usize, ShapeTracer<'a>) {
+        match self.path.split_first() {
+            Some((index, path)) => (*index, ShapeTracer { path }),
+            None => (0, ShapeTracer { path: &[] }),
+        }
+    }
+}
+
+macro_rules! declare_trace {
+    ($method:ident, $shape:expr) => {
+        fn $method<V>(self, _visitor: V) -> Result<V::Value, ShapeError>
+        where
+            V: Visitor<'de>,
+        {
+            Err(ShapeError::Found(Vec::new(), $shape))
+        }
+    };
+}
+
+impl<'de, 'a> serde::de::Deserializer<'de> for ShapeTracer<'a> {
+    type Error = ShapeError;
+
+    declare_trace!(deserialize_any, Shape::Unknown);
+    declare_trace!(deserialize_identifier, Shape::Unknown);
+    declare_trace!(deserialize_ignored_any, Shape::Unknown);
+    declare_trace!(deserialize_bool, Shape::Primitive("bool"));
+    declare_trace!(deserialize_i8, Shape::Primitive("i8"));
+    declare_trace!(deserialize_i16, Shape::Primitive("i16"));
+    declare_trace!(deserialize_i32, Shape::Primitive("i32"));
+    declare_trace!(deserialize_i64, Shape::Primitive("i64"));
+    declare_trace!(deserialize_i128, Shape::Primitive("i128"));
+    declare_trace!(deserialize_u8, Shape::Primitive("u8"));
+    declare_trace!(deserialize_u16, Shape::Primitive("u16"));
+    declare_trace!(deserialize_u32, Shape::Primitive("u32"));
+    declare_trace!(deserialize_u64, Shape::Primitive("u64"));
+    declare_trace!(deserialize_u128, Shape::Primitive("u128"));
+    declare_trace!(deserialize_f32, Shape::Primitive("f32"));
+    declare_trace!(deserialize_f64, Shape::Primitive("f64"));
+    declare_trace!(deserialize_char, Shape::Primitive("char"));
+    declare_trace!(deserialize_str, Shape::Primitive("str"));
+    declare_trace!(deserialize_string, Shape::Primitive("str"));
+    declare_trace!(deserialize_bytes, Shape::Primitive("bytes"));
+    declare_trace!(deserialize_byte_buf, Shape::Primitive("bytes"));
+    declare_trace!(deserialize_unit, Shape::Primitive("unit"));
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        let (_, inner) = self.split();
+        wrap(Step::Option, visitor.visit_some(inner))
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        let (_, inner) = self.split();
+        let seq = TraceSeqAccess {
+            index: 0,
+            len: 1,
+            target: 0,
+            inner: Some(inner),
+        };
+        wrap(Step::Seq, visitor.visit_seq(seq))
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        let (target, inner) = self.split();
+        let seq = TraceSeqAccess {
+            index: 0,
+            len,
+            target,
+            inner: Some(inner),
+        };
+        wrap(Step::Tuple(len), visitor.visit_seq(seq))
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        let (target, inner) = self.split();
+        let map = TraceMapAccess {
+            target,
+            inner: Some(inner),
+        };
+        wrap(Step::Map, visitor.visit_map(map))
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        _visitor: V,
+    ) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ShapeError::Found(Vec::new(), Shape::Named(name)))
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        _visitor: V,
+    ) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ShapeError::Found(Vec::new(), Shape::Named(name)))
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ShapeError::Found(Vec::new(), Shape::Named(name)))
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ShapeError::Found(Vec::new(), Shape::Named(name)))
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ShapeError::Found(Vec::new(), Shape::Named(name)))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Build the elements of a sequence until the target element, which is traced.
+struct TraceSeqAccess<'a> {
+    index: usize,
+    len: usize,
+    target: usize,
+    inner: Option<ShapeTracer<'a>>,
+}
+
+impl<'de, 'a> serde::de::SeqAccess<'de> for TraceSeqAccess<'a> {
+    type Error = ShapeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, ShapeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.index >= self.len {
+            return Ok(None);
+        }
+        let index = self.index;
+        self.index += 1;
+        if index < self.target {
+            seed.deserialize(ValueBuilder { depth: 0 }).map(Some)
+        } else {
+            let inner = self.inner.take().ok_or(ShapeError::Failed)?;
+            seed.deserialize(inner).map(Some)
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len - self.index)
+    }
+}
+
+/// Trace either the key or the value of a single map entry.
+struct TraceMapAccess<'a> {
+    target: usize,
+    inner: Option<ShapeTracer<'a>>,
+}
+
+impl<'de, 'a> serde::de::MapAccess<'de> for TraceMapAccess<'a> {
+    type Error = ShapeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, ShapeError>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.target == 0 {
+            let inner = self.inner.take().ok_or(ShapeError::Failed)?;
+            seed.deserialize(inner).map(Some)
+        } else {
+            seed.deserialize(ValueBuilder { depth: 0 }).map(Some)
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, ShapeError>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let inner = self.inner.take().ok_or(ShapeError::Failed)?;
+        seed.deserialize(inner)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(1)
+    }
+}
+
+/// Implementation of `serde::de::Deserializer` that builds the simplest possible value
+/// (zero, empty sequences, first variants, etc). Used to skip tuple elements and map keys.
+struct ValueBuilder {
+    depth: usize,
+}
+
+impl ValueBuilder {
+    /// Maximal depth of the values, to stop infinite recursions in the first variants of enums.
+    const MAX_DEPTH: usize = 32;
+
+    fn seq(&self, len: usize) -> Result<BuildSeqAccess, ShapeError> {
+        if self.depth >= Self::MAX_DEPTH {
+            return Err(ShapeError::Failed);
+        }
+        Ok(BuildSeqAccess {
+            len,
+            depth: self.depth + 1,
+        })
+    }
+
+    fn nested(&self) -> Result<ValueBuilder, ShapeError> {
+        Ok(ValueBuilder {
+            depth: self.seq(0)?.depth,
+        })
+    }
+}
+
+macro_rules! declare_build {
+    ($method:ident, $visit:ident, $value:expr) => {
+        fn $method<V>(self, visitor: V) -> Result<V::Value, ShapeError>
+        where
+            V: Visitor<'de>,
+        {
+            visitor.$visit($value)
+        }
+    };
+}
+
+impl<'de> serde::de::Deserializer<'de> for ValueBuilder {
+    type Error = ShapeError;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ShapeError::Failed)
+    }
+
+    fn deserialize_identifier<V>(self, _visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ShapeError::Failed)
+    }
+
+    fn deserialize_ignored_any<V>(self, _visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        Err(ShapeError::Failed)
+    }
+
+    declare_build!(deserialize_bool, visit_bool, false);
+    declare_build!(deserialize_i8, visit_i8, 0);
+    declare_build!(deserialize_i16, visit_i16, 0);
+    declare_build!(deserialize_i32, visit_i32, 0);
+    declare_build!(deserialize_i64, visit_i64, 0);
+    declare_build!(deserialize_i128, visit_i128, 0);
+    declare_build!(deserialize_u8, visit_u8, 0);
+    declare_build!(deserialize_u16, visit_u16, 0);
+    declare_build!(deserialize_u32, visit_u32, 0);
+    declare_build!(deserialize_u64, visit_u64, 0);
+    declare_build!(deserialize_u128, visit_u128, 0);
+    declare_build!(deserialize_f32, visit_f32, 0.0);
+    declare_build!(deserialize_f64, visit_f64, 0.0);
+    declare_build!(deserialize_char, visit_char, 'A');
+    declare_build!(deserialize_str, visit_str, "");
+    declare_build!(deserialize_string, visit_str, "");
+    declare_build!(deserialize_bytes, visit_bytes, &[]);
+    declare_build!(deserialize_byte_buf, visit_bytes, &[]);
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_none()
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(self.seq(0)?)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(self.seq(len)?)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(serde::de::value::MapDeserializer::new(std::iter::empty::<(
+            (),
+            (),
+        )>()))
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self.nested()?)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(self.seq(len)?)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(self.seq(fields.len())?)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(self.nested()?)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+struct BuildSeqAccess {
+    len: usize,
+    depth: usize,
+}
+
+impl<'de> serde::de::SeqAccess<'de> for BuildSeqAccess {
+    type Error = ShapeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, ShapeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(ValueBuilder { depth: self.depth })
+            .map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de> serde::de::EnumAccess<'de> for ValueBuilder {
+    type Error = ShapeError;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), ShapeError>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let index = seed.deserialize(0u32.into_deserializer())?;
+        Ok((index, self))
+    }
+}
+
+impl<'de> serde::de::VariantAccess<'de> for ValueBuilder {
+    type Error = ShapeError;
+
+    fn unit_variant(self) -> Result<(), ShapeError> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, ShapeError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(self.seq(len)?)
+    }
+
+    fn struct_variant<V>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ShapeError>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(self.seq(fields.len())?)
+    }
+}
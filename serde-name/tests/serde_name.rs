@@ -1,7 +1,7 @@
 // Copyright (c) Facebook, Inc. and its affiliates
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
-use serde::{de::DeserializeOwned, Deserialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_reflection::{Format, FormatHolder, Samples, Tracer, TracerConfig};
 
 #[derive(Deserialize)]
@@ -51,3 +51,296 @@ fn test_serde_name_and_reflection() {
     assert_eq!(serde_name::trace_name::<u64>(), None);
     assert_eq!(serde_name::trace_name::<(E, E)>(), None);
 }
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+enum List {
+    Cons(u32, Box<List>),
+    Nil,
+}
+
+#[test]
+fn test_serde_shape() {
+    use serde_name::{trace_shape, Shape};
+    use std::collections::{BTreeMap, HashMap};
+
+    assert_eq!(trace_shape::<u64>(), Shape::Primitive("u64"));
+    assert_eq!(trace_shape::<Struct>(), Shape::Named("FooStruct"));
+    assert_eq!(
+        trace_shape::<Option<Box<E>>>(),
+        Shape::Option(Box::new(Shape::Named("E")))
+    );
+    assert_eq!(
+        trace_shape::<Vec<String>>(),
+        Shape::Seq(Box::new(Shape::Primitive("str")))
+    );
+
+    let shape = trace_shape::<BTreeMap<(u8, String), Vec<Option<NewType>>>>();
+    assert_eq!(shape.to_string(), "Map<(u8, str), Seq<Option<NewType>>>");
+    assert_eq!(shape.mangled_name(), "Map_Tuple2_u8_str_Seq_Option_NewType");
+
+    let shape = trace_shape::<(Struct, [u16; 2], HashMap<u64, Tuple>)>();
+    assert_eq!(
+        shape.to_string(),
+        "(FooStruct, (u16, u16), Map<u64, Tuple>)"
+    );
+
+    // No value of `List` can be built with the first variant only.
+    let shape = trace_shape::<(List, u8)>();
+    assert_eq!(shape.to_string(), "(List, ?)");
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(
+    rename = "Tree",
+    bound(
+        serialize = "T: Serialize + DeserializeOwned",
+        deserialize = "T: DeserializeOwned"
+    )
+)]
+struct TreeData<T> {
+    value: T,
+    children: Vec<Tree<T>>,
+}
+
+/// A generic type whose instantiations use distinct Serde names.
+struct Tree<T>(TreeData<T>);
+
+impl<T: DeserializeOwned> Tree<T> {
+    fn name() -> &'static str {
+        let name = serde_name::mangle_generic_name("Tree", &[serde_name::trace_shape::<T>()]);
+        serde_name::intern_name(name)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Serialize for Tree<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serde_name::SerializeNameAdapter::new(
+            serializer,
+            Self::name(),
+        ))
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Tree<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let deserializer = serde_name::DeserializeNameAdapter::new(deserializer, Self::name());
+        TreeData::deserialize(deserializer).map(Tree)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct Forest {
+    a: Tree<u32>,
+    b: Tree<Vec<String>>,
+}
+
+#[test]
+fn test_mangled_names_in_registry() {
+    assert_eq!(Tree::<Option<E>>::name(), "Tree_Option_E");
+    assert!(std::ptr::eq(Tree::<u64>::name(), Tree::<u64>::name()));
+
+    let mut tracer = Tracer::new(TracerConfig::default());
+    let samples = Samples::new();
+    tracer.trace_type::<Forest>(&samples).unwrap();
+    let registry = tracer.registry().unwrap();
+    let names = registry.keys().cloned().collect::<Vec<_>>();
+    assert_eq!(names, vec!["Forest", "Tree_Seq_str", "Tree_u32"]);
+
+    let mut tracer = Tracer::new(TracerConfig::default());
+    let forest = Forest {
+        a: Tree(TreeData {
+            value: 1,
+            children: Vec::new(),
+        }),
+        b: Tree(TreeData {
+            value: vec!["x".to_string()],
+            children: Vec::new(),
+        }),
+    };
+    let mut samples = Samples::new();
+    tracer.trace_value(&mut samples, &forest).unwrap();
+    tracer.trace_type::<Forest>(&samples).unwrap();
+    // Serialization and deserialization agree on the names.
+    let registry = tracer.registry().unwrap();
+    assert_eq!(registry.keys().cloned().collect::<Vec<_>>(), names);
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "T: Serialize + DeserializeOwned",
+    deserialize = "T: DeserializeOwned"
+))]
+struct GenericTreeData<T> {
+    value: T,
+    children: Vec<GenericTree<T>>,
+}
+
+/// Same as `Tree` but instantiations are named after their type arguments.
+struct GenericTree<T>(GenericTreeData<T>);
+
+impl<T: DeserializeOwned> GenericTree<T> {
+    fn name() -> &'static str {
+        let name = serde_name::generic_name("Tree", &[serde_name::trace_shape::<T>()]);
+        serde_name::intern_name(name)
+    }
+}
+
+impl<T: Serialize + DeserializeOwned> Serialize for GenericTree<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serde_name::SerializeNameAdapter::new(
+            serializer,
+            Self::name(),
+        ))
+    }
+}
+
+impl<'de, T: DeserializeOwned> Deserialize<'de> for GenericTree<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let deserializer = serde_name::DeserializeNameAdapter::new(deserializer, Self::name());
+        GenericTreeData::deserialize(deserializer).map(GenericTree)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct GenericForest {
+    a: GenericTree<u32>,
+    b: GenericTree<Vec<String>>,
+}
+
+#[test]
+fn test_generic_names_in_registry() {
+    use serde_reflection::{ContainerFormat, Format, Named};
+
+    assert_eq!(
+        GenericTree::<Option<(u8, E)>>::name(),
+        "Tree<Option<(u8, E)>>"
+    );
+
+    let mut tracer = Tracer::new(TracerConfig::default().recognize_generics(true));
+    let samples = Samples::new();
+    tracer.trace_type::<GenericForest>(&samples).unwrap();
+    let registry = tracer.registry().unwrap();
+    let names = registry.keys().cloned().collect::<Vec<_>>();
+    assert_eq!(names, vec!["GenericForest", "Tree"]);
+
+    let instance = |argument| Format::TypeApp {
+        name: "Tree".into(),
+        arguments: vec![argument],
+    };
+    assert_eq!(
+        registry["GenericForest"],
+        ContainerFormat::Struct(vec![
+            Named {
+                name: "a".into(),
+                value: instance(Format::U32),
+            },
+            Named {
+                name: "b".into(),
+                value: instance(Format::Seq(Box::new(Format::Str))),
+            },
+        ])
+    );
+    assert_eq!(
+        registry["Tree"],
+        ContainerFormat::Generic {
+            parameters: vec!["T0".into()],
+            format: Box::new(ContainerFormat::Struct(vec![
+                Named {
+                    name: "value".into(),
+                    value: Format::Parameter("T0".into()),
+                },
+                Named {
+                    name: "children".into(),
+                    value: Format::Seq(Box::new(instance(Format::Parameter("T0".into())))),
+                },
+            ])),
+        }
+    );
+
+    // Instances are recovered by monomorphization.
+    let registry = serde_reflection::monomorphize(&registry).unwrap();
+    let names = registry.keys().cloned().collect::<Vec<_>>();
+    assert_eq!(names, vec!["GenericForest", "Tree_Seq_str", "Tree_u32"]);
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+struct Fields {
+    first_field: u64,
+    #[serde(skip)]
+    skipped: u32,
+    #[serde(rename = "other")]
+    second_field: String,
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+enum Variants {
+    A,
+    #[serde(rename = "Bee")]
+    B(u64),
+    #[serde(skip)]
+    C,
+    D {
+        x: u8,
+    },
+}
+
+#[test]
+fn test_serde_container_names_and_reflection() {
+    use serde_name::{trace_container_names, ContainerNames};
+    use serde_reflection::{ContainerFormat, Registry};
+
+    let names = trace_container_names::<Fields>().unwrap();
+    assert_eq!(names.name(), "Fields");
+    assert_eq!(names.fields(), Some(&["firstField", "other"][..]));
+    assert_eq!(names.variants(), None);
+
+    let names = trace_container_names::<Variants>().unwrap();
+    assert_eq!(names.variants(), Some(&["A", "Bee", "D"][..]));
+
+    assert_eq!(
+        trace_container_names::<Tuple>(),
+        Some(ContainerNames::TupleStruct("Tuple", 2))
+    );
+    assert_eq!(
+        trace_container_names::<NewType>(),
+        Some(ContainerNames::NewTypeStruct("NewType"))
+    );
+    assert_eq!(
+        trace_container_names::<Unit>(),
+        Some(ContainerNames::UnitStruct("Unit"))
+    );
+    assert_eq!(trace_container_names::<Option<Unit>>(), None);
+
+    // serde-reflection
+    let mut tracer = Tracer::new(TracerConfig::default());
+    let samples = Samples::new();
+    tracer.trace_type::<Fields>(&samples).unwrap();
+    tracer.trace_type::<Variants>(&samples).unwrap();
+    let registry: Registry = tracer.registry().unwrap();
+    match &registry["Fields"] {
+        ContainerFormat::Struct(fields) => {
+            let names = fields.iter().map(|f| f.name.as_str()).collect::<Vec<_>>();
+            let expected = trace_container_names::<Fields>().unwrap().fields().unwrap();
+            assert_eq!(names, expected);
+        }
+        _ => panic!("unexpected format"),
+    }
+    match &registry["Variants"] {
+        ContainerFormat::Enum(variants) => {
+            let names = variants
+                .values()
+                .map(|v| v.name.as_str())
+                .collect::<Vec<_>>();
+            let expected = trace_container_names::<Variants>()
+                .unwrap()
+                .variants()
+                .unwrap();
+            assert_eq!(names, expected);
+        }
+        _ => panic!("unexpected format"),
+    }
+}
@@ -27,6 +27,10 @@ pub enum Error {
     UnknownFormatInContainer(String),
     #[error("Missing variants detected for specific enums: {0:?}")]
     MissingVariants(Vec<String>),
+    #[error("Invalid use of generic containers: {0}")]
+    InvalidGenerics(String),
+    #[error("Container `{0}` does not exist in the registry")]
+    MissingContainer(String),
 }
 
 impl ser::Error for Error {
@@ -119,6 +123,23 @@ corpus of definitions. You may also use `tracer.registry_unchecked()` for debugg
 "#,
                 names)
             }
+            InvalidGenerics(_) => {
+                r#"
+Generic containers could not be recognized or instantiated. This can happen if the names given to the
+instances of a generic container `Foo<T>` collide with the names of other containers, or if a registry
+refers to an instance `Foo<A, B>` with a wrong number of type arguments.
+
+Verify the names of the containers and the definitions of the generic containers in the registry.
+"#.to_string()
+            }
+            MissingContainer(_) => {
+                r#"
+A function operating on the containers of a registry was given the name of a container that is not
+defined in the registry.
+
+Verify the name of the container, or the registry.
+"#.to_string()
+            }
         }
     }
 }
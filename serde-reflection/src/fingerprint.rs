@@ -0,0 +1,214 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Canonical forms and fingerprints of containers.
+//!
+//! The canonical form of a container describes its format together with the formats of all
+//! the containers that it transitively refers to. Containers are numbered in the order of
+//! their first occurrence (`#0` being the container itself) so that the names of containers,
+//! which never appear on the wire, do not matter. Instances of generic containers are
+//! expanded, hence the canonical form is preserved by `generalize` and `monomorphize`.
+//! Field and variant names are kept since self-describing encodings depend on them.
+
+use crate::{
+    error::{Error, Result},
+    format::*,
+    trace::Registry,
+};
+use std::collections::{HashMap, VecDeque};
+
+/// Compute the canonical form of the container `name`, e.g.
+/// `#0=STRUCT(value:U32,children:SEQ(#0))` for `struct Tree { value: u32, children: Vec<Tree> }`.
+/// References to names outside of the registry (i.e. external definitions) are written `@Name`.
+/// Type parameters of a generic container `name` are written `PARAMETER(T0)`, etc.
+pub fn canonical_form(registry: &Registry, name: &str) -> Result<String> {
+    if !registry.contains_key(name) {
+        return Err(Error::MissingContainer(name.to_string()));
+    }
+    let mut canonicalizer = Canonicalizer {
+        registry,
+        indices: HashMap::new(),
+        queue: VecDeque::new(),
+    };
+    canonicalizer.reference(name, Vec::new());
+    let mut definitions = Vec::new();
+    while let Some((name, arguments)) = canonicalizer.queue.pop_front() {
+        let definition = canonicalizer.container(&name, arguments)?;
+        definitions.push(format!("#{}={}", definitions.len(), definition));
+    }
+    Ok(definitions.join(";"))
+}
+
+/// Compute a 64-bit fingerprint of the container `name`, that is, the FNV-1a hash of its
+/// canonical form. Containers that are (de)serialized in the same way have the same
+/// fingerprint, regardless of their names.
+pub fn fingerprint(registry: &Registry, name: &str) -> Result<u64> {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in canonical_form(registry, name)?.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    Ok(hash)
+}
+
+struct Canonicalizer<'a> {
+    registry: &'a Registry,
+    /// Indices of the containers (or instances of generic containers) seen so far, indexed
+    /// by their name followed by their type arguments.
+    indices: HashMap<String, usize>,
+    /// Containers to be defined, in the order of their indices.
+    queue: VecDeque<(String, Vec<Format>)>,
+}
+
+impl Canonicalizer<'_> {
+    fn reference(&mut self, name: &str, arguments: Vec<Format>) -> usize {
+        let key = format!("{}<{}>", name, self.formats_key(&arguments));
+        if let Some(index) = self.indices.get(&key) {
+            return *index;
+        }
+        let index = self.indices.len();
+        self.indices.insert(key, index);
+        self.queue.push_back((name.to_string(), arguments));
+        index
+    }
+
+    /// Key used to identify type arguments. Arguments are already substituted and cannot
+    /// contain type parameters, hence a textual representation is enough.
+    fn formats_key(&self, formats: &[Format]) -> String {
+        formats
+            .iter()
+            .map(|format| format!("{:?}", format))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    fn container(&mut self, name: &str, arguments: Vec<Format>) -> Result<String> {
+        let mut format = self.registry[name].clone();
+        format.normalize()?;
+        if let ContainerFormat::Generic {
+            parameters,
+            format: body,
+        } = format
+        {
+            // Without arguments, type parameters are kept as such.
+            if !arguments.is_empty() && parameters.len() != arguments.len() {
+                return Err(Error::InvalidGenerics(format!(
+                    "container `{}` expects {} type arguments",
+                    name,
+                    parameters.len()
+                )));
+            }
+            let substitution = parameters
+                .into_iter()
+                .zip(arguments.into_iter())
+                .collect::<HashMap<_, _>>();
+            let mut body = *body;
+            body.visit_mut(&mut |format| {
+                if let Format::Parameter(parameter) = format {
+                    if let Some(argument) = substitution.get(parameter) {
+                        *format = argument.clone();
+                    }
+                }
+                Ok(())
+            })?;
+            format = body;
+        }
+        use ContainerFormat::*;
+        Ok(match &format {
+            UnitStruct => "UNITSTRUCT".to_string(),
+            NewTypeStruct(format) => format!("NEWTYPESTRUCT({})", self.format(format)?),
+            TupleStruct(formats) => format!("TUPLESTRUCT({})", self.formats(formats)?),
+            Struct(named_formats) => format!("STRUCT({})", self.named_formats(named_formats)?),
+            Enum(variants) => {
+                let mut result = Vec::new();
+                for (index, variant) in variants {
+                    let variant_format = match &variant.value {
+                        VariantFormat::Variable(_) => return Err(Error::UnknownFormat),
+                        VariantFormat::Unit => "UNIT".to_string(),
+                        VariantFormat::NewType(format) => {
+                            format!("NEWTYPE({})", self.format(format)?)
+                        }
+                        VariantFormat::Tuple(formats) => {
+                            format!("TUPLE({})", self.formats(formats)?)
+                        }
+                        VariantFormat::Struct(named_formats) => {
+                            format!("STRUCT({})", self.named_formats(named_formats)?)
+                        }
+                    };
+                    result.push(format!("{}:{}:{}", index, variant.name, variant_format));
+                }
+                format!("ENUM({})", result.join(","))
+            }
+            Generic { .. } => {
+                return Err(Error::InvalidGenerics(format!(
+                    "container `{}` has nested type parameters",
+                    name
+                )))
+            }
+        })
+    }
+
+    fn named_formats(&mut self, named_formats: &[Named<Format>]) -> Result<String> {
+        let mut result = Vec::new();
+        for named in named_formats {
+            result.push(format!("{}:{}", named.name, self.format(&named.value)?));
+        }
+        Ok(result.join(","))
+    }
+
+    fn formats(&mut self, formats: &[Format]) -> Result<String> {
+        let mut result = Vec::new();
+        for format in formats {
+            result.push(self.format(format)?);
+        }
+        Ok(result.join(","))
+    }
+
+    fn format(&mut self, format: &Format) -> Result<String> {
+        use Format::*;
+        Ok(match format {
+            Variable(_) => return Err(Error::UnknownFormat),
+            TypeName(name) => {
+                if self.registry.contains_key(name) {
+                    format!("#{}", self.reference(name, Vec::new()))
+                } else {
+                    format!("@{}", name)
+                }
+            }
+            TypeApp { name, arguments } => {
+                if self.registry.contains_key(name) {
+                    format!("#{}", self.reference(name, arguments.clone()))
+                } else {
+                    format!("@{}<{}>", name, self.formats(arguments)?)
+                }
+            }
+            Parameter(name) => format!("PARAMETER({})", name),
+            Unit => "UNIT".to_string(),
+            Bool => "BOOL".to_string(),
+            I8 => "I8".to_string(),
+            I16 => "I16".to_string(),
+            I32 => "I32".to_string(),
+            I64 => "I64".to_string(),
+            I128 => "I128".to_string(),
+            U8 => "U8".to_string(),
+            U16 => "U16".to_string(),
+            U32 => "U32".to_string(),
+            U64 => "U64".to_string(),
+            U128 => "U128".to_string(),
+            F32 => "F32".to_string(),
+            F64 => "F64".to_string(),
+            Char => "CHAR".to_string(),
+            Str => "STR".to_string(),
+            Bytes => "BYTES".to_string(),
+            Option(format) => format!("OPTION({})", self.format(format)?),
+            Seq(format) => format!("SEQ({})", self.format(format)?),
+            Map { key, value } => format!("MAP({},{})", self.format(key)?, self.format(value)?),
+            Tuple(formats) => format!("TUPLE({})", self.formats(formats)?),
+            // Arrays are (de)serialized as tuples.
+            TupleArray { content, size } => {
+                let content = self.format(content)?;
+                format!("TUPLE({})", vec![content; *size].join(","))
+            }
+        })
+    }
+}
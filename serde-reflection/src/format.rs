@@ -31,6 +31,14 @@ pub enum Format {
     Variable(#[serde(with = "not_implemented")] Variable<Format>),
     /// The name of a container.
     TypeName(String),
+    /// A type parameter of the enclosing generic container, e.g. `T` in `struct Tree<T> { .. }`.
+    Parameter(String),
+    /// An instance of a generic container, e.g. the format of `Tree<u32>`.
+    #[serde(rename_all = "UPPERCASE")]
+    TypeApp {
+        name: String,
+        arguments: Vec<Format>,
+    },
 
     // The formats of primitive types
     Unit,
@@ -89,6 +97,13 @@ pub enum ContainerFormat {
     /// An enum, that is, an enumeration of variants.
     /// Each variant has a unique name and index within the enum.
     Enum(BTreeMap<u32, Named<VariantFormat>>),
+    /// A generic container, e.g. `struct Tree<T> { .. }`.
+    /// Parameters are referred to by `Format::Parameter` within `format`.
+    #[serde(rename_all = "UPPERCASE")]
+    Generic {
+        parameters: Vec<String>,
+        format: Box<ContainerFormat>,
+    },
 }
 
 #[derive(Debug, Clone, Default, Eq, PartialEq)]
@@ -421,6 +436,7 @@ impl FormatHolder for ContainerFormat {
                     variant.1.visit(f)?;
                 }
             }
+            Self::Generic { format, .. } => format.visit(f)?,
         }
         Ok(())
     }
@@ -444,6 +460,7 @@ impl FormatHolder for ContainerFormat {
                     variant.1.visit_mut(f)?;
                 }
             }
+            Self::Generic { format, .. } => format.visit_mut(f)?,
         }
         Ok(())
     }
@@ -487,6 +504,19 @@ impl FormatHolder for ContainerFormat {
                 }
             }
 
+            (
+                Self::Generic {
+                    parameters: parameters1,
+                    format: format1,
+                },
+                Self::Generic {
+                    parameters: parameters2,
+                    format: format2,
+                },
+            ) if *parameters1 == parameters2 => {
+                format1.as_mut().unify(*format2)?;
+            }
+
             (format1, format2) => {
                 return Err(unification_error(format1, format2));
             }
@@ -504,6 +534,7 @@ impl FormatHolder for Format {
         match self {
             Self::Variable(variable) => variable.visit(f)?,
             Self::TypeName(_)
+            | Self::Parameter(_)
             | Self::Unit
             | Self::Bool
             | Self::I8
@@ -535,7 +566,10 @@ impl FormatHolder for Format {
                 value.visit(f)?;
             }
 
-            Self::Tuple(formats) => {
+            Self::Tuple(formats)
+            | Self::TypeApp {
+                arguments: formats, ..
+            } => {
                 for format in formats {
                     format.visit(f)?;
                 }
@@ -555,6 +589,7 @@ impl FormatHolder for Format {
                     .expect("variable is known");
             }
             Self::TypeName(_)
+            | Self::Parameter(_)
             | Self::Unit
             | Self::Bool
             | Self::I8
@@ -586,7 +621,10 @@ impl FormatHolder for Format {
                 value.visit_mut(f)?;
             }
 
-            Self::Tuple(formats) => {
+            Self::Tuple(formats)
+            | Self::TypeApp {
+                arguments: formats, ..
+            } => {
                 for format in formats {
                     format.visit_mut(f)?;
                 }
@@ -643,7 +681,24 @@ impl FormatHolder for Format {
             | (Self::Str, Self::Str)
             | (Self::Bytes, Self::Bytes) => (),
 
-            (Self::TypeName(name1), Self::TypeName(name2)) if *name1 == name2 => (),
+            (Self::TypeName(name1), Self::TypeName(name2))
+            | (Self::Parameter(name1), Self::Parameter(name2))
+                if *name1 == name2 => {}
+
+            (
+                Self::TypeApp {
+                    name: name1,
+                    arguments: formats1,
+                },
+                Self::TypeApp {
+                    name: name2,
+                    arguments: formats2,
+                },
+            ) if *name1 == name2 && formats1.len() == formats2.len() => {
+                for (format1, format2) in formats1.iter_mut().zip(formats2.into_iter()) {
+                    format1.unify(format2)?;
+                }
+            }
 
             (Self::Option(format1), Self::Option(format2))
             | (Self::Seq(format1), Self::Seq(format2)) => {
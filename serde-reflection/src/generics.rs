@@ -0,0 +1,647 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Support for generic containers.
+//!
+//! During tracing, every instance of a generic container such as `Tree<T>` is a distinct
+//! monomorphic container. When instances are named `Tree<u32>`, `Tree<Seq<str>>`, etc (see
+//! `serde_name::generic_name`), `generalize` recognizes them and computes a single container
+//! `ContainerFormat::Generic` whose instances are referred to by `Format::TypeApp`.
+//! Conversely, `monomorphize` replaces generic containers by their instances.
+
+use crate::{
+    error::{Error, Result},
+    format::*,
+    trace::Registry,
+};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Name of the type parameter of index `index`.
+fn parameter_name(index: usize) -> String {
+    format!("T{}", index)
+}
+
+/// Compute a name made of letters, digits and underscores for an instance of a generic
+/// container, e.g. `Tree_u32` for `Tree<u32>`. This follows `serde_name::mangle_generic_name`.
+pub fn mangled_name(name: &str, arguments: &[Format]) -> String {
+    let mut result = name.to_string();
+    for argument in arguments {
+        result.push('_');
+        result.push_str(&mangled_format_name(argument));
+    }
+    result
+}
+
+fn mangled_format_name(format: &Format) -> String {
+    use Format::*;
+    match format {
+        Variable(_) => "Unknown".to_string(),
+        TypeName(name) | Parameter(name) => name.clone(),
+        TypeApp { name, arguments } => mangled_name(name, arguments),
+        Unit => "unit".to_string(),
+        Bool => "bool".to_string(),
+        I8 => "i8".to_string(),
+        I16 => "i16".to_string(),
+        I32 => "i32".to_string(),
+        I64 => "i64".to_string(),
+        I128 => "i128".to_string(),
+        U8 => "u8".to_string(),
+        U16 => "u16".to_string(),
+        U32 => "u32".to_string(),
+        U64 => "u64".to_string(),
+        U128 => "u128".to_string(),
+        F32 => "f32".to_string(),
+        F64 => "f64".to_string(),
+        Char => "char".to_string(),
+        Str => "str".to_string(),
+        Bytes => "bytes".to_string(),
+        Option(format) => format!("Option_{}", mangled_format_name(format)),
+        Seq(format) => format!("Seq_{}", mangled_format_name(format)),
+        Map { key, value } => format!(
+            "Map_{}_{}",
+            mangled_format_name(key),
+            mangled_format_name(value)
+        ),
+        Tuple(formats) => {
+            let mut name = format!("Tuple{}", formats.len());
+            for format in formats {
+                name.push('_');
+                name.push_str(&mangled_format_name(format));
+            }
+            name
+        }
+        TupleArray { content, size } => {
+            let mut name = format!("Tuple{}", size);
+            for _ in 0..*size {
+                name.push('_');
+                name.push_str(&mangled_format_name(content));
+            }
+            name
+        }
+    }
+}
+
+/// Parse the name of an instance of a generic container, e.g. `Map<str, Tree<u32>>`, into a
+/// base name and a list of type arguments. Type arguments use the syntax of `serde_name::Shape`.
+fn parse_instance_name(name: &str) -> Option<(String, Vec<Format>)> {
+    let mut parser = NameParser { input: name };
+    let (base, arguments) = parser.parse_application()?;
+    if !parser.input.is_empty() || arguments.is_empty() {
+        return None;
+    }
+    let mut arguments = arguments;
+    for argument in &mut arguments {
+        argument.normalize().ok()?;
+    }
+    Some((base, arguments))
+}
+
+struct NameParser<'a> {
+    input: &'a str,
+}
+
+impl<'a> NameParser<'a> {
+    fn eat(&mut self, token: char) -> bool {
+        let input = self.input.trim_start();
+        match input.strip_prefix(token) {
+            Some(rest) => {
+                self.input = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn parse_arguments(&mut self, close: char) -> Option<Vec<Format>> {
+        let mut arguments = Vec::new();
+        if self.eat(close) {
+            return Some(arguments);
+        }
+        loop {
+            arguments.push(self.parse_format()?);
+            if self.eat(close) {
+                return Some(arguments);
+            }
+            if !self.eat(',') {
+                return None;
+            }
+        }
+    }
+
+    fn parse_application(&mut self) -> Option<(String, Vec<Format>)> {
+        let input = self.input.trim_start();
+        let end = input
+            .find(|c: char| "<>(), ".contains(c))
+            .unwrap_or_else(|| input.len());
+        if end == 0 {
+            return None;
+        }
+        let name = input[..end].to_string();
+        self.input = &input[end..];
+        if self.eat('<') {
+            Some((name, self.parse_arguments('>')?))
+        } else {
+            Some((name, Vec::new()))
+        }
+    }
+
+    fn parse_format(&mut self) -> Option<Format> {
+        if self.eat('(') {
+            return Some(Format::Tuple(self.parse_arguments(')')?));
+        }
+        let (name, mut arguments) = self.parse_application()?;
+        let format = match (name.as_str(), arguments.len()) {
+            ("unit", 0) => Format::Unit,
+            ("bool", 0) => Format::Bool,
+            ("i8", 0) => Format::I8,
+            ("i16", 0) => Format::I16,
+            ("i32", 0) => Format::I32,
+            ("i64", 0) => Format::I64,
+            ("i128", 0) => Format::I128,
+            ("u8", 0) => Format::U8,
+            ("u16", 0) => Format::U16,
+            ("u32", 0) => Format::U32,
+            ("u64", 0) => Format::U64,
+            ("u128", 0) => Format::U128,
+            ("f32", 0) => Format::F32,
+            ("f64", 0) => Format::F64,
+            ("char", 0) => Format::Char,
+            ("str", 0) => Format::Str,
+            ("bytes", 0) => Format::Bytes,
+            ("?", _) => return None,
+            ("Option", 1) => Format::Option(Box::new(arguments.remove(0))),
+            ("Seq", 1) => Format::Seq(Box::new(arguments.remove(0))),
+            ("Map", 2) => {
+                let value = arguments.remove(1);
+                let key = arguments.remove(0);
+                Format::Map {
+                    key: Box::new(key),
+                    value: Box::new(value),
+                }
+            }
+            (_, 0) => Format::TypeName(name),
+            _ => Format::TypeApp { name, arguments },
+        };
+        Some(format)
+    }
+}
+
+/// Recognize the instances of generic containers in a registry, that is, the containers named
+/// `Name<Arg1, .., ArgN>`, and replace the instances of each `Name` by a single generic
+/// container when possible. References to instances become `Format::TypeApp`.
+///
+/// Generalization requires at least two instances with the same number of type arguments, each
+/// of which must occur in the resulting format. The instances that cannot be generalized are
+/// renamed with `mangled_name`. Note that fields with the same format as several type
+/// arguments in every instance are attributed to the first such argument.
+pub fn generalize(registry: Registry) -> Result<Registry> {
+    let mut result = Registry::new();
+    let mut instances = BTreeMap::<String, Vec<(Vec<Format>, ContainerFormat)>>::new();
+    for (name, mut format) in registry {
+        format.visit_mut(&mut |format| {
+            if let Format::TypeName(name) = format {
+                if let Some((name, arguments)) = parse_instance_name(name) {
+                    *format = Format::TypeApp { name, arguments };
+                }
+            }
+            Ok(())
+        })?;
+        match parse_instance_name(&name) {
+            Some((base, arguments)) => instances.entry(base).or_default().push((arguments, format)),
+            None => {
+                result.insert(name, format);
+            }
+        }
+    }
+
+    let mut generics = BTreeMap::new();
+    for (base, group) in &instances {
+        if result.contains_key(base) || group.len() < 2 {
+            continue;
+        }
+        let arity = group[0].0.len();
+        if group.iter().any(|(arguments, _)| arguments.len() != arity) {
+            continue;
+        }
+        if let Some(format) = generalize_instances(group) {
+            generics.insert(base.clone(), format);
+        }
+    }
+    // A generic container may not refer to a non-generic one using its own parameters.
+    loop {
+        let invalid = generics
+            .iter()
+            .filter(|(_, format)| {
+                let mut valid = true;
+                format
+                    .visit(&mut |format| {
+                        if let Format::TypeApp { name, arguments } = format {
+                            if !generics.contains_key(name)
+                                && arguments.iter().any(contains_parameters)
+                            {
+                                valid = false;
+                            }
+                        }
+                        Ok(())
+                    })
+                    .expect("formats do not contain variables");
+                !valid
+            })
+            .map(|(name, _)| name.clone())
+            .collect::<Vec<_>>();
+        if invalid.is_empty() {
+            break;
+        }
+        for name in invalid {
+            generics.remove(&name);
+        }
+    }
+
+    let mut rename = |format: &mut Format| {
+        if let Format::TypeApp { name, arguments } = format {
+            if !generics.contains_key(name) {
+                *format = Format::TypeName(mangled_name(name, arguments));
+            }
+        }
+        Ok(())
+    };
+    for format in result.values_mut() {
+        format.visit_mut(&mut rename)?;
+    }
+    let mut containers = Vec::new();
+    for (base, group) in instances {
+        match generics.get(&base) {
+            Some(format) => containers.push((base, format.clone())),
+            None => {
+                for (arguments, mut format) in group {
+                    format.visit_mut(&mut rename)?;
+                    containers.push((mangled_name(&base, &arguments), format));
+                }
+            }
+        }
+    }
+    for (name, format) in containers {
+        if result.contains_key(&name) {
+            return Err(Error::InvalidGenerics(format!(
+                "container `{}` is defined twice",
+                name
+            )));
+        }
+        result.insert(name, format);
+    }
+    Ok(result)
+}
+
+/// Replace generic containers by the instances that are reachable from the other containers.
+/// Instances are named with `mangled_name` and referred to by `Format::TypeName`. Unused
+/// generic containers are removed.
+pub fn monomorphize(registry: &Registry) -> Result<Registry> {
+    let mut result = Registry::new();
+    let mut queue = Vec::new();
+    for (name, format) in registry {
+        if let ContainerFormat::Generic { .. } = format {
+            continue;
+        }
+        let mut format = format.clone();
+        instantiate_references(&mut format, &mut queue)?;
+        result.insert(name.clone(), format);
+    }
+    let mut instances = BTreeMap::new();
+    while let Some((name, arguments)) = queue.pop() {
+        let instance = mangled_name(&name, &arguments);
+        if instances.contains_key(&instance) {
+            continue;
+        }
+        let (parameters, format) = match registry.get(&name) {
+            Some(ContainerFormat::Generic { parameters, format })
+                if parameters.len() == arguments.len() =>
+            {
+                (parameters, format)
+            }
+            _ => {
+                return Err(Error::InvalidGenerics(format!(
+                    "`{}` is not a generic container with {} parameter(s)",
+                    name,
+                    arguments.len()
+                )))
+            }
+        };
+        let mut format = format.as_ref().clone();
+        format.visit_mut(&mut |format| {
+            if let Format::Parameter(parameter) = format {
+                if let Some(index) = parameters.iter().position(|p| p == parameter) {
+                    *format = arguments[index].clone();
+                }
+            }
+            Ok(())
+        })?;
+        instantiate_references(&mut format, &mut queue)?;
+        format.normalize()?;
+        instances.insert(instance, format);
+    }
+    for (name, format) in instances {
+        if result.contains_key(&name) {
+            return Err(Error::InvalidGenerics(format!(
+                "container `{}` is defined twice",
+                name
+            )));
+        }
+        result.insert(name, format);
+    }
+    Ok(result)
+}
+
+/// Replace the instances of generic containers by named references and record them in `queue`.
+fn instantiate_references(
+    format: &mut ContainerFormat,
+    queue: &mut Vec<(String, Vec<Format>)>,
+) -> Result<()> {
+    format.visit_mut(&mut |format| match format {
+        Format::Parameter(name) => Err(Error::InvalidGenerics(format!(
+            "type parameter `{}` is not bound",
+            name
+        ))),
+        Format::TypeApp { name, arguments } => {
+            let instance = mangled_name(name, arguments);
+            queue.push((std::mem::take(name), std::mem::take(arguments)));
+            *format = Format::TypeName(instance);
+            Ok(())
+        }
+        _ => Ok(()),
+    })
+}
+
+fn contains_parameters(format: &Format) -> bool {
+    let mut result = false;
+    format
+        .visit(&mut |format| {
+            if let Format::Parameter(_) = format {
+                result = true;
+            }
+            Ok(())
+        })
+        .expect("formats do not contain variables");
+    result
+}
+
+/// Replace all `TupleArray`s by `Tuple`s so that formats can be compared position-wise.
+fn expand_tuple_arrays<T: FormatHolder>(value: &mut T) {
+    value
+        .visit_mut(&mut |format| {
+            if let Format::TupleArray { content, size } = format {
+                *format = Format::Tuple(vec![content.as_ref().clone(); *size]);
+            }
+            Ok(())
+        })
+        .expect("formats do not contain variables");
+}
+
+/// Compute the most specific generic container that has all the given containers as instances.
+fn generalize_instances(group: &[(Vec<Format>, ContainerFormat)]) -> Option<ContainerFormat> {
+    let mut group = group.to_vec();
+    for (arguments, format) in &mut group {
+        for argument in arguments.iter_mut() {
+            expand_tuple_arrays(argument);
+        }
+        expand_tuple_arrays(format);
+    }
+    let arguments = group
+        .iter()
+        .map(|(arguments, _)| arguments.as_slice())
+        .collect::<Vec<_>>();
+    let formats = group.iter().map(|(_, format)| format).collect::<Vec<_>>();
+    let mut format = Generalizer {
+        arguments: &arguments,
+    }
+    .containers(&formats)?;
+    format.normalize().ok()?;
+    // Type parameters that do not occur in the result would not be needed (and are rejected by
+    // some languages).
+    let parameters = (0..arguments[0].len())
+        .map(parameter_name)
+        .collect::<Vec<_>>();
+    let mut used = BTreeSet::new();
+    format
+        .visit(&mut |format| {
+            if let Format::Parameter(name) = format {
+                used.insert(name.clone());
+            }
+            Ok(())
+        })
+        .ok()?;
+    if parameters.iter().any(|parameter| !used.contains(parameter)) {
+        return None;
+    }
+    Some(ContainerFormat::Generic {
+        parameters,
+        format: Box::new(format),
+    })
+}
+
+/// Anti-unification of the formats found at the same position in several instances.
+struct Generalizer<'a> {
+    arguments: &'a [&'a [Format]],
+}
+
+impl<'a> Generalizer<'a> {
+    fn formats(&self, formats: &[&Format]) -> Option<Format> {
+        for index in 0..self.arguments[0].len() {
+            if formats
+                .iter()
+                .zip(self.arguments)
+                .all(|(format, arguments)| **format == arguments[index])
+            {
+                return Some(Format::Parameter(parameter_name(index)));
+            }
+        }
+        if formats.iter().all(|format| *format == formats[0]) {
+            return Some(formats[0].clone());
+        }
+        match formats[0] {
+            Format::Option(_) => {
+                let formats = project(formats, |format| match format {
+                    Format::Option(format) => Some(format.as_ref()),
+                    _ => None,
+                })?;
+                Some(Format::Option(Box::new(self.formats(&formats)?)))
+            }
+            Format::Seq(_) => {
+                let formats = project(formats, |format| match format {
+                    Format::Seq(format) => Some(format.as_ref()),
+                    _ => None,
+                })?;
+                Some(Format::Seq(Box::new(self.formats(&formats)?)))
+            }
+            Format::Map { .. } => {
+                let keys = project(formats, |format| match format {
+                    Format::Map { key, .. } => Some(key.as_ref()),
+                    _ => None,
+                })?;
+                let values = project(formats, |format| match format {
+                    Format::Map { value, .. } => Some(value.as_ref()),
+                    _ => None,
+                })?;
+                Some(Format::Map {
+                    key: Box::new(self.formats(&keys)?),
+                    value: Box::new(self.formats(&values)?),
+                })
+            }
+            Format::Tuple(_) => {
+                let lists = project(formats, |format| match format {
+                    Format::Tuple(formats) => Some(formats.as_slice()),
+                    _ => None,
+                })?;
+                Some(Format::Tuple(self.lists(&lists)?))
+            }
+            Format::TypeApp { name, .. } => {
+                let lists = project(formats, |format| match format {
+                    Format::TypeApp {
+                        name: other,
+                        arguments,
+                    } if other == name => Some(arguments.as_slice()),
+                    _ => None,
+                })?;
+                Some(Format::TypeApp {
+                    name: name.clone(),
+                    arguments: self.lists(&lists)?,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn lists(&self, lists: &[&[Format]]) -> Option<Vec<Format>> {
+        let len = lists[0].len();
+        if lists.iter().any(|list| list.len() != len) {
+            return None;
+        }
+        (0..len)
+            .map(|i| self.formats(&lists.iter().map(|list| &list[i]).collect::<Vec<_>>()))
+            .collect()
+    }
+
+    fn named_lists(&self, lists: &[&[Named<Format>]]) -> Option<Vec<Named<Format>>> {
+        let len = lists[0].len();
+        if lists.iter().any(|list| list.len() != len) {
+            return None;
+        }
+        (0..len)
+            .map(|i| {
+                let name = &lists[0][i].name;
+                let formats = project(lists, |list| {
+                    if list[i].name == *name {
+                        Some(&list[i].value)
+                    } else {
+                        None
+                    }
+                })?;
+                Some(Named {
+                    name: name.clone(),
+                    value: self.formats(&formats)?,
+                })
+            })
+            .collect()
+    }
+
+    fn variants(&self, variants: &[&VariantFormat]) -> Option<VariantFormat> {
+        match variants[0] {
+            VariantFormat::Unit => {
+                project(variants, |variant| match variant {
+                    VariantFormat::Unit => Some(()),
+                    _ => None,
+                })?;
+                Some(VariantFormat::Unit)
+            }
+            VariantFormat::NewType(_) => {
+                let formats = project(variants, |variant| match variant {
+                    VariantFormat::NewType(format) => Some(format.as_ref()),
+                    _ => None,
+                })?;
+                Some(VariantFormat::NewType(Box::new(self.formats(&formats)?)))
+            }
+            VariantFormat::Tuple(_) => {
+                let lists = project(variants, |variant| match variant {
+                    VariantFormat::Tuple(formats) => Some(formats.as_slice()),
+                    _ => None,
+                })?;
+                Some(VariantFormat::Tuple(self.lists(&lists)?))
+            }
+            VariantFormat::Struct(_) => {
+                let lists = project(variants, |variant| match variant {
+                    VariantFormat::Struct(formats) => Some(formats.as_slice()),
+                    _ => None,
+                })?;
+                Some(VariantFormat::Struct(self.named_lists(&lists)?))
+            }
+            VariantFormat::Variable(_) => None,
+        }
+    }
+
+    fn containers(&self, formats: &[&ContainerFormat]) -> Option<ContainerFormat> {
+        match formats[0] {
+            ContainerFormat::UnitStruct => {
+                project(formats, |format| match format {
+                    ContainerFormat::UnitStruct => Some(()),
+                    _ => None,
+                })?;
+                Some(ContainerFormat::UnitStruct)
+            }
+            ContainerFormat::NewTypeStruct(_) => {
+                let formats = project(formats, |format| match format {
+                    ContainerFormat::NewTypeStruct(format) => Some(format.as_ref()),
+                    _ => None,
+                })?;
+                Some(ContainerFormat::NewTypeStruct(Box::new(
+                    self.formats(&formats)?,
+                )))
+            }
+            ContainerFormat::TupleStruct(_) => {
+                let lists = project(formats, |format| match format {
+                    ContainerFormat::TupleStruct(formats) => Some(formats.as_slice()),
+                    _ => None,
+                })?;
+                Some(ContainerFormat::TupleStruct(self.lists(&lists)?))
+            }
+            ContainerFormat::Struct(_) => {
+                let lists = project(formats, |format| match format {
+                    ContainerFormat::Struct(formats) => Some(formats.as_slice()),
+                    _ => None,
+                })?;
+                Some(ContainerFormat::Struct(self.named_lists(&lists)?))
+            }
+            ContainerFormat::Enum(variants) => {
+                let enums = project(formats, |format| match format {
+                    ContainerFormat::Enum(variants) => Some(variants),
+                    _ => None,
+                })?;
+                let mut result = BTreeMap::new();
+                for (index, variant) in variants {
+                    let formats = project(&enums, |variants| match variants.get(index) {
+                        Some(other) if other.name == variant.name => Some(&other.value),
+                        _ => None,
+                    })?;
+                    result.insert(
+                        *index,
+                        Named {
+                            name: variant.name.clone(),
+                            value: self.variants(&formats)?,
+                        },
+                    );
+                }
+                if enums.iter().any(|variants| variants.len() != result.len()) {
+                    return None;
+                }
+                Some(ContainerFormat::Enum(result))
+            }
+            ContainerFormat::Generic { .. } => None,
+        }
+    }
+}
+
+/// Apply `f` to every item and return the results, unless one of them is `None`.
+fn project<'a, T, U>(items: &[&'a T], f: impl Fn(&'a T) -> Option<U>) -> Option<Vec<U>>
+where
+    T: ?Sized,
+{
+    items.iter().map(|item| f(item)).collect()
+}
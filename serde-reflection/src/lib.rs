@@ -297,15 +297,81 @@
 //! For efficiency reasons, the current algorithm does not attempt to scan the variants of enums
 //! other than the parameter `T` of the main call `trace_type<T>`. As a consequence, each enum type must be
 //! traced separately.
+//!
+//! # Generic Containers
+//!
+//! By default, every instance of a generic container such as `Tree<T>` is traced as a distinct
+//! container under its Serde name. If the instances are given distinct names of the form
+//! `Tree<u32>`, `Tree<Seq<str>>`, etc (e.g. using `serde_name::generic_name`), the option
+//! `TracerConfig::recognize_generics` replaces them by a single `ContainerFormat::Generic` in
+//! the final registry. Instances are then referred to by `Format::TypeApp`, and type
+//! parameters by `Format::Parameter`.
+//!
+//! The functions `generalize` and `monomorphize` respectively perform this transformation and
+//! its converse on existing registries.
+//!
+//! # Merging Registries
+//!
+//! Registries computed separately, e.g. by different crates, can be combined with
+//! `merge_registries`. Containers with the same name are unified as during tracing, and
+//! conflicts are reported with the origins of both formats. Same-named containers that should
+//! be kept apart can be given a prefix with `RegistrySource::prefix`.
+//! ```rust
+//! # use serde_reflection::{merge_registries, Error, Registry, RegistrySource};
+//! # fn main() -> Result<(), Error> {
+//! # let (registry_a, registry_b) = (Registry::new(), Registry::new());
+//! let registry = merge_registries(vec![
+//!     RegistrySource::new("crate_a".to_string(), registry_a),
+//!     RegistrySource::new("crate_b".to_string(), registry_b).prefix("CrateB_".to_string()),
+//! ])?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Fingerprints
+//!
+//! The function `fingerprint` computes a 64-bit hash of the canonical form of a container
+//! (see `canonical_form`), which covers the formats of all the containers that it refers to.
+//! Names of containers are ignored and so are tuple-vs-array differences, so that two
+//! containers with the same fingerprint are (de)serialized in the same way. Peers may exchange
+//! fingerprints to cheaply detect schema mismatches.
+//! ```rust
+//! # use serde::Deserialize;
+//! # use serde_reflection::{fingerprint, Error, Tracer, TracerConfig};
+//! # fn main() -> Result<(), Error> {
+//! #[derive(Deserialize)]
+//! struct Point {
+//!     x: (u32, u32),
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct Position {
+//!     x: [u32; 2],
+//! }
+//!
+//! let mut tracer = Tracer::new(TracerConfig::default());
+//! tracer.trace_simple_type::<Point>()?;
+//! tracer.trace_simple_type::<Position>()?;
+//! let registry = tracer.registry()?;
+//! assert_eq!(fingerprint(&registry, "Point")?, fingerprint(&registry, "Position")?);
+//! # Ok(())
+//! # }
+//! ```
 
 mod de;
 mod error;
+mod fingerprint;
 mod format;
+mod generics;
+mod merge;
 mod ser;
 mod trace;
 mod value;
 
 pub use error::{Error, Result};
+pub use fingerprint::{canonical_form, fingerprint};
 pub use format::{ContainerFormat, Format, FormatHolder, Named, Variable, VariantFormat};
+pub use generics::{generalize, mangled_name, monomorphize};
+pub use merge::{merge_registries, RegistrySource};
 pub use trace::{Registry, Samples, Tracer, TracerConfig};
 pub use value::Value;
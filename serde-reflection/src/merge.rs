@@ -0,0 +1,107 @@
+// Copyright (c) Facebook, Inc. and its affiliates
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Merging of registries.
+//!
+//! Registries computed separately (e.g. by different crates) are merged by unifying the
+//! containers that have the same name, in the same way that the tracer unifies the formats
+//! observed for a given container.
+
+use crate::{
+    error::{Error, Result},
+    format::*,
+    trace::Registry,
+};
+use std::collections::{btree_map::Entry, BTreeMap, BTreeSet};
+
+/// A registry to be merged, together with a description of its origin (e.g. a crate name or
+/// a file path) used in error messages.
+#[derive(Clone, Debug)]
+pub struct RegistrySource {
+    origin: String,
+    registry: Registry,
+    prefix: Option<String>,
+}
+
+impl RegistrySource {
+    /// Create a source for the given registry.
+    pub fn new(origin: String, registry: Registry) -> Self {
+        Self {
+            origin,
+            registry,
+            prefix: None,
+        }
+    }
+
+    /// Prefix the names of the containers of this registry, as well as the references to them,
+    /// e.g. `Foo` becomes `CrateA_Foo` with the prefix `CrateA_`. This keeps containers apart
+    /// from the same-named containers of other sources. References to containers that are not
+    /// defined in this registry (e.g. shared with other sources) are left unchanged.
+    pub fn prefix(mut self, prefix: String) -> Self {
+        self.prefix = Some(prefix);
+        self
+    }
+
+    fn into_registry(self) -> Result<Registry> {
+        let prefix = match self.prefix {
+            None => return Ok(self.registry),
+            Some(prefix) => prefix,
+        };
+        let local_names = self.registry.keys().cloned().collect::<BTreeSet<_>>();
+        let mut registry = Registry::new();
+        for (name, mut format) in self.registry {
+            format.visit_mut(&mut |format| {
+                if let Format::TypeName(name) | Format::TypeApp { name, .. } = format {
+                    if local_names.contains(name) {
+                        name.insert_str(0, &prefix);
+                    }
+                }
+                Ok(())
+            })?;
+            registry.insert(format!("{}{}", prefix, name), format);
+        }
+        Ok(registry)
+    }
+}
+
+/// Merge several registries. Containers with the same name are unified with
+/// `FormatHolder::unify`: for instance, the variants of an enum may be collected from several
+/// sources. When two sources disagree on a container, an error `Error::Incompatible` is returned
+/// that mentions the origins of both formats.
+pub fn merge_registries<I>(sources: I) -> Result<Registry>
+where
+    I: IntoIterator<Item = RegistrySource>,
+{
+    let mut result = Registry::new();
+    // Origins of the formats already merged into `result`, indexed by container names.
+    let mut origins = BTreeMap::<String, Vec<String>>::new();
+    for source in sources {
+        let origin = source.origin.clone();
+        for (name, format) in source.into_registry()? {
+            match result.entry(name.clone()) {
+                Entry::Vacant(entry) => {
+                    entry.insert(format);
+                }
+                Entry::Occupied(mut entry) => {
+                    // Unify a copy so that the error mentions the formats before unification.
+                    let mut unified = entry.get().clone();
+                    if unified.unify(format.clone()).is_err() {
+                        return Err(Error::Incompatible(
+                            format!(
+                                "{:?} (container `{}` in {})",
+                                entry.get(),
+                                name,
+                                origins[&name].join(", ")
+                            ),
+                            format!("{:?} (container `{}` in {})", format, name, origin),
+                        ));
+                    }
+                    unified.reduce();
+                    entry.insert(unified);
+                }
+            }
+            origins.entry(name).or_default().push(origin.clone());
+        }
+    }
+    Ok(result)
+}
@@ -5,6 +5,7 @@ use crate::{
     de::Deserializer,
     error::{Error, Result},
     format::*,
+    generics::generalize,
     ser::Serializer,
     value::Value,
 };
@@ -57,6 +58,7 @@ pub struct TracerConfig {
     pub(crate) record_samples_for_newtype_structs: bool,
     pub(crate) record_samples_for_tuple_structs: bool,
     pub(crate) record_samples_for_structs: bool,
+    pub(crate) recognize_generics: bool,
 }
 
 impl Default for TracerConfig {
@@ -67,6 +69,7 @@ impl Default for TracerConfig {
             record_samples_for_newtype_structs: true,
             record_samples_for_tuple_structs: false,
             record_samples_for_structs: false,
+            recognize_generics: false,
         }
     }
 }
@@ -96,6 +99,13 @@ impl TracerConfig {
         self.record_samples_for_structs = value;
         self
     }
+
+    /// Recognize the instances of generic containers named `Foo<T1, .., TN>` when the registry
+    /// is computed and replace them by generic containers (see `generalize`).
+    pub fn recognize_generics(mut self, value: bool) -> Self {
+        self.recognize_generics = value;
+        self
+    }
 }
 
 impl Tracer {
@@ -229,13 +239,15 @@ impl Tracer {
                 .normalize()
                 .map_err(|_| Error::UnknownFormatInContainer(name.clone()))?;
         }
-        if self.incomplete_enums.is_empty() {
-            Ok(registry)
-        } else {
-            Err(Error::MissingVariants(
+        if !self.incomplete_enums.is_empty() {
+            return Err(Error::MissingVariants(
                 self.incomplete_enums.into_iter().collect(),
-            ))
+            ));
         }
+        if self.config.recognize_generics {
+            registry = generalize(registry)?;
+        }
+        Ok(registry)
     }
 
     /// Same as registry but always return a value, even if we detected issues.
@@ -245,6 +257,11 @@ impl Tracer {
         for format in registry.values_mut() {
             format.normalize().unwrap_or(());
         }
+        if self.config.recognize_generics {
+            if let Ok(generalized) = generalize(registry.clone()) {
+                return generalized;
+            }
+        }
         registry
     }
 
@@ -202,7 +202,7 @@ impl<'de> de::Deserializer<'de> for Deserializer<'de> {
         V: Visitor<'de>,
     {
         match self.value {
-            Value::Seq(x) => visitor.visit_seq(x.into_seq_deserializer()),
+            Value::Seq(x) => visitor.visit_map(x.into_seq_deserializer()),
             _ => Err(Error::DeserializationError("map")),
         }
     }
@@ -274,3 +274,326 @@ fn test_container_format_unification() {
             .is_err());
     }
 }
+
+#[test]
+fn test_generalize_and_monomorphize() {
+    use serde_reflection::{generalize, monomorphize, Registry};
+
+    let registry: Registry = serde_yaml::from_str(
+        r#"
+"Pair<u8, str>":
+  STRUCT:
+    - left: U8
+    - right: STR
+    - tagged:
+        TUPLEARRAY:
+          CONTENT: U8
+          SIZE: 2
+    - count: U64
+"Pair<str, u64>":
+  STRUCT:
+    - left: STR
+    - right: U64
+    - tagged:
+        TUPLE: [U8, STR]
+    - count: U64
+"Single<Seq<u8>>":
+  NEWTYPESTRUCT:
+    SEQ: U8
+User:
+  STRUCT:
+    - a:
+        TYPENAME: "Pair<u8, str>"
+    - b:
+        TYPENAME: "Pair<str, u64>"
+    - c:
+        TYPENAME: "Single<Seq<u8>>"
+"#,
+    )
+    .unwrap();
+    let expected: Registry = serde_yaml::from_str(
+        r#"
+Pair:
+  GENERIC:
+    PARAMETERS: [T0, T1]
+    FORMAT:
+      STRUCT:
+        - left:
+            PARAMETER: T0
+        - right:
+            PARAMETER: T1
+        - tagged:
+            TUPLE:
+              - U8
+              - PARAMETER: T0
+        - count: U64
+Single_Seq_u8:
+  NEWTYPESTRUCT:
+    SEQ: U8
+User:
+  STRUCT:
+    - a:
+        TYPEAPP:
+          NAME: Pair
+          ARGUMENTS: [U8, STR]
+    - b:
+        TYPEAPP:
+          NAME: Pair
+          ARGUMENTS: [STR, U64]
+    - c:
+        TYPENAME: Single_Seq_u8
+"#,
+    )
+    .unwrap();
+    let generalized = generalize(registry.clone()).unwrap();
+    assert_eq!(generalized, expected);
+
+    let instances = monomorphize(&generalized).unwrap();
+    let names = instances.keys().cloned().collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec!["Pair_str_u64", "Pair_u8_str", "Single_Seq_u8", "User"]
+    );
+    assert_eq!(instances["Pair_u8_str"], registry["Pair<u8, str>"]);
+    assert_eq!(instances["Pair_str_u64"], registry["Pair<str, u64>"]);
+
+    let mut invalid = generalized;
+    invalid.insert(
+        "Other".into(),
+        ContainerFormat::NewTypeStruct(Box::new(Format::TypeApp {
+            name: "Pair".into(),
+            arguments: vec![Format::U8],
+        })),
+    );
+    assert!(matches!(
+        monomorphize(&invalid),
+        Err(Error::InvalidGenerics(_))
+    ));
+}
+
+#[test]
+fn test_merge_registries() {
+    use serde_reflection::{merge_registries, Registry, RegistrySource};
+
+    let registry_a: Registry = serde_yaml::from_str(
+        r#"
+Choice:
+  ENUM:
+    0:
+      A: UNIT
+Foo:
+  STRUCT:
+    - choice:
+        TYPENAME: Choice
+"#,
+    )
+    .unwrap();
+    let registry_b: Registry = serde_yaml::from_str(
+        r#"
+Choice:
+  ENUM:
+    1:
+      B:
+        NEWTYPE: U8
+Foo:
+  NEWTYPESTRUCT: U64
+"#,
+    )
+    .unwrap();
+
+    // Same-named containers are unified.
+    let registry = merge_registries(vec![
+        RegistrySource::new("a".to_string(), registry_a.clone()),
+        RegistrySource::new("b".to_string(), registry_b.clone()).prefix("B_".to_string()),
+        RegistrySource::new("c".to_string(), registry_b.clone()).prefix("B_".to_string()),
+    ])
+    .unwrap();
+    assert_eq!(
+        registry.keys().collect::<Vec<_>>(),
+        vec!["B_Choice", "B_Foo", "Choice", "Foo"]
+    );
+
+    // References are prefixed too.
+    let registry = merge_registries(vec![RegistrySource::new(
+        "a".to_string(),
+        registry_a.clone(),
+    )
+    .prefix("A_".to_string())])
+    .unwrap();
+    assert_eq!(
+        registry["A_Foo"],
+        ContainerFormat::Struct(vec![Named {
+            name: "choice".to_string(),
+            value: Format::TypeName("A_Choice".to_string()),
+        }])
+    );
+
+    // References to containers defined by other sources are not prefixed.
+    let mut registry_d = registry_a.clone();
+    registry_d.remove("Choice");
+    let registry = merge_registries(vec![
+        RegistrySource::new("a".to_string(), registry_a.clone()),
+        RegistrySource::new("d".to_string(), registry_d).prefix("D_".to_string()),
+    ])
+    .unwrap();
+    assert_eq!(
+        registry.keys().collect::<Vec<_>>(),
+        vec!["Choice", "D_Foo", "Foo"]
+    );
+    assert_eq!(
+        registry["D_Foo"],
+        ContainerFormat::Struct(vec![Named {
+            name: "choice".to_string(),
+            value: Format::TypeName("Choice".to_string()),
+        }])
+    );
+
+    let mut registry_c = registry_b.clone();
+    registry_c.remove("Foo");
+    let registry = merge_registries(vec![
+        RegistrySource::new("a".to_string(), registry_a.clone()),
+        RegistrySource::new("c".to_string(), registry_c),
+    ])
+    .unwrap();
+    match &registry["Choice"] {
+        ContainerFormat::Enum(variants) => {
+            assert_eq!(
+                variants
+                    .values()
+                    .map(|v| v.name.as_str())
+                    .collect::<Vec<_>>(),
+                vec!["A", "B"]
+            );
+        }
+        format => panic!("unexpected format: {:?}", format),
+    }
+
+    // Conflicts mention both origins.
+    let error = merge_registries(vec![
+        RegistrySource::new("a".to_string(), registry_a.clone()),
+        RegistrySource::new("a2".to_string(), registry_a),
+        RegistrySource::new("b".to_string(), registry_b),
+    ])
+    .unwrap_err();
+    match error {
+        Error::Incompatible(first, second) => {
+            assert!(first.starts_with("Struct("));
+            assert!(first.ends_with("(container `Foo` in a, a2)"));
+            assert_eq!(second, "NewTypeStruct(U64) (container `Foo` in b)");
+        }
+        error => panic!("unexpected error: {:?}", error),
+    }
+}
+
+#[test]
+fn test_canonical_forms_and_fingerprints() {
+    use serde_reflection::{canonical_form, fingerprint, generalize, monomorphize, Registry};
+
+    let registry: Registry = serde_yaml::from_str(
+        r#"
+Tree:
+  STRUCT:
+    - value: U32
+    - children:
+        SEQ:
+          TYPENAME: Tree
+    - tag:
+        TYPENAME: Tag
+Tag:
+  ENUM:
+    0:
+      A: UNIT
+    1:
+      B:
+        NEWTYPE:
+          TUPLEARRAY:
+            CONTENT: U8
+            SIZE: 2
+Other:
+  NEWTYPESTRUCT:
+    TYPENAME: External
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        canonical_form(&registry, "Tree").unwrap(),
+        "#0=STRUCT(value:U32,children:SEQ(#0),tag:#1);#1=ENUM(0:A:UNIT,1:B:NEWTYPE(TUPLE(U8,U8)))"
+    );
+    assert_eq!(
+        canonical_form(&registry, "Other").unwrap(),
+        "#0=NEWTYPESTRUCT(@External)"
+    );
+    assert_eq!(
+        fingerprint(&registry, "Tree").unwrap(),
+        0x28cd_9c3f_7cc7_e016
+    );
+    assert_eq!(
+        fingerprint(&registry, "Missing").unwrap_err(),
+        Error::MissingContainer("Missing".to_string())
+    );
+
+    // Names of containers and arrays vs tuples do not matter.
+    let renamed: Registry = serde_yaml::from_str(
+        r#"
+Node:
+  STRUCT:
+    - value: U32
+    - children:
+        SEQ:
+          TYPENAME: Node
+    - tag:
+        TYPENAME: Label
+Label:
+  ENUM:
+    0:
+      A: UNIT
+    1:
+      B:
+        NEWTYPE:
+          TUPLE: [U8, U8]
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        fingerprint(&registry, "Tree").unwrap(),
+        fingerprint(&renamed, "Node").unwrap()
+    );
+    assert_ne!(
+        fingerprint(&registry, "Tree").unwrap(),
+        fingerprint(&registry, "Tag").unwrap()
+    );
+
+    // Generic containers are expanded.
+    let registry: Registry = serde_yaml::from_str(
+        r#"
+"Pair<u8, str>":
+  STRUCT:
+    - left: U8
+    - right: STR
+"Pair<str, Pair<u8, str>>":
+  STRUCT:
+    - left: STR
+    - right:
+        TYPENAME: "Pair<u8, str>"
+Data:
+  STRUCT:
+    - pair:
+        TYPENAME: "Pair<str, Pair<u8, str>>"
+"#,
+    )
+    .unwrap();
+    let generic_registry = generalize(registry).unwrap();
+    let monomorphic_registry = monomorphize(&generic_registry).unwrap();
+    assert_eq!(
+        canonical_form(&generic_registry, "Data").unwrap(),
+        "#0=STRUCT(pair:#1);#1=STRUCT(left:STR,right:#2);#2=STRUCT(left:U8,right:STR)"
+    );
+    assert_eq!(
+        canonical_form(&monomorphic_registry, "Data").unwrap(),
+        canonical_form(&generic_registry, "Data").unwrap()
+    );
+    assert_eq!(
+        canonical_form(&generic_registry, "Pair").unwrap(),
+        "#0=STRUCT(left:PARAMETER(T0),right:PARAMETER(T1))"
+    );
+}
@@ -436,6 +436,30 @@ fn test_value_recording_for_structs() {
     assert_eq!(tracer.trace_type_once::<T>(&samples).unwrap().1, T(3, 4));
 }
 
+#[test]
+fn test_value_recording_for_maps() {
+    let mut tracer = Tracer::new(TracerConfig::default());
+    let mut samples = Samples::new();
+
+    let map: BTreeMap<u8, String> = vec![(1, "a".to_string()), (2, "b".to_string())]
+        .into_iter()
+        .collect();
+    let (_, value) = tracer.trace_value(&mut samples, &map).unwrap();
+    assert_eq!(
+        value,
+        Value::Seq(vec![
+            Value::U8(1),
+            Value::Str("a".into()),
+            Value::U8(2),
+            Value::Str("b".into()),
+        ])
+    );
+    assert_eq!(
+        map,
+        BTreeMap::<u8, String>::deserialize(value.into_deserializer()).unwrap()
+    );
+}
+
 #[test]
 fn test_repeated_tracing() {
     #[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]